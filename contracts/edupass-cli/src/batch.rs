@@ -0,0 +1,129 @@
+use crate::invoke::{CliError, Invocation};
+
+const HEADER: [&str; 4] = ["beneficiary", "amount", "purpose", "expires_at"];
+
+/// One row of an issuance batch
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IssueRow {
+    pub beneficiary: String,
+    pub amount: i128,
+    pub purpose: String,
+    pub expires_at: u64,
+}
+
+impl IssueRow {
+    pub fn invocation(&self, issuer: &str) -> Invocation {
+        Invocation::new("issue_credits")
+            .arg("issuer", issuer)
+            .arg("beneficiary", self.beneficiary.clone())
+            .arg("amount", self.amount.to_string())
+            .arg("purpose", self.purpose.clone())
+            .arg("expires_at", self.expires_at.to_string())
+    }
+}
+
+/// Parse a batch file with a `beneficiary,amount,purpose,expires_at` header.
+/// Every row is validated before anything is submitted, so a typo on the
+/// last line doesn't leave a half-issued batch.
+pub fn parse(input: &str) -> Result<Vec<IssueRow>, CliError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    match lines.next() {
+        Some((_, header)) if split(header) == HEADER => {}
+        Some((n, _)) => {
+            return Err(CliError::Batch {
+                line: n + 1,
+                reason: format!("expected header `{}`", HEADER.join(",")),
+            })
+        }
+        None => {
+            return Err(CliError::Batch {
+                line: 1,
+                reason: "empty batch".to_string(),
+            })
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (n, line) in lines {
+        let bad = |reason: String| CliError::Batch {
+            line: n + 1,
+            reason,
+        };
+        let fields = split(line);
+        if fields.len() != HEADER.len() {
+            return Err(bad(format!(
+                "expected {} fields, found {}",
+                HEADER.len(),
+                fields.len()
+            )));
+        }
+        let amount: i128 = fields[1]
+            .parse()
+            .map_err(|_| bad(format!("invalid amount `{}`", fields[1])))?;
+        if amount <= 0 {
+            return Err(bad("amount must be positive".to_string()));
+        }
+        rows.push(IssueRow {
+            beneficiary: fields[0].clone(),
+            amount,
+            purpose: fields[2].clone(),
+            expires_at: fields[3]
+                .parse()
+                .map_err(|_| bad(format!("invalid expires_at `{}`", fields[3])))?,
+        });
+    }
+    Ok(rows)
+}
+
+// Split one CSV line; fields may be double-quoted to contain commas
+fn split(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    for c in line.trim().chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_batch() {
+        let rows = parse(
+            "beneficiary,amount,purpose,expires_at\n\
+             GAAA,1000,\"Tuition, Term 1\",1735689600\n\
+             \n\
+             GBBB,250,Books,1735689600\n",
+        )
+        .unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].purpose, "Tuition, Term 1");
+        assert_eq!(rows[1].amount, 250);
+    }
+
+    #[test]
+    fn test_parse_batch_rejects_bad_rows() {
+        assert_eq!(
+            parse("beneficiary,amount,purpose,expires_at\nGAAA,ten,Books,1735689600"),
+            Err(CliError::Batch {
+                line: 2,
+                reason: "invalid amount `ten`".to_string()
+            })
+        );
+        assert!(parse("GAAA,10,Books,1735689600").is_err());
+        assert!(parse("beneficiary,amount,purpose,expires_at\nGAAA,0,Books,1").is_err());
+    }
+}
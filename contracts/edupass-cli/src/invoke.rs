@@ -0,0 +1,134 @@
+use std::fmt;
+use std::process::Command;
+
+/// Where and as whom to invoke the contract
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Target {
+    pub contract_id: String,
+    pub source: String,
+    pub network: String,
+}
+
+/// One contract call: function name plus `--name value` arguments
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Invocation {
+    pub function: &'static str,
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Invocation {
+    pub fn new(function: &'static str) -> Self {
+        Invocation {
+            function,
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.args.push((name, value.into()));
+        self
+    }
+
+    /// Arguments for `stellar contract invoke`, which simulates, signs and
+    /// submits the transaction through the network's soroban-rpc endpoint
+    pub fn argv(&self, target: &Target) -> Vec<String> {
+        let mut argv = vec![
+            "contract".to_string(),
+            "invoke".to_string(),
+            "--id".to_string(),
+            target.contract_id.clone(),
+            "--source-account".to_string(),
+            target.source.clone(),
+            "--network".to_string(),
+            target.network.clone(),
+            "--".to_string(),
+            self.function.to_string(),
+        ];
+        for (name, value) in &self.args {
+            argv.push(format!("--{}", name));
+            argv.push(value.clone());
+        }
+        argv
+    }
+
+    /// Run the invocation and return the contract's printed return value
+    pub fn run(&self, target: &Target) -> Result<String, CliError> {
+        let output = Command::new("stellar")
+            .args(self.argv(target))
+            .output()
+            .map_err(|e| CliError::Spawn(e.to_string()))?;
+        if !output.status.success() {
+            return Err(CliError::Invoke {
+                function: self.function,
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum CliError {
+    /// Bad command line
+    Usage(String),
+    /// Malformed batch file, with its 1-based line number
+    Batch { line: usize, reason: String },
+    /// The `stellar` CLI could not be started
+    Spawn(String),
+    /// The contract call failed
+    Invoke {
+        function: &'static str,
+        stderr: String,
+    },
+    /// The contract is not the deployment named with --instance
+    WrongInstance { expected: String, found: String },
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(reason) => write!(f, "{}", reason),
+            CliError::Batch { line, reason } => write!(f, "line {}: {}", line, reason),
+            CliError::Spawn(reason) => write!(f, "could not run `stellar`: {}", reason),
+            CliError::Invoke { function, stderr } => write!(f, "{} failed: {}", function, stderr),
+            CliError::WrongInstance { expected, found } => {
+                write!(f, "contract is instance {}, expected {}", found, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_argv_matches_stellar_cli_layout() {
+        let target = Target {
+            contract_id: "CABC".to_string(),
+            source: "admin".to_string(),
+            network: "testnet".to_string(),
+        };
+        let invocation = Invocation::new("clawback").arg("beneficiary", "GXYZ");
+
+        assert_eq!(
+            invocation.argv(&target),
+            [
+                "contract",
+                "invoke",
+                "--id",
+                "CABC",
+                "--source-account",
+                "admin",
+                "--network",
+                "testnet",
+                "--",
+                "clawback",
+                "--beneficiary",
+                "GXYZ"
+            ]
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! `edupass`: program operations against a deployed EduPass contract,
+//! invoked through the `stellar` CLI and the network's soroban-rpc endpoint.
+
+mod batch;
+mod invoke;
+
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+use invoke::{CliError, Invocation, Target};
+
+const USAGE: &str = "\
+usage: edupass --id <CONTRACT_ID> --source <ACCOUNT> [--network <NETWORK>] [--instance <ID>] [--dry-run] <command>
+
+  --instance     refuse to run unless the contract's instance_id is <ID>
+
+commands:
+  initialize     --admin <ADDRESS>
+  issue          --issuer <ADDRESS> --beneficiary <ADDRESS> --amount <N> --purpose <TEXT> --expires-at <UNIX>
+  issue-batch    --issuer <ADDRESS> --file <CSV>    (header: beneficiary,amount,purpose,expires_at)
+  clawback       --beneficiary <ADDRESS>
+  sweep-expired  --beneficiary <ADDRESS> [--beneficiary <ADDRESS> ...]
+  report         [--beneficiary <ADDRESS> ...]      (CSV of program totals and balances)";
+
+// Parsed `--name value` flags; names may repeat
+struct Flags {
+    values: HashMap<String, Vec<String>>,
+    dry_run: bool,
+}
+
+impl Flags {
+    fn parse(args: &[String]) -> Result<Self, CliError> {
+        let mut values: HashMap<String, Vec<String>> = HashMap::new();
+        let mut dry_run = false;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| CliError::Usage(format!("unexpected argument `{}`", arg)))?;
+            if name == "dry-run" {
+                dry_run = true;
+                continue;
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| CliError::Usage(format!("missing value for --{}", name)))?;
+            values
+                .entry(name.to_string())
+                .or_default()
+                .push(value.clone());
+        }
+        Ok(Flags { values, dry_run })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .get(name)
+            .and_then(|v| v.last())
+            .map(String::as_str)
+    }
+
+    fn required(&self, name: &str) -> Result<&str, CliError> {
+        self.get(name)
+            .ok_or_else(|| CliError::Usage(format!("missing --{}", name)))
+    }
+
+    fn all(&self, name: &str) -> &[String] {
+        self.values.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+/// Split argv into global flags, the command, and its flags
+fn split_command(args: &[String]) -> Result<(Flags, &str, Flags), CliError> {
+    // The command is the first token that is neither a flag nor a flag's value
+    let mut position = 0;
+    while args.get(position).is_some_and(|arg| arg.starts_with("--")) {
+        position += if args[position] == "--dry-run" { 1 } else { 2 };
+    }
+    if position >= args.len() {
+        return Err(CliError::Usage("missing command".to_string()));
+    }
+    Ok((
+        Flags::parse(&args[..position])?,
+        args[position].as_str(),
+        Flags::parse(&args[position + 1..])?,
+    ))
+}
+
+/// Build the contract calls for a command
+fn plan(command: &str, flags: &Flags) -> Result<Vec<Invocation>, CliError> {
+    let invocations = match command {
+        "initialize" => vec![Invocation::new("initialize").arg("admin", flags.required("admin")?)],
+        "issue" => vec![Invocation::new("issue_credits")
+            .arg("issuer", flags.required("issuer")?)
+            .arg("beneficiary", flags.required("beneficiary")?)
+            .arg("amount", flags.required("amount")?)
+            .arg("purpose", flags.required("purpose")?)
+            .arg("expires_at", flags.required("expires-at")?)],
+        "issue-batch" => {
+            let issuer = flags.required("issuer")?;
+            let path = flags.required("file")?;
+            let input = std::fs::read_to_string(path)
+                .map_err(|e| CliError::Usage(format!("could not read {}: {}", path, e)))?;
+            batch::parse(&input)?
+                .iter()
+                .map(|row| row.invocation(issuer))
+                .collect()
+        }
+        "clawback" => {
+            vec![Invocation::new("clawback").arg("beneficiary", flags.required("beneficiary")?)]
+        }
+        "sweep-expired" => {
+            flags.required("beneficiary")?;
+            flags
+                .all("beneficiary")
+                .iter()
+                .map(|b| Invocation::new("sweep_expired").arg("beneficiary", b.clone()))
+                .collect()
+        }
+        "report" => {
+            let mut invocations = vec![
+                Invocation::new("total_issued"),
+                Invocation::new("total_burned"),
+                Invocation::new("account_count"),
+            ];
+            for beneficiary in flags.all("beneficiary") {
+                invocations.push(Invocation::new("balance").arg("account", beneficiary.clone()));
+            }
+            invocations
+        }
+        _ => return Err(CliError::Usage(format!("unknown command `{}`", command))),
+    };
+    Ok(invocations)
+}
+
+/// Compare the printed return value of `instance_id` with the expected id
+fn check_instance(expected: &str, output: &str) -> Result<(), CliError> {
+    let found = output.trim().trim_matches('"');
+    if found != expected {
+        return Err(CliError::WrongInstance {
+            expected: expected.to_string(),
+            found: found.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<(), CliError> {
+    let (global, command, flags) = split_command(args)?;
+    let target = Target {
+        contract_id: global.required("id")?.to_string(),
+        source: global.required("source")?.to_string(),
+        network: global.get("network").unwrap_or("testnet").to_string(),
+    };
+    let invocations = plan(command, &flags)?;
+    let instance_check = Invocation::new("instance_id");
+
+    if global.dry_run {
+        if global.get("instance").is_some() {
+            println!("stellar {}", instance_check.argv(&target).join(" "));
+        }
+        for invocation in &invocations {
+            println!("stellar {}", invocation.argv(&target).join(" "));
+        }
+        return Ok(());
+    }
+
+    if let Some(expected) = global.get("instance") {
+        check_instance(expected, &instance_check.run(&target)?)?;
+    }
+    if command == "report" {
+        println!("metric,subject,value");
+    }
+    for invocation in &invocations {
+        let output = invocation.run(&target)?;
+        match command {
+            "report" => {
+                let subject = invocation
+                    .args
+                    .first()
+                    .map(|(_, v)| v.as_str())
+                    .unwrap_or("");
+                println!("{},{},{}", invocation.function, subject, output);
+            }
+            _ => println!("{}", output),
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            if let CliError::Usage(_) = error {
+                eprintln!("{}", USAGE);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_split_command() {
+        let args = args(
+            "--id C1 --dry-run --source admin sweep-expired --beneficiary G1 --beneficiary G2",
+        );
+        let (global, command, flags) = split_command(&args).unwrap();
+
+        assert!(global.dry_run);
+        assert_eq!(global.get("id"), Some("C1"));
+        assert_eq!(command, "sweep-expired");
+        assert_eq!(plan(command, &flags).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_check_instance() {
+        assert_eq!(check_instance("eu_west", "\"eu_west\"\n"), Ok(()));
+        assert_eq!(
+            check_instance("eu_west", "null"),
+            Err(CliError::WrongInstance {
+                expected: "eu_west".to_string(),
+                found: "null".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_plan_rejects_missing_flags() {
+        let args = args("--id C1 --source admin clawback");
+        let (_, command, flags) = split_command(&args).unwrap();
+
+        assert_eq!(
+            plan(command, &flags),
+            Err(CliError::Usage("missing --beneficiary".to_string()))
+        );
+    }
+}
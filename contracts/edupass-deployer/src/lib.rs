@@ -0,0 +1,171 @@
+#![no_std]
+//! Deploys an EduPass token from its uploaded wasm, initializes it and wires
+//! its role addresses in one transaction, so no one can initialize it first
+//! or use it before it's configured.
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, vec, xdr::ToXdr, Address, Bytes, BytesN,
+    Env, IntoVal, Symbol, Val, Vec,
+};
+
+// What to deploy and how to wire it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeployConfig {
+    pub token_wasm_hash: BytesN<32>, // Uploaded EduPass token wasm
+    pub salt: BytesN<32>,            // Distinguishes deployments by the same admin
+    pub admin: Address,
+    pub governance: Option<Address>,
+    pub compliance: Option<Address>,
+    pub treasurer: Option<Address>,
+    pub instance_id: Option<Symbol>,
+}
+
+#[contract]
+pub struct EduPassDeployer;
+
+#[contractimpl]
+impl EduPassDeployer {
+    /// Deploy and initialize a token for `config.admin` (the admin), set
+    /// the role addresses given and emit a `deployed` event with its
+    /// address. The admin's authorization covers the wiring calls, which
+    /// the token checks against its new admin.
+    pub fn deploy(env: Env, config: DeployConfig) -> Address {
+        config.admin.require_auth();
+
+        let token = env
+            .deployer()
+            .with_current_contract(admin_salt(&env, &config.admin, &config.salt))
+            .deploy(config.token_wasm_hash);
+        invoke(&env, &token, "initialize", config.admin.into_val(&env));
+        if let Some(governance) = config.governance {
+            invoke(&env, &token, "set_governance", governance.into_val(&env));
+        }
+        if let Some(compliance) = config.compliance {
+            invoke(
+                &env,
+                &token,
+                "set_compliance",
+                Some(compliance).into_val(&env),
+            );
+        }
+        if let Some(treasurer) = config.treasurer {
+            invoke(
+                &env,
+                &token,
+                "set_treasurer",
+                Some(treasurer).into_val(&env),
+            );
+        }
+        if let Some(instance_id) = config.instance_id {
+            invoke(
+                &env,
+                &token,
+                "set_instance_id",
+                Some(instance_id).into_val(&env),
+            );
+        }
+
+        env.events()
+            .publish((symbol_short!("deployed"), config.admin), token.clone());
+        token
+    }
+
+    /// Get the address `deploy` gives a token for `admin` and `salt`
+    pub fn deployed_address(env: Env, admin: Address, salt: BytesN<32>) -> Address {
+        env.deployer()
+            .with_current_contract(admin_salt(&env, &admin, &salt))
+            .deployed_address()
+    }
+}
+
+// Scopes a salt to its admin so others can't take a deployment's address
+fn admin_salt(env: &Env, admin: &Address, salt: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = Bytes::new(env);
+    preimage.append(&admin.clone().to_xdr(env));
+    preimage.append(&salt.clone().into());
+    env.crypto().sha256(&preimage).into()
+}
+
+// Calls a single-argument token setter
+fn invoke(env: &Env, token: &Address, function: &str, arg: Val) {
+    let args: Vec<Val> = vec![env, arg];
+    env.invoke_contract::<()>(token, &Symbol::new(env, function), args);
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use edupass_token::EduPassTokenClient;
+    use soroban_sdk::testutils::{Address as _, Events};
+    use soroban_sdk::TryFromVal;
+
+    // Built with `cargo build --target wasm32v1-none --release -p edupass-token`
+    const TOKEN_WASM: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../target/wasm32v1-none/release/edupass_token.wasm"
+    );
+
+    fn setup() -> (Env, EduPassDeployerClient<'static>, DeployConfig) {
+        let env = Env::default();
+        env.mock_all_auths();
+        // Instantiating the full token wasm is beyond the default budget
+        env.budget().reset_unlimited();
+        let wasm = std::fs::read(TOKEN_WASM).expect("build the edupass-token wasm first");
+        let token_wasm_hash = env
+            .deployer()
+            .upload_contract_wasm(Bytes::from_slice(&env, &wasm));
+        let deployer =
+            EduPassDeployerClient::new(&env, &env.register_contract(None, EduPassDeployer));
+        let config = DeployConfig {
+            token_wasm_hash,
+            salt: BytesN::from_array(&env, &[1; 32]),
+            admin: Address::generate(&env),
+            governance: Some(Address::generate(&env)),
+            compliance: Some(Address::generate(&env)),
+            treasurer: None,
+            instance_id: Some(symbol_short!("mainnet")),
+        };
+        (env, deployer, config)
+    }
+
+    #[test]
+    fn test_deploys_wired_token() {
+        let (env, deployer, config) = setup();
+        let expected = deployer.deployed_address(&config.admin, &config.salt);
+        let address = deployer.deploy(&config);
+        assert_eq!(address, expected);
+        let (_, topics, data) = env.events().all().last().unwrap();
+        assert_eq!(
+            topics,
+            (symbol_short!("deployed"), config.admin.clone()).into_val(&env)
+        );
+        assert_eq!(Address::try_from_val(&env, &data), Ok(address.clone()));
+
+        let token = EduPassTokenClient::new(&env, &address);
+        assert_eq!(token.governance(), config.governance);
+        assert_eq!(token.compliance(), config.compliance);
+        assert_eq!(token.treasurer(), None);
+        assert_eq!(token.instance_id(), config.instance_id);
+        // Already initialized for the admin, who can go on configuring it
+        assert!(token.try_initialize(&Address::generate(&env)).is_err());
+        token.set_treasurer(&Some(config.admin.clone()));
+        assert_eq!(token.treasurer(), Some(config.admin));
+    }
+
+    #[test]
+    fn test_salts_are_scoped_to_the_admin() {
+        let (env, deployer, config) = setup();
+        let first = deployer.deploy(&config);
+        assert!(deployer.try_deploy(&config).is_err());
+
+        // The same salt gives another admin its own address
+        let other = DeployConfig {
+            admin: Address::generate(&env),
+            ..config
+        };
+        assert_ne!(deployer.deploy(&other), first);
+    }
+}
@@ -0,0 +1,244 @@
+//! Indexes EduPass contract events from soroban-rpc `getEvents` pages into
+//! a queryable ledger of issuance, transfer, redemption, expiry and refund
+//! activity, in memory or as SQL for SQLite/Postgres. Telemetry events from
+//! builds with the token's `telemetry` feature are summed per entrypoint,
+//! and other versioned events are counted per topic.
+
+mod record;
+mod rpc;
+mod sql;
+mod store;
+
+use std::fmt;
+
+pub use record::{Entry, Record};
+pub use rpc::{get_events_request, parse_get_events, Page, Resume, MAX_PAGE};
+pub use sql::{insert, SCHEMA};
+pub use store::{DonorTotal, Index, OperationCalls, Settlement};
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum IndexError {
+    /// The RPC server returned an error object
+    Rpc(String),
+    /// The response was not a well-formed getEvents result
+    Response(String),
+    /// An event topic or value was not valid XDR
+    Xdr(String),
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::Rpc(error) => write!(f, "rpc error: {}", error),
+            IndexError::Response(reason) => write!(f, "malformed response: {}", reason),
+            IndexError::Xdr(reason) => write!(f, "invalid xdr: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for IndexError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edupass_token::{Contribution, EduPassToken, EduPassTokenClient};
+    use serde_json::json;
+    use soroban_sdk::testutils::{Address as _, Events};
+    use soroban_sdk::xdr::{Limits, ScVal, WriteXdr};
+    use soroban_sdk::{vec, Address, Env, String as SorobanString, Symbol, TryFromVal};
+
+    // Builds getEvents responses from the events of test invocations
+    struct Recorder {
+        seen: u32,
+        events: Vec<serde_json::Value>,
+    }
+
+    impl Recorder {
+        // Record the events emitted since the last call as one transaction
+        fn capture(&mut self, env: &Env, tx_hash: &str) {
+            let all = env.events().all();
+            for (_, topics, data) in all.slice(self.seen..).iter() {
+                let topics: Vec<_> = topics
+                    .iter()
+                    .map(|topic| {
+                        ScVal::try_from_val(env, &topic)
+                            .unwrap()
+                            .to_xdr_base64(Limits::none())
+                            .unwrap()
+                    })
+                    .collect();
+                let value = ScVal::try_from_val(env, &data).unwrap();
+                self.events.push(json!({
+                    "type": "contract",
+                    "ledger": 100,
+                    "id": format!("{:019}-{:010}", 100, self.events.len()),
+                    "txHash": tx_hash,
+                    "topic": topics,
+                    "value": value.to_xdr_base64(Limits::none()).unwrap(),
+                    "inSuccessfulContractCall": true,
+                }));
+            }
+            self.seen = all.len();
+        }
+
+        fn response(&self) -> std::string::String {
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "events": self.events, "latestLedger": 120 },
+            })
+            .to_string()
+        }
+    }
+
+    #[test]
+    fn test_index_program_activity() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+        let mut recorder = Recorder {
+            seen: 0,
+            events: Vec::new(),
+        };
+
+        let issuer = Address::generate(&env);
+        let donor = Address::generate(&env);
+        let student = Address::generate(&env);
+        let sibling = Address::generate(&env);
+        let school = Address::generate(&env);
+        let purpose = SorobanString::from_str(&env, "Tuition");
+
+        client.initialize(&Address::generate(&env));
+        client.set_instance_id(&Some(Symbol::new(&env, "eu_west")));
+        client.issue_credits(&issuer, &student, &1000, &purpose, &1735689600);
+        recorder.capture(&env, "tx1");
+        let contributions = vec![
+            &env,
+            Contribution {
+                funder: issuer.clone(),
+                amount: 300,
+            },
+            Contribution {
+                funder: donor.clone(),
+                amount: 200,
+            },
+        ];
+        client.issue_cofunded(&sibling, &contributions, &purpose, &1735689600);
+        recorder.capture(&env, "tx2");
+        client.transfer(&student, &school, &600);
+        recorder.capture(&env, "tx3");
+        client.burn(&school, &400);
+        recorder.capture(&env, "tx4");
+        client.clawback(&sibling);
+        recorder.capture(&env, "tx5");
+        let escrow_id =
+            client.issue_to_escrow(&issuer, &sibling, &school, &50, &purpose, &1735689600);
+        client.release_escrow(&sibling, &escrow_id);
+        recorder.capture(&env, "tx6");
+
+        let page = parse_get_events(&recorder.response()).unwrap();
+        assert_eq!(page.latest_ledger, 120);
+
+        let escrowed = page
+            .entries
+            .iter()
+            .find(|entry| entry.record.kind() == "untracked" && entry.tx_hash == "tx6")
+            .unwrap();
+        assert!(insert(escrowed).contains("'untracked', 'escrowed', NULL, 0)"));
+
+        let mut index = Index::new();
+        index.ingest(page.entries.clone());
+        index.ingest(page.entries);
+
+        let student = student.to_string().to_string();
+        let history: Vec<_> = index
+            .beneficiary_history(&student)
+            .iter()
+            .map(|entry| entry.record.kind())
+            .collect();
+        assert_eq!(history, ["issued", "transfer"]);
+        let transfer = &index.beneficiary_history(&student)[1].record;
+        assert!(matches!(
+            transfer,
+            Record::Transferred { authorizer: Some(authorizer), .. } if *authorizer == student
+        ));
+
+        assert_eq!(
+            index.school_settlements(&school.to_string().to_string()),
+            Settlement {
+                received: 600,
+                redeemed: 400
+            }
+        );
+
+        let totals = index.donor_totals();
+        assert_eq!(totals[&issuer.to_string().to_string()].funded, 1300);
+        assert_eq!(
+            totals[&donor.to_string().to_string()],
+            DonorTotal {
+                funded: 200,
+                refunded: 200
+            }
+        );
+
+        // Events outside the ledger are counted under their topic
+        let untracked: Vec<_> = index.untracked_events().into_iter().collect();
+        assert_eq!(
+            untracked,
+            [
+                ("clawback".to_string(), 1),
+                ("escrowack".to_string(), 1),
+                ("escrowed".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sum_telemetry_per_entrypoint() {
+        // The token only emits telemetry with its feature on, so the
+        // events are built by hand
+        use soroban_sdk::xdr::{ScSymbol, ScVec};
+        let symbol = |name: &str| ScVal::Symbol(ScSymbol(name.try_into().unwrap()));
+        let telemetry = |size: u32| {
+            let topics = [symbol("telemetry"), ScVal::U32(5), symbol("clawback")];
+            let data = ScVal::Vec(Some(ScVec(
+                [ScVal::Void, ScVal::U32(size)].try_into().unwrap(),
+            )));
+            Record::decode(&topics, &data).unwrap()
+        };
+
+        let mut index = Index::new();
+        index.ingest((0..2).map(|i| Entry {
+            id: format!("{:019}-{:010}", 100, i),
+            ledger: 100,
+            tx_hash: format!("tx{}", i),
+            record: telemetry(5),
+        }));
+        assert_eq!(
+            index.operation_calls()["clawback"],
+            OperationCalls { calls: 2, size: 10 }
+        );
+        let entry = Entry {
+            id: "1".to_string(),
+            ledger: 100,
+            tx_hash: "tx".to_string(),
+            record: telemetry(3),
+        };
+        assert!(insert(&entry).contains("'telemetry', 'clawback', NULL, 3)"));
+    }
+
+    #[test]
+    fn test_get_events_request_and_errors() {
+        let request: serde_json::Value =
+            serde_json::from_str(&get_events_request("CABC", &Resume::Ledger(50), 20_000)).unwrap();
+        assert_eq!(request["params"]["startLedger"], 50);
+        assert_eq!(request["params"]["pagination"]["limit"], MAX_PAGE);
+
+        assert_eq!(
+            parse_get_events(r#"{"error": {"code": -32600}}"#).err(),
+            Some(IndexError::Rpc(r#"{"code":-32600}"#.to_string()))
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! `edupass-indexer`: glue between soroban-rpc and a SQL database.
+//!
+//! schema                          print the table and views
+//! request <CONTRACT_ID> <LEDGER>  print a getEvents body starting at a ledger
+//! request <CONTRACT_ID> @<CURSOR> print a getEvents body resuming at a cursor
+//! sql                             read a getEvents response on stdin and print
+//!                                 inserts; the resume cursor goes to stderr
+
+use std::io::Read;
+use std::process::ExitCode;
+
+use edupass_indexer::{get_events_request, insert, parse_get_events, Resume, MAX_PAGE, SCHEMA};
+
+const USAGE: &str = "usage: edupass-indexer schema | request <CONTRACT_ID> <LEDGER|@CURSOR> | sql";
+
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [command] if command == "schema" => print!("{}", SCHEMA),
+        [command, contract_id, from] if command == "request" => {
+            let resume = match from.strip_prefix('@') {
+                Some(cursor) => Resume::Cursor(cursor.to_string()),
+                None => Resume::Ledger(
+                    from.parse()
+                        .map_err(|_| format!("invalid ledger `{}`", from))?,
+                ),
+            };
+            println!("{}", get_events_request(contract_id, &resume, MAX_PAGE));
+        }
+        [command] if command == "sql" => {
+            let mut response = String::new();
+            std::io::stdin()
+                .read_to_string(&mut response)
+                .map_err(|e| e.to_string())?;
+            let page = parse_get_events(&response).map_err(|e| e.to_string())?;
+            for entry in &page.entries {
+                println!("{}", insert(entry));
+            }
+            if let Some(cursor) = page.cursor {
+                eprintln!("{}", cursor);
+            }
+        }
+        _ => return Err(USAGE.to_string()),
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use stellar_xdr::curr::{ScVal, ScVec};
+
+// Only versioned events are indexed; legacy-layout duplicates are skipped.
+// Version 3 added the authorizer to transfer and burn data, version 4 the
+// allocation metadata to issued data, and version 5 wrapped all data as
+// (instance_id, data).
+const MIN_SCHEMA_VERSION: u32 = 2;
+const MAX_SCHEMA_VERSION: u32 = 5;
+
+/// Program activity decoded from one contract event
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Record {
+    Issued {
+        beneficiary: String,
+        issuer: String,
+        amount: i128,
+    },
+    /// One funder's share of a co-funded issuance
+    Funded {
+        beneficiary: String,
+        funder: String,
+        amount: i128,
+    },
+    /// Credits moved, with whoever authorized the move when the event
+    /// carries it (schema version 3 on)
+    Transferred {
+        from: String,
+        to: String,
+        amount: i128,
+        authorizer: Option<String>,
+    },
+    /// Credits burned by a school or beneficiary
+    Redeemed {
+        account: String,
+        amount: i128,
+        authorizer: Option<String>,
+    },
+    Expired {
+        beneficiary: String,
+        amount: i128,
+    },
+    Refunded {
+        beneficiary: String,
+        funder: String,
+        amount: i128,
+    },
+    /// An entrypoint call and the data size its cost grows with, emitted
+    /// by builds with the `telemetry` feature
+    Telemetry {
+        entrypoint: String,
+        size: u32,
+    },
+    /// A versioned event the index doesn't decode, kept under its topic so
+    /// it is counted rather than lost
+    Untracked {
+        name: String,
+    },
+}
+
+impl Record {
+    /// Decode a contract event, or `None` for events outside the versioned
+    /// layout. Versioned events of other kinds come back as `Untracked`.
+    pub fn decode(topics: &[ScVal], data: &ScVal) -> Option<Record> {
+        let (name, version, keys) = match topics {
+            [ScVal::Symbol(name), ScVal::U32(version), keys @ ..]
+                if (MIN_SCHEMA_VERSION..=MAX_SCHEMA_VERSION).contains(version) =>
+            {
+                (name.to_utf8_string_lossy(), *version, keys)
+            }
+            _ => return None,
+        };
+        Some(Record::tracked(&name, version, keys, data).unwrap_or(Record::Untracked { name }))
+    }
+
+    fn tracked(name: &str, version: u32, keys: &[ScVal], data: &ScVal) -> Option<Record> {
+        let data = match version {
+            ..=4 => data,
+            _ => pair(data)?.1,
+        };
+        let record = match (name, keys) {
+            ("issued", [beneficiary]) => {
+                let (issuer, amount) = match version {
+                    ..=3 => pair(data)?,
+                    _ => {
+                        let [issuer, amount, _metadata] = items(data)? else {
+                            return None;
+                        };
+                        (issuer, amount)
+                    }
+                };
+                Record::Issued {
+                    beneficiary: address(beneficiary)?,
+                    issuer: address(issuer)?,
+                    amount: int(amount)?,
+                }
+            }
+            ("funded", [beneficiary, funder]) => Record::Funded {
+                beneficiary: address(beneficiary)?,
+                funder: address(funder)?,
+                amount: int(data)?,
+            },
+            ("transfer", [from, to]) => {
+                let (amount, authorizer) = authorized(version, data)?;
+                Record::Transferred {
+                    from: address(from)?,
+                    to: address(to)?,
+                    amount,
+                    authorizer,
+                }
+            }
+            ("burn", [account]) => {
+                let (amount, authorizer) = authorized(version, data)?;
+                Record::Redeemed {
+                    account: address(account)?,
+                    amount,
+                    authorizer,
+                }
+            }
+            ("expired", [beneficiary]) => Record::Expired {
+                beneficiary: address(beneficiary)?,
+                amount: int(data)?,
+            },
+            ("refund", [beneficiary, funder]) => Record::Refunded {
+                beneficiary: address(beneficiary)?,
+                funder: address(funder)?,
+                amount: int(data)?,
+            },
+            ("telemetry", [entrypoint]) => Record::Telemetry {
+                entrypoint: symbol(entrypoint)?,
+                size: uint(data)?,
+            },
+            _ => return None,
+        };
+        Some(record)
+    }
+
+    /// Short name used as the `kind` column
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Record::Issued { .. } => "issued",
+            Record::Funded { .. } => "funded",
+            Record::Transferred { .. } => "transfer",
+            Record::Redeemed { .. } => "redeemed",
+            Record::Expired { .. } => "expired",
+            Record::Refunded { .. } => "refund",
+            Record::Telemetry { .. } => "telemetry",
+            Record::Untracked { .. } => "untracked",
+        }
+    }
+
+    /// The (subject, counterparty, amount) columns used by the SQL schema.
+    /// Telemetry fills them with the entrypoint and the call's data size,
+    /// untracked events with the topic name and 0.
+    pub fn columns(&self) -> (&str, Option<&str>, i128) {
+        match self {
+            Record::Issued {
+                beneficiary,
+                issuer,
+                amount,
+            } => (beneficiary, Some(issuer), *amount),
+            Record::Funded {
+                beneficiary,
+                funder,
+                amount,
+            }
+            | Record::Refunded {
+                beneficiary,
+                funder,
+                amount,
+            } => (beneficiary, Some(funder), *amount),
+            Record::Transferred {
+                from, to, amount, ..
+            } => (from, Some(to), *amount),
+            Record::Redeemed {
+                account, amount, ..
+            } => (account, None, *amount),
+            Record::Expired {
+                beneficiary,
+                amount,
+            } => (beneficiary, None, *amount),
+            Record::Telemetry { entrypoint, size } => (entrypoint, None, *size as i128),
+            Record::Untracked { name } => (name, None, 0),
+        }
+    }
+
+    /// Whether the record touches `account`
+    pub fn involves(&self, account: &str) -> bool {
+        let (subject, counterparty, _) = self.columns();
+        subject == account || counterparty == Some(account)
+    }
+}
+
+/// A decoded record with its position in the ledger
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    pub id: String, // RPC event id, unique and ordered
+    pub ledger: u32,
+    pub tx_hash: String,
+    pub record: Record,
+}
+
+fn address(val: &ScVal) -> Option<String> {
+    match val {
+        ScVal::Address(address) => Some(address.to_string()),
+        _ => None,
+    }
+}
+
+fn uint(val: &ScVal) -> Option<u32> {
+    match val {
+        ScVal::U32(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn symbol(val: &ScVal) -> Option<String> {
+    match val {
+        ScVal::Symbol(symbol) => Some(symbol.to_utf8_string_lossy()),
+        _ => None,
+    }
+}
+
+fn int(val: &ScVal) -> Option<i128> {
+    match val {
+        ScVal::I128(parts) => Some(((parts.hi as i128) << 64) | parts.lo as i128),
+        _ => None,
+    }
+}
+
+// Amount and authorizer of a transfer or burn; version 2 data is the bare
+// amount
+fn authorized(version: u32, data: &ScVal) -> Option<(i128, Option<String>)> {
+    if version < 3 {
+        return Some((int(data)?, None));
+    }
+    let (amount, authorizer) = pair(data)?;
+    Some((int(amount)?, Some(address(authorizer)?)))
+}
+
+fn pair(val: &ScVal) -> Option<(&ScVal, &ScVal)> {
+    match items(val)? {
+        [first, second] => Some((first, second)),
+        _ => None,
+    }
+}
+
+fn items(val: &ScVal) -> Option<&[ScVal]> {
+    match val {
+        ScVal::Vec(Some(ScVec(items))) => Some(items),
+        _ => None,
+    }
+}
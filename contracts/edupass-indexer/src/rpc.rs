@@ -0,0 +1,104 @@
+use serde_json::{json, Value};
+use stellar_xdr::curr::{Limits, ReadXdr, ScVal};
+
+use crate::{Entry, IndexError, Record};
+
+// Largest page soroban-rpc serves for getEvents
+pub const MAX_PAGE: u32 = 10_000;
+
+/// Where the next getEvents request should resume
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Resume {
+    Ledger(u32),
+    Cursor(String),
+}
+
+/// One page of a getEvents response
+#[derive(Debug)]
+pub struct Page {
+    pub entries: Vec<Entry>,
+    pub latest_ledger: u32,
+    pub cursor: Option<String>, // Resume point, absent on an empty page
+}
+
+/// JSON-RPC body for a getEvents request over one contract's events
+pub fn get_events_request(contract_id: &str, resume: &Resume, limit: u32) -> String {
+    let mut params = json!({
+        "filters": [{ "type": "contract", "contractIds": [contract_id] }],
+        "pagination": { "limit": limit.min(MAX_PAGE) },
+    });
+    match resume {
+        Resume::Ledger(ledger) => params["startLedger"] = json!(ledger),
+        Resume::Cursor(cursor) => params["pagination"]["cursor"] = json!(cursor),
+    }
+    json!({ "jsonrpc": "2.0", "id": 1, "method": "getEvents", "params": params }).to_string()
+}
+
+/// Decode a getEvents response, keeping the events in the versioned layout
+pub fn parse_get_events(response: &str) -> Result<Page, IndexError> {
+    let response: Value =
+        serde_json::from_str(response).map_err(|e| IndexError::Response(e.to_string()))?;
+    if let Some(error) = response.get("error") {
+        return Err(IndexError::Rpc(error.to_string()));
+    }
+    let result = &response["result"];
+    let events = result["events"]
+        .as_array()
+        .ok_or_else(|| IndexError::Response("missing result.events".to_string()))?;
+
+    let mut entries = Vec::new();
+    for event in events {
+        // Events of reverted calls are reported but never took effect
+        if event["inSuccessfulContractCall"] == Value::Bool(false) {
+            continue;
+        }
+        let topics = event["topic"]
+            .as_array()
+            .ok_or_else(|| IndexError::Response("missing event topic".to_string()))?
+            .iter()
+            .map(sc_val)
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = sc_val(&event["value"])?;
+        if let Some(record) = Record::decode(&topics, &data) {
+            entries.push(Entry {
+                id: string(event, "id")?,
+                ledger: number(event, "ledger")?,
+                tx_hash: event["txHash"].as_str().unwrap_or_default().to_string(),
+                record,
+            });
+        }
+    }
+
+    // Older RPC versions only return per-event paging tokens
+    let cursor = result["cursor"]
+        .as_str()
+        .or_else(|| events.last().and_then(|e| e["pagingToken"].as_str()))
+        .map(str::to_string);
+
+    Ok(Page {
+        entries,
+        latest_ledger: number(result, "latestLedger")?,
+        cursor,
+    })
+}
+
+fn sc_val(value: &Value) -> Result<ScVal, IndexError> {
+    let encoded = value
+        .as_str()
+        .ok_or_else(|| IndexError::Response("event value is not base64 XDR".to_string()))?;
+    ScVal::from_xdr_base64(encoded, Limits::none()).map_err(|e| IndexError::Xdr(e.to_string()))
+}
+
+fn string(value: &Value, field: &str) -> Result<String, IndexError> {
+    value[field]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| IndexError::Response(format!("missing {}", field)))
+}
+
+fn number(value: &Value, field: &str) -> Result<u32, IndexError> {
+    value[field]
+        .as_u64()
+        .map(|n| n as u32)
+        .ok_or_else(|| IndexError::Response(format!("missing {}", field)))
+}
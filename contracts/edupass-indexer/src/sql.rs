@@ -0,0 +1,78 @@
+use crate::Entry;
+
+/// Table and query views, valid for both SQLite and Postgres. The views
+/// mirror the `Index` queries for dashboards reading the database directly.
+pub const SCHEMA: &str = "\
+CREATE TABLE IF NOT EXISTS edupass_events (
+    id TEXT PRIMARY KEY,
+    ledger INTEGER NOT NULL,
+    tx_hash TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    counterparty TEXT,
+    amount NUMERIC NOT NULL
+);
+CREATE INDEX IF NOT EXISTS edupass_events_subject ON edupass_events (subject);
+CREATE INDEX IF NOT EXISTS edupass_events_counterparty ON edupass_events (counterparty);
+
+DROP VIEW IF EXISTS edupass_history;
+CREATE VIEW edupass_history AS
+    SELECT subject AS account, id, ledger, kind, counterparty, amount FROM edupass_events
+    UNION ALL
+    SELECT counterparty AS account, id, ledger, kind, subject, amount FROM edupass_events
+    WHERE counterparty IS NOT NULL;
+
+DROP VIEW IF EXISTS edupass_school_settlements;
+CREATE VIEW edupass_school_settlements AS
+    SELECT school, SUM(received) AS received, SUM(redeemed) AS redeemed FROM (
+        SELECT counterparty AS school, amount AS received, 0 AS redeemed
+        FROM edupass_events WHERE kind = 'transfer'
+        UNION ALL
+        SELECT subject, 0, amount FROM edupass_events WHERE kind = 'redeemed'
+    ) AS settlements GROUP BY school;
+
+DROP VIEW IF EXISTS edupass_donor_totals;
+CREATE VIEW edupass_donor_totals AS
+    SELECT donor, SUM(funded) AS funded, SUM(refunded) AS refunded FROM (
+        SELECT counterparty AS donor, amount AS funded, 0 AS refunded
+        FROM edupass_events WHERE kind = 'issued'
+        AND tx_hash NOT IN (SELECT tx_hash FROM edupass_events WHERE kind = 'funded')
+        UNION ALL
+        SELECT counterparty, amount, 0 FROM edupass_events WHERE kind = 'funded'
+        UNION ALL
+        SELECT counterparty, 0, amount FROM edupass_events WHERE kind = 'refund'
+    ) AS donations GROUP BY donor;
+
+DROP VIEW IF EXISTS edupass_operation_costs;
+DROP VIEW IF EXISTS edupass_operation_calls;
+CREATE VIEW edupass_operation_calls AS
+    SELECT subject AS entrypoint, COUNT(*) AS calls, SUM(amount) AS size
+    FROM edupass_events WHERE kind = 'telemetry' GROUP BY subject;
+
+DROP VIEW IF EXISTS edupass_untracked_events;
+CREATE VIEW edupass_untracked_events AS
+    SELECT subject AS topic, COUNT(*) AS events
+    FROM edupass_events WHERE kind = 'untracked' GROUP BY subject;
+";
+
+/// Idempotent insert for one entry
+pub fn insert(entry: &Entry) -> String {
+    let (subject, counterparty, amount) = entry.record.columns();
+    format!(
+        "INSERT INTO edupass_events (id, ledger, tx_hash, kind, subject, counterparty, amount) \
+         VALUES ({}, {}, {}, '{}', {}, {}, {}) ON CONFLICT (id) DO NOTHING;",
+        quote(&entry.id),
+        entry.ledger,
+        quote(&entry.tx_hash),
+        entry.record.kind(),
+        quote(subject),
+        counterparty
+            .map(quote)
+            .unwrap_or_else(|| "NULL".to_string()),
+        amount,
+    )
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
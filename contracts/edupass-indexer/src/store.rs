@@ -0,0 +1,134 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{Entry, Record};
+
+/// Credits a school received in transfers and redeemed through burn
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Settlement {
+    pub received: i128,
+    pub redeemed: i128,
+}
+
+/// Credits a donor put into programs and got back through refunds
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DonorTotal {
+    pub funded: i128,
+    pub refunded: i128,
+}
+
+/// Telemetry totals for one entrypoint
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OperationCalls {
+    pub calls: u64,
+    pub size: u64, // Data sizes summed over the calls
+}
+
+/// In-memory ledger of indexed program activity, in event order
+#[derive(Debug, Default)]
+pub struct Index {
+    entries: Vec<Entry>,
+    ids: BTreeSet<String>,
+}
+
+impl Index {
+    pub fn new() -> Self {
+        Index::default()
+    }
+
+    /// Add entries, ignoring ones already indexed so pages can be replayed
+    pub fn ingest(&mut self, entries: impl IntoIterator<Item = Entry>) {
+        for entry in entries {
+            if self.ids.insert(entry.id.clone()) {
+                self.entries.push(entry);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Every record touching an account, oldest first
+    pub fn beneficiary_history(&self, account: &str) -> Vec<&Entry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.record.involves(account))
+            .collect()
+    }
+
+    pub fn school_settlements(&self, school: &str) -> Settlement {
+        let mut settlement = Settlement::default();
+        for entry in &self.entries {
+            match &entry.record {
+                Record::Transferred { to, amount, .. } if to == school => {
+                    settlement.received += amount
+                }
+                Record::Redeemed {
+                    account, amount, ..
+                } if account == school => settlement.redeemed += amount,
+                _ => {}
+            }
+        }
+        settlement
+    }
+
+    /// Totals per donor. Direct issuance is credited to the issuer; a
+    /// co-funded issuance is credited to each funder's share instead.
+    pub fn donor_totals(&self) -> BTreeMap<String, DonorTotal> {
+        let cofunded: BTreeSet<&str> = self
+            .entries
+            .iter()
+            .filter(|entry| matches!(entry.record, Record::Funded { .. }))
+            .map(|entry| entry.tx_hash.as_str())
+            .collect();
+
+        let mut totals: BTreeMap<String, DonorTotal> = BTreeMap::new();
+        for entry in &self.entries {
+            match &entry.record {
+                Record::Issued { issuer, amount, .. }
+                    if !cofunded.contains(entry.tx_hash.as_str()) =>
+                {
+                    totals.entry(issuer.clone()).or_default().funded += amount
+                }
+                Record::Funded { funder, amount, .. } => {
+                    totals.entry(funder.clone()).or_default().funded += amount
+                }
+                Record::Refunded { funder, amount, .. } => {
+                    totals.entry(funder.clone()).or_default().refunded += amount
+                }
+                _ => {}
+            }
+        }
+        totals
+    }
+
+    /// Telemetry totals per entrypoint, for cost dashboards to price with
+    /// measured costs
+    pub fn operation_calls(&self) -> BTreeMap<String, OperationCalls> {
+        let mut calls: BTreeMap<String, OperationCalls> = BTreeMap::new();
+        for entry in &self.entries {
+            if let Record::Telemetry { entrypoint, size } = &entry.record {
+                let total = calls.entry(entrypoint.clone()).or_default();
+                total.calls += 1;
+                total.size += *size as u64;
+            }
+        }
+        calls
+    }
+
+    /// Count of events per topic the index doesn't decode, so new or
+    /// malformed events show up instead of vanishing from the totals
+    pub fn untracked_events(&self) -> BTreeMap<String, u64> {
+        let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+        for entry in &self.entries {
+            if let Record::Untracked { name } = &entry.record {
+                *counts.entry(name.clone()).or_default() += 1;
+            }
+        }
+        counts
+    }
+}
@@ -0,0 +1,73 @@
+use core::fmt;
+
+use edupass_token::Error;
+use soroban_sdk::InvokeError;
+
+/// Failure of a contract call, with contract error codes decoded
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SdkError {
+    /// The contract rejected the call with one of its error codes
+    Contract(Error),
+    /// The host failed the call (auth, budget, storage) without a contract code
+    Host(soroban_sdk::Error),
+    /// The call aborted without any error code
+    Abort,
+    /// The return value could not be decoded
+    Conversion,
+    /// Issuance parameters were incomplete
+    MissingField(&'static str),
+    /// The contract is not the deployment the caller expected
+    WrongInstance,
+}
+
+impl fmt::Display for SdkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SdkError::Contract(error) => write!(f, "contract error {:?}", error),
+            SdkError::Host(error) => write!(f, "host error {:?}", error),
+            SdkError::Abort => write!(f, "call aborted"),
+            SdkError::Conversion => write!(f, "could not decode the return value"),
+            SdkError::MissingField(field) => write!(f, "missing issuance field `{}`", field),
+            SdkError::WrongInstance => write!(f, "contract is a different deployment"),
+        }
+    }
+}
+
+impl std::error::Error for SdkError {}
+
+impl From<Error> for SdkError {
+    fn from(error: Error) -> Self {
+        SdkError::Contract(error)
+    }
+}
+
+impl From<soroban_sdk::Error> for SdkError {
+    fn from(error: soroban_sdk::Error) -> Self {
+        match Error::try_from(error) {
+            Ok(error) => SdkError::Contract(error),
+            Err(_) => SdkError::Host(error),
+        }
+    }
+}
+
+impl From<InvokeError> for SdkError {
+    fn from(error: InvokeError) -> Self {
+        match Error::try_from(error) {
+            Ok(error) => SdkError::Contract(error),
+            Err(_) => SdkError::Abort,
+        }
+    }
+}
+
+/// Flatten the nested result of a generated `try_` client call
+pub fn decode<T, C, E>(result: Result<Result<T, C>, Result<E, InvokeError>>) -> Result<T, SdkError>
+where
+    E: Into<SdkError>,
+{
+    match result {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(_)) => Err(SdkError::Conversion),
+        Err(Ok(error)) => Err(error.into()),
+        Err(Err(error)) => Err(error.into()),
+    }
+}
@@ -0,0 +1,82 @@
+use soroban_sdk::{Address, Env, Map, String, Symbol};
+
+use crate::SdkError;
+
+/// Parameters for a direct issuance
+#[derive(Clone, Debug)]
+pub struct IssueParams {
+    pub issuer: Address,
+    pub beneficiary: Address,
+    pub amount: i128,
+    pub purpose: String,
+    pub expires_at: u64,
+    pub metadata: Map<Symbol, String>,
+}
+
+/// Builder for [`IssueParams`]; amount, purpose and expiry are required
+pub struct IssueBuilder {
+    env: Env,
+    issuer: Address,
+    beneficiary: Address,
+    amount: Option<i128>,
+    purpose: Option<String>,
+    expires_at: Option<u64>,
+    metadata: Map<Symbol, String>,
+}
+
+impl IssueBuilder {
+    pub fn new(env: &Env, issuer: &Address, beneficiary: &Address) -> Self {
+        IssueBuilder {
+            env: env.clone(),
+            issuer: issuer.clone(),
+            beneficiary: beneficiary.clone(),
+            amount: None,
+            purpose: None,
+            expires_at: None,
+            metadata: Map::new(env),
+        }
+    }
+
+    pub fn amount(mut self, amount: i128) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn purpose(mut self, purpose: &str) -> Self {
+        self.purpose = Some(String::from_str(&self.env, purpose));
+        self
+    }
+
+    pub fn expires_at(mut self, expires_at: u64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Expire `secs` after the current ledger time
+    pub fn valid_for(mut self, secs: u64) -> Self {
+        self.expires_at = Some(self.env.ledger().timestamp() + secs);
+        self
+    }
+
+    /// Attach an issuer annotation (e.g. case file number)
+    pub fn tag(mut self, key: &str, value: &str) -> Self {
+        self.metadata.set(
+            Symbol::new(&self.env, key),
+            String::from_str(&self.env, value),
+        );
+        self
+    }
+
+    pub fn build(self) -> Result<IssueParams, SdkError> {
+        Ok(IssueParams {
+            issuer: self.issuer,
+            beneficiary: self.beneficiary,
+            amount: self.amount.ok_or(SdkError::MissingField("amount"))?,
+            purpose: self.purpose.ok_or(SdkError::MissingField("purpose"))?,
+            expires_at: self
+                .expires_at
+                .ok_or(SdkError::MissingField("expires_at"))?,
+            metadata: self.metadata,
+        })
+    }
+}
@@ -0,0 +1,262 @@
+//! High-level helpers over the generated `EduPassTokenClient`, so services
+//! don't hand-roll the same glue around the EduPass contract.
+
+mod error;
+mod issue;
+mod pages;
+
+pub use edupass_token::{Allocation, EduPassTokenClient, Error};
+pub use error::{decode, SdkError};
+pub use issue::{IssueBuilder, IssueParams};
+pub use pages::{TaggedBeneficiaries, MAX_PAGE};
+
+use soroban_sdk::{Address, Env, String, Symbol, Vec};
+
+/// Wrapper around a deployed EduPass contract
+pub struct EduPass<'a> {
+    client: EduPassTokenClient<'a>,
+}
+
+impl<'a> EduPass<'a> {
+    pub fn new(env: &Env, contract_id: &Address) -> Self {
+        EduPass {
+            client: EduPassTokenClient::new(env, contract_id),
+        }
+    }
+
+    /// Connect to one named deployment (see `set_instance_id`), failing
+    /// with `WrongInstance` if the contract carries another name or none,
+    /// so a service can't act on the wrong region's contract by mistake
+    pub fn connect(
+        env: &Env,
+        contract_id: &Address,
+        instance_id: &Symbol,
+    ) -> Result<Self, SdkError> {
+        let edupass = Self::new(env, contract_id);
+        match decode(edupass.client.try_instance_id())? {
+            Some(found) if found == *instance_id => Ok(edupass),
+            _ => Err(SdkError::WrongInstance),
+        }
+    }
+
+    /// The underlying generated client, for calls without a helper
+    pub fn client(&self) -> &EduPassTokenClient<'a> {
+        &self.client
+    }
+
+    /// Issue credits, with annotations if any were given
+    pub fn issue(&self, params: &IssueParams) -> Result<Allocation, SdkError> {
+        if params.metadata.is_empty() {
+            decode(self.client.try_issue_credits(
+                &params.issuer,
+                &params.beneficiary,
+                &params.amount,
+                &params.purpose,
+                &params.expires_at,
+            ))
+        } else {
+            decode(self.client.try_issue_tagged(
+                &params.issuer,
+                &params.beneficiary,
+                &params.amount,
+                &params.purpose,
+                &params.expires_at,
+                &params.metadata,
+            ))
+        }
+    }
+
+    /// Retry-safe issuance: if the beneficiary already holds an allocation
+    /// matching `params` (e.g. a retried request whose response was lost),
+    /// return it instead of issuing again
+    pub fn issue_once(&self, params: &IssueParams) -> Result<Allocation, SdkError> {
+        if let Some(existing) = decode(self.client.try_get_allocation(&params.beneficiary))? {
+            if matches(&existing, params) {
+                return Ok(existing);
+            }
+        }
+        self.issue(params)
+    }
+
+    /// Transfer credits
+    pub fn transfer(&self, from: &Address, to: &Address, amount: i128) -> Result<(), SdkError> {
+        decode(self.client.try_transfer(from, to, &amount))
+    }
+
+    /// Check a transfer without submitting it
+    pub fn can_transfer(&self, from: &Address, to: &Address, amount: i128) -> Result<(), SdkError> {
+        decode(self.client.try_can_transfer(from, to, &amount))
+    }
+
+    /// Get an account's balance
+    pub fn balance(&self, account: &Address) -> Result<i128, SdkError> {
+        decode(self.client.try_balance(account))
+    }
+
+    /// Get the allocations of a whole caseload, calling allocations_bulk
+    /// again from where each call stopped until every beneficiary is listed
+    pub fn allocations_bulk(
+        &self,
+        beneficiaries: &Vec<Address>,
+    ) -> Result<Vec<(Address, Vec<Allocation>)>, SdkError> {
+        let mut synced = Vec::new(&self.client.env);
+        while synced.len() < beneficiaries.len() {
+            let rest = beneficiaries.slice(synced.len()..);
+            synced.append(&decode(self.client.try_allocations_bulk(&rest))?);
+        }
+        Ok(synced)
+    }
+
+    /// Iterate over every beneficiary carrying an issuer's tag
+    pub fn tagged<'c>(
+        &'c self,
+        issuer: &Address,
+        key: &Symbol,
+        value: &String,
+    ) -> TaggedBeneficiaries<'c, 'a> {
+        TaggedBeneficiaries::new(&self.client, issuer.clone(), key.clone(), value.clone())
+    }
+}
+
+fn matches(allocation: &Allocation, params: &IssueParams) -> bool {
+    allocation.issuer == params.issuer
+        && allocation.amount == params.amount
+        && allocation.purpose == params.purpose
+        && allocation.expires_at == params.expires_at
+        && allocation.metadata == params.metadata
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edupass_token::EduPassToken;
+    use soroban_sdk::symbol_short;
+    use soroban_sdk::testutils::Address as _;
+
+    fn setup(env: &Env) -> (EduPass<'_>, Address) {
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let edupass = EduPass::new(env, &contract_id);
+        edupass.client().initialize(&Address::generate(env));
+
+        (edupass, contract_id)
+    }
+
+    #[test]
+    fn test_issue_once_is_retry_safe() {
+        let env = Env::default();
+        let (edupass, _) = setup(&env);
+
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let params = IssueBuilder::new(&env, &issuer, &beneficiary)
+            .amount(1000)
+            .purpose("Tuition")
+            .expires_at(1735689600)
+            .tag("case", "CF-17")
+            .build()
+            .unwrap();
+
+        edupass.issue_once(&params).unwrap();
+        edupass.issue_once(&params).unwrap();
+        assert_eq!(edupass.balance(&beneficiary), Ok(1000));
+        assert_eq!(edupass.client().total_issued(), 1000);
+
+        let missing = IssueBuilder::new(&env, &issuer, &beneficiary)
+            .amount(1000)
+            .build();
+        assert_eq!(missing.err(), Some(SdkError::MissingField("purpose")));
+    }
+
+    #[test]
+    fn test_contract_errors_are_typed() {
+        let env = Env::default();
+        let (edupass, _) = setup(&env);
+
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+
+        assert_eq!(
+            edupass.transfer(&beneficiary, &school, 0),
+            Err(SdkError::Contract(Error::InvalidAmount))
+        );
+        assert_eq!(
+            edupass.can_transfer(&beneficiary, &school, 10),
+            Err(SdkError::Contract(Error::InsufficientBalance))
+        );
+    }
+
+    #[test]
+    fn test_connect_checks_instance_id() {
+        let env = Env::default();
+        let (edupass, contract_id) = setup(&env);
+
+        let eu_west = symbol_short!("eu_west");
+        let result = EduPass::connect(&env, &contract_id, &eu_west);
+        assert!(matches!(result, Err(SdkError::WrongInstance)));
+
+        edupass.client().set_instance_id(&Some(eu_west.clone()));
+        assert!(EduPass::connect(&env, &contract_id, &eu_west).is_ok());
+        let result = EduPass::connect(&env, &contract_id, &symbol_short!("us_east"));
+        assert!(matches!(result, Err(SdkError::WrongInstance)));
+    }
+
+    #[test]
+    fn test_tagged_iterates_across_pages() {
+        let env = Env::default();
+        let (edupass, _) = setup(&env);
+
+        env.budget().reset_unlimited();
+
+        let issuer = Address::generate(&env);
+        for _ in 0..MAX_PAGE + 5 {
+            let params = IssueBuilder::new(&env, &issuer, &Address::generate(&env))
+                .amount(10)
+                .purpose("Books")
+                .expires_at(1735689600)
+                .tag("year", "2025")
+                .build()
+                .unwrap();
+            edupass.issue(&params).unwrap();
+        }
+
+        let tagged: std::vec::Vec<Address> = edupass
+            .tagged(
+                &issuer,
+                &symbol_short!("year"),
+                &String::from_str(&env, "2025"),
+            )
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(tagged.len() as u32, MAX_PAGE + 5);
+    }
+
+    #[test]
+    fn test_allocations_bulk_syncs_a_caseload() {
+        let env = Env::default();
+        let (edupass, _) = setup(&env);
+
+        env.budget().reset_unlimited();
+
+        let issuer = Address::generate(&env);
+        let mut caseload = Vec::new(&env);
+        for _ in 0..MAX_PAGE + 5 {
+            let beneficiary = Address::generate(&env);
+            let params = IssueBuilder::new(&env, &issuer, &beneficiary)
+                .amount(10)
+                .purpose("Meals")
+                .expires_at(1735689600)
+                .build()
+                .unwrap();
+            edupass.issue(&params).unwrap();
+            caseload.push_back(beneficiary);
+        }
+
+        let synced = edupass.allocations_bulk(&caseload).unwrap();
+        assert_eq!(synced.len(), MAX_PAGE + 5);
+        let (beneficiary, allocations) = synced.last().unwrap();
+        assert_eq!(beneficiary, caseload.last().unwrap());
+        assert_eq!(allocations.get(0).unwrap().amount, 10);
+    }
+}
@@ -0,0 +1,79 @@
+use edupass_token::EduPassTokenClient;
+use soroban_sdk::{Address, String, Symbol, Vec};
+
+use crate::{decode, SdkError};
+
+// Largest page the contract returns for list queries
+pub const MAX_PAGE: u32 = 100;
+
+/// Iterator over the beneficiaries carrying an issuer's tag, fetching one
+/// page per contract call
+pub struct TaggedBeneficiaries<'c, 'a> {
+    client: &'c EduPassTokenClient<'a>,
+    issuer: Address,
+    key: Symbol,
+    value: String,
+    cursor: u32,
+    page: Option<Vec<Address>>,
+    index: u32,
+    done: bool,
+}
+
+impl<'c, 'a> TaggedBeneficiaries<'c, 'a> {
+    pub(crate) fn new(
+        client: &'c EduPassTokenClient<'a>,
+        issuer: Address,
+        key: Symbol,
+        value: String,
+    ) -> Self {
+        TaggedBeneficiaries {
+            client,
+            issuer,
+            key,
+            value,
+            cursor: 0,
+            page: None,
+            index: 0,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for TaggedBeneficiaries<'_, '_> {
+    type Item = Result<Address, SdkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(page) = &self.page {
+                if self.index < page.len() {
+                    self.index += 1;
+                    return Some(Ok(page.get_unchecked(self.index - 1)));
+                }
+                // A short page is the last one
+                if page.len() < MAX_PAGE {
+                    self.done = true;
+                }
+            }
+            if self.done {
+                return None;
+            }
+
+            let page = match decode(self.client.try_allocations_by_tag(
+                &self.issuer,
+                &self.key,
+                &self.value,
+                &self.cursor,
+                &MAX_PAGE,
+            )) {
+                Ok(page) => page,
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            };
+            self.cursor += page.len();
+            self.index = 0;
+            self.page = Some(page);
+        }
+    }
+}
@@ -0,0 +1,6 @@
+#![no_main]
+
+use edupass_token::fuzz::{issue_credits, IssueInput};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: IssueInput| issue_credits(input));
@@ -0,0 +1,6 @@
+#![no_main]
+
+use edupass_token::fuzz::{stipends, StipendInput};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: StipendInput| stipends(input));
@@ -0,0 +1,6 @@
+#![no_main]
+
+use edupass_token::fuzz::{transfer, TransferInput};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: TransferInput| transfer(input));
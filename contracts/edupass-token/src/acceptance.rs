@@ -0,0 +1,424 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, xdr::ToXdr, Address, BytesN, Env,
+    IntoVal, Map, String, Vec,
+};
+
+use crate::{
+    add_total_issued, allocate, attestations, bonding, checked_add, credit, events, grace,
+    payloads, require_admin, rotation, telemetry, validity, Allocation, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+// Offers left unanswered this long are treated as declined
+const DEFAULT_ACCEPTANCE_TIMEOUT: u64 = 30 * 24 * 60 * 60;
+// Bounds the work done by one reclaim_offers call
+const MAX_RECLAIM_BATCH: u32 = 50;
+
+#[contracttype]
+enum AcceptanceKey {
+    Offer(BytesN<32>),      // Allocation awaiting the beneficiary's consent
+    OfferTerms(BytesN<32>), // Hash of the terms an offer id was issued with
+    AcceptanceTimeout,      // Seconds a beneficiary has to answer an offer
+}
+
+// An allocation held back until the beneficiary accepts it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingAllocation {
+    pub allocation: Allocation,
+    pub accept_by: u64,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Issue credits that are held pending until the beneficiary accepts
+    /// them; returns the allocation id to accept or decline. The id is
+    /// derived from the issuer, beneficiary and `idempotency_key`, so a
+    /// retry with the same terms returns it again without issuing twice;
+    /// reusing the key with other terms fails with AlreadyExists.
+    pub fn offer_credits(
+        env: Env,
+        issuer: Address,
+        beneficiary: Address,
+        amount: i128,
+        purpose: String,
+        expires_at: u64,
+        idempotency_key: BytesN<32>,
+    ) -> BytesN<32> {
+        telemetry::record(&env, "offer_credits");
+        issuer.require_auth();
+
+        let id = Self::derive_allocation_id(
+            env.clone(),
+            issuer.clone(),
+            beneficiary.clone(),
+            idempotency_key,
+        );
+        let terms = terms_hash(&env, amount, &purpose, expires_at);
+        let terms_key = AcceptanceKey::OfferTerms(id.clone());
+        if let Some(issued) = env.storage().persistent().get::<_, BytesN<32>>(&terms_key) {
+            if issued != terms {
+                panic_with_error!(&env, Error::AlreadyExists);
+            }
+            return id;
+        }
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        let expires_at = validity::resolve_expiry(&env, expires_at);
+        attestations::require_unattested_allowed(&env);
+        bonding::require_bonded(&env, &issuer);
+
+        let timeout = acceptance_timeout(&env);
+        let accept_by = env
+            .ledger()
+            .timestamp()
+            .checked_add(timeout)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::Overflow));
+        let pending = PendingAllocation {
+            allocation: Allocation {
+                beneficiary: beneficiary.clone(),
+                issuer,
+                amount,
+                purpose,
+                expires_at,
+                program_id: None,
+                metadata: Map::new(&env),
+                remaining: amount,
+                effective_expires_at: grace::effective_expiry(&env, expires_at),
+            },
+            accept_by,
+        };
+        // Kept until answered or reclaimed, since it holds issued credits
+        env.storage()
+            .persistent()
+            .set(&AcceptanceKey::Offer(id.clone()), &pending);
+        // Kept for good, so however late a retry comes it finds the id
+        env.storage().persistent().set(&terms_key, &terms);
+
+        // Pending credits count as issued from the moment of the offer
+        add_total_issued(&env, &None, amount);
+
+        events::publish(
+            &env,
+            (symbol_short!("offer"), beneficiary),
+            (id.clone(), amount),
+        );
+
+        id
+    }
+
+    /// Accept a pending allocation, crediting the beneficiary
+    pub fn accept_allocation(env: Env, allocation_id: BytesN<32>) -> Allocation {
+        telemetry::record(&env, "accept_allocation");
+        let pending = take_offer(&env, &allocation_id);
+        pending.allocation.beneficiary.require_auth();
+        accept(&env, &allocation_id, pending)
+    }
+
+    /// Accept a pending allocation on the beneficiary's behalf,
+    /// authenticated by their signature instead of transaction auth, so a
+    /// relayer can pay the fees
+    pub fn accept_allocation_signed(
+        env: Env,
+        allocation_id: BytesN<32>,
+        valid_until: u64,
+        signature: BytesN<64>,
+    ) -> Allocation {
+        telemetry::record(&env, "accept_allocation_signed");
+        let pending = take_offer(&env, &allocation_id);
+        payloads::require_signature(
+            &env,
+            &pending.allocation.beneficiary,
+            symbol_short!("accept"),
+            (allocation_id.clone(),).into_val(&env),
+            valid_until,
+            &signature,
+        );
+        accept(&env, &allocation_id, pending)
+    }
+
+    /// Decline a pending allocation, returning its credits to the issuer.
+    /// Once the acceptance window has passed anyone may decline it.
+    pub fn decline_allocation(env: Env, allocation_id: BytesN<32>) -> i128 {
+        telemetry::record(&env, "decline_allocation");
+        let pending = take_offer(&env, &allocation_id);
+        if env.ledger().timestamp() < pending.accept_by {
+            pending.allocation.beneficiary.require_auth();
+        }
+        return_offer(&env, &allocation_id, pending.allocation)
+    }
+
+    /// Return up to 50 offers whose acceptance window has closed to their
+    /// issuers; anyone may call. Ids of offers already answered or still
+    /// open are skipped. Returns the credits returned.
+    pub fn reclaim_offers(env: Env, allocation_ids: Vec<BytesN<32>>) -> i128 {
+        telemetry::record(&env, "reclaim_offers");
+        if allocation_ids.len() > MAX_RECLAIM_BATCH {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+
+        let now = env.ledger().timestamp();
+        let mut returned = 0;
+        for allocation_id in allocation_ids.iter() {
+            match Self::pending_allocation(env.clone(), allocation_id.clone()) {
+                Some(pending) if now >= pending.accept_by => {
+                    env.storage()
+                        .persistent()
+                        .remove(&AcceptanceKey::Offer(allocation_id.clone()));
+                    let amount = return_offer(&env, &allocation_id, pending.allocation);
+                    returned = checked_add(&env, returned, amount);
+                }
+                _ => {}
+            }
+        }
+        returned
+    }
+
+    /// Get the allocation id offer_credits gives an offer from `issuer` to
+    /// `beneficiary` under `idempotency_key`
+    pub fn derive_allocation_id(
+        env: Env,
+        issuer: Address,
+        beneficiary: Address,
+        idempotency_key: BytesN<32>,
+    ) -> BytesN<32> {
+        let preimage = (issuer, beneficiary, idempotency_key).to_xdr(&env);
+        env.crypto().sha256(&preimage).to_bytes()
+    }
+
+    /// Get a pending allocation
+    pub fn pending_allocation(env: Env, allocation_id: BytesN<32>) -> Option<PendingAllocation> {
+        env.storage()
+            .persistent()
+            .get(&AcceptanceKey::Offer(allocation_id))
+    }
+
+    /// Set how long beneficiaries have to answer an offer (admin only)
+    pub fn set_acceptance_timeout(env: Env, timeout_secs: u64) {
+        telemetry::record(&env, "set_acceptance_timeout");
+        require_admin(&env);
+
+        if timeout_secs == 0 {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        env.storage()
+            .instance()
+            .set(&AcceptanceKey::AcceptanceTimeout, &timeout_secs);
+    }
+
+    /// Get the acceptance window for new offers
+    pub fn acceptance_timeout(env: Env) -> u64 {
+        acceptance_timeout(&env)
+    }
+}
+
+// Credit an authenticated beneficiary with an offer taken from storage
+fn accept(env: &Env, allocation_id: &BytesN<32>, pending: PendingAllocation) -> Allocation {
+    let offer = pending.allocation;
+    if env.ledger().timestamp() >= pending.accept_by {
+        panic_with_error!(env, Error::OfferExpired);
+    }
+
+    let allocation = allocate(
+        env,
+        offer.issuer,
+        offer.beneficiary.clone(),
+        offer.amount,
+        offer.purpose,
+        offer.expires_at,
+        offer.program_id,
+        offer.metadata,
+    );
+    events::publish(
+        env,
+        (symbol_short!("accepted"), offer.beneficiary),
+        allocation_id.clone(),
+    );
+
+    allocation
+}
+
+// Credit a declined or reclaimed offer back to its issuer
+fn return_offer(env: &Env, allocation_id: &BytesN<32>, offer: Allocation) -> i128 {
+    credit(env, &rotation::current(env, &offer.issuer), offer.amount);
+    events::publish(
+        env,
+        (symbol_short!("declined"), offer.beneficiary),
+        (allocation_id.clone(), offer.amount),
+    );
+    offer.amount
+}
+
+fn acceptance_timeout(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&AcceptanceKey::AcceptanceTimeout)
+        .unwrap_or(DEFAULT_ACCEPTANCE_TIMEOUT)
+}
+
+// Terms an offer is checked against on retry, as requested (before the
+// expiry is resolved, which depends on when the call lands)
+fn terms_hash(env: &Env, amount: i128, purpose: &String, expires_at: u64) -> BytesN<32> {
+    let terms = (amount, purpose.clone(), expires_at).to_xdr(env);
+    env.crypto().sha256(&terms).to_bytes()
+}
+
+// Remove and return an offer; each offer is answered at most once
+fn take_offer(env: &Env, allocation_id: &BytesN<32>) -> PendingAllocation {
+    let key = AcceptanceKey::Offer(allocation_id.clone());
+    let pending: PendingAllocation = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+    env.storage().persistent().remove(&key);
+    pending
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    fn setup(env: &Env) -> (EduPassTokenClient<'_>, Address, Address, BytesN<32>) {
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_700_000_000);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(env, &contract_id);
+
+        let issuer = Address::generate(env);
+        let beneficiary = Address::generate(env);
+
+        client.initialize(&Address::generate(env));
+        let id = client.offer_credits(
+            &issuer,
+            &beneficiary,
+            &1000,
+            &String::from_str(env, "Tuition"),
+            &1_800_000_000,
+            &BytesN::from_array(env, &[1; 32]),
+        );
+
+        (client, issuer, beneficiary, id)
+    }
+
+    #[test]
+    fn test_accept_allocation() {
+        let env = Env::default();
+        let (client, _, beneficiary, id) = setup(&env);
+
+        // Nothing is spendable until the beneficiary consents
+        assert_eq!(client.balance(&beneficiary), 0);
+        assert_eq!(client.get_allocation(&beneficiary), None);
+        assert_eq!(client.total_issued(), 1000);
+
+        let allocation = client.accept_allocation(&id);
+        assert_eq!(allocation.amount, 1000);
+        assert_eq!(client.balance(&beneficiary), 1000);
+        assert_eq!(client.pending_allocation(&id), None);
+
+        let result = client.try_accept_allocation(&id);
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+    }
+
+    #[test]
+    fn test_decline_and_timeout_return_to_issuer() {
+        let env = Env::default();
+        let (client, issuer, beneficiary, id) = setup(&env);
+
+        assert_eq!(client.decline_allocation(&id), 1000);
+        assert_eq!(client.balance(&issuer), 1000);
+        assert_eq!(client.balance(&beneficiary), 0);
+
+        let id = client.offer_credits(
+            &issuer,
+            &beneficiary,
+            &500,
+            &String::from_str(&env, "Books"),
+            &1_800_000_000,
+            &BytesN::from_array(&env, &[2; 32]),
+        );
+        env.ledger()
+            .set_timestamp(1_700_000_000 + DEFAULT_ACCEPTANCE_TIMEOUT);
+
+        let result = client.try_accept_allocation(&id);
+        assert_eq!(result, Err(Ok(Error::OfferExpired.into())));
+        assert_eq!(client.decline_allocation(&id), 500);
+        assert_eq!(client.balance(&issuer), 1500);
+    }
+
+    #[test]
+    fn test_reclaim_returns_expired_offers() {
+        let env = Env::default();
+        let (client, issuer, beneficiary, id) = setup(&env);
+        let open = client.offer_credits(
+            &issuer,
+            &Address::generate(&env),
+            &300,
+            &String::from_str(&env, "Books"),
+            &1_800_000_000,
+            &BytesN::from_array(&env, &[2; 32]),
+        );
+        let answered = BytesN::from_array(&env, &[9; 32]);
+        let ids = soroban_sdk::vec![&env, id.clone(), open.clone(), answered];
+        assert_eq!(client.reclaim_offers(&ids), 0);
+
+        env.ledger()
+            .set_timestamp(1_700_000_000 + DEFAULT_ACCEPTANCE_TIMEOUT);
+        let late = client.offer_credits(
+            &issuer,
+            &beneficiary,
+            &200,
+            &String::from_str(&env, "Books"),
+            &1_800_000_000,
+            &BytesN::from_array(&env, &[3; 32]),
+        );
+        // Expired offers go back to the issuer; the new one stays open
+        let ids = soroban_sdk::vec![&env, id.clone(), open, late.clone()];
+        assert_eq!(client.reclaim_offers(&ids), 1300);
+        assert_eq!(client.balance(&issuer), 1300);
+        assert_eq!(client.pending_allocation(&id), None);
+        assert!(client.pending_allocation(&late).is_some());
+        assert_eq!(client.reclaim_offers(&ids), 0);
+    }
+
+    #[test]
+    fn test_retried_offer_keeps_its_id() {
+        let env = Env::default();
+        // Long enough that only the terms record is left to find
+        env.ledger()
+            .with_mut(|ledger| ledger.min_persistent_entry_ttl = 2_000_000);
+        let (client, issuer, beneficiary, id) = setup(&env);
+        let key = BytesN::from_array(&env, &[1; 32]);
+        assert_eq!(client.derive_allocation_id(&issuer, &beneficiary, &key), id);
+
+        // Retried before or after the answer, nothing is issued again
+        let purpose = String::from_str(&env, "Tuition");
+        let retry =
+            || client.offer_credits(&issuer, &beneficiary, &1000, &purpose, &1_800_000_000, &key);
+        assert_eq!(retry(), id);
+        assert_eq!(client.total_issued(), 1000);
+        client.accept_allocation(&id);
+        assert_eq!(retry(), id);
+        assert_eq!(client.total_issued(), 1000);
+        assert_eq!(client.pending_allocation(&id), None);
+        // The terms are kept for good, so a retry however late still
+        // finds the id
+        crate::testutils::advance_ledgers(&env, 1_500_000);
+        assert_eq!(retry(), id);
+        assert_eq!(client.total_issued(), 1000);
+        // Ids are the whole hash
+        let preimage = (issuer.clone(), beneficiary.clone(), key.clone()).to_xdr(&env);
+        assert_eq!(id, env.crypto().sha256(&preimage).to_bytes());
+
+        let result =
+            client.try_offer_credits(&issuer, &beneficiary, &999, &purpose, &1_800_000_000, &key);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+        // Keys are scoped to the beneficiary
+        let other = Address::generate(&env);
+        assert_ne!(client.derive_allocation_id(&issuer, &other, &key), id);
+    }
+}
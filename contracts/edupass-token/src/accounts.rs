@@ -0,0 +1,252 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, symbol_short, Address, Env};
+
+use crate::{
+    bonus, demurrage, events, refunds, require_admin, tags, telemetry, Allocation, DataKey,
+    EduPassToken, EduPassTokenClient, Error,
+};
+
+// Bounds the work done by one gc_accounts call
+const MAX_GC_BATCH: u32 = 100;
+
+#[contracttype]
+enum AccountKey {
+    Activity(Address), // Index position and last balance activity of an account
+    AccountAt(u32),    // Account registered at an index position
+    AccountCount,      // Number of index positions assigned
+    GcDormancy,        // Inactivity required before an account may be collected
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Activity {
+    index: u32,
+    last_active: u64,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set how long an empty account must be inactive before it can be
+    /// garbage collected (admin only)
+    pub fn set_gc_dormancy(env: Env, dormancy_secs: u64) {
+        telemetry::record(&env, "set_gc_dormancy");
+        require_admin(&env);
+        env.storage()
+            .instance()
+            .set(&AccountKey::GcDormancy, &dormancy_secs);
+    }
+
+    /// Get the garbage collection dormancy period, if configured
+    pub fn gc_dormancy(env: Env) -> Option<u64> {
+        env.storage().instance().get(&AccountKey::GcDormancy)
+    }
+
+    /// Get the number of account index positions (the end cursor for gc_accounts)
+    pub fn account_count(env: Env) -> u32 {
+        account_count(&env)
+    }
+
+    /// Remove storage entries of dormant accounts with a zero balance and no
+    /// active allocation, scanning up to `limit` index positions from
+    /// `cursor`. Callable by anyone; returns the cursor to continue from.
+    pub fn gc_accounts(env: Env, cursor: u32, limit: u32) -> u32 {
+        telemetry::record(&env, "gc_accounts");
+        collect_accounts(&env, cursor, limit).0
+    }
+}
+
+/// Garbage collect from `cursor` as gc_accounts does; returns the cursor
+/// to continue from and the number of accounts removed
+pub(crate) fn collect_accounts(env: &Env, cursor: u32, limit: u32) -> (u32, u32) {
+    let dormancy: u64 = env
+        .storage()
+        .instance()
+        .get(&AccountKey::GcDormancy)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotConfigured));
+
+    let now = env.ledger().timestamp();
+    let end = cursor
+        .saturating_add(limit.min(MAX_GC_BATCH))
+        .min(account_count(env));
+
+    let mut removed = 0u32;
+    for index in cursor..end {
+        let account: Address = match env
+            .storage()
+            .persistent()
+            .get(&AccountKey::AccountAt(index))
+        {
+            Some(account) => account,
+            None => continue,
+        };
+        if collectable(env, &account, now, dormancy) {
+            remove_account(env, &account);
+            removed += 1;
+        }
+    }
+
+    events::publish(env, (symbol_short!("gc"), cursor), (end, removed));
+
+    (end, removed)
+}
+
+/// Record balance activity for an account, indexing it on first sight
+pub(crate) fn touch(env: &Env, account: &Address) {
+    let key = AccountKey::Activity(account.clone());
+    let now = env.ledger().timestamp();
+
+    let activity = match env.storage().persistent().get::<_, Activity>(&key) {
+        Some(activity) if activity.last_active == now => return,
+        Some(activity) => Activity {
+            index: activity.index,
+            last_active: now,
+        },
+        None => {
+            let index = account_count(env);
+            env.storage()
+                .persistent()
+                .set(&AccountKey::AccountAt(index), account);
+            env.storage()
+                .instance()
+                .set(&AccountKey::AccountCount, &(index + 1));
+            Activity {
+                index,
+                last_active: now,
+            }
+        }
+    };
+    env.storage().persistent().set(&key, &activity);
+}
+
+/// Delete every storage entry keyed by an account, including its index slot
+pub(crate) fn remove_account(env: &Env, account: &Address) {
+    tags::clear_index(env, account);
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Credits(account.clone()));
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Allocations(account.clone()));
+    refunds::clear_funders(env, account);
+    demurrage::remove_account(env, account);
+    bonus::remove_account(env, account);
+
+    let key = AccountKey::Activity(account.clone());
+    if let Some(activity) = env.storage().persistent().get::<_, Activity>(&key) {
+        env.storage()
+            .persistent()
+            .remove(&AccountKey::AccountAt(activity.index));
+        env.storage().persistent().remove(&key);
+    }
+}
+
+fn account_count(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&AccountKey::AccountCount)
+        .unwrap_or(0)
+}
+
+// Only empty, dormant accounts without an unexpired allocation are collected
+fn collectable(env: &Env, account: &Address, now: u64, dormancy: u64) -> bool {
+    let balance: i128 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Credits(account.clone()))
+        .unwrap_or(0);
+    if balance != 0 {
+        return false;
+    }
+
+    let allocation: Option<Allocation> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Allocations(account.clone()));
+    if let Some(allocation) = allocation {
+        if now < allocation.effective_expires_at {
+            return false;
+        }
+    }
+
+    match env
+        .storage()
+        .persistent()
+        .get::<_, Activity>(&AccountKey::Activity(account.clone()))
+    {
+        Some(activity) => now.saturating_sub(activity.last_active) >= dormancy,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::String;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_gc_removes_only_dormant_empty_accounts() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_700_000_000);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let issuer = Address::generate(&env);
+        let spent = Address::generate(&env);
+        let active = Address::generate(&env);
+        let school = Address::generate(&env);
+        let purpose = String::from_str(&env, "Tuition");
+
+        client.initialize(&Address::generate(&env));
+        client.set_gc_dormancy(&(30 * DAY));
+
+        client.issue_credits(&issuer, &spent, &100, &purpose, &(1_700_000_000 + DAY));
+        client.issue_credits(&issuer, &active, &100, &purpose, &(1_700_000_000 + DAY));
+        client.transfer(&spent, &school, &100);
+        assert_eq!(client.account_count(), 3);
+
+        // Not dormant yet
+        env.ledger().set_timestamp(1_700_000_000 + 10 * DAY);
+        assert_eq!(client.gc_accounts(&0, &10), 3);
+        assert!(client.get_allocation(&spent).is_some());
+
+        env.ledger().set_timestamp(1_700_000_000 + 31 * DAY);
+        client.gc_accounts(&0, &10);
+        assert_eq!(client.get_allocation(&spent), None);
+
+        // Accounts holding credits are never touched
+        assert!(client.get_allocation(&active).is_some());
+        assert_eq!(client.balance(&active), 100);
+        assert_eq!(client.balance(&school), 100);
+    }
+
+    #[test]
+    fn test_gc_keeps_unexpired_allocations() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_700_000_000);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let beneficiary = Address::generate(&env);
+
+        client.initialize(&Address::generate(&env));
+        client.set_gc_dormancy(&DAY);
+        client.issue_credits(
+            &Address::generate(&env),
+            &beneficiary,
+            &100,
+            &String::from_str(&env, "Tuition"),
+            &1_800_000_000,
+        );
+        client.burn(&beneficiary, &100);
+
+        env.ledger().set_timestamp(1_700_000_000 + 2 * DAY);
+        client.gc_accounts(&0, &10);
+        assert!(client.get_allocation(&beneficiary).is_some());
+    }
+}
@@ -0,0 +1,208 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, symbol_short, Address, Env};
+
+use crate::{
+    add_total_burned, add_total_issued, checked_add, credit_capped, demurrage, events, freeze,
+    is_beneficiary, require_admin, spend_allocation, telemetry, write_balance, DataKey,
+    EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum AdvanceKey {
+    AdvanceLimit,         // Most a beneficiary may have advanced at once (absent when off)
+    Outstanding(Address), // Advance a beneficiary hasn't repaid yet
+    Advances,             // Number of beneficiaries with an outstanding advance
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the most a beneficiary may have advanced at once
+    /// (admin only); advances are off without a limit
+    pub fn set_advance_limit(env: Env, limit: Option<i128>) {
+        telemetry::record(&env, "set_advance_limit");
+        require_admin(&env);
+
+        match limit {
+            Some(limit) => {
+                if limit <= 0 {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage()
+                    .instance()
+                    .set(&AdvanceKey::AdvanceLimit, &limit);
+            }
+            None => env.storage().instance().remove(&AdvanceKey::AdvanceLimit),
+        }
+    }
+
+    /// Get the advance limit, if advances are on
+    pub fn advance_limit(env: Env) -> Option<i128> {
+        env.storage().instance().get(&AdvanceKey::AdvanceLimit)
+    }
+
+    /// Advance credits to a beneficiary ahead of their next issuance (the
+    /// beneficiary). The outstanding advance can't exceed the limit; it is
+    /// repaid out of the next credits issued to them.
+    pub fn request_advance(env: Env, beneficiary: Address, amount: i128) {
+        telemetry::record(&env, "request_advance");
+        beneficiary.require_auth();
+
+        let Some(limit) = Self::advance_limit(env.clone()) else {
+            panic_with_error!(&env, Error::NotConfigured);
+        };
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if !is_beneficiary(&env, &beneficiary) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        freeze::require_account_unfrozen(&env, &beneficiary);
+        let owed = outstanding(&env, &beneficiary);
+        let total = checked_add(&env, owed, amount);
+        if total > limit {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+
+        if owed == 0 {
+            set_advances(&env, advances(&env) + 1);
+        }
+        set_outstanding(&env, &beneficiary, total);
+        add_total_issued(&env, &None, amount);
+        credit_capped(&env, &beneficiary, amount);
+        events::publish(&env, (symbol_short!("advance"), beneficiary), amount);
+    }
+
+    /// Get a beneficiary's outstanding advance
+    pub fn outstanding_advance(env: Env, beneficiary: Address) -> i128 {
+        outstanding(&env, &beneficiary)
+    }
+}
+
+/// Repay a beneficiary's outstanding advance out of `issued` credits just
+/// issued to them, burning what is repaid
+pub(crate) fn repay(env: &Env, beneficiary: &Address, issued: i128) {
+    // Spares the lookup on every issuance until the first advance
+    if advances(env) == 0 {
+        return;
+    }
+    let owed = outstanding(env, beneficiary);
+    if owed == 0 {
+        return;
+    }
+
+    let repaid = owed.min(issued);
+    let balance = demurrage::settle(env, beneficiary, true);
+    let remaining = balance - repaid;
+    write_balance(env, beneficiary, remaining);
+    spend_allocation(
+        env,
+        beneficiary,
+        env.storage()
+            .persistent()
+            .get(&DataKey::Allocations(beneficiary.clone())),
+        remaining,
+    );
+    add_total_burned(env, repaid);
+    if repaid == owed {
+        set_advances(env, advances(env) - 1);
+    }
+    set_outstanding(env, beneficiary, owed - repaid);
+    events::publish(env, (symbol_short!("repaid"), beneficiary.clone()), repaid);
+}
+
+/// A beneficiary's outstanding advance
+pub(crate) fn outstanding(env: &Env, beneficiary: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&AdvanceKey::Outstanding(beneficiary.clone()))
+        .unwrap_or(0)
+}
+
+fn set_outstanding(env: &Env, beneficiary: &Address, owed: i128) {
+    let key = AdvanceKey::Outstanding(beneficiary.clone());
+    if owed == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &owed);
+    }
+}
+
+fn advances(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&AdvanceKey::Advances)
+        .unwrap_or(0)
+}
+
+fn set_advances(env: &Env, count: u32) {
+    env.storage().instance().set(&AdvanceKey::Advances, &count);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+
+    fn setup() -> Scenario {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 100);
+        s.client().set_advance_limit(&Some(150));
+        s
+    }
+
+    #[test]
+    fn test_advance_repaid_from_next_issuance() {
+        let s = setup();
+        let client = s.client();
+        let beneficiary = s.beneficiary(0);
+        client.request_advance(&beneficiary, &120);
+        assert_eq!(client.balance(&beneficiary), 220);
+        assert_eq!(client.balance_breakdown(&beneficiary).advance, 120);
+        client.transfer(&beneficiary, &s.school(0), &220);
+
+        // A smaller issuance repays part of it, the next one the rest
+        let s = s.issue(0, 0, 100);
+        let client = s.client();
+        assert_eq!(client.balance(&beneficiary), 0);
+        assert_eq!(client.outstanding_advance(&beneficiary), 20);
+        let s = s.issue(0, 0, 100);
+        let client = s.client();
+        assert_eq!(client.balance(&beneficiary), 80);
+        assert_eq!(client.allocation_remaining(&beneficiary), 80);
+        assert_eq!(client.outstanding_advance(&beneficiary), 0);
+        assert_eq!(client.total_issued(), 420);
+        assert_eq!(client.total_burned(), 120);
+    }
+
+    #[test]
+    fn test_advances_strictly_capped() {
+        let s = setup();
+        let client = s.client();
+        client.request_advance(&s.beneficiary(0), &100);
+        let result = client.try_request_advance(&s.beneficiary(0), &51);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+        client.request_advance(&s.beneficiary(0), &50);
+        let result = client.try_request_advance(&s.school(0), &10);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        client.set_advance_limit(&None);
+        let result = client.try_request_advance(&s.beneficiary(0), &1);
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+    }
+
+    #[test]
+    fn test_advances_keep_to_the_holding_cap() {
+        let s = setup();
+        let client = s.client();
+        client.set_max_balance_per_beneficiary(&Some(200));
+
+        let result = client.try_request_advance(&s.beneficiary(0), &101);
+        assert_eq!(result, Err(Ok(Error::BalanceCapExceeded.into())));
+        assert_eq!(client.outstanding_advance(&s.beneficiary(0)), 0);
+        client.request_advance(&s.beneficiary(0), &100);
+        assert_eq!(client.balance(&s.beneficiary(0)), 200);
+    }
+}
@@ -0,0 +1,272 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, symbol_short, Address, Env};
+
+use crate::{
+    burn_credits, checked_sub, consent, events, telemetry, transfer_credits, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum AllowanceKey {
+    Allowance(Address, Address), // Credits a spender may move for an owner, until a ledger
+}
+
+// An owner's approval of a spender, in the soroban token layout
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Allowance {
+    amount: i128,
+    expiration_ledger: u32, // Last ledger the approval can be used in
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Let `spender` move up to `amount` of `from`'s credits through ledger
+    /// `expiration_ledger` (the owner). Replaces any earlier approval; an
+    /// amount of 0 revokes it.
+    pub fn approve(
+        env: Env,
+        from: Address,
+        spender: Address,
+        amount: i128,
+        expiration_ledger: u32,
+    ) {
+        telemetry::record(&env, "approve");
+        from.require_auth();
+
+        if amount < 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        // The approval has to fit within one TTL extension
+        let ledger = env.ledger().sequence();
+        if amount > 0
+            && (expiration_ledger < ledger || expiration_ledger - ledger > env.storage().max_ttl())
+        {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+
+        let key = AllowanceKey::Allowance(from.clone(), spender.clone());
+        let temporary = env.storage().temporary();
+        temporary.set(
+            &key,
+            &Allowance {
+                amount,
+                expiration_ledger,
+            },
+        );
+        // Lapsed approvals expire from storage instead of paying rent
+        if amount > 0 {
+            let ttl = expiration_ledger - ledger;
+            temporary.extend_ttl(&key, ttl, ttl);
+        }
+        events::publish_token(
+            &env,
+            (symbol_short!("approve"), from, spender),
+            (amount, expiration_ledger),
+            (amount, expiration_ledger),
+        );
+    }
+
+    /// Credits `spender` may still move for `from` (0 once lapsed)
+    pub fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        read_allowance(&env, &from, &spender).amount
+    }
+
+    /// Move `from`'s credits to `to` under an approval (the spender)
+    pub fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        telemetry::record(&env, "transfer_from");
+        spender.require_auth();
+
+        consent::require_consent(&env, &from, amount);
+        spend_allowance(&env, &from, &spender, amount);
+        transfer_credits(&env, &spender, &from, &to, amount);
+    }
+
+    /// Burn `from`'s credits under an approval (the spender)
+    pub fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
+        telemetry::record(&env, "burn_from");
+        spender.require_auth();
+
+        consent::require_consent(&env, &from, amount);
+        spend_allowance(&env, &from, &spender, amount);
+        burn_credits(&env, &spender, &from, amount);
+    }
+}
+
+fn read_allowance(env: &Env, from: &Address, spender: &Address) -> Allowance {
+    let allowance: Option<Allowance> = env
+        .storage()
+        .temporary()
+        .get(&AllowanceKey::Allowance(from.clone(), spender.clone()));
+    match allowance {
+        Some(allowance) if allowance.expiration_ledger >= env.ledger().sequence() => allowance,
+        _ => Allowance {
+            amount: 0,
+            expiration_ledger: 0,
+        },
+    }
+}
+
+// Take `amount` out of an approval, failing with InsufficientAllowance if it
+// doesn't cover it
+fn spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
+    if amount <= 0 {
+        panic_with_error!(env, Error::InvalidAmount);
+    }
+    let mut allowance = read_allowance(env, from, spender);
+    if allowance.amount < amount {
+        panic_with_error!(env, Error::InsufficientAllowance);
+    }
+
+    allowance.amount = checked_sub(env, allowance.amount, amount);
+    env.storage().temporary().set(
+        &AllowanceKey::Allowance(from.clone(), spender.clone()),
+        &allowance,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance_ledgers, Scenario};
+    use soroban_sdk::testutils::{Address as _, Events};
+    use soroban_sdk::{vec, IntoVal, Symbol};
+
+    fn setup() -> (Scenario, Address) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let spender = Address::generate(&s.env);
+        let expiration = s.env.ledger().sequence() + 100;
+        s.client()
+            .approve(&s.beneficiary(0), &spender, &500, &expiration);
+        (s, spender)
+    }
+
+    #[test]
+    fn test_spender_moves_credits_within_allowance() {
+        let (s, spender) = setup();
+        let client = s.client();
+        assert_eq!(client.allowance(&s.beneficiary(0), &spender), 500);
+
+        client.transfer_from(&spender, &s.beneficiary(0), &s.school(0), &300);
+        assert_eq!(client.balance(&s.school(0)), 300);
+        assert_eq!(client.allowance(&s.beneficiary(0), &spender), 200);
+        let result = client.try_burn_from(&spender, &s.beneficiary(0), &201);
+        assert_eq!(result, Err(Ok(Error::InsufficientAllowance.into())));
+        client.burn_from(&spender, &s.beneficiary(0), &200);
+        assert_eq!(client.balance(&s.beneficiary(0)), 500);
+        assert_eq!(client.allowance(&s.beneficiary(0), &spender), 0);
+    }
+
+    #[test]
+    fn test_allowance_lapses_after_expiration_ledger() {
+        let (s, spender) = setup();
+        let client = s.client();
+        advance_ledgers(&s.env, 101);
+
+        assert_eq!(client.allowance(&s.beneficiary(0), &spender), 0);
+        let result = client.try_transfer_from(&spender, &s.beneficiary(0), &s.school(0), &1);
+        assert_eq!(result, Err(Ok(Error::InsufficientAllowance.into())));
+        let expired = s.env.ledger().sequence() - 1;
+        let result = client.try_approve(&s.beneficiary(0), &spender, &1, &expired);
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+    }
+
+    #[test]
+    fn test_expiration_within_the_max_ttl() {
+        let (s, spender) = setup();
+        let client = s.client();
+        let last = s.env.ledger().sequence() + s.env.storage().max_ttl();
+
+        let result = client.try_approve(&s.beneficiary(0), &spender, &1, &(last + 1));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+        client.approve(&s.beneficiary(0), &spender, &1, &last);
+        assert_eq!(client.allowance(&s.beneficiary(0), &spender), 1);
+        // Revocations don't extend anything
+        client.approve(&s.beneficiary(0), &spender, &0, &(last + 1));
+    }
+
+    #[test]
+    fn test_token_events_in_soroban_token_layout() {
+        let (s, spender) = setup();
+        let client = s.client();
+        let expiration = s.env.ledger().sequence() + 10;
+        client.approve(&s.beneficiary(0), &spender, &100, &expiration);
+        let all = s.env.events().all();
+        assert_eq!(
+            all.slice(all.len() - 2..),
+            vec![
+                &s.env,
+                (
+                    s.contract_id.clone(),
+                    (symbol_short!("approve"), s.beneficiary(0), spender.clone()).into_val(&s.env),
+                    (100i128, expiration).into_val(&s.env)
+                ),
+                (
+                    s.contract_id.clone(),
+                    (
+                        symbol_short!("approve"),
+                        5u32,
+                        s.beneficiary(0),
+                        spender.clone()
+                    )
+                        .into_val(&s.env),
+                    (None::<Symbol>, (100i128, expiration)).into_val(&s.env)
+                ),
+            ]
+        );
+
+        client.transfer_from(&spender, &s.beneficiary(0), &s.school(0), &40);
+        client.burn(&s.school(0), &40);
+        let all = s.env.events().all();
+        let token_events = vec![
+            &s.env,
+            (
+                s.contract_id.clone(),
+                (symbol_short!("transfer"), s.beneficiary(0), s.school(0)).into_val(&s.env),
+                40i128.into_val(&s.env),
+            ),
+            (
+                s.contract_id.clone(),
+                (symbol_short!("burn"), s.school(0)).into_val(&s.env),
+                40i128.into_val(&s.env),
+            ),
+            // The versioned event names the spender that authorized it
+            (
+                s.contract_id.clone(),
+                (
+                    symbol_short!("transfer"),
+                    5u32,
+                    s.beneficiary(0),
+                    s.school(0),
+                )
+                    .into_val(&s.env),
+                (None::<Symbol>, (40i128, spender.clone())).into_val(&s.env),
+            ),
+            (
+                s.contract_id.clone(),
+                (symbol_short!("burn"), 5u32, s.school(0)).into_val(&s.env),
+                (None::<Symbol>, (40i128, s.school(0))).into_val(&s.env),
+            ),
+        ];
+        for event in token_events.iter() {
+            assert!(all.contains(event));
+        }
+
+        // With legacy events on, the legacy copy is the token event
+        client.set_legacy_events(&true);
+        client.transfer(&s.beneficiary(0), &s.school(0), &10);
+        let all = s.env.events().all();
+        let canonical = (
+            s.contract_id.clone(),
+            (symbol_short!("transfer"), s.beneficiary(0), s.school(0)).into_val(&s.env),
+            10i128.into_val(&s.env),
+        );
+        let first = all.first_index_of(&canonical);
+        assert!(first.is_some());
+        assert_eq!(first, all.last_index_of(&canonical));
+    }
+}
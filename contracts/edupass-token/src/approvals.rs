@@ -0,0 +1,253 @@
+// Dual control over community pool draws. The program manager proposes a
+// grant and the treasurer approves it before anyone can execute it, so
+// neither role can issue pool credits alone. Proposals lapse if they
+// aren't approved and executed within the approval window. Governance
+// grants (grant_from_pool) are already collective decisions and don't go
+// through this.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, String, Symbol,
+};
+
+use crate::{
+    community, events, require_admin, storage, telemetry, treasury, Allocation, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+// How long a proposal can wait for approval and execution
+const DRAW_WINDOW_SECS: u64 = 7 * 24 * 60 * 60;
+
+#[contracttype]
+enum ApprovalKey {
+    ProgramManager,    // Address allowed to propose pool draws
+    DrawProposal(u64), // Proposed pool draw (temporary, lapses with its window)
+    DrawProposalCount, // Last assigned proposal id
+}
+
+// A pool grant awaiting approval or execution
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DrawProposal {
+    pub proposer: Address,
+    pub recipient: Address,
+    pub amount: i128,
+    pub purpose: String,
+    pub expires_at: u64, // Of the allocation granted
+    pub program_id: Option<Symbol>,
+    pub approved_by: Option<Address>,
+    pub deadline: u64, // Last time it can be approved or executed
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the program manager (admin only)
+    pub fn set_program_manager(env: Env, manager: Option<Address>) {
+        telemetry::record(&env, "set_program_manager");
+        require_admin(&env);
+
+        match manager {
+            Some(manager) => env
+                .storage()
+                .instance()
+                .set(&ApprovalKey::ProgramManager, &manager),
+            None => env
+                .storage()
+                .instance()
+                .remove(&ApprovalKey::ProgramManager),
+        }
+    }
+
+    /// Get the program manager, if set
+    pub fn program_manager(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ApprovalKey::ProgramManager)
+    }
+
+    /// Propose a grant from the community pool (program manager only);
+    /// returns the proposal id for the treasurer to approve
+    pub fn propose_draw(
+        env: Env,
+        recipient: Address,
+        amount: i128,
+        purpose: String,
+        expires_at: u64,
+        program_id: Option<Symbol>,
+    ) -> u64 {
+        telemetry::record(&env, "propose_draw");
+        let proposer = Self::program_manager(env.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        proposer.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        let id = env
+            .storage()
+            .instance()
+            .get(&ApprovalKey::DrawProposalCount)
+            .unwrap_or(0u64)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&ApprovalKey::DrawProposalCount, &id);
+
+        let deadline = env.ledger().timestamp() + DRAW_WINDOW_SECS;
+        let proposal = DrawProposal {
+            proposer: proposer.clone(),
+            recipient: recipient.clone(),
+            amount,
+            purpose,
+            expires_at,
+            program_id,
+            approved_by: None,
+            deadline,
+        };
+        storage::set_temporary(
+            &env,
+            &ApprovalKey::DrawProposal(id),
+            &proposal,
+            DRAW_WINDOW_SECS,
+        );
+        events::publish(
+            &env,
+            (symbol_short!("drawprop"), id),
+            (proposer, recipient, amount, deadline),
+        );
+
+        id
+    }
+
+    /// Approve a proposed pool draw (treasurer only). The treasurer can't
+    /// approve their own proposal.
+    pub fn approve_draw(env: Env, proposal_id: u64) {
+        telemetry::record(&env, "approve_draw");
+        let treasurer = treasury::require_treasurer(&env);
+
+        let mut proposal = open_proposal(&env, proposal_id);
+        if treasurer == proposal.proposer {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if proposal.approved_by.is_some() {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        proposal.approved_by = Some(treasurer.clone());
+        let lifetime = proposal.deadline - env.ledger().timestamp();
+        storage::set_temporary(
+            &env,
+            &ApprovalKey::DrawProposal(proposal_id),
+            &proposal,
+            lifetime,
+        );
+        events::publish(&env, (symbol_short!("drawappr"), proposal_id), treasurer);
+    }
+
+    /// Execute an approved pool draw, granting its credits (anyone, so the
+    /// proposer can once it's approved). Unapproved proposals fail with
+    /// Unauthorized.
+    pub fn execute_draw(env: Env, proposal_id: u64) -> Allocation {
+        telemetry::record(&env, "execute_draw");
+        let proposal = open_proposal(&env, proposal_id);
+        if proposal.approved_by.is_none() {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        env.storage()
+            .temporary()
+            .remove(&ApprovalKey::DrawProposal(proposal_id));
+        events::publish(
+            &env,
+            (symbol_short!("drawexec"), proposal_id),
+            (proposal.recipient.clone(), proposal.amount),
+        );
+        community::grant(
+            &env,
+            proposal.proposer,
+            proposal.recipient,
+            proposal.amount,
+            proposal.purpose,
+            proposal.expires_at,
+            proposal.program_id,
+        )
+    }
+
+    /// Get a pool draw proposal, until it's executed or lapses
+    pub fn draw_proposal(env: Env, proposal_id: u64) -> Option<DrawProposal> {
+        env.storage()
+            .temporary()
+            .get(&ApprovalKey::DrawProposal(proposal_id))
+    }
+}
+
+// A proposal still within its window
+fn open_proposal(env: &Env, proposal_id: u64) -> DrawProposal {
+    let proposal = EduPassToken::draw_proposal(env.clone(), proposal_id)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+    if env.ledger().timestamp() > proposal.deadline {
+        panic_with_error!(env, Error::ProposalExpired);
+    }
+    proposal
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::Vec;
+
+    fn setup() -> (Scenario, Address, Address, u64) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(2)
+            .issue(0, 1, 1000);
+        let client = s.client();
+        let (manager, treasurer) = (Address::generate(&s.env), Address::generate(&s.env));
+        client.set_program_manager(&Some(manager.clone()));
+        client.set_treasurer(&Some(treasurer.clone()));
+        client.deposit_to_pool(&s.beneficiary(1), &1000, &Vec::new(&s.env));
+        let id = client.propose_draw(
+            &s.beneficiary(0),
+            &400,
+            &String::from_str(&s.env, "Emergency Grant"),
+            &s.expires_at(),
+            &None,
+        );
+        (s, manager, treasurer, id)
+    }
+
+    #[test]
+    fn test_draw_needs_the_treasurer_approval() {
+        let (s, manager, treasurer, id) = setup();
+        let client = s.client();
+        assert_eq!(s.env.auths()[0].0, manager);
+        let result = client.try_execute_draw(&id);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        client.approve_draw(&id);
+        assert_eq!(s.env.auths()[0].0, treasurer);
+        assert_eq!(
+            client.draw_proposal(&id).unwrap().approved_by,
+            Some(treasurer)
+        );
+        let allocation = client.execute_draw(&id);
+        assert_eq!(allocation.issuer, manager);
+        assert_eq!(client.balance(&s.beneficiary(0)), 400);
+        assert_eq!(client.community_pool_balance(), 600);
+        assert_eq!(client.draw_proposal(&id), None);
+    }
+
+    #[test]
+    fn test_roles_must_differ_and_proposals_lapse() {
+        let (s, manager, _, id) = setup();
+        let client = s.client();
+        client.set_treasurer(&Some(manager));
+        let result = client.try_approve_draw(&id);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        client.set_treasurer(&Some(Address::generate(&s.env)));
+        advance(&s.env, 7 * DAY_SECS + 1);
+        let result = client.try_approve_draw(&id);
+        assert_eq!(result, Err(Ok(Error::ProposalExpired.into())));
+    }
+}
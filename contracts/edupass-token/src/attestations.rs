@@ -0,0 +1,343 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, BytesN, Env, Map, String,
+};
+
+use crate::{
+    commitments, events, mint_allocation, require_admin, rotation, telemetry, validity, Allocation,
+    EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum AttestationKey {
+    Verifier(Address), // Verifier approved to publish eligibility attestations
+    Attestation(u64),  // A published attestation
+    AttestationCount,  // Last assigned attestation id
+    RequiredCriteria,  // Criteria every issuance must be attested against (absent when optional)
+}
+
+// A verifier's signed statement that a beneficiary meets some criteria
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attestation {
+    pub verifier: Address,
+    pub criteria_hash: BytesN<32>, // Hash of the eligibility criteria checked
+    pub subject: BytesN<32>,       // Commitment to the beneficiary, as in issue_to_commitment
+    pub expires_at: u64,
+    pub revoked: bool,
+    pub used: bool, // Backed an issuance, so it can't back another
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Approve or revoke a verifier (admin only). Attestations from a
+    /// revoked verifier stop being accepted.
+    pub fn approve_verifier(env: Env, verifier: Address, approved: bool) {
+        telemetry::record(&env, "approve_verifier");
+        require_admin(&env);
+
+        let key = AttestationKey::Verifier(verifier);
+        if approved {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    /// Check whether a verifier is approved
+    pub fn is_verifier(env: Env, verifier: Address) -> bool {
+        is_verifier(&env, &verifier)
+    }
+
+    /// Require (Some) or stop requiring (None) an attestation against the
+    /// given criteria for every issuance (admin only)
+    pub fn set_required_criteria(env: Env, criteria_hash: Option<BytesN<32>>) {
+        telemetry::record(&env, "set_required_criteria");
+        require_admin(&env);
+
+        match criteria_hash {
+            Some(criteria_hash) => env
+                .storage()
+                .instance()
+                .set(&AttestationKey::RequiredCriteria, &criteria_hash),
+            None => env
+                .storage()
+                .instance()
+                .remove(&AttestationKey::RequiredCriteria),
+        }
+    }
+
+    /// Get the criteria issuances must be attested against, if required
+    pub fn required_criteria(env: Env) -> Option<BytesN<32>> {
+        required_criteria(&env)
+    }
+
+    /// Publish an eligibility attestation (approved verifiers only);
+    /// returns its id
+    pub fn publish_attestation(
+        env: Env,
+        verifier: Address,
+        criteria_hash: BytesN<32>,
+        subject: BytesN<32>,
+        expires_at: u64,
+    ) -> u64 {
+        telemetry::record(&env, "publish_attestation");
+        verifier.require_auth();
+
+        if !is_verifier(&env, &verifier) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if expires_at <= env.ledger().timestamp() {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+
+        let id = env
+            .storage()
+            .instance()
+            .get(&AttestationKey::AttestationCount)
+            .unwrap_or(0u64)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&AttestationKey::AttestationCount, &id);
+
+        let attestation = Attestation {
+            verifier: verifier.clone(),
+            criteria_hash: criteria_hash.clone(),
+            subject,
+            expires_at,
+            revoked: false,
+            used: false,
+        };
+        env.storage()
+            .persistent()
+            .set(&AttestationKey::Attestation(id), &attestation);
+        events::publish(
+            &env,
+            (symbol_short!("attested"), verifier),
+            (id, criteria_hash, expires_at),
+        );
+
+        id
+    }
+
+    /// Revoke an attestation (its verifier only)
+    pub fn revoke_attestation(env: Env, attestation_id: u64) {
+        telemetry::record(&env, "revoke_attestation");
+        let key = AttestationKey::Attestation(attestation_id);
+        let mut attestation: Attestation = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        attestation.verifier.require_auth();
+
+        attestation.revoked = true;
+        env.storage().persistent().set(&key, &attestation);
+        events::publish(
+            &env,
+            (symbol_short!("attrevoke"), attestation.verifier),
+            attestation_id,
+        );
+    }
+
+    /// Get an attestation
+    pub fn attestation(env: Env, attestation_id: u64) -> Option<Attestation> {
+        env.storage()
+            .persistent()
+            .get(&AttestationKey::Attestation(attestation_id))
+    }
+
+    /// Issue credits backed by an attestation, which is used up by it.
+    /// `salt` opens the attestation's subject commitment to the
+    /// beneficiary.
+    #[allow(clippy::too_many_arguments)]
+    pub fn issue_attested(
+        env: Env,
+        issuer: Address,
+        beneficiary: Address,
+        amount: i128,
+        purpose: String,
+        expires_at: u64,
+        attestation_id: u64,
+        salt: BytesN<32>,
+    ) -> Allocation {
+        telemetry::record(&env, "issue_attested");
+        issuer.require_auth();
+
+        let key = AttestationKey::Attestation(attestation_id);
+        let mut attestation: Attestation = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        let criteria_met = match required_criteria(&env) {
+            Some(criteria_hash) => attestation.criteria_hash == criteria_hash,
+            None => true,
+        };
+        if attestation.revoked
+            || attestation.used
+            || !criteria_met
+            || env.ledger().timestamp() >= attestation.expires_at
+            || !is_verifier(&env, &attestation.verifier)
+            || commitments::commitment_of(&env, &salt, &beneficiary) != attestation.subject
+        {
+            panic_with_error!(&env, Error::InvalidAttestation);
+        }
+        attestation.used = true;
+        env.storage().persistent().set(&key, &attestation);
+
+        let expires_at = validity::resolve_expiry(&env, expires_at);
+        mint_allocation(
+            &env,
+            issuer,
+            beneficiary,
+            amount,
+            purpose,
+            expires_at,
+            None,
+            Map::new(&env),
+        )
+    }
+}
+
+/// Refuse an issuance made without an attestation while one is required
+pub(crate) fn require_unattested_allowed(env: &Env) {
+    if let Err(error) = check_unattested(env) {
+        panic_with_error!(env, error);
+    }
+}
+
+/// Dry-run form of require_unattested_allowed, for can_issue
+pub(crate) fn check_unattested(env: &Env) -> Result<(), Error> {
+    match required_criteria(env) {
+        Some(_) => Err(Error::AttestationRequired),
+        None => Ok(()),
+    }
+}
+
+/// Move a verifier's approval to its rotated key. Attestations it
+/// published name the old key and stop being accepted.
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &AttestationKey::Verifier(old.clone()),
+        &AttestationKey::Verifier(new.clone()),
+    );
+}
+
+fn required_criteria(env: &Env) -> Option<BytesN<32>> {
+    env.storage()
+        .instance()
+        .get(&AttestationKey::RequiredCriteria)
+}
+
+fn is_verifier(env: &Env, verifier: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&AttestationKey::Verifier(verifier.clone()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+    use soroban_sdk::testutils::Address as _;
+
+    const CRITERIA: [u8; 32] = [1; 32];
+    const SALT: [u8; 32] = [2; 32];
+
+    // Require the criteria and attest that the first beneficiary meets them
+    fn setup() -> (Scenario, Address, u64) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(2);
+        let verifier = Address::generate(&s.env);
+        let criteria = BytesN::from_array(&s.env, &CRITERIA);
+        let client = s.client();
+        client.approve_verifier(&verifier, &true);
+        client.set_required_criteria(&Some(criteria.clone()));
+        let salt = BytesN::from_array(&s.env, &SALT);
+        let subject = commitments::commitment_of(&s.env, &salt, &s.beneficiary(0));
+        let id = client.publish_attestation(&verifier, &criteria, &subject, &(s.now() + DAY_SECS));
+        (s, verifier, id)
+    }
+
+    fn issue_attested(s: &Scenario, id: u64, beneficiary: u32) -> Result<(), soroban_sdk::Error> {
+        let result = s.client().try_issue_attested(
+            &s.issuer(0),
+            &s.beneficiary(beneficiary),
+            &100,
+            &String::from_str(&s.env, "Tuition"),
+            &s.expires_at(),
+            &id,
+            &BytesN::from_array(&s.env, &SALT),
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(Ok(error)) => Err(error),
+            Err(Err(error)) => panic!("unexpected host error: {error:?}"),
+        }
+    }
+
+    #[test]
+    fn test_issuance_requires_valid_attestation() {
+        let (s, _, id) = setup();
+        let result = s.client().try_issue_credits(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &100,
+            &String::from_str(&s.env, "Tuition"),
+            &s.expires_at(),
+        );
+        assert_eq!(result, Err(Ok(Error::AttestationRequired.into())));
+
+        // The attestation's subject is the first beneficiary only
+        assert_eq!(
+            issue_attested(&s, id, 1),
+            Err(Error::InvalidAttestation.into())
+        );
+        assert_eq!(issue_attested(&s, id, 0), Ok(()));
+        assert_eq!(s.client().balance(&s.beneficiary(0)), 100);
+    }
+
+    #[test]
+    fn test_attestations_back_one_issuance() {
+        let (s, _, id) = setup();
+        assert_eq!(issue_attested(&s, id, 0), Ok(()));
+        assert!(s.client().attestation(&id).unwrap().used);
+
+        // A second issuance can't reuse it
+        assert_eq!(
+            issue_attested(&s, id, 0),
+            Err(Error::InvalidAttestation.into())
+        );
+        assert_eq!(s.client().balance(&s.beneficiary(0)), 100);
+    }
+
+    #[test]
+    fn test_expired_or_revoked_attestations_are_refused() {
+        let (s, _, id) = setup();
+        advance(&s.env, DAY_SECS);
+        assert_eq!(
+            issue_attested(&s, id, 0),
+            Err(Error::InvalidAttestation.into())
+        );
+
+        let (s, _, id) = setup();
+        s.client().revoke_attestation(&id);
+        assert!(s.client().attestation(&id).unwrap().revoked);
+        assert_eq!(
+            issue_attested(&s, id, 0),
+            Err(Error::InvalidAttestation.into())
+        );
+
+        // Revoking the verifier voids what it published
+        let (s, verifier, id) = setup();
+        s.client().approve_verifier(&verifier, &false);
+        assert_eq!(
+            issue_attested(&s, id, 0),
+            Err(Error::InvalidAttestation.into())
+        );
+    }
+}
@@ -0,0 +1,299 @@
+// Receipt audit roots. Receipts are folded, in id order, into one Merkle
+// tree per period. A period's root is sealed on chain once its last
+// receipt is folded, so auditors can check a quarter's redemptions against
+// it without trusting an indexer.
+
+use soroban_sdk::{contractimpl, contracttype, symbol_short, xdr::ToXdr, Bytes, BytesN, Env, Vec};
+
+use crate::{events, reputation, telemetry, EduPassToken, EduPassTokenClient};
+
+// Receipts are grouped into 13-week periods counted from the Unix epoch
+const RECEIPT_PERIOD_SECS: u64 = 91 * 24 * 60 * 60;
+// Bounds the work done by one fold_receipts call
+const MAX_FOLD_BATCH: u32 = 100;
+
+#[contracttype]
+enum AuditKey {
+    ReceiptsFolded,  // Last receipt id folded into a tree
+    ReceiptTree,     // Tree of the period being folded (absent between periods)
+    PeriodRoot(u64), // Sealed root of a period's receipts
+}
+
+// The sealed Merkle root of a period's receipts
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiptRoot {
+    pub root: BytesN<32>,
+    pub first_receipt: u64, // Receipts first_receipt .. first_receipt + receipts - 1 are leaves
+    pub receipts: u32,
+}
+
+// A period's tree while receipts are folded into it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ReceiptTree {
+    period: u64,
+    first_receipt: u64,
+    leaves: u32,
+    frontier: Vec<BytesN<32>>, // Root of the last complete subtree at each height
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Fold up to `limit` receipts from ended periods into their period's
+    /// tree, sealing each period's root once its last receipt is in (anyone
+    /// can call). Returns the number of receipts folded.
+    pub fn fold_receipts(env: Env, limit: u32) -> u32 {
+        telemetry::record(&env, "fold_receipts");
+        let current_period = period_of(env.ledger().timestamp());
+        let count = reputation::receipt_count(&env);
+        let mut folded: u64 = env
+            .storage()
+            .instance()
+            .get(&AuditKey::ReceiptsFolded)
+            .unwrap_or(0);
+        let mut tree: Option<ReceiptTree> = env.storage().persistent().get(&AuditKey::ReceiptTree);
+
+        let mut done = 0;
+        let mut caught_up = folded == count;
+        while done < limit.min(MAX_FOLD_BATCH) && !caught_up {
+            let id = folded + 1;
+            let receipt = reputation::read_receipt(&env, id);
+            let period = period_of(receipt.redeemed_at);
+            // Receipts of the running period wait until it ends
+            if period >= current_period {
+                caught_up = true;
+                break;
+            }
+            match tree {
+                Some(ref t) if t.period == period => {}
+                _ => {
+                    if let Some(t) = tree.take() {
+                        seal(&env, &t);
+                    }
+                    tree = Some(ReceiptTree {
+                        period,
+                        first_receipt: id,
+                        leaves: 0,
+                        frontier: Vec::new(&env),
+                    });
+                }
+            }
+            if let Some(ref mut t) = tree {
+                append(&env, t, receipt_hash(&env, id, &receipt));
+            }
+            folded = id;
+            done += 1;
+            caught_up = folded == count;
+        }
+
+        // No later receipt can land in an ended period
+        match tree {
+            Some(t) if caught_up => {
+                seal(&env, &t);
+                env.storage().persistent().remove(&AuditKey::ReceiptTree);
+            }
+            Some(t) => env.storage().persistent().set(&AuditKey::ReceiptTree, &t),
+            None => {}
+        }
+        env.storage()
+            .instance()
+            .set(&AuditKey::ReceiptsFolded, &folded);
+        done
+    }
+
+    /// Get the sealed root of a period's receipts, if sealed. Periods are
+    /// 91 days long, numbered from the Unix epoch.
+    pub fn period_root(env: Env, period: u64) -> Option<ReceiptRoot> {
+        env.storage()
+            .persistent()
+            .get(&AuditKey::PeriodRoot(period))
+    }
+
+    /// Get the leaf a receipt contributes to its period's tree
+    pub fn receipt_hash(env: Env, receipt_id: u64) -> BytesN<32> {
+        let receipt = reputation::read_receipt(&env, receipt_id);
+        receipt_hash(&env, receipt_id, &receipt)
+    }
+
+    /// Check a receipt hash against a period's sealed root. The proof lists
+    /// the sibling at each height, leaf first, and must reach the root's
+    /// full depth.
+    pub fn verify_receipt_inclusion(
+        env: Env,
+        period: u64,
+        receipt_hash: BytesN<32>,
+        proof: Vec<BytesN<32>>,
+    ) -> bool {
+        let Some(root) = Self::period_root(env.clone(), period) else {
+            return false;
+        };
+        // A shorter proof would let an inner node pass as a leaf
+        if proof.len() != depth(root.receipts) {
+            return false;
+        }
+
+        let mut node = receipt_hash;
+        for sibling in proof.iter() {
+            node = hash_pair(&env, &node, &sibling);
+        }
+        node == root.root
+    }
+}
+
+fn period_of(timestamp: u64) -> u64 {
+    timestamp / RECEIPT_PERIOD_SECS
+}
+
+// SHA-256 of the receipt's fixed fields in XDR; the rating is left out as
+// it can change after the receipt is folded
+fn receipt_hash(env: &Env, id: u64, receipt: &reputation::Receipt) -> BytesN<32> {
+    let fields = (
+        id,
+        receipt.beneficiary.clone(),
+        receipt.school.clone(),
+        receipt.amount,
+        receipt.redeemed_at,
+    );
+    env.crypto().sha256(&fields.to_xdr(env)).to_bytes()
+}
+
+// Nodes hash the sorted pair, so proofs don't need the leaf's position
+fn hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let mut preimage = Bytes::from_array(env, &low.to_array());
+    preimage.append(&Bytes::from_array(env, &high.to_array()));
+    env.crypto().sha256(&preimage).to_bytes()
+}
+
+// Height of a tree over `leaves` leaves, padded to a power of two
+fn depth(leaves: u32) -> u32 {
+    leaves.next_power_of_two().trailing_zeros()
+}
+
+fn append(env: &Env, tree: &mut ReceiptTree, leaf: BytesN<32>) {
+    let mut node = leaf;
+    let mut size = tree.leaves;
+    let mut height = 0;
+    while size & 1 == 1 {
+        node = hash_pair(env, &tree.frontier.get_unchecked(height), &node);
+        size >>= 1;
+        height += 1;
+    }
+    if height < tree.frontier.len() {
+        tree.frontier.set(height, node);
+    } else {
+        tree.frontier.push_back(node);
+    }
+    tree.leaves += 1;
+}
+
+// Root of the tree, with the leaves past the last padded with zero hashes
+fn root(env: &Env, tree: &ReceiptTree) -> BytesN<32> {
+    let depth = depth(tree.leaves);
+    if tree.leaves.is_power_of_two() {
+        return tree.frontier.get_unchecked(depth);
+    }
+
+    let mut zero = BytesN::from_array(env, &[0; 32]);
+    let mut node = zero.clone();
+    let mut size = tree.leaves;
+    for height in 0..depth {
+        node = match size & 1 {
+            1 => hash_pair(env, &tree.frontier.get_unchecked(height), &node),
+            _ => hash_pair(env, &node, &zero),
+        };
+        zero = hash_pair(env, &zero, &zero);
+        size >>= 1;
+    }
+    node
+}
+
+fn seal(env: &Env, tree: &ReceiptTree) {
+    let sealed = ReceiptRoot {
+        root: root(env, tree),
+        first_receipt: tree.first_receipt,
+        receipts: tree.leaves,
+    };
+    env.storage()
+        .persistent()
+        .set(&AuditKey::PeriodRoot(tree.period), &sealed);
+    events::publish(
+        env,
+        (symbol_short!("sealed"), tree.period),
+        (sealed.root, sealed.receipts),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+    use soroban_sdk::vec;
+
+    // Three redemptions in one period, one in the next
+    fn setup() -> (Scenario, u64) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        client.set_reputation_config(&Some(crate::ReputationConfig {
+            settlement_window_secs: DAY_SECS,
+            large_redemption: 10_000,
+            min_score: 0,
+        }));
+        let period = period_of(s.now());
+        for amount in [10, 20, 30] {
+            client.transfer(&s.beneficiary(0), &s.school(0), &amount);
+        }
+        advance(&s.env, RECEIPT_PERIOD_SECS);
+        client.transfer(&s.beneficiary(0), &s.school(0), &40);
+        (s, period)
+    }
+
+    #[test]
+    fn test_ended_periods_sealed_in_batches() {
+        let (s, period) = setup();
+        let client = s.client();
+
+        // The running period's receipt waits
+        assert_eq!(client.fold_receipts(&2), 2);
+        assert_eq!(client.period_root(&period), None);
+        assert_eq!(client.fold_receipts(&10), 1);
+        let sealed = client.period_root(&period).unwrap();
+        assert_eq!((sealed.first_receipt, sealed.receipts), (1, 3));
+        assert_eq!(client.fold_receipts(&10), 0);
+
+        advance(&s.env, RECEIPT_PERIOD_SECS);
+        assert_eq!(client.fold_receipts(&10), 1);
+        let next = client.period_root(&(period + 1)).unwrap();
+        assert_eq!(next.root, client.receipt_hash(&4));
+        assert!(client.verify_receipt_inclusion(&(period + 1), &next.root, &vec![&s.env]));
+    }
+
+    #[test]
+    fn test_inclusion_proofs() {
+        let (s, period) = setup();
+        let client = s.client();
+        client.fold_receipts(&10);
+        let [a, b, c] = [1, 2, 3].map(|id| client.receipt_hash(&id));
+        let zero = BytesN::from_array(&s.env, &[0; 32]);
+        let ab = hash_pair(&s.env, &a, &b);
+        let c0 = hash_pair(&s.env, &c, &zero);
+        assert_eq!(
+            client.period_root(&period).unwrap().root,
+            hash_pair(&s.env, &ab, &c0)
+        );
+
+        assert!(client.verify_receipt_inclusion(&period, &b, &vec![&s.env, a.clone(), c0.clone()]));
+        assert!(client.verify_receipt_inclusion(&period, &c, &vec![&s.env, zero, ab.clone()]));
+        // The fourth receipt belongs to the next period
+        let d = client.receipt_hash(&4);
+        assert!(!client.verify_receipt_inclusion(&period, &d, &vec![&s.env, a, c0.clone()]));
+        // An inner node is not a leaf
+        assert!(!client.verify_receipt_inclusion(&period, &ab, &vec![&s.env, c0]));
+    }
+}
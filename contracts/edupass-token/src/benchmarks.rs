@@ -0,0 +1,307 @@
+// Budget benchmarks: CPU, memory and ledger footprint of each entrypoint
+// under representative data sizes, checked against the baselines in
+// benchmarks/budget.txt. Set EDUPASS_BENCH_UPDATE=1 to rewrite the
+// baselines after an intended cost change.
+
+extern crate std;
+
+use soroban_env_host::storage::{AccessType, Footprint};
+use soroban_sdk::testutils::{Address as _, EnvTestConfig};
+use soroban_sdk::{symbol_short, vec, Address, Env, Map, String, Symbol, Vec};
+use std::collections::BTreeMap;
+use std::format;
+use std::string::String as StdString;
+use std::vec::Vec as StdVec;
+
+use crate::testutils::Scenario;
+use crate::Contribution;
+
+const BASELINE_PATH: &str = "benchmarks/budget.txt";
+const BASELINE: &str = include_str!("../benchmarks/budget.txt");
+
+// Allowed CPU and memory growth over the baseline, in percent. Footprint
+// sizes must not grow at all.
+const THRESHOLD_PERCENT: u64 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Cost {
+    cpu: u64,
+    mem: u64,
+    reads: u32,
+    writes: u32,
+}
+
+fn scenario() -> Scenario {
+    let env = Env::new_with_config(EnvTestConfig {
+        capture_snapshot_at_drop: false,
+    });
+    let s = Scenario::with_env(env)
+        .with_admin()
+        .with_issuers(1)
+        .with_schools(1)
+        .with_beneficiaries(1);
+    s.env.budget().reset_unlimited();
+    s
+}
+
+// Cost of `call` alone: the budget and the recorded footprint are reset
+// first, so setup done beforehand is not counted
+fn measure(env: &Env, call: impl FnOnce()) -> Cost {
+    let host = env.host();
+    host.with_mut_storage(|storage| {
+        storage.footprint = Footprint::default();
+        Ok(())
+    })
+    .unwrap();
+    env.budget().reset_default();
+
+    call();
+
+    let cpu = env.budget().cpu_instruction_cost();
+    let mem = env.budget().memory_bytes_cost();
+    let (reads, writes) = host
+        .with_mut_storage(|storage| {
+            let budget = host.budget_cloned();
+            let mut counts = (0, 0);
+            for (_, access) in storage.footprint.0.iter(&budget)? {
+                match access {
+                    AccessType::ReadOnly => counts.0 += 1,
+                    AccessType::ReadWrite => counts.1 += 1,
+                }
+            }
+            Ok(counts)
+        })
+        .unwrap();
+    env.budget().reset_unlimited();
+
+    Cost {
+        cpu,
+        mem,
+        reads,
+        writes,
+    }
+}
+
+fn tags(env: &Env, count: u32) -> Map<Symbol, String> {
+    let mut metadata = Map::new(env);
+    for i in 0..count {
+        metadata.set(
+            Symbol::new(env, &format!("tag{}", i)),
+            String::from_str(env, "value"),
+        );
+    }
+    metadata
+}
+
+fn run_benchmarks() -> StdVec<(&'static str, Cost)> {
+    let mut results = StdVec::new();
+    let purpose = |env: &Env| String::from_str(env, "Tuition");
+
+    let s = scenario();
+    let client = s.client();
+    let (issuer, beneficiary) = (s.issuer(0), s.beneficiary(0));
+    results.push((
+        "issue_credits",
+        measure(&s.env, || {
+            client.issue_credits(
+                &issuer,
+                &beneficiary,
+                &1000,
+                &purpose(&s.env),
+                &s.expires_at(),
+            );
+        }),
+    ));
+    results.push((
+        "balance",
+        measure(&s.env, || {
+            client.balance(&beneficiary);
+        }),
+    ));
+    results.push((
+        "can_transfer",
+        measure(&s.env, || {
+            client.can_transfer(&beneficiary, &s.school(0), &10)
+        }),
+    ));
+    client.transfer(&beneficiary, &s.school(0), &10);
+    results.push((
+        "transfer",
+        measure(&s.env, || client.transfer(&beneficiary, &s.school(0), &10)),
+    ));
+    results.push(("burn", measure(&s.env, || client.burn(&s.school(0), &10))));
+
+    let s = scenario();
+    let client = s.client();
+    let metadata = tags(&s.env, 8);
+    results.push((
+        "issue_tagged_8_tags",
+        measure(&s.env, || {
+            client.issue_tagged(
+                &s.issuer(0),
+                &s.beneficiary(0),
+                &1000,
+                &purpose(&s.env),
+                &s.expires_at(),
+                &metadata,
+            );
+        }),
+    ));
+
+    let s = scenario();
+    let client = s.client();
+    client.fund_bonus_pool(&s.issuer(0), &10_000);
+    client.set_priority_purpose(&symbol_short!("books"), &Some(1000));
+    client.issue_credits(
+        &s.issuer(0),
+        &s.beneficiary(0),
+        &1000,
+        &purpose(&s.env),
+        &s.expires_at(),
+    );
+    results.push((
+        "transfer_for_purpose",
+        measure(&s.env, || {
+            client.transfer_for_purpose(
+                &s.beneficiary(0),
+                &s.school(0),
+                &100,
+                &symbol_short!("books"),
+            );
+        }),
+    ));
+
+    let s = scenario();
+    let client = s.client();
+    let mut contributions = Vec::new(&s.env);
+    for _ in 0..5 {
+        contributions.push_back(Contribution {
+            funder: Address::generate(&s.env),
+            amount: 200,
+        });
+    }
+    client.issue_cofunded(
+        &s.beneficiary(0),
+        &contributions,
+        &purpose(&s.env),
+        &s.expires_at(),
+    );
+    results.push((
+        "clawback_5_funders",
+        measure(&s.env, || {
+            client.clawback(&s.beneficiary(0));
+        }),
+    ));
+
+    let s = scenario()
+        .with_validity(100)
+        .issue(0, 0, 1000)
+        .advance_time(100);
+    let client = s.client();
+    results.push((
+        "sweep_expired",
+        measure(&s.env, || {
+            client.sweep_expired(&s.beneficiary(0));
+        }),
+    ));
+
+    let s = scenario();
+    let client = s.client();
+    let value = String::from_str(&s.env, "2025");
+    let mut sample = vec![&s.env];
+    for _ in 0..100 {
+        let beneficiary = Address::generate(&s.env);
+        let mut metadata = Map::new(&s.env);
+        metadata.set(symbol_short!("year"), value.clone());
+        client.issue_tagged(
+            &s.issuer(0),
+            &beneficiary,
+            &10,
+            &purpose(&s.env),
+            &s.expires_at(),
+            &metadata,
+        );
+        sample.push_back(beneficiary);
+    }
+    results.push((
+        "allocations_by_tag_100",
+        measure(&s.env, || {
+            client.allocations_by_tag(&s.issuer(0), &symbol_short!("year"), &value, &0, &100);
+        }),
+    ));
+    results.push((
+        "check_invariants_100",
+        measure(&s.env, || {
+            client.check_invariants(&sample);
+        }),
+    ));
+
+    results
+}
+
+fn parse_baseline() -> BTreeMap<StdString, Cost> {
+    BASELINE
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .map(|line| {
+            let fields: StdVec<&str> = line.split_whitespace().collect();
+            let number = |i: usize| fields[i].parse::<u64>().unwrap();
+            (
+                fields[0].into(),
+                Cost {
+                    cpu: number(1),
+                    mem: number(2),
+                    reads: number(3) as u32,
+                    writes: number(4) as u32,
+                },
+            )
+        })
+        .collect()
+}
+
+fn format_baseline(results: &[(&str, Cost)]) -> StdString {
+    let mut out = StdString::from("# entrypoint cpu_instructions memory_bytes reads writes\n");
+    for (name, cost) in results {
+        out.push_str(&format!(
+            "{} {} {} {} {}\n",
+            name, cost.cpu, cost.mem, cost.reads, cost.writes
+        ));
+    }
+    out
+}
+
+#[test]
+#[cfg_attr(
+    feature = "telemetry",
+    ignore = "baselines are for builds without telemetry"
+)]
+fn test_budget_against_baseline() {
+    let results = run_benchmarks();
+    if std::env::var_os("EDUPASS_BENCH_UPDATE").is_some() {
+        std::fs::write(BASELINE_PATH, format_baseline(&results)).unwrap();
+        return;
+    }
+
+    let baseline = parse_baseline();
+    let within =
+        |measured: u64, allowed: u64| measured <= allowed + allowed * THRESHOLD_PERCENT / 100;
+    let mut regressions = StdVec::new();
+    for (name, cost) in &results {
+        let Some(base) = baseline.get(*name) else {
+            regressions.push(format!("{}: no baseline", name));
+            continue;
+        };
+        if !within(cost.cpu, base.cpu)
+            || !within(cost.mem, base.mem)
+            || cost.reads > base.reads
+            || cost.writes > base.writes
+        {
+            regressions.push(format!("{}: {:?} vs baseline {:?}", name, cost, base));
+        }
+    }
+    assert!(
+        regressions.is_empty(),
+        "budget regressions (rerun with EDUPASS_BENCH_UPDATE=1 if intended):\n{}",
+        regressions.join("\n")
+    );
+}
@@ -0,0 +1,421 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, token, Address, Env, Symbol,
+};
+
+use crate::{
+    checked_add, events, require_admin, reserves, rotation, telemetry, wiring, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum BondKey {
+    BondConfig,    // Issuer bonding terms (absent when disabled)
+    Bond(Address), // Reserve asset posted by an issuer
+    TotalBonded,   // Reserve asset held for all bonds
+}
+
+// Issuers must keep a bond in a reserve asset to issue credits
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BondConfig {
+    pub asset: Address,     // Stellar asset contract bonds are posted in
+    pub min_bond: i128,     // Bond an issuer must hold to issue
+    pub cooldown_secs: u64, // Time after an issuer's last issuance before it may withdraw
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Bond {
+    pub amount: i128,
+    pub last_issued_at: u64,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Enable, update or disable (None) issuer bonding (admin only). The
+    /// asset can't change while bonds are outstanding.
+    pub fn set_bond_config(env: Env, config: Option<BondConfig>) {
+        telemetry::record(&env, "set_bond_config");
+        require_admin(&env);
+
+        match config {
+            Some(config) => {
+                if config.min_bond <= 0 {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                wiring::require_asset(&env, &config.asset);
+                if let Some(current) = bond_config(&env) {
+                    if current.asset != config.asset && total_bonded(&env) > 0 {
+                        panic_with_error!(&env, Error::InvalidConfig);
+                    }
+                }
+                env.storage().instance().set(&BondKey::BondConfig, &config);
+            }
+            None => env.storage().instance().remove(&BondKey::BondConfig),
+        }
+    }
+
+    /// Get the bonding terms, if enabled
+    pub fn bond_config(env: Env) -> Option<BondConfig> {
+        bond_config(&env)
+    }
+
+    /// Get an issuer's bond
+    pub fn bond(env: Env, issuer: Address) -> Bond {
+        read_bond(&env, &issuer)
+    }
+
+    /// Post reserve asset to an issuer's bond; returns the new bond amount
+    pub fn post_bond(env: Env, issuer: Address, amount: i128) -> i128 {
+        telemetry::record(&env, "post_bond");
+        issuer.require_auth();
+
+        let config = require_config(&env);
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        let mut bond = read_bond(&env, &issuer);
+        bond.amount = checked_add(&env, bond.amount, amount);
+        write_bond(&env, &issuer, &bond);
+        add_total_bonded(&env, amount);
+        reserves::deposit(&env, &config.asset, amount);
+
+        token::Client::new(&env, &config.asset).transfer(
+            &issuer,
+            &env.current_contract_address(),
+            &amount,
+        );
+        events::publish(&env, (symbol_short!("bonded"), issuer), amount);
+
+        bond.amount
+    }
+
+    /// Withdraw part of an issuer's bond once the cooldown since its last
+    /// issuance has passed; returns the remaining bond
+    pub fn withdraw_bond(env: Env, issuer: Address, amount: i128) -> i128 {
+        telemetry::record(&env, "withdraw_bond");
+        issuer.require_auth();
+
+        let config = require_config(&env);
+        let mut bond = read_bond(&env, &issuer);
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if amount > bond.amount {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+        if env.ledger().timestamp() < bond.last_issued_at.saturating_add(config.cooldown_secs) {
+            panic_with_error!(&env, Error::BondLocked);
+        }
+        // The bond stays to answer any case over the issuer's escrows
+        #[cfg(feature = "disputes")]
+        if crate::disputes::has_open_disputes(&env, &issuer) {
+            panic_with_error!(&env, Error::BondLocked);
+        }
+
+        bond.amount -= amount;
+        write_bond(&env, &issuer, &bond);
+        add_total_bonded(&env, -amount);
+        reserves::withdraw(&env, &config.asset, amount);
+
+        token::Client::new(&env, &config.asset).transfer(
+            &env.current_contract_address(),
+            &issuer,
+            &amount,
+        );
+        events::publish(&env, (symbol_short!("unbonded"), issuer), amount);
+
+        bond.amount
+    }
+
+    /// Slash an issuer's bond for misuse, paying it to an affected
+    /// beneficiary or a pool account (admin only); returns the remaining bond
+    pub fn slash_bond(
+        env: Env,
+        issuer: Address,
+        amount: i128,
+        reason: Symbol,
+        recipient: Address,
+    ) -> i128 {
+        telemetry::record(&env, "slash_bond");
+        require_admin(&env);
+
+        let config = require_config(&env);
+        let mut bond = read_bond(&env, &issuer);
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if amount > bond.amount {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+
+        bond.amount -= amount;
+        write_bond(&env, &issuer, &bond);
+        add_total_bonded(&env, -amount);
+        reserves::withdraw(&env, &config.asset, amount);
+
+        token::Client::new(&env, &config.asset).transfer(
+            &env.current_contract_address(),
+            &recipient,
+            &amount,
+        );
+        events::publish(
+            &env,
+            (symbol_short!("slashed"), issuer, recipient),
+            (amount, reason),
+        );
+
+        bond.amount
+    }
+}
+
+/// Check an issuer is bonded before it issues, restarting its withdrawal
+/// cooldown; a no-op while bonding is disabled
+pub(crate) fn require_bonded(env: &Env, issuer: &Address) {
+    let Some(config) = bond_config(env) else {
+        return;
+    };
+
+    let mut bond = read_bond(env, issuer);
+    if bond.amount < config.min_bond {
+        panic_with_error!(env, Error::BondRequired);
+    }
+    bond.last_issued_at = env.ledger().timestamp();
+    write_bond(env, issuer, &bond);
+}
+
+/// Dry-run form of require_bonded, for issuance queued ahead of time
+pub(crate) fn check_bonded(env: &Env, issuer: &Address) -> Result<(), Error> {
+    match bond_config(env) {
+        Some(config) if read_bond(env, issuer).amount < config.min_bond => Err(Error::BondRequired),
+        _ => Ok(()),
+    }
+}
+
+/// Reserve asset held for bonds, if `asset` is the bond asset
+pub(crate) fn bonded_in(env: &Env, asset: &Address) -> i128 {
+    match bond_config(env) {
+        Some(config) if config.asset == *asset => total_bonded(env),
+        _ => 0,
+    }
+}
+
+/// Move an issuer's bond to its rotated key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &BondKey::Bond(old.clone()),
+        &BondKey::Bond(new.clone()),
+    );
+}
+
+fn bond_config(env: &Env) -> Option<BondConfig> {
+    env.storage().instance().get(&BondKey::BondConfig)
+}
+
+fn require_config(env: &Env) -> BondConfig {
+    bond_config(env).unwrap_or_else(|| panic_with_error!(env, Error::NotConfigured))
+}
+
+fn read_bond(env: &Env, issuer: &Address) -> Bond {
+    env.storage()
+        .persistent()
+        .get(&BondKey::Bond(issuer.clone()))
+        .unwrap_or_default()
+}
+
+fn write_bond(env: &Env, issuer: &Address, bond: &Bond) {
+    env.storage()
+        .persistent()
+        .set(&BondKey::Bond(issuer.clone()), bond);
+}
+
+fn total_bonded(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&BondKey::TotalBonded)
+        .unwrap_or(0)
+}
+
+fn add_total_bonded(env: &Env, amount: i128) {
+    let total = checked_add(env, total_bonded(env), amount);
+    env.storage().instance().set(&BondKey::TotalBonded, &total);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    const MIN_BOND: i128 = 5_000;
+    const COOLDOWN: u64 = 7 * 24 * 60 * 60;
+
+    fn setup() -> (Scenario, Address) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(1);
+        let asset = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        StellarAssetClient::new(&s.env, &asset).mint(&s.issuer(0), &10_000);
+        s.client().set_bond_config(&Some(BondConfig {
+            asset: asset.clone(),
+            min_bond: MIN_BOND,
+            cooldown_secs: COOLDOWN,
+        }));
+        (s, asset)
+    }
+
+    #[test]
+    fn test_issuance_requires_bond() {
+        let (s, asset) = setup();
+        let client = s.client();
+
+        let result = client.try_issue_credits(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &100,
+            &soroban_sdk::String::from_str(&s.env, "Tuition"),
+            &s.expires_at(),
+        );
+        assert_eq!(result, Err(Ok(Error::BondRequired.into())));
+
+        assert_eq!(client.post_bond(&s.issuer(0), &MIN_BOND), MIN_BOND);
+        assert_eq!(
+            TokenClient::new(&s.env, &asset).balance(&s.contract_id),
+            MIN_BOND
+        );
+        s.issue_allocation(0, 0, 100);
+        assert_eq!(client.bond(&s.issuer(0)).last_issued_at, s.now());
+    }
+
+    #[test]
+    fn test_dry_run_reports_missing_bond() {
+        let (s, _) = setup();
+        let client = s.client();
+        let purpose = soroban_sdk::String::from_str(&s.env, "Tuition");
+
+        let dry_run = client.try_can_issue(&s.issuer(0), &s.beneficiary(0), &100, &purpose);
+        let result = client.try_issue_credits(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &100,
+            &purpose,
+            &s.expires_at(),
+        );
+        assert_eq!(dry_run, Err(Ok(Error::BondRequired)));
+        assert_eq!(result, Err(Ok(Error::BondRequired.into())));
+
+        client.post_bond(&s.issuer(0), &MIN_BOND);
+        client.can_issue(&s.issuer(0), &s.beneficiary(0), &100, &purpose);
+    }
+
+    #[cfg(feature = "disputes")]
+    #[test]
+    fn test_open_disputes_lock_the_bond() {
+        use crate::testutils::DAY_SECS;
+        use crate::{DisputeConfig, DisputeOutcome};
+        use soroban_sdk::testutils::Address as _;
+
+        let (s, _) = setup();
+        let client = s.client();
+        client.post_bond(&s.issuer(0), &MIN_BOND);
+        client.set_dispute_config(&Some(DisputeConfig {
+            voting_secs: DAY_SECS,
+            default_outcome: DisputeOutcome::ToIssuer,
+            appeal_secs: 0,
+        }));
+        let school = Address::generate(&s.env);
+        let escrow_id = client.issue_to_escrow(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &school,
+            &800,
+            &soroban_sdk::String::from_str(&s.env, "Tuition"),
+            &(s.now() + 30 * DAY_SECS),
+        );
+        let case_id = client.open_dispute(&school, &escrow_id);
+        let s = s.advance_time(COOLDOWN);
+
+        let client = s.client();
+        let result = client.try_withdraw_bond(&s.issuer(0), &1000);
+        assert_eq!(result, Err(Ok(Error::BondLocked.into())));
+
+        // Settling the case frees the bond
+        client.finalize_dispute(&case_id);
+        assert_eq!(client.withdraw_bond(&s.issuer(0), &1000), MIN_BOND - 1000);
+    }
+
+    #[test]
+    fn test_withdraw_after_cooldown() {
+        let (s, asset) = setup();
+        let client = s.client();
+        client.post_bond(&s.issuer(0), &MIN_BOND);
+        let s = s.issue(0, 0, 100).advance_time(COOLDOWN - 1);
+
+        let client = s.client();
+        let result = client.try_withdraw_bond(&s.issuer(0), &1000);
+        assert_eq!(result, Err(Ok(Error::BondLocked.into())));
+
+        let s = s.advance_time(1);
+        let client = s.client();
+        assert_eq!(client.withdraw_bond(&s.issuer(0), &1000), MIN_BOND - 1000);
+        assert_eq!(
+            TokenClient::new(&s.env, &asset).balance(&s.issuer(0)),
+            6_000
+        );
+
+        // Below the minimum the issuer can no longer issue
+        let result = client.try_issue_credits(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &100,
+            &soroban_sdk::String::from_str(&s.env, "Tuition"),
+            &s.expires_at(),
+        );
+        assert_eq!(result, Err(Ok(Error::BondRequired.into())));
+    }
+
+    #[test]
+    fn test_slash_bond_pays_recipient() {
+        let (s, asset) = setup();
+        let client = s.client();
+        client.post_bond(&s.issuer(0), &MIN_BOND);
+
+        let result = client.try_slash_bond(
+            &s.issuer(0),
+            &(MIN_BOND + 1),
+            &symbol_short!("fraud"),
+            &s.beneficiary(0),
+        );
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+
+        let remaining = client.slash_bond(
+            &s.issuer(0),
+            &2000,
+            &symbol_short!("fraud"),
+            &s.beneficiary(0),
+        );
+        assert_eq!(remaining, 3000);
+        assert_eq!(
+            TokenClient::new(&s.env, &asset).balance(&s.beneficiary(0)),
+            2000
+        );
+
+        // Bonds are outstanding, so the asset is pinned
+        let other = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        let result = client.try_set_bond_config(&Some(BondConfig {
+            asset: other,
+            min_bond: MIN_BOND,
+            cooldown_secs: COOLDOWN,
+        }));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+    }
+}
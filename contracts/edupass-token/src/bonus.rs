@@ -0,0 +1,276 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, Address, Env, Symbol};
+
+use crate::{
+    add_total_issued, balance_cap, checked_add, checked_sub, is_beneficiary,
+    math::{self, Rounding, BPS},
+    require_admin, telemetry, write_balance, DataKey, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum BonusKey {
+    BonusPool,                    // Credits available for priority-spend matching
+    PriorityPurpose(Symbol),      // Match rate in bps for a priority purpose
+    BonusCap,                     // Default lifetime bonus cap per beneficiary
+    BeneficiaryBonusCap(Address), // Per-beneficiary bonus cap override
+    BonusReceived(Address),       // Lifetime bonus received by a beneficiary
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Mint credits into the bonus pool that funds priority-spend matching
+    pub fn fund_bonus_pool(env: Env, issuer: Address, amount: i128) {
+        telemetry::record(&env, "fund_bonus_pool");
+        issuer.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        set_pool_balance(&env, checked_add(&env, pool_balance(&env), amount));
+        add_total_issued(&env, &None, amount);
+    }
+
+    /// Mark a spending purpose as priority with a match rate, or clear it (admin only)
+    pub fn set_priority_purpose(env: Env, purpose: Symbol, match_bps: Option<u32>) {
+        telemetry::record(&env, "set_priority_purpose");
+        require_admin(&env);
+
+        let key = BonusKey::PriorityPurpose(purpose);
+        match match_bps {
+            Some(match_bps) => {
+                if match_bps == 0 || match_bps as i128 > BPS {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage().persistent().set(&key, &match_bps);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Set the default lifetime bonus cap per beneficiary (admin only, None removes it)
+    pub fn set_bonus_cap(env: Env, cap: Option<i128>) {
+        telemetry::record(&env, "set_bonus_cap");
+        require_admin(&env);
+
+        match cap {
+            Some(cap) => {
+                if cap < 0 {
+                    panic_with_error!(&env, Error::InvalidAmount);
+                }
+                env.storage().instance().set(&BonusKey::BonusCap, &cap);
+            }
+            None => env.storage().instance().remove(&BonusKey::BonusCap),
+        }
+    }
+
+    /// Override the lifetime bonus cap for one beneficiary (admin only, None removes it)
+    pub fn set_beneficiary_bonus_cap(env: Env, beneficiary: Address, cap: Option<i128>) {
+        telemetry::record(&env, "set_beneficiary_bonus_cap");
+        require_admin(&env);
+
+        let key = BonusKey::BeneficiaryBonusCap(beneficiary);
+        match cap {
+            Some(cap) => {
+                if cap < 0 {
+                    panic_with_error!(&env, Error::InvalidAmount);
+                }
+                env.storage().persistent().set(&key, &cap);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Get the match rate for a priority purpose
+    pub fn priority_match_bps(env: Env, purpose: Symbol) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get(&BonusKey::PriorityPurpose(purpose))
+    }
+
+    /// Get credits available in the bonus pool
+    pub fn bonus_pool_balance(env: Env) -> i128 {
+        pool_balance(&env)
+    }
+
+    /// Get total bonus credits a beneficiary has received
+    pub fn bonus_received(env: Env, beneficiary: Address) -> i128 {
+        received(&env, &beneficiary)
+    }
+
+    /// Get the effective lifetime bonus cap for a beneficiary
+    pub fn bonus_cap(env: Env, beneficiary: Address) -> Option<i128> {
+        effective_cap(&env, &beneficiary)
+    }
+}
+
+/// Top up a beneficiary for spending on a priority purpose. The bonus is
+/// limited by the pool, the beneficiary's bonus cap and holding cap, so
+/// the transfer itself never fails for lack of bonus funding.
+pub(crate) fn match_priority_spend(
+    env: &Env,
+    beneficiary: &Address,
+    amount: i128,
+    purpose: &Symbol,
+) -> i128 {
+    let match_bps: u32 = match env
+        .storage()
+        .persistent()
+        .get(&BonusKey::PriorityPurpose(purpose.clone()))
+    {
+        Some(match_bps) => match_bps,
+        None => return 0,
+    };
+    if !is_beneficiary(env, beneficiary) {
+        return 0;
+    }
+
+    let balance: i128 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Credits(beneficiary.clone()))
+        .unwrap_or(0);
+    let already_received = received(env, beneficiary);
+
+    let mut bonus = math::mul_div(env, amount, match_bps as i128, BPS, Rounding::Down);
+    bonus = bonus.min(pool_balance(env));
+    if let Some(cap) = effective_cap(env, beneficiary) {
+        bonus = bonus.min(cap - already_received);
+    }
+    if let Some(cap) = balance_cap(env, beneficiary) {
+        bonus = bonus.min(cap - balance);
+    }
+    if bonus <= 0 {
+        return 0;
+    }
+
+    set_pool_balance(env, checked_sub(env, pool_balance(env), bonus));
+    write_balance(env, beneficiary, checked_add(env, balance, bonus));
+    env.storage().persistent().set(
+        &BonusKey::BonusReceived(beneficiary.clone()),
+        &checked_add(env, already_received, bonus),
+    );
+
+    bonus
+}
+
+/// Drop an account's bonus records
+pub(crate) fn remove_account(env: &Env, account: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&BonusKey::BonusReceived(account.clone()));
+    env.storage()
+        .persistent()
+        .remove(&BonusKey::BeneficiaryBonusCap(account.clone()));
+}
+
+pub(crate) fn pool_balance(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&BonusKey::BonusPool)
+        .unwrap_or(0)
+}
+
+fn set_pool_balance(env: &Env, balance: i128) {
+    env.storage().instance().set(&BonusKey::BonusPool, &balance);
+}
+
+fn received(env: &Env, beneficiary: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&BonusKey::BonusReceived(beneficiary.clone()))
+        .unwrap_or(0)
+}
+
+// Per-beneficiary override, falling back to the default cap
+fn effective_cap(env: &Env, beneficiary: &Address) -> Option<i128> {
+    env.storage()
+        .persistent()
+        .get(&BonusKey::BeneficiaryBonusCap(beneficiary.clone()))
+        .or_else(|| env.storage().instance().get(&BonusKey::BonusCap))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{symbol_short, String};
+
+    #[test]
+    fn test_priority_spend_matching() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+
+        client.initialize(&admin);
+        client.fund_bonus_pool(&issuer, &1000);
+        client.set_priority_purpose(&symbol_short!("stem"), &Some(1000));
+        client.set_bonus_cap(&Some(150));
+
+        client.issue_credits(
+            &issuer,
+            &beneficiary,
+            &2000,
+            &String::from_str(&env, "Tuition"),
+            &1735689600,
+        );
+
+        // 10% match on priority spending
+        let bonus =
+            client.transfer_for_purpose(&beneficiary, &school, &1000, &symbol_short!("stem"));
+        assert_eq!(bonus, 100);
+        assert_eq!(client.balance(&beneficiary), 1100);
+
+        // Non-priority spending earns nothing
+        let bonus =
+            client.transfer_for_purpose(&beneficiary, &school, &100, &symbol_short!("books"));
+        assert_eq!(bonus, 0);
+
+        // The bonus cap limits further matching
+        let bonus =
+            client.transfer_for_purpose(&beneficiary, &school, &1000, &symbol_short!("stem"));
+        assert_eq!(bonus, 50);
+        assert_eq!(client.bonus_received(&beneficiary), 150);
+        assert_eq!(client.bonus_pool_balance(), 850);
+        assert_eq!(client.total_issued(), 3000);
+    }
+
+    #[test]
+    fn test_bonus_limited_by_pool() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+
+        client.initialize(&admin);
+        client.fund_bonus_pool(&issuer, &30);
+        client.set_priority_purpose(&symbol_short!("stem"), &Some(5000));
+        client.set_beneficiary_bonus_cap(&beneficiary, &Some(1000));
+
+        client.issue_credits(
+            &issuer,
+            &beneficiary,
+            &500,
+            &String::from_str(&env, "Tuition"),
+            &1735689600,
+        );
+
+        let bonus =
+            client.transfer_for_purpose(&beneficiary, &school, &100, &symbol_short!("stem"));
+        assert_eq!(bonus, 30);
+        assert_eq!(client.bonus_pool_balance(), 0);
+        assert_eq!(client.bonus_cap(&beneficiary), Some(1000));
+    }
+}
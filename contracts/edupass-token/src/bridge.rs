@@ -0,0 +1,515 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, xdr::ToXdr, Address, Bytes, BytesN,
+    Env, Symbol,
+};
+
+use crate::{
+    checked_add, credit_capped, debit, events, replay, require_admin, telemetry, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum BridgeKey {
+    BridgeRelayer,       // Relayer trusted to verify inbound transfers
+    BridgeChain(Symbol), // Limits for a connected chain (absent when not connected)
+    BridgeFlow(Symbol),  // Credits moved to and from a chain in the current period
+    ChainPaused(Symbol), // Set while bridging to and from a chain is paused
+    BridgeEscrow,        // Credits locked while they circulate on other chains
+    ChainEscrow(Symbol), // Credits locked for one chain and not yet released back
+    BridgeLockCount,     // Last assigned lock id
+}
+
+// A chain credits can be bridged to, with per-period limits each way
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BridgeChain {
+    pub outbound_limit: i128, // Credits that may be locked for the chain per period
+    pub inbound_limit: i128,  // Credits that may be released from the chain per period
+    pub period_secs: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BridgeFlow {
+    pub started_at: u64,
+    pub outbound: i128,
+    pub inbound: i128,
+}
+
+// An inbound transfer as attested by the relayer
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BridgeProof {
+    pub source_chain: Symbol,
+    pub source_tx: BytesN<32>, // Hash of the transaction that burned or locked on the source chain
+    pub recipient: Address,
+    pub amount: i128,
+    pub valid_until: u64, // Last moment the relayer's attestation may be acted on
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the relayer that verifies inbound transfers
+    /// (admin only)
+    pub fn set_bridge_relayer(env: Env, relayer: Option<Address>) {
+        telemetry::record(&env, "set_bridge_relayer");
+        require_admin(&env);
+
+        match relayer {
+            Some(relayer) => env
+                .storage()
+                .instance()
+                .set(&BridgeKey::BridgeRelayer, &relayer),
+            None => env.storage().instance().remove(&BridgeKey::BridgeRelayer),
+        }
+    }
+
+    /// Get the bridge relayer, if set
+    pub fn bridge_relayer(env: Env) -> Option<Address> {
+        env.storage().instance().get(&BridgeKey::BridgeRelayer)
+    }
+
+    /// Connect, update or disconnect (None) a chain (admin only)
+    pub fn set_bridge_chain(env: Env, chain: Symbol, config: Option<BridgeChain>) {
+        telemetry::record(&env, "set_bridge_chain");
+        require_admin(&env);
+
+        let key = BridgeKey::BridgeChain(chain);
+        match config {
+            Some(config) => {
+                if config.outbound_limit < 0 || config.inbound_limit < 0 || config.period_secs == 0
+                {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage().persistent().set(&key, &config);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Get a connected chain's limits
+    pub fn bridge_chain(env: Env, chain: Symbol) -> Option<BridgeChain> {
+        env.storage()
+            .persistent()
+            .get(&BridgeKey::BridgeChain(chain))
+    }
+
+    /// Get the credits moved to and from a chain in its current period
+    pub fn bridge_flow(env: Env, chain: Symbol) -> BridgeFlow {
+        env.storage()
+            .persistent()
+            .get(&BridgeKey::BridgeFlow(chain))
+            .unwrap_or_default()
+    }
+
+    /// Pause or resume bridging to and from a chain (admin only)
+    pub fn set_bridge_paused(env: Env, chain: Symbol, paused: bool) {
+        telemetry::record(&env, "set_bridge_paused");
+        require_admin(&env);
+
+        let key = BridgeKey::ChainPaused(chain.clone());
+        if paused {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        events::publish(&env, (symbol_short!("brpause"), chain), paused);
+    }
+
+    /// Check whether bridging to and from a chain is paused
+    pub fn is_bridge_paused(env: Env, chain: Symbol) -> bool {
+        is_paused(&env, &chain)
+    }
+
+    /// Credits locked in escrow while they circulate on other chains
+    pub fn bridge_escrow(env: Env) -> i128 {
+        escrow_balance(&env)
+    }
+
+    /// Credits locked for a chain that haven't been released back from it
+    pub fn bridge_chain_escrow(env: Env, chain: Symbol) -> i128 {
+        chain_escrow(&env, &chain)
+    }
+
+    /// Escrow credits for delivery to `dest_addr` on another chain; the
+    /// relayer picks up the event. Returns the lock id.
+    pub fn lock_for_bridge(
+        env: Env,
+        beneficiary: Address,
+        amount: i128,
+        dest_chain: Symbol,
+        dest_addr: Bytes,
+    ) -> u64 {
+        telemetry::record(&env, "lock_for_bridge");
+        beneficiary.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        let chain = require_chain(&env, &dest_chain);
+        let mut flow = current_flow(&env, &dest_chain, &chain);
+        flow.outbound = checked_add(&env, flow.outbound, amount);
+        if flow.outbound > chain.outbound_limit {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+
+        debit(&env, &beneficiary, amount);
+        set_escrow_balance(&env, checked_add(&env, escrow_balance(&env), amount));
+        let locked = checked_add(&env, chain_escrow(&env, &dest_chain), amount);
+        set_chain_escrow(&env, &dest_chain, locked);
+        write_flow(&env, &dest_chain, &flow);
+
+        let id = env
+            .storage()
+            .instance()
+            .get(&BridgeKey::BridgeLockCount)
+            .unwrap_or(0u64)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&BridgeKey::BridgeLockCount, &id);
+        events::publish(
+            &env,
+            (symbol_short!("bridged"), dest_chain, beneficiary),
+            (id, amount, dest_addr),
+        );
+
+        id
+    }
+
+    /// Release escrowed credits for an inbound transfer (relayer only).
+    /// Each source transaction is released once, before the proof's
+    /// deadline; released transactions are remembered for good, so a
+    /// re-signed proof for one is still refused.
+    pub fn release(env: Env, proof: BridgeProof) {
+        telemetry::record(&env, "release");
+        let relayer: Address = env
+            .storage()
+            .instance()
+            .get(&BridgeKey::BridgeRelayer)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        relayer.require_auth();
+
+        if proof.amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        let chain = require_chain(&env, &proof.source_chain);
+        if env.ledger().timestamp() > proof.valid_until {
+            panic_with_error!(&env, Error::SignatureExpired);
+        }
+        replay::consume(&env, &release_id(&env, &proof), None);
+        let mut flow = current_flow(&env, &proof.source_chain, &chain);
+        flow.inbound = checked_add(&env, flow.inbound, proof.amount);
+        if flow.inbound > chain.inbound_limit {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+
+        // Only credits that left for the chain can come back from it
+        let locked = chain_escrow(&env, &proof.source_chain);
+        if locked < proof.amount {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+        set_chain_escrow(&env, &proof.source_chain, locked - proof.amount);
+        set_escrow_balance(&env, escrow_balance(&env) - proof.amount);
+        write_flow(&env, &proof.source_chain, &flow);
+        credit_capped(&env, &proof.recipient, proof.amount);
+
+        events::publish(
+            &env,
+            (
+                symbol_short!("released"),
+                proof.source_chain,
+                proof.recipient,
+            ),
+            (proof.amount, proof.source_tx),
+        );
+    }
+}
+
+/// Credits held in bridge escrow
+pub(crate) fn escrow_balance(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&BridgeKey::BridgeEscrow)
+        .unwrap_or(0)
+}
+
+fn set_escrow_balance(env: &Env, balance: i128) {
+    env.storage()
+        .instance()
+        .set(&BridgeKey::BridgeEscrow, &balance);
+}
+
+fn chain_escrow(env: &Env, chain: &Symbol) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&BridgeKey::ChainEscrow(chain.clone()))
+        .unwrap_or(0)
+}
+
+fn set_chain_escrow(env: &Env, chain: &Symbol, balance: i128) {
+    let key = BridgeKey::ChainEscrow(chain.clone());
+    if balance == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &balance);
+    }
+}
+
+// Replay key of a release: the source transaction, scoped to its chain
+fn release_id(env: &Env, proof: &BridgeProof) -> BytesN<32> {
+    let source = (proof.source_chain.clone(), proof.source_tx.clone());
+    env.crypto().sha256(&source.to_xdr(env)).to_bytes()
+}
+
+fn is_paused(env: &Env, chain: &Symbol) -> bool {
+    env.storage()
+        .persistent()
+        .has(&BridgeKey::ChainPaused(chain.clone()))
+}
+
+// Load a connected chain's limits, refusing chains that are unknown or paused
+fn require_chain(env: &Env, chain: &Symbol) -> BridgeChain {
+    let config = env
+        .storage()
+        .persistent()
+        .get(&BridgeKey::BridgeChain(chain.clone()))
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotConfigured));
+    if is_paused(env, chain) {
+        panic_with_error!(env, Error::Paused);
+    }
+    config
+}
+
+// A chain's flow, restarted once its period has run out
+fn current_flow(env: &Env, chain: &Symbol, config: &BridgeChain) -> BridgeFlow {
+    let now = env.ledger().timestamp();
+    let flow: BridgeFlow = env
+        .storage()
+        .persistent()
+        .get(&BridgeKey::BridgeFlow(chain.clone()))
+        .unwrap_or_default();
+    if now >= flow.started_at.saturating_add(config.period_secs) {
+        return BridgeFlow {
+            started_at: now,
+            outbound: 0,
+            inbound: 0,
+        };
+    }
+    flow
+}
+
+fn write_flow(env: &Env, chain: &Symbol, flow: &BridgeFlow) {
+    env.storage()
+        .persistent()
+        .set(&BridgeKey::BridgeFlow(chain.clone()), flow);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, advance_ledgers, Scenario, DAY_SECS};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    fn setup() -> (Scenario, Address) {
+        setup_with(Env::default())
+    }
+
+    fn setup_with(env: Env) -> (Scenario, Address) {
+        let s = Scenario::with_env(env)
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(2)
+            .issue(0, 0, 1000);
+        let relayer = Address::generate(&s.env);
+        let client = s.client();
+        client.set_bridge_relayer(&Some(relayer.clone()));
+        client.set_bridge_chain(
+            &symbol_short!("partner"),
+            &Some(BridgeChain {
+                outbound_limit: 500,
+                inbound_limit: 500,
+                period_secs: DAY_SECS,
+            }),
+        );
+        (s, relayer)
+    }
+
+    fn proof(s: &Scenario, tx: u8, recipient: Address, amount: i128) -> BridgeProof {
+        BridgeProof {
+            source_chain: symbol_short!("partner"),
+            source_tx: BytesN::from_array(&s.env, &[tx; 32]),
+            recipient,
+            amount,
+            valid_until: s.now() + DAY_SECS,
+        }
+    }
+
+    #[test]
+    fn test_lock_and_release_round_trip() {
+        let (s, _) = setup();
+        let client = s.client();
+        let chain = symbol_short!("partner");
+        let dest = Bytes::from_slice(&s.env, b"0xabc");
+
+        assert_eq!(
+            client.lock_for_bridge(&s.beneficiary(0), &400, &chain, &dest),
+            1
+        );
+        assert_eq!(client.balance(&s.beneficiary(0)), 600);
+        assert_eq!(client.bridge_escrow(), 400);
+
+        // Credits come back to a different beneficiary
+        client.release(&proof(&s, 1, s.beneficiary(1), 250));
+        assert_eq!(client.balance(&s.beneficiary(1)), 250);
+        assert_eq!(client.bridge_escrow(), 150);
+        let result = client.try_release(&proof(&s, 1, s.beneficiary(1), 250));
+        assert_eq!(result, Err(Ok(Error::NonceAlreadyUsed.into())));
+        let mut stale = proof(&s, 3, s.beneficiary(1), 10);
+        stale.valid_until = s.now() - 1;
+        let result = client.try_release(&stale);
+        assert_eq!(result, Err(Ok(Error::SignatureExpired.into())));
+
+        // Escrow, not the relayer, bounds what can be released
+        let result = client.try_release(&proof(&s, 2, s.beneficiary(1), 200));
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+
+        let sample = soroban_sdk::vec![&s.env, s.beneficiary(0), s.beneficiary(1)];
+        assert!(client.check_invariants(&sample).ok);
+    }
+
+    #[test]
+    fn test_locks_spend_the_allocation_and_releases_respect_caps() {
+        let (s, _) = setup();
+        let client = s.client();
+        let beneficiary = s.beneficiary(0);
+        let dest = Bytes::from_slice(&s.env, b"0xabc");
+        client.lock_for_bridge(&beneficiary, &400, &symbol_short!("partner"), &dest);
+
+        client.set_max_balance_per_beneficiary(&Some(700));
+        let result = client.try_release(&proof(&s, 1, beneficiary.clone(), 250));
+        assert_eq!(result, Err(Ok(Error::BalanceCapExceeded.into())));
+        client.release(&proof(&s, 1, beneficiary.clone(), 100));
+        assert_eq!(client.balance(&beneficiary), 700);
+        // The locked credits were spent from the allocation, so credits
+        // coming back don't restore it
+        assert_eq!(client.allocation_remaining(&beneficiary), 600);
+    }
+
+    #[test]
+    fn test_source_transactions_release_once_for_good() {
+        // Entries written from here on outlive the ledgers advanced below
+        let env = Env::default();
+        env.ledger()
+            .with_mut(|ledger| ledger.min_persistent_entry_ttl = 100_000);
+        let (s, _) = setup_with(env);
+        let client = s.client();
+        let chain = symbol_short!("partner");
+        let dest = Bytes::from_slice(&s.env, b"0xabc");
+        client.lock_for_bridge(&s.beneficiary(0), &400, &chain, &dest);
+        client.release(&proof(&s, 1, s.beneficiary(1), 100));
+
+        // Long after the first proof's deadline, a fresh proof for the same
+        // source transaction is still refused
+        advance_ledgers(&s.env, (2 * DAY_SECS / 5) as u32);
+        advance(&s.env, 2 * DAY_SECS);
+        let result = client.try_release(&proof(&s, 1, s.beneficiary(1), 100));
+        assert_eq!(result, Err(Ok(Error::NonceAlreadyUsed.into())));
+
+        // The same transaction hash from another chain is a different
+        // transfer
+        let other = symbol_short!("other");
+        client.set_bridge_chain(
+            &other,
+            &Some(BridgeChain {
+                outbound_limit: 500,
+                inbound_limit: 500,
+                period_secs: DAY_SECS,
+            }),
+        );
+        client.lock_for_bridge(&s.beneficiary(0), &100, &other, &dest);
+        let mut proof = proof(&s, 1, s.beneficiary(1), 100);
+        proof.source_chain = other;
+        client.release(&proof);
+        assert_eq!(client.balance(&s.beneficiary(1)), 200);
+    }
+
+    #[test]
+    fn test_releases_only_spend_their_chains_escrow() {
+        let (s, _) = setup();
+        let client = s.client();
+        let (partner, other) = (symbol_short!("partner"), symbol_short!("other"));
+        client.set_bridge_chain(
+            &other,
+            &Some(BridgeChain {
+                outbound_limit: 500,
+                inbound_limit: 500,
+                period_secs: DAY_SECS,
+            }),
+        );
+        let dest = Bytes::from_slice(&s.env, b"0xabc");
+        client.lock_for_bridge(&s.beneficiary(0), &300, &partner, &dest);
+        client.lock_for_bridge(&s.beneficiary(0), &100, &other, &dest);
+        assert_eq!(client.bridge_chain_escrow(&partner), 300);
+        assert_eq!(client.bridge_chain_escrow(&other), 100);
+
+        // The other chain can't draw on credits locked for the partner
+        let mut from_other = proof(&s, 1, s.beneficiary(1), 150);
+        from_other.source_chain = other.clone();
+        let result = client.try_release(&from_other);
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+        from_other.amount = 100;
+        client.release(&from_other);
+        client.release(&proof(&s, 2, s.beneficiary(1), 150));
+        assert_eq!(
+            (
+                client.bridge_chain_escrow(&partner),
+                client.bridge_chain_escrow(&other),
+                client.bridge_escrow()
+            ),
+            (150, 0, 150)
+        );
+    }
+
+    #[test]
+    fn test_per_chain_limits_reset_each_period() {
+        let (s, _) = setup();
+        let client = s.client();
+        let chain = symbol_short!("partner");
+        let dest = Bytes::from_slice(&s.env, b"0xabc");
+        client.lock_for_bridge(&s.beneficiary(0), &500, &chain, &dest);
+
+        let result = client.try_lock_for_bridge(&s.beneficiary(0), &1, &chain, &dest);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+        let result = client.try_release(&proof(&s, 1, s.beneficiary(1), 501));
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+
+        let s = s.advance_days(1);
+        let client = s.client();
+        client.lock_for_bridge(&s.beneficiary(0), &100, &chain, &dest);
+        assert_eq!(client.bridge_flow(&chain).outbound, 100);
+
+        let result =
+            client.try_lock_for_bridge(&s.beneficiary(0), &1, &symbol_short!("other"), &dest);
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+    }
+
+    #[test]
+    fn test_pause_stops_both_directions() {
+        let (s, _) = setup();
+        let client = s.client();
+        let chain = symbol_short!("partner");
+        let dest = Bytes::from_slice(&s.env, b"0xabc");
+        client.lock_for_bridge(&s.beneficiary(0), &100, &chain, &dest);
+
+        client.set_bridge_paused(&chain, &true);
+        let result = client.try_lock_for_bridge(&s.beneficiary(0), &100, &chain, &dest);
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+        let result = client.try_release(&proof(&s, 1, s.beneficiary(1), 100));
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+
+        client.set_bridge_paused(&chain, &false);
+        client.release(&proof(&s, 1, s.beneficiary(1), 100));
+        assert_eq!(client.bridge_escrow(), 0);
+    }
+}
@@ -0,0 +1,235 @@
+// Per-purpose sub-budgets of a program. The admin caps how much of a
+// program's credits its beneficiaries may spend on a purpose through
+// transfer_for_purpose; purposes without a budget are unrestricted.
+// Governance can move unspent budget between purposes mid-year, and every
+// move is kept in the program's rebalance log.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Symbol,
+};
+
+use crate::{
+    checked_add, events, program_of, require_admin, require_governance, telemetry, DataKey,
+    EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum BudgetKey {
+    PurposeBudget(Symbol, Symbol), // A program's budget for one purpose
+    BudgetRebalance(Symbol, u32),  // One entry of a program's rebalance log
+    RebalanceCount(Symbol),        // Entries in a program's rebalance log
+}
+
+// What a program may spend on a purpose, and has spent
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PurposeBudget {
+    pub cap: i128,
+    pub spent: i128,
+}
+
+// Unspent budget governance moved between two purposes of a program
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BudgetRebalance {
+    pub from_purpose: Symbol,
+    pub to_purpose: Symbol,
+    pub amount: i128,
+    pub at: u64,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the cap on what a program's beneficiaries may
+    /// spend on a purpose (admin only). What was spent so far still counts
+    /// against a new cap.
+    pub fn set_purpose_budget(env: Env, program_id: Symbol, purpose: Symbol, cap: Option<i128>) {
+        telemetry::record(&env, "set_purpose_budget");
+        require_admin(&env);
+
+        let key = BudgetKey::PurposeBudget(program_id, purpose);
+        match cap {
+            Some(cap) => {
+                if cap < 0 {
+                    panic_with_error!(&env, Error::InvalidAmount);
+                }
+                let mut budget: PurposeBudget =
+                    env.storage().persistent().get(&key).unwrap_or_default();
+                budget.cap = cap;
+                env.storage().persistent().set(&key, &budget);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Get a program's budget for a purpose, if it has one
+    pub fn purpose_budget(env: Env, program_id: Symbol, purpose: Symbol) -> Option<PurposeBudget> {
+        env.storage()
+            .persistent()
+            .get(&BudgetKey::PurposeBudget(program_id, purpose))
+    }
+
+    /// Move `amount` of unspent budget from one purpose of a program to
+    /// another (governance only) and log it. Both purposes must have a
+    /// budget; moving more than `from_purpose` has unspent fails with
+    /// BudgetExceeded.
+    pub fn rebalance_budget(
+        env: Env,
+        program_id: Symbol,
+        from_purpose: Symbol,
+        to_purpose: Symbol,
+        amount: i128,
+    ) {
+        telemetry::record(&env, "rebalance_budget");
+        require_governance(&env);
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if from_purpose == to_purpose {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        let budget_of = |purpose: &Symbol| {
+            Self::purpose_budget(env.clone(), program_id.clone(), purpose.clone())
+                .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound))
+        };
+        let mut from = budget_of(&from_purpose);
+        let mut to = budget_of(&to_purpose);
+        if from.cap - from.spent < amount {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        from.cap -= amount;
+        to.cap = checked_add(&env, to.cap, amount);
+        let storage = env.storage().persistent();
+        storage.set(
+            &BudgetKey::PurposeBudget(program_id.clone(), from_purpose.clone()),
+            &from,
+        );
+        storage.set(
+            &BudgetKey::PurposeBudget(program_id.clone(), to_purpose.clone()),
+            &to,
+        );
+
+        let index = Self::rebalance_count(env.clone(), program_id.clone());
+        storage.set(
+            &BudgetKey::BudgetRebalance(program_id.clone(), index),
+            &BudgetRebalance {
+                from_purpose: from_purpose.clone(),
+                to_purpose: to_purpose.clone(),
+                amount,
+                at: env.ledger().timestamp(),
+            },
+        );
+        storage.set(&BudgetKey::RebalanceCount(program_id.clone()), &(index + 1));
+        events::publish(
+            &env,
+            (symbol_short!("rebalance"), program_id),
+            (from_purpose, to_purpose, amount),
+        );
+    }
+
+    /// Number of entries in a program's rebalance log
+    pub fn rebalance_count(env: Env, program_id: Symbol) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&BudgetKey::RebalanceCount(program_id))
+            .unwrap_or(0)
+    }
+
+    /// Get an entry of a program's rebalance log, oldest first
+    pub fn budget_rebalance(env: Env, program_id: Symbol, index: u32) -> Option<BudgetRebalance> {
+        env.storage()
+            .persistent()
+            .get(&BudgetKey::BudgetRebalance(program_id, index))
+    }
+}
+
+/// Count `amount` spent by `account` on `purpose` against its program's
+/// budget for it, failing with BudgetExceeded past the cap
+pub(crate) fn spend(env: &Env, account: &Address, amount: i128, purpose: &Symbol) {
+    let Some(program_id) = program_of(
+        &env.storage()
+            .persistent()
+            .get(&DataKey::Allocations(account.clone())),
+    ) else {
+        return;
+    };
+    let key = BudgetKey::PurposeBudget(program_id, purpose.clone());
+    let Some(mut budget) = env.storage().persistent().get::<_, PurposeBudget>(&key) else {
+        return;
+    };
+    budget.spent = checked_add(env, budget.spent, amount);
+    if budget.spent > budget.cap {
+        panic_with_error!(env, Error::BudgetExceeded);
+    }
+    env.storage().persistent().set(&key, &budget);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::String;
+
+    fn setup() -> (Scenario, Symbol) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1);
+        let client = s.client();
+        client.set_governance(&Address::generate(&s.env));
+        let program = symbol_short!("bursary");
+        client.save_template(
+            &s.issuer(0),
+            &symbol_short!("term"),
+            &String::from_str(&s.env, "Tuition"),
+            &1000,
+            &(90 * 24 * 60 * 60),
+            &program,
+        );
+        client.issue_from_template(&s.issuer(0), &symbol_short!("term"), &s.beneficiary(0));
+        client.set_purpose_budget(&program, &symbol_short!("books"), &Some(300));
+        client.set_purpose_budget(&program, &symbol_short!("meals"), &Some(100));
+        (s, program)
+    }
+
+    #[test]
+    fn test_purpose_spending_capped() {
+        let (s, program) = setup();
+        let client = s.client();
+        let (beneficiary, school) = (s.beneficiary(0), s.school(0));
+        client.transfer_for_purpose(&beneficiary, &school, &250, &symbol_short!("books"));
+        let result =
+            client.try_transfer_for_purpose(&beneficiary, &school, &51, &symbol_short!("books"));
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+        // Purposes without a budget are unrestricted
+        client.transfer_for_purpose(&beneficiary, &school, &400, &symbol_short!("fees"));
+        assert_eq!(
+            client.purpose_budget(&program, &symbol_short!("books")),
+            Some(PurposeBudget {
+                cap: 300,
+                spent: 250
+            })
+        );
+    }
+
+    #[test]
+    fn test_rebalance_moves_unspent_budget() {
+        let (s, program) = setup();
+        let client = s.client();
+        let (books, meals) = (symbol_short!("books"), symbol_short!("meals"));
+        client.transfer_for_purpose(&s.beneficiary(0), &s.school(0), &250, &books);
+        let result = client.try_rebalance_budget(&program, &books, &meals, &51);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+
+        client.rebalance_budget(&program, &books, &meals, &50);
+        assert_eq!(client.purpose_budget(&program, &books).unwrap().cap, 250);
+        assert_eq!(client.purpose_budget(&program, &meals).unwrap().cap, 150);
+        assert_eq!(client.rebalance_count(&program), 1);
+        let entry = client.budget_rebalance(&program, &0).unwrap();
+        assert_eq!((entry.from_purpose, entry.amount), (books, 50));
+        client.transfer_for_purpose(&s.beneficiary(0), &s.school(0), &150, &meals);
+    }
+}
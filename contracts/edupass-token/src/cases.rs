@@ -0,0 +1,245 @@
+// Fraud case records, so an investigation's trail lives next to the
+// actions it led to. The compliance officer opens a case against a subject
+// with a hash of the evidence held off chain, attaches the related
+// accounts and redemption receipts, moves it through open, frozen and
+// resolved, and links the program freezes, clawback batches and bond
+// slashes taken under it. Resolved cases are read-only.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, BytesN, Env, Symbol, Vec,
+};
+
+use crate::{
+    clawbacks, events, freeze, reputation, telemetry, EduPassToken, EduPassTokenClient, Error,
+};
+
+// Accounts, receipts and actions one case can list, each
+const MAX_CASE_ITEMS: u32 = 32;
+
+#[contracttype]
+enum CaseKey {
+    FraudCase(u64), // A case and its trail
+    FraudCaseCount, // Last assigned case id
+}
+
+// Where an investigation stands
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CaseStatus {
+    Open,     // Under investigation
+    Frozen,   // Funds secured while the investigation continues
+    Resolved, // Closed; the record no longer changes
+}
+
+// An enforcement action taken under a case
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CaseAction {
+    Freeze(Symbol),       // A program frozen with freeze_program
+    Clawback(u64),        // A batch scheduled with clawback_batch
+    Slash(Address, i128), // An issuer's bond slashed with slash_bond, and by how much
+}
+
+// An investigation and everything linked to it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FraudCase {
+    pub subject: Address,
+    pub evidence_hash: BytesN<32>, // Hash of the evidence bundle held off chain
+    pub opened_at: u64,
+    pub status: CaseStatus,
+    pub accounts: Vec<Address>, // Related accounts
+    pub receipts: Vec<u64>,     // Related redemption receipts
+    pub actions: Vec<CaseAction>,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Open a case against a subject (compliance officer only); returns
+    /// the case id
+    pub fn open_case(env: Env, subject: Address, evidence_hash: BytesN<32>) -> u64 {
+        telemetry::record(&env, "open_case");
+        freeze::require_compliance(&env);
+
+        let id = env
+            .storage()
+            .instance()
+            .get(&CaseKey::FraudCaseCount)
+            .unwrap_or(0u64)
+            + 1;
+        env.storage().instance().set(&CaseKey::FraudCaseCount, &id);
+
+        write_case(
+            &env,
+            id,
+            &FraudCase {
+                subject: subject.clone(),
+                evidence_hash: evidence_hash.clone(),
+                opened_at: env.ledger().timestamp(),
+                status: CaseStatus::Open,
+                accounts: Vec::new(&env),
+                receipts: Vec::new(&env),
+                actions: Vec::new(&env),
+            },
+        );
+        events::publish(
+            &env,
+            (symbol_short!("caseopen"), id),
+            (subject, evidence_hash),
+        );
+
+        id
+    }
+
+    /// Attach related accounts to a case (compliance officer only);
+    /// accounts already attached are skipped
+    pub fn attach_accounts(env: Env, case_id: u64, accounts: Vec<Address>) {
+        telemetry::record(&env, "attach_accounts");
+        let mut case = editable_case(&env, case_id);
+        for account in accounts.iter() {
+            if !case.accounts.contains(&account) {
+                case.accounts.push_back(account);
+            }
+        }
+        if case.accounts.len() > MAX_CASE_ITEMS {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        write_case(&env, case_id, &case);
+    }
+
+    /// Attach related redemption receipts to a case (compliance officer
+    /// only); receipts already attached are skipped
+    pub fn attach_receipts(env: Env, case_id: u64, receipts: Vec<u64>) {
+        telemetry::record(&env, "attach_receipts");
+        let mut case = editable_case(&env, case_id);
+        for receipt_id in receipts.iter() {
+            reputation::read_receipt(&env, receipt_id);
+            if !case.receipts.contains(receipt_id) {
+                case.receipts.push_back(receipt_id);
+            }
+        }
+        if case.receipts.len() > MAX_CASE_ITEMS {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        write_case(&env, case_id, &case);
+    }
+
+    /// Move a case between open and frozen, or resolve it (compliance
+    /// officer only)
+    pub fn set_case_status(env: Env, case_id: u64, status: CaseStatus) {
+        telemetry::record(&env, "set_case_status");
+        let mut case = editable_case(&env, case_id);
+        case.status = status;
+        write_case(&env, case_id, &case);
+        events::publish(&env, (symbol_short!("casestat"), case_id), status);
+    }
+
+    /// Link an action taken under a case to it (compliance officer only).
+    /// Freezes must be in force and clawback batches must have been
+    /// scheduled; slashes are recorded as given.
+    pub fn link_case_action(env: Env, case_id: u64, action: CaseAction) {
+        telemetry::record(&env, "link_case_action");
+        let mut case = editable_case(&env, case_id);
+        let taken = match &action {
+            CaseAction::Freeze(program_id) => {
+                Self::is_program_frozen(env.clone(), program_id.clone())
+            }
+            CaseAction::Clawback(batch_id) => {
+                *batch_id > 0 && *batch_id <= clawbacks::batch_count(&env)
+            }
+            CaseAction::Slash(_, amount) => *amount > 0,
+        };
+        if !taken {
+            panic_with_error!(&env, Error::NotFound);
+        }
+        if case.actions.contains(&action) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        if case.actions.len() >= MAX_CASE_ITEMS {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        case.actions.push_back(action.clone());
+        write_case(&env, case_id, &case);
+        events::publish(&env, (symbol_short!("caselink"), case_id), action);
+    }
+
+    /// Get a case
+    pub fn fraud_case(env: Env, case_id: u64) -> Option<FraudCase> {
+        env.storage().persistent().get(&CaseKey::FraudCase(case_id))
+    }
+}
+
+// A case the compliance officer may still change. Resolved cases fail
+// with AlreadyExists.
+fn editable_case(env: &Env, case_id: u64) -> FraudCase {
+    freeze::require_compliance(env);
+    let case = EduPassToken::fraud_case(env.clone(), case_id)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+    if case.status == CaseStatus::Resolved {
+        panic_with_error!(env, Error::AlreadyExists);
+    }
+    case
+}
+
+fn write_case(env: &Env, case_id: u64, case: &FraudCase) {
+    env.storage()
+        .persistent()
+        .set(&CaseKey::FraudCase(case_id), case);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_case_trail_links_actions() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(2);
+        let client = s.client();
+        let compliance = Address::generate(&s.env);
+        client.set_compliance(&Some(compliance.clone()));
+        let evidence = BytesN::from_array(&s.env, &[7; 32]);
+        let id = client.open_case(&s.issuer(0), &evidence);
+        assert_eq!(s.env.auths()[0].0, compliance);
+
+        let accounts = vec![&s.env, s.beneficiary(0), s.beneficiary(1)];
+        client.attach_accounts(&id, &accounts);
+        client.attach_accounts(&id, &vec![&s.env, s.beneficiary(0)]);
+        let result = client.try_attach_receipts(&id, &vec![&s.env, 1]);
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+
+        let program = symbol_short!("uni");
+        let result = client.try_link_case_action(&id, &CaseAction::Freeze(program.clone()));
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+        client.freeze_program(&compliance, &program);
+        client.link_case_action(&id, &CaseAction::Freeze(program.clone()));
+        let batch = client.clawback_batch(
+            &vec![&s.env, (s.beneficiary(0), 100)],
+            &symbol_short!("ring7"),
+        );
+        client.link_case_action(&id, &CaseAction::Clawback(batch));
+        client.set_case_status(&id, &CaseStatus::Frozen);
+
+        let case = client.fraud_case(&id).unwrap();
+        assert_eq!(case.accounts, accounts);
+        assert_eq!(
+            case.actions,
+            vec![
+                &s.env,
+                CaseAction::Freeze(program),
+                CaseAction::Clawback(batch)
+            ]
+        );
+        assert_eq!(case.status, CaseStatus::Frozen);
+
+        // Resolved cases don't change
+        client.set_case_status(&id, &CaseStatus::Resolved);
+        let result = client.try_attach_accounts(&id, &vec![&s.env, s.issuer(0)]);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+}
@@ -0,0 +1,221 @@
+// Bulk clawbacks for fraud response. The compliance officer schedules a
+// batch of (account, amount) clawbacks under a reason; after a timelock
+// the admin can use to cancel it, the batch is executed a bounded number of
+// accounts per call. Each account's clawed credits go back to its funders
+// pro rata, as with clawback, and each gets its own event carrying the
+// batch id so the trail can be followed per account.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Symbol, Vec,
+};
+
+use crate::{
+    checked_add, events, freeze, refunds, require_admin, telemetry, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+// Delay between scheduling and executing a batch, long enough for the
+// admin to review and cancel but short enough to act before funds move
+const CLAWBACK_TIMELOCK_SECS: u64 = 24 * 60 * 60;
+// Accounts one batch can name
+const MAX_BATCH_ACCOUNTS: u32 = 200;
+// Accounts one execute_clawback call processes at most
+const MAX_CLAWBACKS_PER_CALL: u32 = 20;
+
+#[contracttype]
+enum ClawbackKey {
+    ClawbackBatch(u64), // A scheduled batch and how far execution has got
+    ClawbackCount,      // Last assigned batch id
+}
+
+// Clawbacks scheduled together, executed account by account
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClawbackBatch {
+    pub entries: Vec<(Address, i128)>, // Accounts and the most to claw back from each
+    pub reason: Symbol,
+    pub executable_at: u64,
+    pub next: u32,    // Index of the next entry to execute
+    pub clawed: i128, // Credits clawed back so far
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Schedule clawbacks from many accounts at once (compliance officer
+    /// only); executable after the timelock unless the admin cancels it.
+    /// Returns the batch id.
+    pub fn clawback_batch(env: Env, entries: Vec<(Address, i128)>, reason: Symbol) -> u64 {
+        telemetry::record(&env, "clawback_batch");
+        freeze::require_compliance(&env);
+
+        if entries.is_empty() || entries.len() > MAX_BATCH_ACCOUNTS {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        for (_, amount) in entries.iter() {
+            if amount <= 0 {
+                panic_with_error!(&env, Error::InvalidAmount);
+            }
+        }
+
+        let id = batch_count(&env) + 1;
+        env.storage()
+            .instance()
+            .set(&ClawbackKey::ClawbackCount, &id);
+
+        let batch = ClawbackBatch {
+            entries,
+            reason: reason.clone(),
+            executable_at: env.ledger().timestamp() + CLAWBACK_TIMELOCK_SECS,
+            next: 0,
+            clawed: 0,
+        };
+        write_batch(&env, id, &batch);
+        events::publish(
+            &env,
+            (symbol_short!("cbsched"), id),
+            (batch.entries.len(), reason, batch.executable_at),
+        );
+
+        id
+    }
+
+    /// Cancel a batch's remaining clawbacks (admin only)
+    pub fn cancel_clawback(env: Env, batch_id: u64) {
+        telemetry::record(&env, "cancel_clawback");
+        require_admin(&env);
+
+        read_batch(&env, batch_id);
+        env.storage()
+            .persistent()
+            .remove(&ClawbackKey::ClawbackBatch(batch_id));
+        events::publish(&env, (symbol_short!("cbcancel"), batch_id), ());
+    }
+
+    /// Execute up to `limit` of a batch's clawbacks once its timelock has
+    /// passed (compliance officer only); returns the entries left. The
+    /// batch is removed once every entry has run.
+    pub fn execute_clawback(env: Env, batch_id: u64, limit: u32) -> u32 {
+        telemetry::record(&env, "execute_clawback");
+        freeze::require_compliance(&env);
+
+        let mut batch = read_batch(&env, batch_id);
+        if env.ledger().timestamp() < batch.executable_at {
+            panic_with_error!(&env, Error::NotExpired);
+        }
+
+        let end = batch
+            .next
+            .saturating_add(limit.min(MAX_CLAWBACKS_PER_CALL))
+            .min(batch.entries.len());
+        for index in batch.next..end {
+            let (account, amount) = batch.entries.get_unchecked(index);
+            let clawed = refunds::refund_part(&env, &account, amount);
+            batch.clawed = checked_add(&env, batch.clawed, clawed);
+            events::publish(
+                &env,
+                (symbol_short!("clawbatch"), batch_id, account),
+                (clawed, batch.reason.clone()),
+            );
+        }
+        batch.next = end;
+
+        let left = batch.entries.len() - end;
+        if left == 0 {
+            env.storage()
+                .persistent()
+                .remove(&ClawbackKey::ClawbackBatch(batch_id));
+            events::publish(&env, (symbol_short!("cbdone"), batch_id), batch.clawed);
+        } else {
+            write_batch(&env, batch_id, &batch);
+        }
+        left
+    }
+
+    /// Get a batch until it has been fully executed or cancelled
+    pub fn clawback_batch_status(env: Env, batch_id: u64) -> Option<ClawbackBatch> {
+        env.storage()
+            .persistent()
+            .get(&ClawbackKey::ClawbackBatch(batch_id))
+    }
+}
+
+/// Batches scheduled so far, including finished and cancelled ones
+pub(crate) fn batch_count(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&ClawbackKey::ClawbackCount)
+        .unwrap_or(0)
+}
+
+fn read_batch(env: &Env, batch_id: u64) -> ClawbackBatch {
+    EduPassToken::clawback_batch_status(env.clone(), batch_id)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound))
+}
+
+fn write_batch(env: &Env, batch_id: u64, batch: &ClawbackBatch) {
+    env.storage()
+        .persistent()
+        .set(&ClawbackKey::ClawbackBatch(batch_id), batch);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_batch_clawed_back_after_timelock() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(3)
+            .issue(0, 0, 1000)
+            .issue(0, 1, 1000)
+            .issue(0, 2, 1000);
+        let client = s.client();
+        let compliance = Address::generate(&s.env);
+        client.set_compliance(&Some(compliance.clone()));
+        let entries = vec![
+            &s.env,
+            (s.beneficiary(0), 1000),
+            (s.beneficiary(1), 300),
+            (s.beneficiary(2), 5000),
+        ];
+        let id = client.clawback_batch(&entries, &symbol_short!("ring7"));
+        assert_eq!(s.env.auths()[0].0, compliance);
+
+        let result = client.try_execute_clawback(&id, &10);
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+        let s = s.advance_time(CLAWBACK_TIMELOCK_SECS);
+        let client = s.client();
+        assert_eq!(client.execute_clawback(&id, &2), 1);
+        assert_eq!(client.balance(&s.beneficiary(1)), 700);
+        assert_eq!(client.clawback_batch_status(&id).unwrap().clawed, 1300);
+        // A clawback takes no more than the account holds
+        assert_eq!(client.execute_clawback(&id, &2), 0);
+        assert_eq!(client.balance(&s.beneficiary(2)), 0);
+        assert_eq!(client.balance(&s.issuer(0)), 2300);
+        assert_eq!(client.clawback_batch_status(&id), None);
+    }
+
+    #[test]
+    fn test_admin_cancels_batch() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        client.set_compliance(&Some(Address::generate(&s.env)));
+        let entries = vec![&s.env, (s.beneficiary(0), 500)];
+        let id = client.clawback_batch(&entries, &symbol_short!("ring7"));
+        client.cancel_clawback(&id);
+
+        let s = s.advance_time(CLAWBACK_TIMELOCK_SECS);
+        let result = s.client().try_execute_clawback(&id, &10);
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+        assert_eq!(s.client().balance(&s.beneficiary(0)), 1000);
+    }
+}
@@ -0,0 +1,157 @@
+use soroban_sdk::{contractimpl, panic_with_error, symbol_short, Address, Env};
+
+use crate::{
+    accounts, demurrage, events, freeze, grace, holds, is_beneficiary, refunds, telemetry,
+    transfer_credits, Allocation, DataKey, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contractimpl]
+impl EduPassToken {
+    /// Close an account, returning any remainder and deleting its entries.
+    /// Unexpired credits are transferred to `return_to` if given, with the
+    /// checks of any transfer, otherwise back to the allocation's funders;
+    /// expired credits follow the expiry policy.
+    /// Returns the amount moved out of the account.
+    pub fn close_account(env: Env, account: Address, return_to: Option<Address>) -> i128 {
+        telemetry::record(&env, "close_account");
+        account.require_auth();
+        freeze::require_account_unfrozen(&env, &account);
+
+        // Held credits stay put until their school takes them or they lapse
+        if holds::held(&env, &account) > 0 {
+            panic_with_error!(&env, Error::CreditsOnHold);
+        }
+
+        let allocation: Option<Allocation> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Allocations(account.clone()));
+        let expired = match &allocation {
+            Some(allocation) => grace::is_expired(&env, allocation),
+            None => false,
+        };
+
+        let balance = demurrage::settle(&env, &account, is_beneficiary(&env, &account));
+        let returned = if balance <= 0 {
+            0
+        } else if expired {
+            refunds::sweep(&env, &account)
+        } else if let Some(return_to) = return_to {
+            transfer_credits(&env, &account, &account, &return_to, balance);
+            balance
+        } else {
+            refunds::refund_funders(&env, &account)
+        };
+
+        // Without an allocation there is no one to return credits to
+        if returned != balance {
+            panic_with_error!(&env, Error::NotFound);
+        }
+
+        // Reclaim rent for everything keyed by the account
+        accounts::remove_account(&env, &account);
+
+        events::publish(&env, (symbol_short!("closed"), account), returned);
+
+        returned
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::String;
+
+    fn setup(env: &Env) -> (EduPassTokenClient<'_>, Address, Address) {
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_700_000_000);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(env, &contract_id);
+
+        let issuer = Address::generate(env);
+        let beneficiary = Address::generate(env);
+
+        client.initialize(&Address::generate(env));
+        client.issue_credits(
+            &issuer,
+            &beneficiary,
+            &1000,
+            &String::from_str(env, "Tuition"),
+            &1_800_000_000,
+        );
+
+        (client, issuer, beneficiary)
+    }
+
+    #[test]
+    fn test_close_account_returns_to_issuer() {
+        let env = Env::default();
+        let (client, issuer, beneficiary) = setup(&env);
+
+        client.transfer(&beneficiary, &Address::generate(&env), &400);
+
+        assert_eq!(client.close_account(&beneficiary, &None), 600);
+        assert_eq!(client.balance(&issuer), 600);
+        assert_eq!(client.balance(&beneficiary), 0);
+        assert_eq!(client.get_allocation(&beneficiary), None);
+    }
+
+    #[test]
+    fn test_close_account_returns_to_address() {
+        let env = Env::default();
+        let (client, issuer, beneficiary) = setup(&env);
+        let sibling_school = Address::generate(&env);
+
+        assert_eq!(
+            client.close_account(&beneficiary, &Some(sibling_school.clone())),
+            1000
+        );
+        assert_eq!(client.balance(&sibling_school), 1000);
+        assert_eq!(client.balance(&issuer), 0);
+    }
+
+    #[test]
+    fn test_close_account_transfers_with_transfer_checks() {
+        let env = Env::default();
+        let (client, issuer, beneficiary) = setup(&env);
+        let sibling = Address::generate(&env);
+        client.issue_credits(
+            &issuer,
+            &sibling,
+            &100,
+            &String::from_str(&env, "Tuition"),
+            &1_800_000_000,
+        );
+
+        // The remainder can't dodge the recipient's holding cap
+        client.set_max_balance_per_beneficiary(&Some(500));
+        let result = client.try_close_account(&beneficiary, &Some(sibling.clone()));
+        assert_eq!(result, Err(Ok(Error::BalanceCapExceeded.into())));
+        assert_eq!(client.balance(&beneficiary), 1000);
+
+        client.set_max_balance_per_beneficiary(&None);
+        assert_eq!(
+            client.close_account(&beneficiary, &Some(sibling.clone())),
+            1000
+        );
+        assert_eq!(client.balance(&sibling), 1100);
+    }
+
+    #[test]
+    fn test_close_account_without_funders() {
+        let env = Env::default();
+        let (client, _, beneficiary) = setup(&env);
+        let school = Address::generate(&env);
+
+        client.transfer(&beneficiary, &school, &100);
+
+        let result = client.try_close_account(&school, &None);
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+        assert_eq!(
+            client.close_account(&school, &Some(beneficiary.clone())),
+            100
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, xdr::ToXdr, Address, Bytes, BytesN,
+    Env, IntoVal, Map, String,
+};
+
+use crate::{
+    add_total_issued, allocate, attestations, bonding, checked_add, events, payloads, telemetry,
+    validity, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum CommitmentKey {
+    Commitment(BytesN<32>), // Credits issued against an identity commitment, awaiting binding
+    CommittedTotal,         // Credits held for all unbound commitments
+}
+
+// Credits issued to a salted identity commitment rather than an address
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommittedAllocation {
+    pub issuer: Address,
+    pub amount: i128,
+    pub expires_at: u64,
+    pub issued_at: u64,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Issue credits against `commitment`, the SHA-256 of a secret salt
+    /// followed by the beneficiary's address in XDR. No purpose or program
+    /// is recorded on chain.
+    pub fn issue_to_commitment(
+        env: Env,
+        issuer: Address,
+        commitment: BytesN<32>,
+        amount: i128,
+        expires_at: u64,
+    ) {
+        telemetry::record(&env, "issue_to_commitment");
+        issuer.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        let key = CommitmentKey::Commitment(commitment.clone());
+        if env.storage().persistent().has(&key) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        let expires_at = validity::resolve_expiry(&env, expires_at);
+        attestations::require_unattested_allowed(&env);
+        bonding::require_bonded(&env, &issuer);
+
+        let committed = CommittedAllocation {
+            issuer: issuer.clone(),
+            amount,
+            expires_at,
+            issued_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&key, &committed);
+        set_committed_total(&env, checked_add(&env, committed_total(&env), amount));
+        add_total_issued(&env, &None, amount);
+        events::publish(
+            &env,
+            (symbol_short!("committed"), commitment),
+            (issuer, amount),
+        );
+    }
+
+    /// Bind a commitment to the beneficiary it was made for by revealing
+    /// the salt (beneficiary auth); the credits become an ordinary
+    /// allocation without a purpose
+    pub fn bind_commitment(
+        env: Env,
+        commitment: BytesN<32>,
+        salt: BytesN<32>,
+        beneficiary: Address,
+    ) {
+        telemetry::record(&env, "bind_commitment");
+        beneficiary.require_auth();
+        bind(&env, commitment, salt, beneficiary);
+    }
+
+    /// Bind a commitment on the beneficiary's behalf, authenticated by
+    /// their signature instead of transaction auth, so a relayer can pay
+    /// the fees
+    pub fn bind_commitment_signed(
+        env: Env,
+        commitment: BytesN<32>,
+        salt: BytesN<32>,
+        beneficiary: Address,
+        valid_until: u64,
+        signature: BytesN<64>,
+    ) {
+        telemetry::record(&env, "bind_commitment_signed");
+        payloads::require_signature(
+            &env,
+            &beneficiary,
+            symbol_short!("bind"),
+            (commitment.clone(), salt.clone(), beneficiary.clone()).into_val(&env),
+            valid_until,
+            &signature,
+        );
+        bind(&env, commitment, salt, beneficiary);
+    }
+
+    /// Get the credits awaiting binding to a commitment
+    pub fn committed_allocation(env: Env, commitment: BytesN<32>) -> Option<CommittedAllocation> {
+        env.storage()
+            .persistent()
+            .get(&CommitmentKey::Commitment(commitment))
+    }
+}
+
+// Turn a commitment into the beneficiary's allocation, once the salt
+// shows it was made for them
+fn bind(env: &Env, commitment: BytesN<32>, salt: BytesN<32>, beneficiary: Address) {
+    let key = CommitmentKey::Commitment(commitment.clone());
+    let committed: CommittedAllocation = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+    if commitment_of(env, &salt, &beneficiary) != commitment {
+        panic_with_error!(env, Error::Unauthorized);
+    }
+
+    env.storage().persistent().remove(&key);
+    set_committed_total(env, committed_total(env) - committed.amount);
+    allocate(
+        env,
+        committed.issuer,
+        beneficiary.clone(),
+        committed.amount,
+        String::from_str(env, ""),
+        committed.expires_at,
+        None,
+        Map::new(env),
+    );
+    events::publish(
+        env,
+        (symbol_short!("bound"), commitment, beneficiary),
+        committed.amount,
+    );
+}
+
+/// Credits issued to commitments that are not yet bound
+pub(crate) fn committed_total(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&CommitmentKey::CommittedTotal)
+        .unwrap_or(0)
+}
+
+fn set_committed_total(env: &Env, total: i128) {
+    env.storage()
+        .instance()
+        .set(&CommitmentKey::CommittedTotal, &total);
+}
+
+// SHA-256 of the salt followed by the address's XDR encoding
+pub(crate) fn commitment_of(env: &Env, salt: &BytesN<32>, beneficiary: &Address) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &salt.to_array());
+    preimage.append(&beneficiary.clone().to_xdr(env));
+    env.crypto().sha256(&preimage).to_bytes()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::vec;
+
+    fn setup() -> (Scenario, BytesN<32>, BytesN<32>) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(2);
+        let salt = BytesN::from_array(&s.env, &[9; 32]);
+        let commitment = commitment_of(&s.env, &salt, &s.beneficiary(0));
+        s.client()
+            .issue_to_commitment(&s.issuer(0), &commitment, &500, &s.expires_at());
+        (s, commitment, salt)
+    }
+
+    #[test]
+    fn test_bind_reveals_allocation_to_beneficiary() {
+        let (s, commitment, salt) = setup();
+        let client = s.client();
+        let committed = client.committed_allocation(&commitment).unwrap();
+        assert_eq!((committed.issuer, committed.amount), (s.issuer(0), 500));
+        assert_eq!(client.total_issued(), 500);
+        assert!(client.check_invariants(&vec![&s.env]).ok);
+
+        client.bind_commitment(&commitment, &salt, &s.beneficiary(0));
+        assert_eq!(client.balance(&s.beneficiary(0)), 500);
+        assert_eq!(client.committed_allocation(&commitment), None);
+        let allocation = client.get_allocation(&s.beneficiary(0)).unwrap();
+        assert_eq!(allocation.purpose, String::from_str(&s.env, ""));
+        assert_eq!(allocation.expires_at, s.expires_at());
+        assert!(client.check_invariants(&vec![&s.env, s.beneficiary(0)]).ok);
+    }
+
+    #[test]
+    fn test_bind_requires_matching_salt_and_address() {
+        let (s, commitment, salt) = setup();
+        let client = s.client();
+
+        let result = client.try_bind_commitment(&commitment, &salt, &s.beneficiary(1));
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        let wrong_salt = BytesN::from_array(&s.env, &[8; 32]);
+        let result = client.try_bind_commitment(&commitment, &wrong_salt, &s.beneficiary(0));
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        // A commitment is issued against once
+        let result =
+            client.try_issue_to_commitment(&s.issuer(0), &commitment, &100, &s.expires_at());
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+}
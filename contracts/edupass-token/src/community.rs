@@ -0,0 +1,516 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Map, String, Symbol,
+    Vec,
+};
+
+use crate::{
+    allocate, checked_add, checked_sub, credit, debit, demurrage, events, freeze,
+    math::{self, Rounding},
+    require_governance, restrictions, screening, telemetry, validity, write_balance, Allocation,
+    EduPassToken, EduPassTokenClient, Error,
+};
+
+// Shares the pool share price is quoted per
+const PRICE_UNIT: i128 = 1_000_000;
+
+#[contracttype]
+enum CommunityKey {
+    CommunityPool,   // Escheated and donated credits available for emergency grants
+    TotalShares,     // Pool shares outstanding in the current epoch
+    ShareEpoch,      // Bumped when grants drain the pool, voiding its shares
+    Shares(Address), // A holder's pool position
+}
+
+// Shares held in the pool, valid only in the epoch they were minted in
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Position {
+    epoch: u32,
+    shares: i128,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Get credits held in the community pool
+    pub fn community_pool_balance(env: Env) -> i128 {
+        pool_balance(&env)
+    }
+
+    /// Grant credits from the community pool as a new allocation in
+    /// `program_id` (governance only). Credits donors restricted are only
+    /// used if the program's tags satisfy their restrictions.
+    pub fn grant_from_pool(
+        env: Env,
+        recipient: Address,
+        amount: i128,
+        purpose: String,
+        expires_at: u64,
+        program_id: Option<Symbol>,
+    ) -> Allocation {
+        telemetry::record(&env, "grant_from_pool");
+        let governance = require_governance(&env);
+
+        grant(
+            &env, governance, recipient, amount, purpose, expires_at, program_id,
+        )
+    }
+
+    /// Move a donor's credits into the community pool (the donor), in
+    /// exchange for pool shares at the current share price; returns the
+    /// shares minted.
+    /// Grants and escheats move the share price, so a position follows the
+    /// pool's fortunes. Non-empty `restrictions` limit the credits to
+    /// grants in programs tagged with all of them.
+    pub fn deposit_to_pool(
+        env: Env,
+        donor: Address,
+        amount: i128,
+        restrictions: Vec<Symbol>,
+    ) -> i128 {
+        telemetry::record(&env, "deposit_to_pool");
+        donor.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        let balance = pool_balance(&env);
+        let mut total = total_shares(&env);
+        if total == 0 && balance > 0 {
+            // Credits escheated while no shares were out stay with the
+            // community rather than going to the first donor
+            let community = env.current_contract_address();
+            set_shares(&env, &community, balance);
+            total = balance;
+        }
+        let minted = if total == 0 {
+            amount
+        } else {
+            math::mul_div(&env, amount, total, balance, Rounding::Down)
+        };
+        if minted == 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        debit(&env, &donor, amount);
+        restrictions::add(&env, &donor, amount, restrictions);
+        set_pool_balance(&env, checked_add(&env, balance, amount));
+        set_total_shares(&env, checked_add(&env, total, minted));
+        set_shares(
+            &env,
+            &donor,
+            checked_add(&env, shares_of(&env, &donor), minted),
+        );
+        events::publish(&env, (symbol_short!("pooldep"), donor), (amount, minted));
+
+        minted
+    }
+
+    /// Redeem pool shares for their value in credits, paid to the holder's
+    /// balance (the holder); returns the credits paid. Restricted credits
+    /// only leave the pool through grants, so this fails with
+    /// InsufficientBalance beyond the unrestricted credits.
+    pub fn withdraw_from_pool(env: Env, holder: Address, shares: i128) -> i128 {
+        telemetry::record(&env, "withdraw_from_pool");
+        holder.require_auth();
+        freeze::require_account_unfrozen(&env, &holder);
+
+        if shares <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        let held = shares_of(&env, &holder);
+        if held < shares {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+
+        let amount = share_value(&env, shares);
+        if amount > restrictions::unrestricted(&env, pool_balance(&env)) {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+        set_shares(&env, &holder, held - shares);
+        set_total_shares(&env, total_shares(&env) - shares);
+        set_pool_balance(&env, checked_sub(&env, pool_balance(&env), amount));
+        credit(&env, &holder, amount);
+        events::publish(&env, (symbol_short!("poolwd"), holder), (shares, amount));
+
+        amount
+    }
+
+    /// Move pool shares to another holder (the holder), e.g. into custody
+    pub fn transfer_pool_shares(env: Env, from: Address, to: Address, shares: i128) {
+        telemetry::record(&env, "transfer_pool_shares");
+        from.require_auth();
+
+        if shares <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if from == to {
+            panic_with_error!(&env, Error::SelfTransfer);
+        }
+        let held = shares_of(&env, &from);
+        if held < shares {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+
+        set_shares(&env, &from, held - shares);
+        set_shares(&env, &to, checked_add(&env, shares_of(&env, &to), shares));
+        events::publish(&env, (symbol_short!("shares"), from, to), shares);
+    }
+
+    /// Get the pool shares a holder has
+    pub fn pool_shares(env: Env, holder: Address) -> i128 {
+        shares_of(&env, &holder)
+    }
+
+    /// Get the pool shares outstanding
+    pub fn total_pool_shares(env: Env) -> i128 {
+        total_shares(&env)
+    }
+
+    /// Credits a million pool shares are worth; deposits into a pool
+    /// without shares mint them one for one
+    pub fn pool_share_price(env: Env) -> i128 {
+        if total_shares(&env) == 0 {
+            return PRICE_UNIT;
+        }
+        share_value(&env, PRICE_UNIT)
+    }
+}
+
+/// Grant pool credits to `recipient` as a new allocation from `issuer`
+pub(crate) fn grant(
+    env: &Env,
+    issuer: Address,
+    recipient: Address,
+    amount: i128,
+    purpose: String,
+    expires_at: u64,
+    program_id: Option<Symbol>,
+) -> Allocation {
+    if amount <= 0 {
+        panic_with_error!(env, Error::InvalidAmount);
+    }
+    let balance = pool_balance(env);
+    if balance < amount {
+        panic_with_error!(env, Error::InsufficientBalance);
+    }
+    screening::require_clear(env, &recipient);
+    let expires_at = validity::resolve_expiry(env, expires_at);
+    restrictions::draw(env, &program_id, amount, balance);
+    set_pool_balance(env, checked_sub(env, balance, amount));
+    if balance == amount {
+        // Nothing is left to back the outstanding shares
+        let epoch = share_epoch(env);
+        env.storage()
+            .instance()
+            .set(&CommunityKey::ShareEpoch, &(epoch + 1));
+        set_total_shares(env, 0);
+    }
+
+    let allocation = allocate(
+        env,
+        issuer,
+        recipient.clone(),
+        amount,
+        purpose,
+        expires_at,
+        program_id,
+        Map::new(env),
+    );
+    events::publish(env, (symbol_short!("grant"), recipient), amount);
+
+    allocation
+}
+
+/// Move a beneficiary's remaining credits into the community pool
+pub(crate) fn escheat(env: &Env, beneficiary: &Address) -> i128 {
+    let remaining = demurrage::settle(env, beneficiary, true);
+    if remaining <= 0 {
+        return 0;
+    }
+
+    write_balance(env, beneficiary, 0);
+    set_pool_balance(env, checked_add(env, pool_balance(env), remaining));
+
+    events::publish(
+        env,
+        (symbol_short!("escheat"), beneficiary.clone()),
+        remaining,
+    );
+
+    remaining
+}
+
+pub(crate) fn pool_balance(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&CommunityKey::CommunityPool)
+        .unwrap_or(0)
+}
+
+fn set_pool_balance(env: &Env, balance: i128) {
+    env.storage()
+        .instance()
+        .set(&CommunityKey::CommunityPool, &balance);
+}
+
+// Credits `shares` are redeemable for, rounded in the pool's favour
+fn share_value(env: &Env, shares: i128) -> i128 {
+    math::mul_div(
+        env,
+        shares,
+        pool_balance(env),
+        total_shares(env),
+        Rounding::Down,
+    )
+}
+
+fn total_shares(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&CommunityKey::TotalShares)
+        .unwrap_or(0)
+}
+
+fn set_total_shares(env: &Env, total: i128) {
+    env.storage()
+        .instance()
+        .set(&CommunityKey::TotalShares, &total);
+}
+
+fn share_epoch(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&CommunityKey::ShareEpoch)
+        .unwrap_or(0)
+}
+
+fn shares_of(env: &Env, holder: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get::<_, Position>(&CommunityKey::Shares(holder.clone()))
+        .filter(|position| position.epoch == share_epoch(env))
+        .map_or(0, |position| position.shares)
+}
+
+fn set_shares(env: &Env, holder: &Address, shares: i128) {
+    let key = CommunityKey::Shares(holder.clone());
+    if shares == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        let epoch = share_epoch(env);
+        env.storage()
+            .persistent()
+            .set(&key, &Position { epoch, shares });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use crate::ExpiryPolicy;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_escheat_and_grant() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_700_000_000);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let governance = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let recipient = Address::generate(&env);
+
+        client.initialize(&admin);
+        client.set_governance(&governance);
+        client.set_expiry_policy(&ExpiryPolicy::Escheat);
+
+        client.issue_credits(
+            &issuer,
+            &beneficiary,
+            &1000,
+            &String::from_str(&env, "Tuition"),
+            &1_700_000_100,
+        );
+
+        env.ledger().set_timestamp(1_700_000_100);
+        assert_eq!(client.sweep_expired(&beneficiary), 1000);
+        assert_eq!(client.balance(&issuer), 0);
+        assert_eq!(client.community_pool_balance(), 1000);
+
+        let allocation = client.grant_from_pool(
+            &recipient,
+            &400,
+            &String::from_str(&env, "Emergency Grant"),
+            &1_800_000_000,
+            &None,
+        );
+        assert_eq!(allocation.issuer, governance);
+        assert_eq!(client.balance(&recipient), 400);
+        assert_eq!(client.community_pool_balance(), 600);
+
+        // Grants redistribute already issued credits
+        assert_eq!(client.total_issued(), 1000);
+
+        let result = client.try_grant_from_pool(
+            &recipient,
+            &700,
+            &String::from_str(&env, "Emergency Grant"),
+            &1_800_000_000,
+            &None,
+        );
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+    }
+
+    #[test]
+    fn test_pool_shares_track_the_pool() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(2)
+            .issue(0, 1, 1400);
+        let client = s.client();
+        let governance = Address::generate(&s.env);
+        client.set_governance(&governance);
+        let (donor, custody) = (s.beneficiary(1), Address::generate(&s.env));
+
+        assert_eq!(
+            client.deposit_to_pool(&donor, &1000, &Vec::new(&s.env)),
+            1000
+        );
+        // Deposits move the donor's credits rather than minting new ones
+        assert_eq!(client.balance(&donor), 400);
+        assert_eq!(client.total_issued(), 1400);
+        let result = client.try_deposit_to_pool(&donor, &401, &Vec::new(&s.env));
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+        let purpose = String::from_str(&s.env, "Emergency Grant");
+        client.grant_from_pool(&s.beneficiary(0), &200, &purpose, &s.expires_at(), &None);
+        assert_eq!(client.pool_share_price(), 800_000);
+
+        // Later deposits mint at the lower price
+        assert_eq!(client.deposit_to_pool(&donor, &400, &Vec::new(&s.env)), 500);
+        client.transfer_pool_shares(&donor, &custody, &750);
+        assert_eq!(client.pool_shares(&custody), 750);
+        assert_eq!(client.withdraw_from_pool(&custody, &750), 600);
+        assert_eq!(client.balance(&custody), 600);
+        assert_eq!(
+            (client.total_pool_shares(), client.community_pool_balance()),
+            (750, 600)
+        );
+        let result = client.try_withdraw_from_pool(&custody, &1);
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+
+        // Draining the pool voids its shares
+        client.grant_from_pool(&s.beneficiary(0), &600, &purpose, &s.expires_at(), &None);
+        assert_eq!(client.pool_shares(&donor), 0);
+        assert_eq!(
+            client.deposit_to_pool(&custody, &100, &Vec::new(&s.env)),
+            100
+        );
+        assert_eq!(client.pool_share_price(), PRICE_UNIT);
+    }
+
+    #[test]
+    fn test_escheats_before_shares_stay_with_the_community() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(2)
+            .with_validity(100)
+            .issue(0, 0, 500)
+            .advance_time(100);
+        let client = s.client();
+        client.set_expiry_policy(&ExpiryPolicy::Escheat);
+        client.sweep_expired(&s.beneficiary(0));
+        s.issue_allocation(0, 1, 500);
+
+        let donor = s.beneficiary(1);
+        assert_eq!(client.deposit_to_pool(&donor, &500, &Vec::new(&s.env)), 500);
+        assert_eq!(client.pool_shares(&s.contract_id), 500);
+        assert_eq!(client.withdraw_from_pool(&donor, &500), 500);
+    }
+
+    #[test]
+    fn test_draining_the_pool_voids_every_position() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(3)
+            .issue(0, 0, 300)
+            .issue(0, 1, 300);
+        let client = s.client();
+        client.set_governance(&Address::generate(&s.env));
+        let (first, second) = (s.beneficiary(0), s.beneficiary(1));
+        client.deposit_to_pool(&first, &300, &Vec::new(&s.env));
+        client.deposit_to_pool(&second, &200, &Vec::new(&s.env));
+
+        // A grant that empties the pool leaves nothing behind any
+        // donor's shares, so every position goes, not just the granter's
+        let purpose = String::from_str(&s.env, "Emergency Grant");
+        client.grant_from_pool(&s.beneficiary(2), &500, &purpose, &s.expires_at(), &None);
+        assert_eq!(client.pool_shares(&first), 0);
+        assert_eq!(client.pool_shares(&second), 0);
+        assert_eq!(client.total_pool_shares(), 0);
+        let result = client.try_withdraw_from_pool(&first, &1);
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+
+        // Voided shares don't dilute the next epoch's deposits
+        assert_eq!(
+            client.deposit_to_pool(&second, &100, &Vec::new(&s.env)),
+            100
+        );
+        assert_eq!(client.pool_shares(&second), 100);
+        assert_eq!(client.withdraw_from_pool(&second, &100), 100);
+        assert_eq!(client.balance(&second), 100);
+    }
+
+    #[test]
+    fn test_grants_keep_to_the_validity_bounds() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(2)
+            .issue(0, 1, 1000);
+        let client = s.client();
+        client.set_governance(&Address::generate(&s.env));
+        client.deposit_to_pool(&s.beneficiary(1), &1000, &Vec::new(&s.env));
+        client.set_validity_config(&Some(crate::ValidityConfig {
+            min_validity_secs: 60,
+            max_validity_secs: 1000,
+            default_validity_secs: 500,
+        }));
+        let purpose = String::from_str(&s.env, "Emergency Grant");
+
+        let allocation = client.grant_from_pool(&s.beneficiary(0), &200, &purpose, &0, &None);
+        assert_eq!(allocation.expires_at, s.now() + 500);
+        let result =
+            client.try_grant_from_pool(&s.beneficiary(0), &200, &purpose, &(s.now() - 1), &None);
+        assert_eq!(result, Err(Ok(Error::ExpiryTooSoon.into())));
+        assert_eq!(client.community_pool_balance(), 800);
+    }
+
+    #[test]
+    fn test_grant_requires_governance() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+
+        let result = client.try_grant_from_pool(
+            &Address::generate(&env),
+            &1,
+            &String::from_str(&env, "Emergency Grant"),
+            &1_800_000_000,
+            &None,
+        );
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+    }
+}
@@ -0,0 +1,290 @@
+// Confidential amounts. Balances are kept as Pedersen commitments that the
+// contract can't open; the host has no curve operations to check range
+// proofs or commitment arithmetic, so an approved proof verifier checks
+// them off chain and co-signs every state change. Aggregate totals stay
+// public: issuance and redemption amounts are revealed in bulk, moves
+// between accounts are not.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, BytesN, Env, Vec,
+};
+
+use crate::{
+    add_total_burned, add_total_issued, attestations, bonding, checked_add, events, replay,
+    require_admin, rotation, telemetry, EduPassToken, EduPassTokenClient, Error,
+};
+
+// Bounds the work done by one confidential_issue call
+const MAX_CONFIDENTIAL_BATCH: u32 = 50;
+
+#[contracttype]
+enum ConfidentialKey {
+    ProofVerifier,             // Verifier that checks range and balance proofs
+    CommittedBalance(Address), // Pedersen commitment to an account's confidential balance
+    ConfidentialSupply,        // Credits held in confidential balances
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the proof verifier (admin only); confidential
+    /// operations are disabled without one
+    pub fn set_proof_verifier(env: Env, verifier: Option<Address>) {
+        telemetry::record(&env, "set_proof_verifier");
+        require_admin(&env);
+
+        match verifier {
+            Some(verifier) => env
+                .storage()
+                .instance()
+                .set(&ConfidentialKey::ProofVerifier, &verifier),
+            None => env
+                .storage()
+                .instance()
+                .remove(&ConfidentialKey::ProofVerifier),
+        }
+    }
+
+    /// Get the proof verifier, if set
+    pub fn proof_verifier(env: Env) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&ConfidentialKey::ProofVerifier)
+    }
+
+    /// Get an account's balance commitment, if it holds confidential credits
+    pub fn committed_balance(env: Env, account: Address) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&ConfidentialKey::CommittedBalance(account))
+    }
+
+    /// Credits held in confidential balances
+    pub fn confidential_supply(env: Env) -> i128 {
+        supply(&env)
+    }
+
+    /// Issue `total` credits across a batch of accounts without revealing
+    /// each award (issuer and verifier). Each entry is the account's new
+    /// balance commitment; the verifier attests that the increments are in
+    /// range and sum to `total`.
+    pub fn confidential_issue(
+        env: Env,
+        issuer: Address,
+        total: i128,
+        commitments: Vec<(Address, BytesN<32>)>,
+        proof_hash: BytesN<32>,
+    ) {
+        telemetry::record(&env, "confidential_issue");
+        issuer.require_auth();
+        require_verifier(&env, &proof_hash);
+
+        if total <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if commitments.is_empty() || commitments.len() > MAX_CONFIDENTIAL_BATCH {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        attestations::require_unattested_allowed(&env);
+        bonding::require_bonded(&env, &issuer);
+
+        for (account, commitment) in commitments.iter() {
+            write_commitment(&env, &account, &commitment);
+        }
+        set_supply(&env, checked_add(&env, supply(&env), total));
+        add_total_issued(&env, &None, total);
+        events::publish(
+            &env,
+            (symbol_short!("cissued"), issuer),
+            (total, commitments.len(), proof_hash),
+        );
+    }
+
+    /// Move credits between confidential balances without revealing the
+    /// amount (sender and verifier). The verifier attests that both new
+    /// commitments are in range and conserve the pair's combined balance.
+    pub fn confidential_transfer(
+        env: Env,
+        from: Address,
+        to: Address,
+        from_commitment: BytesN<32>,
+        to_commitment: BytesN<32>,
+        proof_hash: BytesN<32>,
+    ) {
+        telemetry::record(&env, "confidential_transfer");
+        from.require_auth();
+        require_verifier(&env, &proof_hash);
+
+        if from == to {
+            panic_with_error!(&env, Error::SelfTransfer);
+        }
+        if !has_commitment(&env, &from) {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+
+        write_commitment(&env, &from, &from_commitment);
+        write_commitment(&env, &to, &to_commitment);
+        events::publish(&env, (symbol_short!("ctransfer"), from, to), proof_hash);
+    }
+
+    /// Redeem a public `amount` out of a confidential balance (account and
+    /// verifier). The verifier attests that the new commitment is the old
+    /// one less `amount` and still in range.
+    pub fn confidential_redeem(
+        env: Env,
+        account: Address,
+        amount: i128,
+        new_commitment: BytesN<32>,
+        proof_hash: BytesN<32>,
+    ) {
+        telemetry::record(&env, "confidential_redeem");
+        account.require_auth();
+        require_verifier(&env, &proof_hash);
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        let supply = supply(&env);
+        if !has_commitment(&env, &account) || supply < amount {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+
+        write_commitment(&env, &account, &new_commitment);
+        set_supply(&env, supply - amount);
+        add_total_burned(&env, amount);
+        events::publish(
+            &env,
+            (symbol_short!("credeem"), account),
+            (amount, proof_hash),
+        );
+    }
+}
+
+/// Move an account's balance commitment to its rotated key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &ConfidentialKey::CommittedBalance(old.clone()),
+        &ConfidentialKey::CommittedBalance(new.clone()),
+    );
+}
+
+/// Credits held in confidential balances
+pub(crate) fn supply(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&ConfidentialKey::ConfidentialSupply)
+        .unwrap_or(0)
+}
+
+fn set_supply(env: &Env, supply: i128) {
+    env.storage()
+        .instance()
+        .set(&ConfidentialKey::ConfidentialSupply, &supply);
+}
+
+// Load the proof verifier and require its co-signature on a proof that
+// hasn't been acted on before
+fn require_verifier(env: &Env, proof_hash: &BytesN<32>) {
+    let verifier: Address = env
+        .storage()
+        .instance()
+        .get(&ConfidentialKey::ProofVerifier)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotConfigured));
+    verifier.require_auth();
+    replay::consume(env, proof_hash, None);
+}
+
+fn has_commitment(env: &Env, account: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&ConfidentialKey::CommittedBalance(account.clone()))
+}
+
+fn write_commitment(env: &Env, account: &Address, commitment: &BytesN<32>) {
+    env.storage().persistent().set(
+        &ConfidentialKey::CommittedBalance(account.clone()),
+        commitment,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::vec;
+
+    // Stand-in for both commitments and proof hashes
+    fn hash(s: &Scenario, byte: u8) -> BytesN<32> {
+        BytesN::from_array(&s.env, &[byte; 32])
+    }
+
+    fn setup() -> Scenario {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(2);
+        let client = s.client();
+        client.set_proof_verifier(&Some(Address::generate(&s.env)));
+        let batch = vec![
+            &s.env,
+            (s.beneficiary(0), hash(&s, 1)),
+            (s.beneficiary(1), hash(&s, 2)),
+        ];
+        client.confidential_issue(&s.issuer(0), &1000, &batch, &hash(&s, 0xa0));
+        s
+    }
+
+    #[test]
+    fn test_totals_public_balances_committed() {
+        let s = setup();
+        let client = s.client();
+        assert_eq!(client.total_issued(), 1000);
+        assert_eq!(client.confidential_supply(), 1000);
+        assert_eq!(
+            client.committed_balance(&s.beneficiary(1)),
+            Some(hash(&s, 2))
+        );
+        // Nothing shows up in the public balances
+        assert_eq!(client.balance(&s.beneficiary(0)), 0);
+
+        client.confidential_transfer(
+            &s.beneficiary(0),
+            &s.school(0),
+            &hash(&s, 3),
+            &hash(&s, 4),
+            &hash(&s, 0xa1),
+        );
+        client.confidential_redeem(&s.school(0), &400, &hash(&s, 5), &hash(&s, 0xa2));
+        assert_eq!(client.confidential_supply(), 600);
+        assert_eq!(client.total_burned(), 400);
+        assert!(client.check_invariants(&vec![&s.env]).ok);
+
+        // A proof is acted on once
+        let result =
+            client.try_confidential_redeem(&s.school(0), &1, &hash(&s, 6), &hash(&s, 0xa2));
+        assert_eq!(result, Err(Ok(Error::NonceAlreadyUsed.into())));
+    }
+
+    #[test]
+    fn test_operations_need_a_verifier_and_a_balance() {
+        let s = setup();
+        let client = s.client();
+        let c = hash(&s, 9);
+
+        // Only accounts holding a commitment can send
+        let result = client.try_confidential_transfer(
+            &s.school(0),
+            &s.beneficiary(0),
+            &c,
+            &c,
+            &hash(&s, 0xa1),
+        );
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+
+        client.set_proof_verifier(&None);
+        let result = client.try_confidential_redeem(&s.beneficiary(0), &1, &c, &hash(&s, 0xa1));
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+    }
+}
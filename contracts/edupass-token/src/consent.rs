@@ -0,0 +1,148 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, String, Symbol,
+};
+
+use crate::{
+    burn_credits, events, program_of, require_admin, telemetry, transfer_credits, Allocation,
+    DataKey, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum ConsentKey {
+    ConsentThreshold(Symbol), // Largest redemption a program's beneficiaries needn't co-sign
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the consent threshold of a program (admin
+    /// only). Spending more than it of a beneficiary's credits on their
+    /// behalf, under an approval, then needs the beneficiary's co-signature
+    /// through redeem_with_consent; 0 requires it for every amount.
+    pub fn set_consent_threshold(env: Env, program_id: Symbol, threshold: Option<i128>) {
+        telemetry::record(&env, "set_consent_threshold");
+        require_admin(&env);
+
+        let key = ConsentKey::ConsentThreshold(program_id);
+        match threshold {
+            Some(threshold) => {
+                if threshold < 0 {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage().persistent().set(&key, &threshold);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Get a program's consent threshold, if set
+    pub fn consent_threshold(env: Env, program_id: Symbol) -> Option<i128> {
+        env.storage()
+            .persistent()
+            .get(&ConsentKey::ConsentThreshold(program_id))
+    }
+
+    /// Redeem a beneficiary's credits at a school in one step (the school
+    /// and the beneficiary). The beneficiary's co-signature acknowledges
+    /// the service named by `reference`; the credits move to the school and
+    /// are burned as its redemption.
+    pub fn redeem_with_consent(
+        env: Env,
+        school: Address,
+        beneficiary: Address,
+        amount: i128,
+        reference: String,
+    ) {
+        telemetry::record(&env, "redeem_with_consent");
+        school.require_auth();
+        beneficiary.require_auth();
+
+        transfer_credits(&env, &beneficiary, &beneficiary, &school, amount);
+        burn_credits(&env, &school, &school, amount);
+        events::publish(
+            &env,
+            (symbol_short!("consented"), school, beneficiary),
+            (amount, reference),
+        );
+    }
+}
+
+/// Fail with ConsentRequired if spending `amount` of `from`'s credits on
+/// their behalf needs their co-signature under their program's threshold
+pub(crate) fn require_consent(env: &Env, from: &Address, amount: i128) {
+    let allocation: Option<Allocation> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Allocations(from.clone()));
+    let Some(program_id) = program_of(&allocation) else {
+        return;
+    };
+    if EduPassToken::consent_threshold(env.clone(), program_id)
+        .is_some_and(|threshold| amount > threshold)
+    {
+        panic_with_error!(env, Error::ConsentRequired);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{Scenario, DAY_SECS};
+
+    fn setup() -> Scenario {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1);
+        let client = s.client();
+        let uni = Symbol::new(&s.env, "uni");
+        client.save_template(
+            &s.issuer(0),
+            &uni,
+            &String::from_str(&s.env, "Tuition"),
+            &1000,
+            &(365 * DAY_SECS),
+            &uni,
+        );
+        client.issue_from_template(&s.issuer(0), &uni, &s.beneficiary(0));
+        client.set_consent_threshold(&uni, &Some(100));
+        client.approve(&s.beneficiary(0), &s.school(0), &1000, &1000);
+        s
+    }
+
+    #[test]
+    fn test_redemptions_above_threshold_need_consent() {
+        let s = setup();
+        let client = s.client();
+        let (school, beneficiary) = (s.school(0), s.beneficiary(0));
+
+        // Small redemptions under an approval go through as before
+        client.burn_from(&school, &beneficiary, &100);
+        let result = client.try_burn_from(&school, &beneficiary, &101);
+        assert_eq!(result, Err(Ok(Error::ConsentRequired.into())));
+        let result = client.try_transfer_from(&school, &beneficiary, &school, &101);
+        assert_eq!(result, Err(Ok(Error::ConsentRequired.into())));
+
+        let reference = String::from_str(&s.env, "INV-2025-014");
+        client.redeem_with_consent(&school, &beneficiary, &400, &reference);
+        assert_eq!(client.balance(&beneficiary), 500);
+        assert_eq!(client.balance(&school), 0);
+        assert_eq!(client.total_burned(), 500);
+        assert_eq!(client.allocation_remaining(&beneficiary), 500);
+    }
+
+    #[test]
+    fn test_threshold_is_per_program() {
+        let s = setup();
+        let client = s.client();
+        let uni = Symbol::new(&s.env, "uni");
+        assert_eq!(client.consent_threshold(&uni), Some(100));
+        let result = client.try_set_consent_threshold(&uni, &Some(-1));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+
+        // Other programs' thresholds don't apply
+        client.set_consent_threshold(&Symbol::new(&s.env, "k12"), &Some(0));
+        client.set_consent_threshold(&uni, &None);
+        client.burn_from(&s.school(0), &s.beneficiary(0), &500);
+    }
+}
@@ -0,0 +1,296 @@
+// Conversion of residual credits between programs, for a beneficiary who
+// moves on from one program (say, food support) to another (tuition).
+// Governance opens a route between two programs with a rate and caps; the
+// beneficiary converts credits left on an allocation of the first program
+// into a new allocation of the second, keeping its issuer, purpose and
+// expiry. Rates never exceed par, so a conversion can't create credits:
+// the spread is burned. Each program counts what was converted out of and
+// into it, and each route what it has converted overall.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Map, Symbol,
+};
+
+use crate::{
+    add_total_burned, allocate, checked_add, demurrage, events, freeze, holds,
+    math::{self, Rounding},
+    read_allocation, require_governance, telemetry, validity, write_balance, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum ConversionKey {
+    ConversionRate(Symbol, Symbol), // Terms of a route between two programs (absent when closed)
+    RouteConverted(Symbol, Symbol), // Credits a route has taken in so far
+    ProgramConverted(Symbol),       // Credits converted out of and into a program
+}
+
+// Terms governance sets for converting one program's credits into another's
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConversionRate {
+    pub rate_bps: u32,    // Credits received per 10_000 converted, at most par
+    pub max_amount: i128, // Most one conversion may take (0 for no limit)
+    pub route_cap: i128,  // Most the route may take overall (0 for no limit)
+}
+
+// Credits a program has seen converted, before the rate is applied on the
+// way out and after it on the way in
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProgramConversions {
+    pub converted_out: i128,
+    pub converted_in: i128,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Open, update or close (None) the conversion route from one program
+    /// to another (governance only). What the route converted so far still
+    /// counts against a new cap.
+    pub fn set_conversion_rate(
+        env: Env,
+        from_program: Symbol,
+        to_program: Symbol,
+        rate: Option<ConversionRate>,
+    ) {
+        telemetry::record(&env, "set_conversion_rate");
+        require_governance(&env);
+
+        let key = ConversionKey::ConversionRate(from_program.clone(), to_program.clone());
+        match rate {
+            Some(rate) => {
+                if from_program == to_program
+                    || rate.rate_bps == 0
+                    || rate.rate_bps > 10_000
+                    || rate.max_amount < 0
+                    || rate.route_cap < 0
+                {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage().persistent().set(&key, &rate);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Get the terms of a conversion route, if open
+    pub fn conversion_rate(
+        env: Env,
+        from_program: Symbol,
+        to_program: Symbol,
+    ) -> Option<ConversionRate> {
+        env.storage()
+            .persistent()
+            .get(&ConversionKey::ConversionRate(from_program, to_program))
+    }
+
+    /// Convert credits left on the beneficiary's allocation of one program
+    /// into a new allocation of another at the route's rate (the
+    /// beneficiary); returns the credits received. Other credits the
+    /// beneficiary holds carry over, as with any new allocation.
+    pub fn convert(
+        env: Env,
+        beneficiary: Address,
+        from_program: Symbol,
+        to_program: Symbol,
+        amount: i128,
+    ) -> i128 {
+        telemetry::record(&env, "convert");
+        beneficiary.require_auth();
+
+        let rate = Self::conversion_rate(env.clone(), from_program.clone(), to_program.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        let allocation = read_allocation(&env, &beneficiary)
+            .filter(|allocation| allocation.program_id == Some(from_program.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        if amount > allocation.remaining {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+        if let Err(error) = freeze::check_unfrozen(&env, &Some(from_program.clone())) {
+            panic_with_error!(&env, error);
+        }
+        if rate.max_amount > 0 && amount > rate.max_amount {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        // The converted credits keep the allocation's expiry, within the
+        // bounds in effect now
+        let expires_at = validity::resolve_expiry(&env, allocation.expires_at);
+        let route_key = ConversionKey::RouteConverted(from_program.clone(), to_program.clone());
+        let route_converted = checked_add(
+            &env,
+            env.storage().persistent().get(&route_key).unwrap_or(0),
+            amount,
+        );
+        if rate.route_cap > 0 && route_converted > rate.route_cap {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        env.storage().persistent().set(&route_key, &route_converted);
+
+        let converted = math::mul_div(&env, amount, rate.rate_bps as i128, 10_000, Rounding::Down);
+        let balance = demurrage::settle(&env, &beneficiary, true);
+        holds::require_spendable(&env, &beneficiary, balance, amount);
+        write_balance(&env, &beneficiary, balance - amount);
+        add_total_burned(&env, amount - converted);
+        allocate(
+            &env,
+            allocation.issuer,
+            beneficiary.clone(),
+            converted,
+            allocation.purpose,
+            expires_at,
+            Some(to_program.clone()),
+            Map::new(&env),
+        );
+
+        let mut from = Self::program_conversions(env.clone(), from_program.clone());
+        from.converted_out = checked_add(&env, from.converted_out, amount);
+        write_conversions(&env, &from_program, &from);
+        let mut to = Self::program_conversions(env.clone(), to_program.clone());
+        to.converted_in = checked_add(&env, to.converted_in, converted);
+        write_conversions(&env, &to_program, &to);
+
+        events::publish(
+            &env,
+            (symbol_short!("convert"), beneficiary),
+            (from_program, to_program, amount, converted),
+        );
+
+        converted
+    }
+
+    /// Get credits a conversion route has taken in so far
+    pub fn route_converted(env: Env, from_program: Symbol, to_program: Symbol) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&ConversionKey::RouteConverted(from_program, to_program))
+            .unwrap_or(0)
+    }
+
+    /// Get credits converted out of and into a program
+    pub fn program_conversions(env: Env, program_id: Symbol) -> ProgramConversions {
+        env.storage()
+            .persistent()
+            .get(&ConversionKey::ProgramConverted(program_id))
+            .unwrap_or_default()
+    }
+}
+
+fn write_conversions(env: &Env, program_id: &Symbol, conversions: &ProgramConversions) {
+    env.storage().persistent().set(
+        &ConversionKey::ProgramConverted(program_id.clone()),
+        conversions,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{Scenario, DAY_SECS};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::String;
+
+    #[test]
+    fn test_residual_credits_convert_at_the_governed_rate() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1);
+        let client = s.client();
+        let (food, tuition) = (symbol_short!("food"), symbol_short!("tuition"));
+        client.save_template(
+            &s.issuer(0),
+            &symbol_short!("meals"),
+            &String::from_str(&s.env, "Meals"),
+            &1000,
+            &(90 * DAY_SECS),
+            &food,
+        );
+        let beneficiary = s.beneficiary(0);
+        client.issue_from_template(&s.issuer(0), &symbol_short!("meals"), &beneficiary);
+        client.transfer(&beneficiary, &s.school(0), &400);
+
+        let result = client.try_convert(&beneficiary, &food, &tuition, &100);
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+        let governance = Address::generate(&s.env);
+        client.set_governance(&governance);
+        client.set_conversion_rate(
+            &food,
+            &tuition,
+            &Some(ConversionRate {
+                rate_bps: 8_000,
+                max_amount: 500,
+                route_cap: 800,
+            }),
+        );
+        assert_eq!(s.env.auths()[0].0, governance);
+        let result = client.try_convert(&beneficiary, &food, &tuition, &600);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+
+        assert_eq!(client.convert(&beneficiary, &food, &tuition, &500), 400);
+        assert_eq!(s.env.auths()[0].0, beneficiary);
+        // The spread is burned; the unconverted 100 carry over
+        assert_eq!(client.balance(&beneficiary), 500);
+        let allocation = client.get_allocation(&beneficiary).unwrap();
+        assert_eq!(allocation.program_id, Some(tuition.clone()));
+        assert_eq!(allocation.remaining, 400);
+        assert_eq!(
+            client.program_conversions(&food),
+            ProgramConversions {
+                converted_out: 500,
+                converted_in: 0,
+            }
+        );
+        assert_eq!(client.program_conversions(&tuition).converted_in, 400);
+        assert_eq!(client.route_converted(&food, &tuition), 500);
+
+        // Credits are no longer on a food allocation
+        let result = client.try_convert(&beneficiary, &food, &tuition, &100);
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+    }
+
+    #[test]
+    fn test_conversions_keep_to_the_validity_bounds() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(2);
+        let client = s.client();
+        let (food, tuition) = (symbol_short!("food"), symbol_short!("tuition"));
+        client.save_template(
+            &s.issuer(0),
+            &symbol_short!("meals"),
+            &String::from_str(&s.env, "Meals"),
+            &1000,
+            &(90 * DAY_SECS),
+            &food,
+        );
+        client.issue_from_template(&s.issuer(0), &symbol_short!("meals"), &s.beneficiary(0));
+        client.issue_from_template(&s.issuer(0), &symbol_short!("meals"), &s.beneficiary(1));
+        client.set_governance(&Address::generate(&s.env));
+        let rate = ConversionRate {
+            rate_bps: 10_000,
+            max_amount: 0,
+            route_cap: 0,
+        };
+        client.set_conversion_rate(&food, &tuition, &Some(rate));
+
+        client.set_validity_config(&Some(crate::ValidityConfig {
+            min_validity_secs: DAY_SECS,
+            max_validity_secs: 365 * DAY_SECS,
+            default_validity_secs: 180 * DAY_SECS,
+        }));
+        client.convert(&s.beneficiary(1), &food, &tuition, &100);
+        let allocation = client.get_allocation(&s.beneficiary(1)).unwrap();
+        assert_eq!(allocation.expires_at, s.now() + 90 * DAY_SECS);
+
+        // Lapsed credits can't be revived on a new allocation
+        crate::testutils::advance(&s.env, 91 * DAY_SECS);
+        let result = client.try_convert(&s.beneficiary(0), &food, &tuition, &100);
+        assert_eq!(result, Err(Ok(Error::ExpiryTooSoon.into())));
+    }
+}
@@ -0,0 +1,306 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, Address, Env};
+
+use crate::{
+    advances, checked_add, is_beneficiary,
+    math::{self, Rounding, BPS},
+    require_admin,
+    storage::AccountState,
+    telemetry, write_balance, DataKey, EduPassToken, EduPassTokenClient, Error,
+};
+
+// Fixed-point scale for the retained-balance factor
+const SCALE: i128 = 1_000_000_000_000_000_000;
+
+#[contracttype]
+enum DemurrageKey {
+    Demurrage,              // Demurrage config (absent when disabled)
+    LastDemurrage(Address), // When demurrage was last applied to an account
+    TotalDemurraged,
+}
+
+// Opt-in decay of unspent beneficiary credits
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DemurrageConfig {
+    pub rate_bps: u32,   // Share of the balance removed per epoch
+    pub epoch_secs: u64, // Length of one epoch
+}
+
+// Gross (stored) vs net (after pending demurrage) balance
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BalanceBreakdown {
+    pub gross: i128,
+    pub net: i128,
+    pub last_demurrage_applied: u64,
+    pub advance: i128, // Outstanding advance, repaid from the next issuance
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Enable, update or disable (None) demurrage (admin only)
+    pub fn set_demurrage(env: Env, config: Option<DemurrageConfig>) {
+        telemetry::record(&env, "set_demurrage");
+        require_admin(&env);
+
+        match config {
+            Some(config) => {
+                if config.rate_bps == 0 || config.rate_bps as i128 >= BPS || config.epoch_secs == 0
+                {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage()
+                    .instance()
+                    .set(&DemurrageKey::Demurrage, &config);
+            }
+            None => env.storage().instance().remove(&DemurrageKey::Demurrage),
+        }
+    }
+
+    /// Get the demurrage config, if enabled
+    pub fn demurrage_config(env: Env) -> Option<DemurrageConfig> {
+        env.storage().instance().get(&DemurrageKey::Demurrage)
+    }
+
+    /// Get gross vs net balance for an account
+    pub fn balance_breakdown(env: Env, account: Address) -> BalanceBreakdown {
+        let gross = stored_balance(&env, &account);
+        let last_demurrage_applied = last_applied(&env, &account).unwrap_or(0);
+        let net = pending(&env, &account, gross).unwrap_or(gross);
+
+        BalanceBreakdown {
+            gross,
+            net,
+            last_demurrage_applied,
+            advance: advances::outstanding(&env, &account),
+        }
+    }
+
+    /// Get total credits removed by demurrage
+    pub fn total_demurraged(env: Env) -> i128 {
+        total_demurraged(&env)
+    }
+}
+
+/// Credits removed by demurrage so far
+pub(crate) fn total_demurraged(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DemurrageKey::TotalDemurraged)
+        .unwrap_or(0)
+}
+
+/// Balance after pending demurrage, without persisting anything
+pub(crate) fn net_balance(env: &Env, account: &Address) -> i128 {
+    let gross = stored_balance(env, account);
+    pending(env, account, gross).unwrap_or(gross)
+}
+
+/// Net balance of an already loaded account, without persisting anything
+pub(crate) fn net_balance_of(env: &Env, account: &Address, state: &AccountState) -> i128 {
+    if !state.is_beneficiary() {
+        return state.balance;
+    }
+    let config: DemurrageConfig = match env.storage().instance().get(&DemurrageKey::Demurrage) {
+        Some(config) => config,
+        None => return state.balance,
+    };
+    match last_applied(env, account) {
+        Some(last) => {
+            let epochs = env.ledger().timestamp().saturating_sub(last) / config.epoch_secs;
+            decay(env, state.balance, config.rate_bps, epochs)
+        }
+        None => state.balance,
+    }
+}
+
+/// Apply pending demurrage to an account and return its settled balance.
+/// Must be called before any write to the account's balance.
+pub(crate) fn settle(env: &Env, account: &Address, beneficiary: bool) -> i128 {
+    settle_gross(env, account, stored_balance(env, account), beneficiary)
+}
+
+/// Settle an already loaded account, sparing the storage reads
+pub(crate) fn settle_loaded(env: &Env, account: &Address, state: &AccountState) -> i128 {
+    settle_gross(env, account, state.balance, state.is_beneficiary())
+}
+
+fn settle_gross(env: &Env, account: &Address, gross: i128, beneficiary: bool) -> i128 {
+    let config: DemurrageConfig = match env.storage().instance().get(&DemurrageKey::Demurrage) {
+        Some(config) if beneficiary => config,
+        _ => return gross,
+    };
+
+    let now = env.ledger().timestamp();
+    let last = match last_applied(env, account) {
+        Some(last) => last,
+        None => {
+            // Start the clock on first contact
+            set_last_applied(env, account, now);
+            return gross;
+        }
+    };
+
+    let epochs = now.saturating_sub(last) / config.epoch_secs;
+    if epochs == 0 {
+        return gross;
+    }
+
+    let net = decay(env, gross, config.rate_bps, epochs);
+    if net != gross {
+        write_balance(env, account, net);
+
+        let total = total_demurraged(env);
+        env.storage().instance().set(
+            &DemurrageKey::TotalDemurraged,
+            &checked_add(env, total, gross - net),
+        );
+    }
+
+    // Keep progress into the current epoch
+    set_last_applied(env, account, last + epochs * config.epoch_secs);
+
+    net
+}
+
+/// Drop an account's demurrage clock
+pub(crate) fn remove_account(env: &Env, account: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&DemurrageKey::LastDemurrage(account.clone()));
+}
+
+// Net balance if demurrage applies to the account
+fn pending(env: &Env, account: &Address, gross: i128) -> Option<i128> {
+    let config: DemurrageConfig = env.storage().instance().get(&DemurrageKey::Demurrage)?;
+    if !is_beneficiary(env, account) {
+        return None;
+    }
+
+    let last = last_applied(env, account)?;
+    let epochs = env.ledger().timestamp().saturating_sub(last) / config.epoch_secs;
+
+    Some(decay(env, gross, config.rate_bps, epochs))
+}
+
+pub(crate) fn stored_balance(env: &Env, account: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Credits(account.clone()))
+        .unwrap_or(0)
+}
+
+fn last_applied(env: &Env, account: &Address) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get(&DemurrageKey::LastDemurrage(account.clone()))
+}
+
+fn set_last_applied(env: &Env, account: &Address, timestamp: u64) {
+    env.storage()
+        .persistent()
+        .set(&DemurrageKey::LastDemurrage(account.clone()), &timestamp);
+}
+
+// Compound `rate_bps` over `epochs`, rounding the retained balance down
+fn decay(env: &Env, gross: i128, rate_bps: u32, epochs: u64) -> i128 {
+    let mut base = (BPS - rate_bps as i128) * (SCALE / BPS);
+    let mut factor = SCALE;
+    let mut n = epochs;
+    while n > 0 && factor > 0 {
+        if n & 1 == 1 {
+            factor = math::mul_div(env, factor, base, SCALE, Rounding::Down);
+        }
+        base = math::mul_div(env, base, base, SCALE, Rounding::Down);
+        n >>= 1;
+    }
+
+    math::mul_div(env, gross, factor, SCALE, Rounding::Down)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::String;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_decay_math() {
+        let env = Env::default();
+        assert_eq!(decay(&env, 1000, 1000, 0), 1000);
+        assert_eq!(decay(&env, 1000, 1000, 1), 900);
+        assert_eq!(decay(&env, 1000, 1000, 2), 810);
+        assert_eq!(decay(&env, 1000, 5000, 200), 0);
+        assert!(decay(&env, i128::MAX, 1, 1) < i128::MAX);
+    }
+
+    #[test]
+    fn test_demurrage_applied_lazily() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_700_000_000);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+
+        client.initialize(&admin);
+        client.set_demurrage(&Some(DemurrageConfig {
+            rate_bps: 1000,
+            epoch_secs: 30 * DAY,
+        }));
+
+        client.issue_credits(
+            &issuer,
+            &beneficiary,
+            &1000,
+            &String::from_str(&env, "Tuition"),
+            &1_800_000_000,
+        );
+
+        // Two full epochs and part of a third pass
+        env.ledger().set_timestamp(1_700_000_000 + 70 * DAY);
+
+        let breakdown = client.balance_breakdown(&beneficiary);
+        assert_eq!(breakdown.gross, 1000);
+        assert_eq!(breakdown.net, 810);
+        assert_eq!(client.balance(&beneficiary), 810);
+        assert_eq!(client.total_demurraged(), 0);
+
+        // The next write settles the pending demurrage
+        client.transfer(&beneficiary, &school, &10);
+
+        let breakdown = client.balance_breakdown(&beneficiary);
+        assert_eq!(breakdown.gross, 800);
+        assert_eq!(breakdown.net, 800);
+        assert_eq!(breakdown.last_demurrage_applied, 1_700_000_000 + 60 * DAY);
+        assert_eq!(client.total_demurraged(), 190);
+
+        // Schools are not subject to demurrage
+        env.ledger().set_timestamp(1_700_000_000 + 200 * DAY);
+        assert_eq!(client.balance(&school), 10);
+    }
+
+    #[test]
+    fn test_invalid_demurrage_config() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        client.initialize(&Address::generate(&env));
+
+        let result = client.try_set_demurrage(&Some(DemurrageConfig {
+            rate_bps: 10_000,
+            epoch_secs: DAY,
+        }));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+    }
+}
@@ -0,0 +1,700 @@
+// Disputes over escrowed awards. Either party to an escrow (its
+// beneficiary or school) can open a case, which holds the escrow until the
+// case resolves. The admin assigns approved arbiters to each case's panel,
+// and an outcome wins as soon as a majority of the panel votes for it.
+// Arbiters with a conflict recuse themselves before voting and can be
+// replaced. A case without a majority by its deadline takes the configured
+// default outcome.
+//
+// A decision settles the escrow only once its appeal window passes. Within
+// the window the losing party can appeal once to the appellate role that
+// governance designates, whose ruling is final. If the appellate doesn't
+// rule within another window, the panel's decision stands, so nobody can
+// hold a settlement up indefinitely.
+//
+// A beneficiary can also dispute a redemption the school has already been
+// paid for, through its receipt. There is no escrow to hold, so a decision
+// against the school is paid out of the insurance bucket instead.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Map, Vec,
+};
+
+use crate::{
+    escrow, events, insurance, reputation, require_admin, require_governance, rotation, telemetry,
+    EduPassToken, EduPassTokenClient, Error,
+};
+
+// Arbiters on one case's panel
+const MAX_PANEL: u32 = 7;
+
+#[contracttype]
+enum DisputeKey {
+    Arbiter(Address),        // Approved dispute arbiter
+    Appellate,               // Address ruling on appeals, designated by governance
+    DisputeConfig,           // Voting period and default outcome (absent when disabled)
+    DisputeCase(u64),        // A dispute and its panel's votes
+    DisputeCount,            // Last assigned case id
+    EscrowDispute(u64),      // Open case holding an escrow
+    IssuerDisputes(Address), // Open cases over an issuer's escrows
+    RedemptionDispute(u64),  // Case over a receipt, which can be disputed once
+}
+
+// Where a disputed escrow's credits go
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DisputeOutcome {
+    ToSchool, // Released to the escrow's school; a disputed payment stands
+    ToIssuer, // Returned to the escrow's issuer; a disputed payment is refunded from insurance
+}
+
+// Where a case stands
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DisputeStatus {
+    Open,     // The panel is voting
+    Decided,  // Awaiting appeal until the appeal deadline
+    Appealed, // Awaiting the appellate's ruling until the appeal deadline
+    Resolved, // Settled, final
+}
+
+// How cases are decided
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeConfig {
+    pub voting_secs: u64,                // From opening a case to its deadline
+    pub default_outcome: DisputeOutcome, // Applies without a majority by the deadline
+    pub appeal_secs: u64,                // Appeal window, and the appellate's (0 for no appeals)
+}
+
+// A dispute over an escrow
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeCase {
+    pub escrow_id: u64,  // 0 for a case over a receipt
+    pub receipt_id: u64, // 0 for a case over an escrow
+    pub opened_by: Address,
+    pub panel: Vec<Address>,
+    pub votes: Map<Address, DisputeOutcome>,
+    pub deadline: u64, // Of the panel's vote
+    pub status: DisputeStatus,
+    pub outcome: DisputeOutcome, // The default outcome until decided
+    pub appeal_secs: u64,        // The config's when the case opened
+    pub appeal_deadline: u64,    // Of the current appeal window (0 until decided)
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Approve or revoke a dispute arbiter (admin only). Revoking doesn't
+    /// remove the arbiter from panels it already sits on.
+    pub fn set_arbiter(env: Env, arbiter: Address, approved: bool) {
+        telemetry::record(&env, "set_arbiter");
+        require_admin(&env);
+
+        let key = DisputeKey::Arbiter(arbiter);
+        if approved {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    /// Check whether an address is an approved arbiter
+    pub fn is_arbiter(env: Env, arbiter: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DisputeKey::Arbiter(arbiter))
+    }
+
+    /// Designate or clear (None) the appellate role (governance only)
+    pub fn set_appellate(env: Env, appellate: Option<Address>) {
+        telemetry::record(&env, "set_appellate");
+        require_governance(&env);
+
+        match appellate {
+            Some(appellate) => env
+                .storage()
+                .instance()
+                .set(&DisputeKey::Appellate, &appellate),
+            None => env.storage().instance().remove(&DisputeKey::Appellate),
+        }
+    }
+
+    /// Get the appellate role, if designated
+    pub fn appellate(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DisputeKey::Appellate)
+    }
+
+    /// Enable, update or disable (None) disputes (admin only). Open cases
+    /// keep their deadline and appeal window.
+    pub fn set_dispute_config(env: Env, config: Option<DisputeConfig>) {
+        telemetry::record(&env, "set_dispute_config");
+        require_admin(&env);
+
+        match config {
+            Some(config) => {
+                if config.voting_secs == 0 {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage()
+                    .instance()
+                    .set(&DisputeKey::DisputeConfig, &config);
+            }
+            None => env.storage().instance().remove(&DisputeKey::DisputeConfig),
+        }
+    }
+
+    /// Get the dispute config, if enabled
+    pub fn dispute_config(env: Env) -> Option<DisputeConfig> {
+        env.storage().instance().get(&DisputeKey::DisputeConfig)
+    }
+
+    /// Open a dispute over an escrow (its beneficiary or school), holding
+    /// it until the case resolves; returns the case id
+    pub fn open_dispute(env: Env, party: Address, escrow_id: u64) -> u64 {
+        telemetry::record(&env, "open_dispute");
+        party.require_auth();
+
+        let config = Self::dispute_config(env.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        let escrow = Self::escrow(env.clone(), escrow_id)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        if party != escrow.beneficiary && party != rotation::current(&env, &escrow.school) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        let key = DisputeKey::EscrowDispute(escrow_id);
+        if env.storage().persistent().has(&key) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+
+        let case = new_case(&env, &config, &party, escrow_id, 0);
+        let id = create_case(&env, &key, &case);
+        add_issuer_disputes(&env, &escrow.issuer, 1);
+        events::publish(
+            &env,
+            (symbol_short!("dispute"), id),
+            (escrow_id, party, case.deadline),
+        );
+
+        id
+    }
+
+    /// Dispute a redemption the school has already been paid for (the
+    /// receipt's beneficiary, once per receipt); returns the case id. A
+    /// decision for ToIssuer refunds the beneficiary from insurance.
+    pub fn dispute_receipt(env: Env, beneficiary: Address, receipt_id: u64) -> u64 {
+        telemetry::record(&env, "dispute_receipt");
+        beneficiary.require_auth();
+
+        let config = Self::dispute_config(env.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        let receipt = reputation::read_receipt(&env, receipt_id);
+        if beneficiary != receipt.beneficiary {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        let key = DisputeKey::RedemptionDispute(receipt_id);
+        if env.storage().persistent().has(&key) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+
+        let case = new_case(&env, &config, &beneficiary, 0, receipt_id);
+        let id = create_case(&env, &key, &case);
+        events::publish(
+            &env,
+            (symbol_short!("disprcpt"), id),
+            (receipt_id, beneficiary, case.deadline),
+        );
+
+        id
+    }
+
+    /// Assign an approved arbiter to an open case's panel (admin only).
+    /// The escrow's parties can't sit on it.
+    pub fn assign_arbiter(env: Env, case_id: u64, arbiter: Address) {
+        telemetry::record(&env, "assign_arbiter");
+        require_admin(&env);
+
+        let mut case = open_case(&env, case_id);
+        if !Self::is_arbiter(env.clone(), arbiter.clone()) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        let (beneficiary, school, issuer) = parties(&env, &case);
+        if arbiter == beneficiary || arbiter == school || Some(arbiter.clone()) == issuer {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if case.panel.contains(&arbiter) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        if case.panel.len() >= MAX_PANEL {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+
+        case.panel.push_back(arbiter.clone());
+        write_case(&env, case_id, &case);
+        events::publish(&env, (symbol_short!("arbassign"), case_id), arbiter);
+    }
+
+    /// Step down from a case's panel before voting on it (the arbiter)
+    pub fn recuse(env: Env, arbiter: Address, case_id: u64) {
+        telemetry::record(&env, "recuse");
+        arbiter.require_auth();
+
+        let mut case = open_case(&env, case_id);
+        let Some(index) = case.panel.first_index_of(&arbiter) else {
+            panic_with_error!(&env, Error::Unauthorized);
+        };
+        if case.votes.contains_key(arbiter.clone()) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+
+        case.panel.remove(index);
+        events::publish(&env, (symbol_short!("recused"), case_id), arbiter);
+        // A smaller panel can already hold a majority
+        match majority(&case) {
+            Some(outcome) => decide(&env, case_id, case, outcome, false),
+            None => write_case(&env, case_id, &case),
+        }
+    }
+
+    /// Vote on an open case (a panel arbiter, once). The case is decided as
+    /// soon as an outcome has a majority of the panel.
+    pub fn vote_dispute(env: Env, arbiter: Address, case_id: u64, outcome: DisputeOutcome) {
+        telemetry::record(&env, "vote_dispute");
+        arbiter.require_auth();
+
+        let mut case = open_case(&env, case_id);
+        if !case.panel.contains(&arbiter) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if env.ledger().timestamp() > case.deadline {
+            panic_with_error!(&env, Error::ProposalExpired);
+        }
+        if case.votes.contains_key(arbiter.clone()) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+
+        case.votes.set(arbiter.clone(), outcome);
+        events::publish(&env, (symbol_short!("disvote"), case_id, arbiter), outcome);
+        match majority(&case) {
+            Some(outcome) => decide(&env, case_id, case, outcome, false),
+            None => write_case(&env, case_id, &case),
+        }
+    }
+
+    /// Appeal a decision within its appeal window (the losing party: the
+    /// school against ToIssuer, the beneficiary or issuer against
+    /// ToSchool). A case can be appealed once.
+    pub fn appeal(env: Env, party: Address, case_id: u64) {
+        telemetry::record(&env, "appeal");
+        party.require_auth();
+
+        let mut case = read_case(&env, case_id);
+        if case.status != DisputeStatus::Decided {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        if env.ledger().timestamp() > case.appeal_deadline {
+            panic_with_error!(&env, Error::ProposalExpired);
+        }
+        if Self::appellate(env.clone()).is_none() {
+            panic_with_error!(&env, Error::NotConfigured);
+        }
+        let (beneficiary, school, issuer) = parties(&env, &case);
+        let lost = match case.outcome {
+            DisputeOutcome::ToSchool => party == beneficiary || Some(party.clone()) == issuer,
+            DisputeOutcome::ToIssuer => party == school,
+        };
+        if !lost {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        case.status = DisputeStatus::Appealed;
+        case.appeal_deadline = env.ledger().timestamp() + case.appeal_secs;
+        write_case(&env, case_id, &case);
+        events::publish(
+            &env,
+            (symbol_short!("appealed"), case_id),
+            (party, case.appeal_deadline),
+        );
+    }
+
+    /// Rule on an appealed case, settling it (the appellate, before the
+    /// appeal deadline)
+    pub fn resolve_appeal(env: Env, case_id: u64, outcome: DisputeOutcome) {
+        telemetry::record(&env, "resolve_appeal");
+        let appellate = Self::appellate(env.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        appellate.require_auth();
+
+        let mut case = read_case(&env, case_id);
+        if case.status != DisputeStatus::Appealed {
+            panic_with_error!(&env, Error::NotFound);
+        }
+        if env.ledger().timestamp() > case.appeal_deadline {
+            panic_with_error!(&env, Error::ProposalExpired);
+        }
+
+        case.outcome = outcome;
+        settle(&env, case_id, case);
+    }
+
+    /// Move a case on once its current deadline has passed (anyone): a case
+    /// the panel didn't decide takes the default outcome, and a decision
+    /// past its appeal window settles. Returns the case's outcome; fails
+    /// with NotExpired before the deadline.
+    pub fn finalize_dispute(env: Env, case_id: u64) -> DisputeOutcome {
+        telemetry::record(&env, "finalize_dispute");
+        let case = read_case(&env, case_id);
+        let deadline = match case.status {
+            DisputeStatus::Open => case.deadline,
+            DisputeStatus::Decided | DisputeStatus::Appealed => case.appeal_deadline,
+            DisputeStatus::Resolved => panic_with_error!(&env, Error::AlreadyExists),
+        };
+        if env.ledger().timestamp() <= deadline {
+            panic_with_error!(&env, Error::NotExpired);
+        }
+
+        let outcome = case.outcome;
+        if case.status == DisputeStatus::Open {
+            decide(&env, case_id, case, outcome, true);
+        } else {
+            settle(&env, case_id, case);
+        }
+        outcome
+    }
+
+    /// Get a dispute case
+    pub fn dispute_case(env: Env, case_id: u64) -> Option<DisputeCase> {
+        env.storage()
+            .persistent()
+            .get(&DisputeKey::DisputeCase(case_id))
+    }
+}
+
+/// Whether any case over an issuer's escrows is still unsettled
+pub(crate) fn has_open_disputes(env: &Env, issuer: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DisputeKey::IssuerDisputes(issuer.clone()))
+}
+
+/// Move the count of open cases over an issuer's escrows to its new key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &DisputeKey::IssuerDisputes(old.clone()),
+        &DisputeKey::IssuerDisputes(new.clone()),
+    );
+}
+
+/// Fail with CreditsOnHold while an open case holds the escrow
+pub(crate) fn require_undisputed(env: &Env, escrow_id: u64) {
+    if env
+        .storage()
+        .persistent()
+        .has(&DisputeKey::EscrowDispute(escrow_id))
+    {
+        panic_with_error!(env, Error::CreditsOnHold);
+    }
+}
+
+// An open case as configured now, not yet stored
+fn new_case(
+    env: &Env,
+    config: &DisputeConfig,
+    opened_by: &Address,
+    escrow_id: u64,
+    receipt_id: u64,
+) -> DisputeCase {
+    DisputeCase {
+        escrow_id,
+        receipt_id,
+        opened_by: opened_by.clone(),
+        panel: Vec::new(env),
+        votes: Map::new(env),
+        deadline: env.ledger().timestamp() + config.voting_secs,
+        status: DisputeStatus::Open,
+        outcome: config.default_outcome,
+        appeal_secs: config.appeal_secs,
+        appeal_deadline: 0,
+    }
+}
+
+// Store a new case under the next id and mark what it is about as disputed
+fn create_case(env: &Env, subject_key: &DisputeKey, case: &DisputeCase) -> u64 {
+    let id = env
+        .storage()
+        .instance()
+        .get(&DisputeKey::DisputeCount)
+        .unwrap_or(0u64)
+        + 1;
+    env.storage().instance().set(&DisputeKey::DisputeCount, &id);
+    write_case(env, id, case);
+    env.storage().persistent().set(subject_key, &id);
+    id
+}
+
+// Count cases opened or settled over an issuer's escrows, under the
+// issuer's current key. The count is absent at zero.
+fn add_issuer_disputes(env: &Env, issuer: &Address, delta: i32) {
+    let key = DisputeKey::IssuerDisputes(rotation::current(env, issuer));
+    let count = env.storage().persistent().get::<_, u32>(&key).unwrap_or(0);
+    match count.saturating_add_signed(delta) {
+        0 => env.storage().persistent().remove(&key),
+        count => env.storage().persistent().set(&key, &count),
+    }
+}
+
+// The beneficiary, school and issuer (escrows only) a case is between
+fn parties(env: &Env, case: &DisputeCase) -> (Address, Address, Option<Address>) {
+    if case.receipt_id != 0 {
+        let receipt = reputation::read_receipt(env, case.receipt_id);
+        let school = rotation::current(env, &receipt.school);
+        return (receipt.beneficiary, school, None);
+    }
+    let escrow = EduPassToken::escrow(env.clone(), case.escrow_id).unwrap();
+    let school = rotation::current(env, &escrow.school);
+    (escrow.beneficiary, school, Some(escrow.issuer))
+}
+
+fn read_case(env: &Env, case_id: u64) -> DisputeCase {
+    EduPassToken::dispute_case(env.clone(), case_id)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound))
+}
+
+fn open_case(env: &Env, case_id: u64) -> DisputeCase {
+    let case = read_case(env, case_id);
+    if case.status != DisputeStatus::Open {
+        panic_with_error!(env, Error::AlreadyExists);
+    }
+    case
+}
+
+fn write_case(env: &Env, case_id: u64, case: &DisputeCase) {
+    env.storage()
+        .persistent()
+        .set(&DisputeKey::DisputeCase(case_id), case);
+}
+
+// The outcome a majority of the panel voted for, if any
+fn majority(case: &DisputeCase) -> Option<DisputeOutcome> {
+    if case.panel.is_empty() {
+        return None;
+    }
+    for outcome in [DisputeOutcome::ToSchool, DisputeOutcome::ToIssuer] {
+        let votes = case
+            .votes
+            .values()
+            .iter()
+            .filter(|vote| *vote == outcome)
+            .count();
+        if votes as u32 * 2 > case.panel.len() {
+            return Some(outcome);
+        }
+    }
+    None
+}
+
+// Record the outcome and open its appeal window, settling straight away
+// when the case allows no appeals
+fn decide(
+    env: &Env,
+    case_id: u64,
+    mut case: DisputeCase,
+    outcome: DisputeOutcome,
+    by_default: bool,
+) {
+    case.outcome = outcome;
+    case.appeal_deadline = env.ledger().timestamp() + case.appeal_secs;
+    events::publish(
+        env,
+        (symbol_short!("decided"), case_id),
+        (outcome, by_default, case.appeal_deadline),
+    );
+    if case.appeal_secs == 0 {
+        settle(env, case_id, case);
+    } else {
+        case.status = DisputeStatus::Decided;
+        write_case(env, case_id, &case);
+    }
+}
+
+// Pay the escrow out as the case's outcome says and release its hold, or
+// refund a disputed payment the school lost from insurance
+fn settle(env: &Env, case_id: u64, mut case: DisputeCase) {
+    case.status = DisputeStatus::Resolved;
+    write_case(env, case_id, &case);
+    if case.receipt_id != 0 {
+        if case.outcome == DisputeOutcome::ToIssuer {
+            let receipt = reputation::read_receipt(env, case.receipt_id);
+            insurance::cover(
+                env,
+                case_id,
+                case.receipt_id,
+                &receipt.beneficiary,
+                receipt.amount,
+            );
+        }
+    } else {
+        env.storage()
+            .persistent()
+            .remove(&DisputeKey::EscrowDispute(case.escrow_id));
+        let escrow = EduPassToken::escrow(env.clone(), case.escrow_id).unwrap();
+        add_issuer_disputes(env, &escrow.issuer, -1);
+        escrow::settle(
+            env,
+            case.escrow_id,
+            case.outcome == DisputeOutcome::ToSchool,
+        );
+    }
+    events::publish(env, (symbol_short!("resolved"), case_id), case.outcome);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::String;
+
+    fn setup(arbiters: u32, appeal_secs: u64) -> (Scenario, Vec<Address>, u64) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1);
+        let client = s.client();
+        client.set_dispute_config(&Some(DisputeConfig {
+            voting_secs: 7 * DAY_SECS,
+            default_outcome: DisputeOutcome::ToIssuer,
+            appeal_secs,
+        }));
+        let escrow_id = client.issue_to_escrow(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &s.school(0),
+            &800,
+            &String::from_str(&s.env, "Tuition"),
+            &(s.now() + 30 * DAY_SECS),
+        );
+        let case_id = client.open_dispute(&s.school(0), &escrow_id);
+        let mut panel = Vec::new(&s.env);
+        for _ in 0..arbiters {
+            let arbiter = Address::generate(&s.env);
+            client.set_arbiter(&arbiter, &true);
+            client.assign_arbiter(&case_id, &arbiter);
+            panel.push_back(arbiter);
+        }
+        (s, panel, case_id)
+    }
+
+    #[test]
+    fn test_panel_majority_resolves_the_case() {
+        let (s, panel, case_id) = setup(3, 0);
+        let client = s.client();
+        let escrow_id = client.dispute_case(&case_id).unwrap().escrow_id;
+        let result = client.try_release_escrow(&s.beneficiary(0), &escrow_id);
+        assert_eq!(result, Err(Ok(Error::CreditsOnHold.into())));
+        // Parties can't sit on the panel
+        client.set_arbiter(&s.beneficiary(0), &true);
+        let result = client.try_assign_arbiter(&case_id, &s.beneficiary(0));
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        client.vote_dispute(&panel.get(0).unwrap(), &case_id, &DisputeOutcome::ToSchool);
+        assert_eq!(s.env.auths()[0].0, panel.get(0).unwrap());
+        client.vote_dispute(&panel.get(1).unwrap(), &case_id, &DisputeOutcome::ToSchool);
+        let case = client.dispute_case(&case_id).unwrap();
+        assert_eq!(
+            (case.status, case.outcome),
+            (DisputeStatus::Resolved, DisputeOutcome::ToSchool)
+        );
+        assert_eq!(client.balance(&s.school(0)), 800);
+        assert_eq!(client.escrow(&escrow_id), None);
+
+        let result =
+            client.try_vote_dispute(&panel.get(2).unwrap(), &case_id, &DisputeOutcome::ToIssuer);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+
+    #[test]
+    fn test_default_outcome_applies_without_a_majority() {
+        let (s, panel, case_id) = setup(3, 0);
+        let client = s.client();
+        let recused = panel.get(0).unwrap();
+        client.recuse(&recused, &case_id);
+        let result = client.try_vote_dispute(&recused, &case_id, &DisputeOutcome::ToSchool);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        // One vote each way is no majority of the remaining two
+        client.vote_dispute(&panel.get(1).unwrap(), &case_id, &DisputeOutcome::ToSchool);
+        client.vote_dispute(&panel.get(2).unwrap(), &case_id, &DisputeOutcome::ToIssuer);
+        let result = client.try_finalize_dispute(&case_id);
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+
+        advance(&s.env, 7 * DAY_SECS + 1);
+        assert_eq!(client.finalize_dispute(&case_id), DisputeOutcome::ToIssuer);
+        assert_eq!(client.balance(&s.issuer(0)), 800);
+        assert_eq!(client.balance(&s.school(0)), 0);
+    }
+
+    fn decided_for_school(appeal_secs: u64) -> (Scenario, Address, u64) {
+        let (s, panel, case_id) = setup(3, appeal_secs);
+        let client = s.client();
+        let appellate = Address::generate(&s.env);
+        client.set_governance(&Address::generate(&s.env));
+        client.set_appellate(&Some(appellate.clone()));
+        for arbiter in panel.iter().take(2) {
+            client.vote_dispute(&arbiter, &case_id, &DisputeOutcome::ToSchool);
+        }
+        (s, appellate, case_id)
+    }
+
+    #[test]
+    fn test_losing_party_appeals_once() {
+        let (s, appellate, case_id) = decided_for_school(3 * DAY_SECS);
+        let client = s.client();
+        // Decided, but held until the appeal window passes
+        assert_eq!(
+            client.dispute_case(&case_id).unwrap().status,
+            DisputeStatus::Decided
+        );
+        assert_eq!(client.balance(&s.school(0)), 0);
+
+        let result = client.try_appeal(&s.school(0), &case_id);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        client.appeal(&s.beneficiary(0), &case_id);
+        let result = client.try_appeal(&s.issuer(0), &case_id);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+
+        client.resolve_appeal(&case_id, &DisputeOutcome::ToIssuer);
+        assert_eq!(s.env.auths()[0].0, appellate);
+        let case = client.dispute_case(&case_id).unwrap();
+        assert_eq!(
+            (case.status, case.outcome),
+            (DisputeStatus::Resolved, DisputeOutcome::ToIssuer)
+        );
+        assert_eq!(client.balance(&s.issuer(0)), 800);
+    }
+
+    #[test]
+    fn test_decisions_are_final_after_the_appeal_window() {
+        let (s, _, case_id) = decided_for_school(3 * DAY_SECS);
+        let client = s.client();
+        let result = client.try_finalize_dispute(&case_id);
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+        advance(&s.env, 3 * DAY_SECS + 1);
+        let result = client.try_appeal(&s.beneficiary(0), &case_id);
+        assert_eq!(result, Err(Ok(Error::ProposalExpired.into())));
+        assert_eq!(client.finalize_dispute(&case_id), DisputeOutcome::ToSchool);
+        assert_eq!(client.balance(&s.school(0)), 800);
+
+        // An appellate that doesn't rule in time can't hold the case up
+        let (s, _, case_id) = decided_for_school(3 * DAY_SECS);
+        let client = s.client();
+        client.appeal(&s.beneficiary(0), &case_id);
+        advance(&s.env, 3 * DAY_SECS + 1);
+        let result = client.try_resolve_appeal(&case_id, &DisputeOutcome::ToIssuer);
+        assert_eq!(result, Err(Ok(Error::ProposalExpired.into())));
+        assert_eq!(client.finalize_dispute(&case_id), DisputeOutcome::ToSchool);
+        assert_eq!(client.balance(&s.school(0)), 800);
+    }
+}
@@ -0,0 +1,150 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, symbol_short, Address, Env};
+
+use crate::{
+    checked_add, demurrage, events, is_beneficiary, require_admin, telemetry, treasury,
+    write_balance, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum DustKey {
+    MinTransfer, // Smallest amount a transfer may move
+    DustBucket,  // Swept sub-minimum residual balances
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the minimum transfer amount (admin only)
+    pub fn set_min_transfer(env: Env, min: Option<i128>) {
+        telemetry::record(&env, "set_min_transfer");
+        require_admin(&env);
+
+        match min {
+            Some(min) => {
+                if min <= 0 {
+                    panic_with_error!(&env, Error::InvalidAmount);
+                }
+                env.storage().instance().set(&DustKey::MinTransfer, &min);
+            }
+            None => env.storage().instance().remove(&DustKey::MinTransfer),
+        }
+    }
+
+    /// Get the minimum transfer amount, if set
+    pub fn min_transfer(env: Env) -> Option<i128> {
+        min_transfer(&env)
+    }
+
+    /// Fold an account's residual balance into the dust bucket when it is
+    /// below the minimum transfer and can no longer be spent; returns the
+    /// amount swept
+    pub fn sweep_dust(env: Env, account: Address) -> i128 {
+        telemetry::record(&env, "sweep_dust");
+        let min = match min_transfer(&env) {
+            Some(min) => min,
+            None => return 0,
+        };
+
+        let balance = demurrage::settle(&env, &account, is_beneficiary(&env, &account));
+        if balance <= 0 || balance >= min {
+            return 0;
+        }
+
+        write_balance(&env, &account, 0);
+        collect(&env, balance);
+        events::publish(&env, (symbol_short!("dust"), account), balance);
+
+        balance
+    }
+
+    /// Get credits collected in the dust bucket
+    pub fn dust_balance(env: Env) -> i128 {
+        dust_balance(&env)
+    }
+}
+
+/// Reject transfers below the configured minimum
+pub(crate) fn check_min_transfer(env: &Env, amount: i128) -> Result<(), Error> {
+    match min_transfer(env) {
+        Some(min) if amount < min => Err(Error::BelowMinTransfer),
+        _ => Ok(()),
+    }
+}
+
+fn min_transfer(env: &Env) -> Option<i128> {
+    env.storage().instance().get(&DustKey::MinTransfer)
+}
+
+/// Put credits collected as fees into the dust bucket, on the treasury's
+/// statement, less the insurance bucket's share. The caller has already
+/// debited them from their holder.
+pub(crate) fn collect(env: &Env, amount: i128) {
+    #[cfg(feature = "disputes")]
+    let amount = amount - crate::insurance::take_share(env, amount);
+    env.storage().instance().set(
+        &DustKey::DustBucket,
+        &checked_add(env, dust_balance(env), amount),
+    );
+    treasury::record_collected(env, amount);
+}
+
+/// Take credits out of the dust bucket, failing with InsufficientBalance if
+/// it holds less
+pub(crate) fn take_dust(env: &Env, amount: i128) {
+    let balance = dust_balance(env);
+    if balance < amount {
+        panic_with_error!(env, Error::InsufficientBalance);
+    }
+    env.storage()
+        .instance()
+        .set(&DustKey::DustBucket, &(balance - amount));
+}
+
+pub(crate) fn dust_balance(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DustKey::DustBucket)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::String;
+
+    #[test]
+    fn test_min_transfer_and_dust_sweep() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+
+        client.initialize(&admin);
+        client.set_min_transfer(&Some(10));
+
+        client.issue_credits(
+            &issuer,
+            &beneficiary,
+            &105,
+            &String::from_str(&env, "Tuition"),
+            &1735689600,
+        );
+
+        let result = client.try_transfer(&beneficiary, &school, &9);
+        assert_eq!(result, Err(Ok(Error::BelowMinTransfer.into())));
+
+        // Spendable balances are left alone
+        assert_eq!(client.sweep_dust(&beneficiary), 0);
+
+        client.transfer(&beneficiary, &school, &100);
+        assert_eq!(client.sweep_dust(&beneficiary), 5);
+        assert_eq!(client.balance(&beneficiary), 0);
+        assert_eq!(client.dust_balance(), 5);
+    }
+}
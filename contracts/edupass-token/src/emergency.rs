@@ -0,0 +1,249 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, token, Address, Env,
+};
+
+use crate::{
+    bonding, checked_add, events,
+    math::{self, Rounding, BPS},
+    require_admin, require_governance, telemetry, wrap, DataKey, EduPassToken, EduPassTokenClient,
+    Error,
+};
+
+// Delay between scheduling and executing, long enough for governance to
+// review and veto
+const EMERGENCY_TIMELOCK_SECS: u64 = 7 * 24 * 60 * 60;
+// Share of an asset's reserves that may leave in one period
+const EMERGENCY_LIMIT_BPS: i128 = 2_000;
+const EMERGENCY_PERIOD_SECS: u64 = 30 * 24 * 60 * 60;
+
+#[contracttype]
+enum EmergencyKey {
+    Withdrawal(u64),          // Scheduled emergency withdrawal
+    WithdrawalCount,          // Last assigned withdrawal id
+    WithdrawnPeriod(Address), // Emergency withdrawals of an asset in the current period
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmergencyWithdrawal {
+    pub asset: Address,
+    pub amount: i128,
+    pub destination: Address,
+    pub executable_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct WithdrawnPeriod {
+    started_at: u64,
+    withdrawn: i128,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Schedule a withdrawal of reserve asset held by the contract (admin
+    /// only); executable after the timelock unless governance vetoes it.
+    /// Returns the withdrawal id.
+    pub fn schedule_emergency_withdrawal(
+        env: Env,
+        asset: Address,
+        amount: i128,
+        destination: Address,
+    ) -> u64 {
+        telemetry::record(&env, "schedule_emergency_withdrawal");
+        require_admin(&env);
+
+        // Without governance nobody could veto
+        if !env.storage().instance().has(&DataKey::Governance) {
+            panic_with_error!(&env, Error::NotConfigured);
+        }
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        let id = env
+            .storage()
+            .instance()
+            .get(&EmergencyKey::WithdrawalCount)
+            .unwrap_or(0u64)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&EmergencyKey::WithdrawalCount, &id);
+
+        let withdrawal = EmergencyWithdrawal {
+            asset: asset.clone(),
+            amount,
+            destination: destination.clone(),
+            executable_at: env.ledger().timestamp() + EMERGENCY_TIMELOCK_SECS,
+        };
+        env.storage()
+            .persistent()
+            .set(&EmergencyKey::Withdrawal(id), &withdrawal);
+        events::publish(
+            &env,
+            (symbol_short!("emsched"), id),
+            (asset, amount, destination, withdrawal.executable_at),
+        );
+
+        id
+    }
+
+    /// Veto a scheduled emergency withdrawal (governance only)
+    pub fn veto_emergency_withdrawal(env: Env, withdrawal_id: u64) {
+        telemetry::record(&env, "veto_emergency_withdrawal");
+        require_governance(&env);
+
+        take_withdrawal(&env, withdrawal_id);
+        events::publish(&env, (symbol_short!("emveto"), withdrawal_id), ());
+    }
+
+    /// Execute a scheduled emergency withdrawal once its timelock has
+    /// passed (admin only). Bonded funds and the wrap reserve are never in
+    /// scope.
+    pub fn execute_emergency_withdrawal(env: Env, withdrawal_id: u64) {
+        telemetry::record(&env, "execute_emergency_withdrawal");
+        require_admin(&env);
+
+        let withdrawal = take_withdrawal(&env, withdrawal_id);
+        let now = env.ledger().timestamp();
+        if now < withdrawal.executable_at {
+            panic_with_error!(&env, Error::NotExpired);
+        }
+
+        let key = EmergencyKey::WithdrawnPeriod(withdrawal.asset.clone());
+        let mut period: WithdrawnPeriod = env.storage().persistent().get(&key).unwrap_or_default();
+        if now >= period.started_at.saturating_add(EMERGENCY_PERIOD_SECS) {
+            period = WithdrawnPeriod {
+                started_at: now,
+                withdrawn: 0,
+            };
+        }
+
+        // The limit applies to reserves as they stood when the period began
+        let available = free_reserves(&env, &withdrawal.asset);
+        let reserves = checked_add(&env, available.max(0), period.withdrawn);
+        let withdrawn = checked_add(&env, period.withdrawn, withdrawal.amount);
+        if withdrawal.amount > available
+            || withdrawn > math::mul_div(&env, reserves, EMERGENCY_LIMIT_BPS, BPS, Rounding::Down)
+        {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        period.withdrawn = withdrawn;
+        env.storage().persistent().set(&key, &period);
+
+        token::Client::new(&env, &withdrawal.asset).transfer(
+            &env.current_contract_address(),
+            &withdrawal.destination,
+            &withdrawal.amount,
+        );
+        events::publish(
+            &env,
+            (symbol_short!("emexec"), withdrawal_id),
+            (withdrawal.asset, withdrawal.amount, withdrawal.destination),
+        );
+    }
+
+    /// Get a scheduled emergency withdrawal
+    pub fn emergency_withdrawal(env: Env, withdrawal_id: u64) -> Option<EmergencyWithdrawal> {
+        env.storage()
+            .persistent()
+            .get(&EmergencyKey::Withdrawal(withdrawal_id))
+    }
+}
+
+/// An asset held by the contract beyond posted bonds and the wrap reserve,
+/// which never leave through withdrawals
+pub(crate) fn free_reserves(env: &Env, asset: &Address) -> i128 {
+    token::Client::new(env, asset).balance(&env.current_contract_address())
+        - bonding::bonded_in(env, asset)
+        - wrap::reserved_in(env, asset)
+}
+
+// Remove and return a scheduled withdrawal; each is executed or vetoed once
+fn take_withdrawal(env: &Env, withdrawal_id: u64) -> EmergencyWithdrawal {
+    let key = EmergencyKey::Withdrawal(withdrawal_id);
+    let withdrawal = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+    env.storage().persistent().remove(&key);
+    withdrawal
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use crate::BondConfig;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    fn setup() -> (Scenario, Address, Address) {
+        let s = Scenario::new().with_admin().with_issuers(1);
+        let asset = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        StellarAssetClient::new(&s.env, &asset).mint(&s.contract_id, &10_000);
+        let governance = Address::generate(&s.env);
+        s.client().set_governance(&governance);
+        (s, asset, governance)
+    }
+
+    #[test]
+    fn test_timelock_and_period_limit() {
+        let (s, asset, _) = setup();
+        let client = s.client();
+        let destination = Address::generate(&s.env);
+        let first = client.schedule_emergency_withdrawal(&asset, &1500, &destination);
+        let second = client.schedule_emergency_withdrawal(&asset, &1000, &destination);
+
+        let s = s.advance_time(EMERGENCY_TIMELOCK_SECS - 1);
+        let result = s.client().try_execute_emergency_withdrawal(&first);
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+
+        let s = s.advance_time(1);
+        let client = s.client();
+        client.execute_emergency_withdrawal(&first);
+        assert_eq!(TokenClient::new(&s.env, &asset).balance(&destination), 1500);
+        assert_eq!(client.emergency_withdrawal(&first), None);
+
+        // 2,500 of 10,000 is over the 20% allowed per period
+        let result = client.try_execute_emergency_withdrawal(&second);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+    }
+
+    #[test]
+    fn test_governance_veto() {
+        let (s, asset, _) = setup();
+        let client = s.client();
+        let id = client.schedule_emergency_withdrawal(&asset, &500, &Address::generate(&s.env));
+
+        client.veto_emergency_withdrawal(&id);
+        assert_eq!(client.emergency_withdrawal(&id), None);
+        let s = s.advance_time(EMERGENCY_TIMELOCK_SECS);
+        let result = s.client().try_execute_emergency_withdrawal(&id);
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+    }
+
+    #[test]
+    fn test_bonds_out_of_scope() {
+        let (s, asset, _) = setup();
+        let client = s.client();
+        StellarAssetClient::new(&s.env, &asset).mint(&s.issuer(0), &90_000);
+        client.set_bond_config(&Some(BondConfig {
+            asset: asset.clone(),
+            min_bond: 1,
+            cooldown_secs: 0,
+        }));
+        client.post_bond(&s.issuer(0), &90_000);
+
+        // Only the 10,000 not held for bonds counts as reserves
+        let id = client.schedule_emergency_withdrawal(&asset, &2001, &Address::generate(&s.env));
+        let s = s.advance_time(EMERGENCY_TIMELOCK_SECS);
+        let result = s.client().try_execute_emergency_withdrawal(&id);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+    }
+}
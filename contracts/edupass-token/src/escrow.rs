@@ -0,0 +1,250 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, String,
+};
+
+use crate::{
+    add_total_issued, attestations, bonding, checked_add, credit, events, freeze, rotation,
+    telemetry, validity, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum EscrowKey {
+    Escrow(u64),   // Credits issued for a beneficiary, payable only to a school
+    EscrowCount,   // Last assigned escrow id
+    EscrowedTotal, // Credits held for all unreleased escrows
+}
+
+// An award paid to a school once its beneficiary acknowledges it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Escrow {
+    pub issuer: Address,
+    pub beneficiary: Address,
+    pub school: Address,
+    pub amount: i128,
+    pub purpose: String,
+    pub expires_at: u64, // Unacknowledged credits go back to the issuer after this
+    pub issued_at: u64,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Issue credits straight into escrow for `school` (the issuer). The
+    /// beneficiary sees the award and releases it to the school with
+    /// release_escrow. Returns the escrow id.
+    pub fn issue_to_escrow(
+        env: Env,
+        issuer: Address,
+        beneficiary: Address,
+        school: Address,
+        amount: i128,
+        purpose: String,
+        expires_at: u64,
+    ) -> u64 {
+        telemetry::record(&env, "issue_to_escrow");
+        issuer.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if beneficiary == school {
+            panic_with_error!(&env, Error::SelfTransfer);
+        }
+        let expires_at = validity::resolve_expiry(&env, expires_at);
+        attestations::require_unattested_allowed(&env);
+        bonding::require_bonded(&env, &issuer);
+
+        let id = env
+            .storage()
+            .instance()
+            .get(&EscrowKey::EscrowCount)
+            .unwrap_or(0u64)
+            + 1;
+        env.storage().instance().set(&EscrowKey::EscrowCount, &id);
+
+        let escrow = Escrow {
+            issuer: issuer.clone(),
+            beneficiary: beneficiary.clone(),
+            school: school.clone(),
+            amount,
+            purpose,
+            expires_at,
+            issued_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&EscrowKey::Escrow(id), &escrow);
+        set_escrowed_total(&env, checked_add(&env, escrowed_total(&env), amount));
+        add_total_issued(&env, &None, amount);
+        events::publish(
+            &env,
+            (symbol_short!("escrowed"), beneficiary, school),
+            (id, issuer, amount),
+        );
+
+        id
+    }
+
+    /// Acknowledge an escrowed award, paying it to its school (the
+    /// beneficiary). Fails with OfferExpired once the escrow has expired.
+    pub fn release_escrow(env: Env, beneficiary: Address, escrow_id: u64) {
+        telemetry::record(&env, "release_escrow");
+        beneficiary.require_auth();
+
+        #[cfg(feature = "disputes")]
+        crate::disputes::require_undisputed(&env, escrow_id);
+        let escrow = read_escrow(&env, escrow_id);
+        freeze::require_account_unfrozen(&env, &escrow.beneficiary);
+        if escrow.beneficiary != beneficiary {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if env.ledger().timestamp() >= escrow.expires_at {
+            panic_with_error!(&env, Error::OfferExpired);
+        }
+
+        remove_escrow(&env, escrow_id, escrow.amount);
+        let school = rotation::current(&env, &escrow.school);
+        credit(&env, &school, escrow.amount);
+        events::publish(
+            &env,
+            (symbol_short!("escrowack"), beneficiary, school),
+            (escrow_id, escrow.amount),
+        );
+    }
+
+    /// Return an expired, unacknowledged escrow to its issuer; anyone may
+    /// call. Fails with NotExpired before the escrow expires.
+    pub fn reclaim_escrow(env: Env, escrow_id: u64) -> i128 {
+        telemetry::record(&env, "reclaim_escrow");
+        #[cfg(feature = "disputes")]
+        crate::disputes::require_undisputed(&env, escrow_id);
+        let escrow = read_escrow(&env, escrow_id);
+        freeze::require_account_unfrozen(&env, &escrow.beneficiary);
+        if env.ledger().timestamp() < escrow.expires_at {
+            panic_with_error!(&env, Error::NotExpired);
+        }
+
+        remove_escrow(&env, escrow_id, escrow.amount);
+        credit(&env, &escrow.issuer, escrow.amount);
+        events::publish(
+            &env,
+            (symbol_short!("escreturn"), escrow.issuer),
+            (escrow_id, escrow.amount),
+        );
+
+        escrow.amount
+    }
+
+    /// Get an unreleased escrow
+    pub fn escrow(env: Env, escrow_id: u64) -> Option<Escrow> {
+        env.storage()
+            .persistent()
+            .get(&EscrowKey::Escrow(escrow_id))
+    }
+}
+
+/// Credits issued into escrows that are not yet released or reclaimed
+pub(crate) fn escrowed_total(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&EscrowKey::EscrowedTotal)
+        .unwrap_or(0)
+}
+
+fn set_escrowed_total(env: &Env, total: i128) {
+    env.storage()
+        .instance()
+        .set(&EscrowKey::EscrowedTotal, &total);
+}
+
+/// Pay out a disputed escrow as its case decided: to the school, or back
+/// to the issuer
+#[cfg(feature = "disputes")]
+pub(crate) fn settle(env: &Env, escrow_id: u64, to_school: bool) {
+    let escrow = read_escrow(env, escrow_id);
+    freeze::require_account_unfrozen(env, &escrow.beneficiary);
+    remove_escrow(env, escrow_id, escrow.amount);
+    let payee = if to_school {
+        rotation::current(env, &escrow.school)
+    } else {
+        escrow.issuer
+    };
+    credit(env, &payee, escrow.amount);
+}
+
+fn read_escrow(env: &Env, escrow_id: u64) -> Escrow {
+    env.storage()
+        .persistent()
+        .get(&EscrowKey::Escrow(escrow_id))
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound))
+}
+
+fn remove_escrow(env: &Env, escrow_id: u64, amount: i128) {
+    env.storage()
+        .persistent()
+        .remove(&EscrowKey::Escrow(escrow_id));
+    set_escrowed_total(env, escrowed_total(env) - amount);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+    use soroban_sdk::vec;
+
+    fn setup() -> (Scenario, u64) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(2);
+        let id = s.client().issue_to_escrow(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &s.school(0),
+            &800,
+            &String::from_str(&s.env, "Tuition"),
+            &(s.now() + 30 * DAY_SECS),
+        );
+        (s, id)
+    }
+
+    #[test]
+    fn test_acknowledgement_releases_to_school() {
+        let (s, id) = setup();
+        let client = s.client();
+        let escrow = client.escrow(&id).unwrap();
+        assert_eq!((escrow.school, escrow.amount), (s.school(0), 800));
+        assert_eq!(client.total_issued(), 800);
+        assert_eq!(client.balance(&s.school(0)), 0);
+        assert!(client.check_invariants(&vec![&s.env]).ok);
+
+        // Only the named beneficiary can release it
+        let result = client.try_release_escrow(&s.beneficiary(1), &id);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        client.release_escrow(&s.beneficiary(0), &id);
+        assert_eq!(client.balance(&s.school(0)), 800);
+        assert_eq!(client.balance(&s.beneficiary(0)), 0);
+        assert_eq!(client.escrow(&id), None);
+        assert!(client.check_invariants(&vec![&s.env, s.school(0)]).ok);
+
+        let result = client.try_release_escrow(&s.beneficiary(0), &id);
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+    }
+
+    #[test]
+    fn test_expired_escrow_returns_to_issuer() {
+        let (s, id) = setup();
+        let client = s.client();
+        let result = client.try_reclaim_escrow(&id);
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+
+        advance(&s.env, 30 * DAY_SECS);
+        let result = client.try_release_escrow(&s.beneficiary(0), &id);
+        assert_eq!(result, Err(Ok(Error::OfferExpired.into())));
+        assert_eq!(client.reclaim_escrow(&id), 800);
+        assert_eq!(client.balance(&s.issuer(0)), 800);
+        assert_eq!(client.balance(&s.school(0)), 0);
+        assert!(client.check_invariants(&vec![&s.env, s.issuer(0)]).ok);
+    }
+}
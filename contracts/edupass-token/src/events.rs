@@ -0,0 +1,217 @@
+use soroban_sdk::{
+    contractimpl, contracttype, events::Topics, map, Env, IntoVal, Map, String, Symbol, Val, Vec,
+};
+
+use crate::{require_admin, telemetry, EduPassToken, EduPassTokenClient};
+
+// Version carried as the second topic of every event. Version 1 is the
+// original unversioned layout, still emitted while legacy events are on.
+const EVENT_SCHEMA_VERSION: u32 = 5;
+
+#[contracttype]
+enum EventKey {
+    LegacyEvents, // Also emit events in the unversioned layout
+    InstanceId,   // Operator-chosen name of this deployment
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Get the schema version carried by emitted events
+    pub fn event_schema_version(_env: Env) -> u32 {
+        EVENT_SCHEMA_VERSION
+    }
+
+    /// Get a summary of each event schema version
+    pub fn event_schema_changelog(env: Env) -> Map<u32, String> {
+        map![
+            &env,
+            (1, String::from_str(&env, "Topics (name, ...keys)")),
+            (
+                2,
+                String::from_str(&env, "Topics (name, schema_version, ...keys)")
+            ),
+            (
+                3,
+                String::from_str(&env, "Transfer and burn data (amount, authorizer)")
+            ),
+            (
+                4,
+                String::from_str(&env, "Issued data (issuer, amount, metadata)")
+            ),
+            (5, String::from_str(&env, "Data (instance_id, data)")),
+        ]
+    }
+
+    /// Name this deployment (admin only), e.g. by region. The name is
+    /// carried in every versioned event and reported by info() so clients
+    /// can check they are talking to the deployment they expect.
+    pub fn set_instance_id(env: Env, instance_id: Option<Symbol>) {
+        telemetry::record(&env, "set_instance_id");
+        require_admin(&env);
+
+        match instance_id {
+            Some(instance_id) => env
+                .storage()
+                .instance()
+                .set(&EventKey::InstanceId, &instance_id),
+            None => env.storage().instance().remove(&EventKey::InstanceId),
+        }
+    }
+
+    /// Get this deployment's name, if set
+    pub fn instance_id(env: Env) -> Option<Symbol> {
+        env.storage().instance().get(&EventKey::InstanceId)
+    }
+
+    /// Also emit events in the unversioned layout so indexers can migrate
+    /// without gaps (admin only). Kept for one release.
+    pub fn set_legacy_events(env: Env, enabled: bool) {
+        telemetry::record(&env, "set_legacy_events");
+        require_admin(&env);
+
+        if enabled {
+            env.storage().instance().set(&EventKey::LegacyEvents, &true);
+        } else {
+            env.storage().instance().remove(&EventKey::LegacyEvents);
+        }
+    }
+
+    /// Check whether legacy-layout events are emitted
+    pub fn legacy_events(env: Env) -> bool {
+        legacy_events(&env)
+    }
+}
+
+/// Publish an event with the schema version inserted after its name
+pub(crate) fn publish<T, D>(env: &Env, topics: T, data: D)
+where
+    T: Topics,
+    D: IntoVal<Env, Val>,
+{
+    let topics: Vec<Val> = topics.into_val(env);
+    let data: Val = data.into_val(env);
+
+    if legacy_events(env) {
+        env.events().publish(topics.clone(), data);
+    }
+    publish_versioned(env, topics, data);
+}
+
+/// Publish a token event (approve, transfer, burn) versioned and, with
+/// `token_data`, in the soroban token layout standard token indexers
+/// expect. That layout is the legacy one, so it stands in for the legacy
+/// copy.
+pub(crate) fn publish_token<T, D, E>(env: &Env, topics: T, token_data: D, data: E)
+where
+    T: Topics,
+    D: IntoVal<Env, Val>,
+    E: IntoVal<Env, Val>,
+{
+    let topics: Vec<Val> = topics.into_val(env);
+    env.events()
+        .publish(topics.clone(), token_data.into_val(env));
+    publish_versioned(env, topics, data.into_val(env));
+}
+
+fn publish_versioned(env: &Env, topics: Vec<Val>, data: Val) {
+    let mut versioned = topics;
+    versioned.insert(1, EVENT_SCHEMA_VERSION.into_val(env));
+    let instance_id = EduPassToken::instance_id(env.clone());
+    env.events().publish(versioned, (instance_id, data));
+}
+
+fn legacy_events(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&EventKey::LegacyEvents)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Events};
+    use soroban_sdk::{symbol_short, vec, Address};
+
+    #[test]
+    fn test_versioned_and_legacy_events() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+
+        client.initialize(&Address::generate(&env));
+        assert_eq!(client.event_schema_version(), 5);
+        assert_eq!(client.event_schema_changelog().len(), 5);
+
+        client.set_min_transfer(&Some(10));
+        client.issue_credits(
+            &Address::generate(&env),
+            &beneficiary,
+            &105,
+            &String::from_str(&env, "Tuition"),
+            &1735689600,
+        );
+        client.transfer(&beneficiary, &school, &100);
+
+        client.sweep_dust(&beneficiary);
+        let all = env.events().all();
+        assert_eq!(
+            all.slice(all.len() - 1..),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("dust"), 5u32, beneficiary.clone()).into_val(&env),
+                    (None::<Symbol>, 5i128).into_val(&env)
+                ),
+            ]
+        );
+
+        // Legacy layout events precede their versioned counterparts
+        client.set_legacy_events(&true);
+        client.transfer(&school, &beneficiary, &95);
+        client.sweep_dust(&school);
+        let all = env.events().all();
+        assert_eq!(
+            all.slice(all.len() - 2..),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("dust"), school.clone()).into_val(&env),
+                    5i128.into_val(&env)
+                ),
+                (
+                    contract_id.clone(),
+                    (symbol_short!("dust"), 5u32, school.clone()).into_val(&env),
+                    (None::<Symbol>, 5i128).into_val(&env)
+                ),
+            ]
+        );
+
+        // Named deployments carry their name in versioned events only
+        let region = symbol_short!("eu_west");
+        client.set_instance_id(&Some(region.clone()));
+        assert_eq!(client.info().instance_id, Some(region.clone()));
+        client.transfer(&beneficiary, &school, &90);
+        client.set_legacy_events(&false);
+        client.sweep_dust(&beneficiary);
+        let all = env.events().all();
+        assert_eq!(
+            all.slice(all.len() - 1..),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("dust"), 5u32, beneficiary.clone()).into_val(&env),
+                    (Some(region), 5i128).into_val(&env)
+                ),
+            ]
+        );
+    }
+}
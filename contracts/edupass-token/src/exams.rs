@@ -0,0 +1,240 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, Address, Env, Map, String, Symbol,
+};
+
+use crate::{
+    checked_sub, issue, read_admin, require_admin, rotation, telemetry, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum ExamKey {
+    Examiner(Address),           // Approved examiner
+    ExamBonus(Symbol),           // Bonus configuration for an exam
+    ExamResult(Symbol, Address), // Reported score band for (exam, beneficiary)
+    BonusBudget(Symbol),         // Remaining bonus budget for a program
+}
+
+// Bonus allocation issued for qualifying score bands of an exam
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExamBonus {
+    pub program_id: Symbol,
+    pub purpose: String,
+    pub validity_secs: u64,
+    pub band_amounts: Map<u32, i128>, // Bonus per qualifying score band
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Approve or revoke an examiner (admin only)
+    pub fn set_examiner(env: Env, examiner: Address, approved: bool) {
+        telemetry::record(&env, "set_examiner");
+        require_admin(&env);
+
+        let key = ExamKey::Examiner(examiner);
+        if approved {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    /// Check whether an address is an approved examiner
+    pub fn is_examiner(env: Env, examiner: Address) -> bool {
+        env.storage().persistent().has(&ExamKey::Examiner(examiner))
+    }
+
+    /// Configure the bonus paid for an exam's qualifying bands (admin only)
+    pub fn set_exam_bonus(env: Env, exam_id: Symbol, bonus: ExamBonus) {
+        telemetry::record(&env, "set_exam_bonus");
+        require_admin(&env);
+
+        for (_, amount) in bonus.band_amounts.iter() {
+            if amount <= 0 {
+                panic_with_error!(&env, Error::InvalidAmount);
+            }
+        }
+        env.storage()
+            .persistent()
+            .set(&ExamKey::ExamBonus(exam_id), &bonus);
+    }
+
+    /// Set the remaining performance bonus budget for a program (admin only)
+    pub fn set_program_bonus_budget(env: Env, program_id: Symbol, budget: i128) {
+        telemetry::record(&env, "set_program_bonus_budget");
+        require_admin(&env);
+
+        if budget < 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        env.storage()
+            .persistent()
+            .set(&ExamKey::BonusBudget(program_id), &budget);
+    }
+
+    /// Report a beneficiary's exam result; issues the configured bonus for
+    /// qualifying bands and returns the amount issued
+    pub fn report_result(
+        env: Env,
+        examiner: Address,
+        beneficiary: Address,
+        exam_id: Symbol,
+        score_band: u32,
+    ) -> i128 {
+        telemetry::record(&env, "report_result");
+        examiner.require_auth();
+
+        if !env.storage().persistent().has(&ExamKey::Examiner(examiner)) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        let bonus: ExamBonus = env
+            .storage()
+            .persistent()
+            .get(&ExamKey::ExamBonus(exam_id.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+
+        // One result per beneficiary per exam
+        let result_key = ExamKey::ExamResult(exam_id, beneficiary.clone());
+        if env.storage().persistent().has(&result_key) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        env.storage().persistent().set(&result_key, &score_band);
+
+        let amount = match bonus.band_amounts.get(score_band) {
+            Some(amount) => amount,
+            None => return 0,
+        };
+
+        let budget_key = ExamKey::BonusBudget(bonus.program_id.clone());
+        let budget: i128 = env.storage().persistent().get(&budget_key).unwrap_or(0);
+        if budget < amount {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        env.storage()
+            .persistent()
+            .set(&budget_key, &checked_sub(&env, budget, amount));
+
+        // Bonus allocations are issued on behalf of the admin
+        let admin = read_admin(&env);
+        let expires_at = env.ledger().timestamp() + bonus.validity_secs;
+        issue(
+            &env,
+            admin,
+            beneficiary,
+            amount,
+            bonus.purpose,
+            expires_at,
+            Some(bonus.program_id),
+            Map::new(&env),
+        );
+
+        amount
+    }
+
+    /// Get the bonus configuration for an exam
+    pub fn exam_bonus(env: Env, exam_id: Symbol) -> Option<ExamBonus> {
+        env.storage().persistent().get(&ExamKey::ExamBonus(exam_id))
+    }
+
+    /// Get the reported score band for a beneficiary's exam
+    pub fn exam_result(env: Env, exam_id: Symbol, beneficiary: Address) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get(&ExamKey::ExamResult(exam_id, beneficiary))
+    }
+
+    /// Get the remaining performance bonus budget for a program
+    pub fn program_bonus_budget(env: Env, program_id: Symbol) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&ExamKey::BonusBudget(program_id))
+            .unwrap_or(0)
+    }
+}
+
+/// Move an examiner's approval to its rotated key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &ExamKey::Examiner(old.clone()),
+        &ExamKey::Examiner(new.clone()),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::symbol_short;
+    use soroban_sdk::testutils::Address as _;
+
+    fn setup(env: &Env) -> (EduPassTokenClient<'_>, Address, Address) {
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(env, &contract_id);
+
+        let admin = Address::generate(env);
+        let examiner = Address::generate(env);
+
+        client.initialize(&admin);
+        client.set_examiner(&examiner, &true);
+
+        let mut band_amounts = Map::new(env);
+        band_amounts.set(1, 500);
+        band_amounts.set(2, 200);
+        client.set_exam_bonus(
+            &symbol_short!("kcse"),
+            &ExamBonus {
+                program_id: symbol_short!("merit"),
+                purpose: String::from_str(env, "Merit Bonus"),
+                validity_secs: 365 * 24 * 60 * 60,
+                band_amounts,
+            },
+        );
+        client.set_program_bonus_budget(&symbol_short!("merit"), &600);
+
+        (client, admin, examiner)
+    }
+
+    #[test]
+    fn test_report_result_issues_bonus() {
+        let env = Env::default();
+        let (client, admin, examiner) = setup(&env);
+        let student = Address::generate(&env);
+
+        let issued = client.report_result(&examiner, &student, &symbol_short!("kcse"), &1);
+        assert_eq!(issued, 500);
+        assert_eq!(client.balance(&student), 500);
+        assert_eq!(client.program_bonus_budget(&symbol_short!("merit")), 100);
+
+        let allocation = client.get_allocation(&student).unwrap();
+        assert_eq!(allocation.issuer, admin);
+        assert_eq!(allocation.program_id, Some(symbol_short!("merit")));
+
+        // Non-qualifying bands are recorded without a bonus
+        let other = Address::generate(&env);
+        assert_eq!(
+            client.report_result(&examiner, &other, &symbol_short!("kcse"), &5),
+            0
+        );
+        assert_eq!(client.exam_result(&symbol_short!("kcse"), &other), Some(5));
+    }
+
+    #[test]
+    fn test_report_result_dedup_and_budget() {
+        let env = Env::default();
+        let (client, _, examiner) = setup(&env);
+        let student = Address::generate(&env);
+
+        client.report_result(&examiner, &student, &symbol_short!("kcse"), &1);
+        let result = client.try_report_result(&examiner, &student, &symbol_short!("kcse"), &1);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+
+        // Only 100 of the budget remains
+        let other = Address::generate(&env);
+        let result = client.try_report_result(&examiner, &other, &symbol_short!("kcse"), &2);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+    }
+}
@@ -0,0 +1,154 @@
+// Budget forecast: credits the contract has committed to issue or pay but
+// hasn't yet, against the reserves that back them. Commitments come from
+// counters the owning modules keep as obligations are made and met:
+// stipend tranches not yet claimed or forfeited, escrowed awards not yet
+// released or reclaimed, and queued issuances not yet processed. Stipends
+// and escrows are issued outside any program, so they are reported under
+// `None`; queued issuances under their program.
+
+use soroban_sdk::{contractimpl, contracttype, Env, Symbol};
+
+use crate::{checked_add, community, queue, EduPassToken, EduPassTokenClient};
+
+// Obligations outstanding under one program, or outside any (None)
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Commitments {
+    pub stipends: i128, // Stipend tranches not yet claimed or forfeited
+    pub escrows: i128,  // Escrowed awards not yet released or reclaimed
+    pub queued: i128,   // Queued issuances not yet processed or cancelled
+    pub total: i128,
+}
+
+// All outstanding obligations against the reserves backing them
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundingGap {
+    pub committed: i128,
+    pub reserves: i128, // Credits held in the community pool
+    pub gap: i128,      // Committed less reserves; negative when covered
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Get the obligations outstanding under a program, or outside any
+    /// (None)
+    pub fn commitments(env: Env, program_id: Option<Symbol>) -> Commitments {
+        let commitments = match program_id {
+            Some(program_id) => Commitments {
+                queued: queue::queued_amount(&env, &program_id),
+                ..Default::default()
+            },
+            None => Commitments {
+                stipends: stipends_outstanding(&env),
+                escrows: escrowed_total(&env),
+                ..Default::default()
+            },
+        };
+        let total = checked_add(&env, commitments.stipends, commitments.escrows);
+        Commitments {
+            total: checked_add(&env, total, commitments.queued),
+            ..commitments
+        }
+    }
+
+    /// Compare every outstanding obligation with the reserves
+    pub fn funding_gap(env: Env) -> FundingGap {
+        let committed = checked_add(
+            &env,
+            Self::commitments(env.clone(), None).total,
+            queue::queued_total(&env),
+        );
+        let reserves = community::pool_balance(&env);
+        FundingGap {
+            committed,
+            reserves,
+            gap: committed - reserves,
+        }
+    }
+}
+
+#[cfg(feature = "escrow")]
+fn escrowed_total(env: &Env) -> i128 {
+    crate::escrow::escrowed_total(env)
+}
+
+#[cfg(not(feature = "escrow"))]
+fn escrowed_total(_env: &Env) -> i128 {
+    0
+}
+
+#[cfg(feature = "stipends")]
+fn stipends_outstanding(env: &Env) -> i128 {
+    crate::schedule::outstanding(env)
+}
+
+#[cfg(not(feature = "stipends"))]
+fn stipends_outstanding(_env: &Env) -> i128 {
+    0
+}
+
+#[cfg(all(test, feature = "escrow"))]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use crate::PendingIssuance;
+    use soroban_sdk::{symbol_short, String};
+
+    #[test]
+    fn test_commitments_follow_obligations() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(3)
+            .issue(0, 2, 500);
+        let client = s.client();
+        let purpose = String::from_str(&s.env, "Tuition");
+        let escrow_id = client.issue_to_escrow(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &s.school(0),
+            &700,
+            &purpose,
+            &s.expires_at(),
+        );
+        let program = symbol_short!("bursary");
+        let pending_id = client.queue_issuance(&PendingIssuance {
+            issuer: s.issuer(0),
+            beneficiary: s.beneficiary(1),
+            amount: 400,
+            purpose: purpose.clone(),
+            expires_at: s.expires_at(),
+            program_id: program.clone(),
+            need_score: 0,
+            grade_level: 0,
+        });
+        assert_eq!(client.commitments(&None).escrows, 700);
+        assert_eq!(
+            client.commitments(&Some(program.clone())),
+            Commitments {
+                stipends: 0,
+                escrows: 0,
+                queued: 400,
+                total: 400,
+            }
+        );
+
+        client.deposit_to_pool(&s.beneficiary(2), &500, &soroban_sdk::Vec::new(&s.env));
+        assert_eq!(
+            client.funding_gap(),
+            FundingGap {
+                committed: 1100,
+                reserves: 500,
+                gap: 600,
+            }
+        );
+
+        // Meeting an obligation takes it out of the forecast
+        client.release_escrow(&s.beneficiary(0), &escrow_id);
+        client.cancel_issuance(&s.issuer(0), &pending_id);
+        assert_eq!(client.funding_gap().gap, -500);
+        assert_eq!(client.commitments(&Some(program)).total, 0);
+    }
+}
@@ -0,0 +1,261 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Symbol,
+};
+
+use crate::{
+    events, program_of, read_admin, require_admin, telemetry, Allocation, DataKey, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum FreezeKey {
+    Compliance,            // Address allowed to freeze and unfreeze programs
+    ProgramFrozen(Symbol), // Set while a program's credits can't be issued or spent
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the compliance officer (admin only)
+    pub fn set_compliance(env: Env, compliance: Option<Address>) {
+        telemetry::record(&env, "set_compliance");
+        require_admin(&env);
+
+        match compliance {
+            Some(compliance) => env
+                .storage()
+                .instance()
+                .set(&FreezeKey::Compliance, &compliance),
+            None => env.storage().instance().remove(&FreezeKey::Compliance),
+        }
+    }
+
+    /// Get the compliance officer, if set
+    pub fn compliance(env: Env) -> Option<Address> {
+        env.storage().instance().get(&FreezeKey::Compliance)
+    }
+
+    /// Freeze a program (the admin or the compliance officer): allocations
+    /// tagged with it can't be issued, transferred or redeemed until the
+    /// compliance officer unfreezes it. Other programs are unaffected.
+    pub fn freeze_program(env: Env, caller: Address, program_id: Symbol) {
+        telemetry::record(&env, "freeze_program");
+        caller.require_auth();
+
+        if caller != read_admin(&env) && Some(caller) != Self::compliance(env.clone()) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        env.storage()
+            .persistent()
+            .set(&FreezeKey::ProgramFrozen(program_id.clone()), &true);
+        events::publish(&env, (symbol_short!("frozen"), program_id), true);
+    }
+
+    /// Unfreeze a program (compliance officer only)
+    pub fn unfreeze_program(env: Env, program_id: Symbol) {
+        telemetry::record(&env, "unfreeze_program");
+        require_compliance(&env);
+
+        env.storage()
+            .persistent()
+            .remove(&FreezeKey::ProgramFrozen(program_id.clone()));
+        events::publish(&env, (symbol_short!("frozen"), program_id), false);
+    }
+
+    /// Check whether a program is frozen
+    pub fn is_program_frozen(env: Env, program_id: Symbol) -> bool {
+        is_frozen(&env, &program_id)
+    }
+}
+
+/// Require the compliance officer's authorization; fails with
+/// NotConfigured while none is set
+pub(crate) fn require_compliance(env: &Env) -> Address {
+    let compliance = EduPassToken::compliance(env.clone())
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotConfigured));
+    compliance.require_auth();
+    compliance
+}
+
+fn is_frozen(env: &Env, program_id: &Symbol) -> bool {
+    env.storage()
+        .persistent()
+        .has(&FreezeKey::ProgramFrozen(program_id.clone()))
+}
+
+/// Fail with Paused if credits of the given program are frozen. Credits
+/// outside any program are never frozen.
+pub(crate) fn check_unfrozen(env: &Env, program_id: &Option<Symbol>) -> Result<(), Error> {
+    match program_id {
+        Some(program_id) if is_frozen(env, program_id) => Err(Error::Paused),
+        _ => Ok(()),
+    }
+}
+
+/// Fail with Paused if an account's allocation belongs to a frozen
+/// program, for paths that move its credits outside a transfer
+pub(crate) fn require_account_unfrozen(env: &Env, account: &Address) {
+    let allocation: Option<Allocation> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Allocations(account.clone()));
+    if let Err(error) = check_unfrozen(env, &program_of(&allocation)) {
+        panic_with_error!(env, error);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{Scenario, DAY_SECS};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::String;
+
+    fn setup() -> (Scenario, Address) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(2);
+        let client = s.client();
+        let compliance = Address::generate(&s.env);
+        client.set_compliance(&Some(compliance.clone()));
+        let purpose = String::from_str(&s.env, "Tuition");
+        for (beneficiary, program) in [(0, "uni"), (1, "k12")] {
+            let program = Symbol::new(&s.env, program);
+            client.save_template(
+                &s.issuer(0),
+                &program,
+                &purpose,
+                &1000,
+                &(365 * DAY_SECS),
+                &program,
+            );
+            client.issue_from_template(&s.issuer(0), &program, &s.beneficiary(beneficiary));
+        }
+        (s, compliance)
+    }
+
+    #[test]
+    fn test_frozen_program_blocks_only_its_allocations() {
+        let (s, compliance) = setup();
+        let client = s.client();
+        let uni = Symbol::new(&s.env, "uni");
+        client.freeze_program(&compliance, &uni);
+        assert!(client.is_program_frozen(&uni));
+
+        let result = client.try_transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+        let result = client.try_can_transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::Paused)));
+        let result = client.try_burn(&s.beneficiary(0), &100);
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+        let result = client.try_issue_from_template(&s.issuer(0), &uni, &s.beneficiary(0));
+        assert_eq!(result.err(), Some(Ok(Error::Paused.into())));
+
+        // Other programs carry on
+        client.transfer(&s.beneficiary(1), &s.school(0), &100);
+
+        client.unfreeze_program(&uni);
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+    }
+
+    #[test]
+    fn test_freeze_blocks_bridge_locks() {
+        use crate::BridgeChain;
+        use soroban_sdk::Bytes;
+        let (s, compliance) = setup();
+        let client = s.client();
+        let chain = Symbol::new(&s.env, "partner");
+        client.set_bridge_chain(
+            &chain,
+            &Some(BridgeChain {
+                outbound_limit: 500,
+                inbound_limit: 500,
+                period_secs: DAY_SECS,
+            }),
+        );
+        client.freeze_program(&compliance, &Symbol::new(&s.env, "uni"));
+
+        let dest = Bytes::from_slice(&s.env, b"0xabc");
+        let result = client.try_lock_for_bridge(&s.beneficiary(0), &100, &chain, &dest);
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+        client.lock_for_bridge(&s.beneficiary(1), &100, &chain, &dest);
+    }
+
+    #[test]
+    fn test_freeze_blocks_account_closure() {
+        let (s, compliance) = setup();
+        let client = s.client();
+        client.freeze_program(&compliance, &Symbol::new(&s.env, "uni"));
+
+        let result = client.try_close_account(&s.beneficiary(0), &None);
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+        let result = client.try_close_account(&s.beneficiary(0), &Some(s.school(0)));
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+    }
+
+    #[test]
+    fn test_freeze_blocks_advances() {
+        let (s, compliance) = setup();
+        let client = s.client();
+        client.set_advance_limit(&Some(500));
+        client.freeze_program(&compliance, &Symbol::new(&s.env, "uni"));
+
+        let result = client.try_request_advance(&s.beneficiary(0), &100);
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+        client.request_advance(&s.beneficiary(1), &100);
+    }
+
+    #[test]
+    #[cfg(feature = "escrow")]
+    fn test_freeze_blocks_escrow_payouts() {
+        let (s, compliance) = setup();
+        let client = s.client();
+        let purpose = String::from_str(&s.env, "Tuition");
+        let issue = || {
+            client.issue_to_escrow(
+                &s.issuer(0),
+                &s.beneficiary(0),
+                &s.school(0),
+                &100,
+                &purpose,
+                &(s.now() + DAY_SECS),
+            )
+        };
+        let (released, reclaimed) = (issue(), issue());
+        client.freeze_program(&compliance, &Symbol::new(&s.env, "uni"));
+
+        let result = client.try_release_escrow(&s.beneficiary(0), &released);
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+        crate::testutils::advance(&s.env, DAY_SECS);
+        let result = client.try_reclaim_escrow(&reclaimed);
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+    }
+
+    #[test]
+    fn test_freeze_blocks_pool_withdrawals() {
+        let (s, compliance) = setup();
+        let client = s.client();
+        let shares =
+            client.deposit_to_pool(&s.beneficiary(0), &100, &soroban_sdk::Vec::new(&s.env));
+        client.freeze_program(&compliance, &Symbol::new(&s.env, "uni"));
+
+        let result = client.try_withdraw_from_pool(&s.beneficiary(0), &shares);
+        assert_eq!(result, Err(Ok(Error::Paused.into())));
+    }
+
+    #[test]
+    fn test_only_compliance_unfreezes() {
+        let (s, _) = setup();
+        let client = s.client();
+        let uni = Symbol::new(&s.env, "uni");
+        let result = client.try_freeze_program(&s.beneficiary(0), &uni);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        client.freeze_program(&s.admin(), &uni);
+
+        client.set_compliance(&None);
+        let result = client.try_unfreeze_program(&uni);
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+        assert!(client.is_program_frozen(&uni));
+    }
+}
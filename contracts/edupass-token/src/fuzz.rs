@@ -0,0 +1,441 @@
+//! Fuzz harnesses shared by the cargo-fuzz targets in `fuzz/` and the
+//! seeded runs in this crate's tests. Each one drives the contract with
+//! arbitrary input and panics if a call fails with anything other than a
+//! contract error, or leaves the accounting inconsistent.
+
+extern crate std;
+
+use soroban_sdk::testutils::arbitrary::arbitrary::{self, Arbitrary};
+use soroban_sdk::testutils::EnvTestConfig;
+use soroban_sdk::xdr::ScErrorType;
+use soroban_sdk::{Address, Env, String, Vec};
+use std::fmt::Debug;
+use std::string::String as StdString;
+use std::vec::Vec as StdVec;
+
+#[cfg(any(feature = "acceptance", feature = "stipends"))]
+use crate::testutils::set_time;
+use crate::testutils::Scenario;
+
+// Longest operation sequence a single input can run
+pub const MAX_STEPS: usize = 32;
+
+const BENEFICIARIES: u32 = 2;
+const SCHOOLS: u32 = 1;
+
+#[derive(Arbitrary, Debug)]
+pub struct IssueInput {
+    pub now: u64,
+    pub amount: i128,
+    pub purpose: StdString,
+    pub expires_at: u64,
+}
+
+#[derive(Arbitrary, Debug)]
+pub struct TransferStep {
+    pub from: u8,
+    pub to: u8,
+    pub amount: i128,
+    pub advance_secs: u32,
+}
+
+#[derive(Arbitrary, Debug)]
+pub struct TransferInput {
+    pub grants: [i128; BENEFICIARIES as usize],
+    pub expires_at: u64,
+    pub steps: StdVec<TransferStep>,
+}
+
+#[cfg(feature = "acceptance")]
+#[derive(Arbitrary, Debug)]
+pub enum OfferStep {
+    Offer {
+        beneficiary: u8,
+        amount: i128,
+        expires_at: u64,
+        key: u8, // Idempotency key, so offers can be retried
+    },
+    Accept {
+        offer: u8, // Index into the ids offered so far
+    },
+    Decline {
+        offer: u8,
+    },
+    Advance {
+        secs: u64,
+    },
+}
+
+#[cfg(feature = "acceptance")]
+#[derive(Arbitrary, Debug)]
+pub struct OfferInput {
+    pub timeout_secs: u64,
+    pub steps: StdVec<OfferStep>,
+}
+
+#[cfg(feature = "stipends")]
+#[derive(Arbitrary, Debug)]
+pub enum StipendStep {
+    Attest { month: u32 },
+    Claim { schedule_id: u64 },
+    SetTime { timestamp: u64 },
+}
+
+#[cfg(feature = "stipends")]
+#[derive(Arbitrary, Debug)]
+pub struct StipendInput {
+    pub monthly_amount: i128,
+    pub start_month: u32,
+    pub months: u32,
+    pub defer: bool,
+    pub expires_at: u64,
+    pub steps: StdVec<StipendStep>,
+}
+
+// Fuzzing never wants snapshots, and long sequences would trip the
+// default budget before they found anything
+fn scenario() -> Scenario {
+    let env = Env::new_with_config(EnvTestConfig {
+        capture_snapshot_at_drop: false,
+    });
+    let s = Scenario::with_env(env)
+        .with_admin()
+        .with_issuers(1)
+        .with_beneficiaries(BENEFICIARIES)
+        .with_schools(SCHOOLS);
+    s.env.budget().reset_unlimited();
+    s
+}
+
+// Issuer, then beneficiaries, then schools
+fn parties(s: &Scenario) -> Vec<Address> {
+    let mut parties = Vec::new(&s.env);
+    parties.push_back(s.issuer(0));
+    for i in 0..BENEFICIARIES {
+        parties.push_back(s.beneficiary(i));
+    }
+    for i in 0..SCHOOLS {
+        parties.push_back(s.school(i));
+    }
+    parties
+}
+
+// A contract error is an expected rejection; anything else (a panic,
+// overflow or host error) means the input made the contract trap
+fn expect_typed<T, C, E, H>(result: Result<Result<T, C>, Result<E, H>>) -> Option<T>
+where
+    C: Debug,
+    E: Into<soroban_sdk::Error> + Debug,
+    H: Debug,
+{
+    match result {
+        Ok(value) => Some(value.expect("return value")),
+        Err(Ok(error)) => {
+            let error: soroban_sdk::Error = error.into();
+            assert!(error.is_type(ScErrorType::Contract), "trapped: {:?}", error);
+            None
+        }
+        Err(Err(error)) => panic!("trapped: {:?}", error),
+    }
+}
+
+// Every credit issued is either burned, held by a party, sitting in a
+// pool, or still pending in an offer
+fn check_accounting(s: &Scenario, parties: &Vec<Address>, pending: i128) {
+    let client = s.client();
+    let mut balances = 0i128;
+    for account in parties.iter() {
+        let balance = client.balance(&account);
+        assert!(balance >= 0, "negative balance");
+        balances += balance;
+    }
+
+    let report = client.check_invariants(parties);
+    assert!(report.ok, "{:?}", report);
+    assert_eq!(
+        balances + report.held_in_pools + pending,
+        client.total_issued() - client.total_burned(),
+        "supply leak"
+    );
+}
+
+/// Issue once with arbitrary amount, purpose, expiry and ledger time
+pub fn issue_credits(input: IssueInput) {
+    let s = scenario().set_time(input.now);
+    let client = s.client();
+    let purpose = String::from_str(&s.env, &input.purpose);
+    let issued = expect_typed(client.try_issue_credits(
+        &s.issuer(0),
+        &s.beneficiary(0),
+        &input.amount,
+        &purpose,
+        &input.expires_at,
+    ));
+
+    if issued.is_some() {
+        assert_eq!(client.balance(&s.beneficiary(0)), input.amount);
+        assert_eq!(client.total_issued(), input.amount);
+    } else {
+        assert_eq!(client.total_issued(), 0);
+    }
+    check_accounting(&s, &parties(&s), 0);
+}
+
+/// Transfers between arbitrary parties, amounts and times after an
+/// arbitrary issuance to each beneficiary
+pub fn transfer(input: TransferInput) {
+    let mut s = scenario();
+    let parties = parties(&s);
+    let client = s.client();
+    let purpose = String::from_str(&s.env, "Tuition");
+    for (i, amount) in input.grants.iter().enumerate() {
+        expect_typed(client.try_issue_credits(
+            &s.issuer(0),
+            &s.beneficiary(i as u32),
+            amount,
+            &purpose,
+            &input.expires_at,
+        ));
+    }
+    check_accounting(&s, &parties, 0);
+
+    for step in input.steps.iter().take(MAX_STEPS) {
+        let from = parties.get_unchecked(step.from as u32 % parties.len());
+        let to = parties.get_unchecked(step.to as u32 % parties.len());
+        let balances = || (s.client().balance(&from), s.client().balance(&to));
+        let before = balances();
+        let moved = expect_typed(s.client().try_transfer(&from, &to, &step.amount));
+
+        if moved.is_some() {
+            assert!(step.amount > 0 && from != to);
+        } else {
+            assert_eq!(balances(), before, "rejected transfer moved credits");
+        }
+        check_accounting(&s, &parties, 0);
+        s = s.advance_time(step.advance_secs as u64);
+    }
+}
+
+/// Offers accepted, declined or left to lapse at arbitrary times
+#[cfg(feature = "acceptance")]
+pub fn offers(input: OfferInput) {
+    use soroban_sdk::BytesN;
+
+    let s = scenario();
+    let parties = parties(&s);
+    let client = s.client();
+    expect_typed(client.try_set_acceptance_timeout(&input.timeout_secs));
+
+    let mut offered = std::collections::BTreeMap::new();
+    // Ids are kept as arrays, which order without an Env
+    let mut ids: std::vec::Vec<[u8; 32]> = std::vec::Vec::new();
+    let id_at = |ids: &std::vec::Vec<[u8; 32]>, offer: u8| match ids.len() {
+        0 => BytesN::from_array(&s.env, &[0; 32]),
+        len => BytesN::from_array(&s.env, &ids[offer as usize % len]),
+    };
+    let pending = |offered: &std::collections::BTreeMap<[u8; 32], i128>| offered.values().sum();
+    let purpose = String::from_str(&s.env, "Tuition");
+    for step in input.steps.iter().take(MAX_STEPS) {
+        let client = s.client();
+        match *step {
+            OfferStep::Offer {
+                beneficiary,
+                amount,
+                expires_at,
+                key,
+            } => {
+                let beneficiary = s.beneficiary(beneficiary as u32 % BENEFICIARIES);
+                let offer = client.try_offer_credits(
+                    &s.issuer(0),
+                    &beneficiary,
+                    &amount,
+                    &purpose,
+                    &expires_at,
+                    &BytesN::from_array(&s.env, &[key; 32]),
+                );
+                // A retry returns the id of an offer that may be answered
+                if let Some(id) = expect_typed(offer) {
+                    let pending = client.pending_allocation(&id).is_some();
+                    let id = id.to_array();
+                    if pending && !ids.contains(&id) {
+                        offered.insert(id, amount);
+                        ids.push(id);
+                    }
+                }
+            }
+            OfferStep::Accept { offer } => {
+                let allocation_id = id_at(&ids, offer);
+                if expect_typed(client.try_accept_allocation(&allocation_id)).is_some() {
+                    assert!(offered.remove(&allocation_id.to_array()).is_some());
+                }
+            }
+            OfferStep::Decline { offer } => {
+                let allocation_id = id_at(&ids, offer);
+                if let Some(amount) = expect_typed(client.try_decline_allocation(&allocation_id)) {
+                    assert_eq!(offered.remove(&allocation_id.to_array()), Some(amount));
+                }
+            }
+            OfferStep::Advance { secs } => set_time(&s.env, s.now().saturating_add(secs)),
+        }
+        check_accounting(&s, &parties, pending(&offered));
+    }
+}
+
+/// A stipend schedule with arbitrary terms, attested and claimed in
+/// arbitrary months
+#[cfg(feature = "stipends")]
+pub fn stipends(input: StipendInput) {
+    use crate::MissedMonthPolicy;
+
+    let s = scenario();
+    let parties = parties(&s);
+    let client = s.client();
+    let attestor = s.school(0);
+    client.set_attestor(&attestor, &true);
+    let policy = if input.defer {
+        MissedMonthPolicy::Defer
+    } else {
+        MissedMonthPolicy::Forfeit
+    };
+    expect_typed(client.try_create_schedule(
+        &s.issuer(0),
+        &s.beneficiary(0),
+        &input.monthly_amount,
+        &input.start_month,
+        &input.months,
+        &String::from_str(&s.env, "Stipend"),
+        &input.expires_at,
+        &policy,
+    ));
+
+    for step in input.steps.iter().take(MAX_STEPS) {
+        let client = s.client();
+        match *step {
+            StipendStep::Attest { month } => {
+                expect_typed(client.try_attest_attendance(&attestor, &s.beneficiary(0), &month));
+            }
+            StipendStep::Claim { schedule_id } => {
+                let before = client.balance(&s.beneficiary(0));
+                if let Some(claimed) =
+                    expect_typed(client.try_claim_tranches(&s.beneficiary(0), &schedule_id))
+                {
+                    assert!(claimed > 0);
+                    assert_eq!(client.balance(&s.beneficiary(0)), before + claimed);
+                }
+            }
+            StipendStep::SetTime { timestamp } => set_time(&s.env, timestamp),
+        }
+        check_accounting(&s, &parties, 0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{RngCore, SeedableRng};
+    use soroban_sdk::testutils::arbitrary::arbitrary::Unstructured;
+
+    // Seeded inputs so the harnesses run in `cargo test` without cargo-fuzz
+    const CASES: u64 = 64;
+    const INPUT_BYTES: usize = 1024;
+
+    fn run<I: for<'a> Arbitrary<'a>>(harness: fn(I)) {
+        for seed in 0..CASES {
+            let mut bytes = [0u8; INPUT_BYTES];
+            StdRng::seed_from_u64(seed).fill_bytes(&mut bytes);
+            // Uniform bytes make every amount and time huge; mask half the
+            // cases down so small values that pass validation come up too
+            if seed % 2 == 1 {
+                bytes.iter_mut().for_each(|byte| *byte &= 0x03);
+            }
+            let input = I::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            harness(input);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_issue_credits() {
+        run(issue_credits);
+        for amount in [i128::MIN, -1, 0, 1, i128::MAX] {
+            for now in [0, u64::MAX] {
+                issue_credits(IssueInput {
+                    now,
+                    amount,
+                    purpose: StdString::new(),
+                    expires_at: u64::MAX - now,
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_transfer() {
+        run(transfer);
+        transfer(TransferInput {
+            grants: [i128::MAX, i128::MAX],
+            expires_at: u64::MAX,
+            steps: std::vec![
+                TransferStep {
+                    from: 1,
+                    to: 2,
+                    amount: i128::MAX,
+                    advance_secs: u32::MAX,
+                },
+                TransferStep {
+                    from: 3,
+                    to: 1,
+                    amount: i128::MIN,
+                    advance_secs: 0,
+                },
+            ],
+        });
+    }
+
+    #[cfg(feature = "acceptance")]
+    #[test]
+    fn test_fuzz_offers() {
+        run(offers);
+        offers(OfferInput {
+            timeout_secs: u64::MAX,
+            steps: std::vec![
+                OfferStep::Offer {
+                    beneficiary: 0,
+                    amount: i128::MAX,
+                    expires_at: u64::MAX,
+                    key: 0,
+                },
+                OfferStep::Advance { secs: u64::MAX },
+                OfferStep::Decline { offer: 0 },
+            ],
+        });
+    }
+
+    #[cfg(feature = "stipends")]
+    #[test]
+    fn test_fuzz_stipends() {
+        use crate::testutils::month_start;
+
+        run(stipends);
+        stipends(StipendInput {
+            monthly_amount: i128::MAX,
+            start_month: 202601,
+            months: 2,
+            defer: true,
+            expires_at: u64::MAX,
+            steps: std::vec![
+                StipendStep::SetTime {
+                    timestamp: month_start(202603),
+                },
+                StipendStep::Attest { month: 202601 },
+                StipendStep::Attest { month: 202602 },
+                StipendStep::Claim { schedule_id: 1 },
+                StipendStep::SetTime {
+                    timestamp: u64::MAX,
+                },
+                StipendStep::Attest { month: 999912 },
+                StipendStep::Claim { schedule_id: 1 },
+            ],
+        });
+    }
+}
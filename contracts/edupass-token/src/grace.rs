@@ -0,0 +1,125 @@
+use soroban_sdk::{contractimpl, contracttype, Env};
+
+use crate::{require_admin, telemetry, Allocation, EduPassToken, EduPassTokenClient, Error};
+
+#[contracttype]
+enum GraceKey {
+    ExpiryGrace, // Time after expiry new allocations can still pay schools
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the grace period given to allocations issued
+    /// from now on (admin only). After its expiry, an allocation with a
+    /// grace period can still pay schools, but not other beneficiaries,
+    /// until the grace period ends; only then can it be swept.
+    pub fn set_expiry_grace(env: Env, grace_secs: Option<u64>) {
+        telemetry::record(&env, "set_expiry_grace");
+        require_admin(&env);
+
+        match grace_secs {
+            Some(grace_secs) => env
+                .storage()
+                .instance()
+                .set(&GraceKey::ExpiryGrace, &grace_secs),
+            None => env.storage().instance().remove(&GraceKey::ExpiryGrace),
+        }
+    }
+
+    /// Get the grace period given to new allocations, if set
+    pub fn expiry_grace(env: Env) -> Option<u64> {
+        env.storage().instance().get(&GraceKey::ExpiryGrace)
+    }
+}
+
+/// Effective expiry of an allocation issued now with `expires_at`: the end
+/// of its grace period, or the expiry itself without one
+pub(crate) fn effective_expiry(env: &Env, expires_at: u64) -> u64 {
+    let grace_secs = EduPassToken::expiry_grace(env.clone()).unwrap_or(0);
+    expires_at.saturating_add(grace_secs)
+}
+
+/// Whether an allocation's credits can be swept
+pub(crate) fn is_expired(env: &Env, allocation: &Allocation) -> bool {
+    env.ledger().timestamp() >= allocation.effective_expires_at
+}
+
+/// Fail with AllocationExpired if an allocation in its grace period would
+/// pay another beneficiary, or one past its grace period would pay anyone.
+/// Allocations issued without a grace period keep paying until swept.
+pub(crate) fn check_payable(
+    env: &Env,
+    allocation: &Option<Allocation>,
+    to_beneficiary: bool,
+) -> Result<(), Error> {
+    let Some(allocation) = allocation else {
+        return Ok(());
+    };
+    if allocation.effective_expires_at == allocation.expires_at
+        || env.ledger().timestamp() < allocation.expires_at
+    {
+        return Ok(());
+    }
+    if to_beneficiary || is_expired(env, allocation) {
+        return Err(Error::AllocationExpired);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+
+    fn setup() -> Scenario {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(2);
+        s.client().set_expiry_grace(&Some(30 * DAY_SECS));
+        s.issue(0, 0, 1000).issue(0, 1, 100)
+    }
+
+    #[test]
+    fn test_grace_period_pays_schools_only() {
+        let s = setup();
+        let client = s.client();
+        let allocation = client.get_allocation(&s.beneficiary(0)).unwrap();
+        assert_eq!(
+            allocation.effective_expires_at,
+            allocation.expires_at + 30 * DAY_SECS
+        );
+
+        advance(&s.env, allocation.expires_at - s.now());
+        let result = client.try_transfer(&s.beneficiary(0), &s.beneficiary(1), &100);
+        assert_eq!(result, Err(Ok(Error::AllocationExpired.into())));
+        let result = client.try_can_transfer(&s.beneficiary(0), &s.beneficiary(1), &100);
+        assert_eq!(result, Err(Ok(Error::AllocationExpired)));
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        let result = client.try_sweep_expired(&s.beneficiary(0));
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+
+        // Past the grace period the sweep policy applies
+        advance(&s.env, 30 * DAY_SECS);
+        let result = client.try_transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::AllocationExpired.into())));
+        assert_eq!(client.sweep_expired(&s.beneficiary(0)), 900);
+    }
+
+    #[test]
+    fn test_allocations_keep_their_issuance_terms() {
+        let s = setup();
+        s.client().set_expiry_grace(&None);
+        assert_eq!(s.client().expiry_grace(), None);
+        let s = s.issue(0, 1, 100);
+        let client = s.client();
+        let allocation = client.get_allocation(&s.beneficiary(1)).unwrap();
+        assert_eq!(allocation.effective_expires_at, allocation.expires_at);
+
+        // Without a grace period, nothing changes at expiry until the sweep
+        advance(&s.env, allocation.expires_at - s.now());
+        client.transfer(&s.beneficiary(1), &s.beneficiary(0), &100);
+        assert_eq!(client.sweep_expired(&s.beneficiary(1)), 100);
+    }
+}
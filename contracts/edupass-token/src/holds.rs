@@ -0,0 +1,257 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Vec};
+
+use crate::{
+    checked_add, demurrage, events, is_beneficiary, rotation, telemetry, transfer_credits,
+    EduPassToken, EduPassTokenClient, Error,
+};
+
+// Bounds the holds summed on every spend from an account
+const MAX_HOLDS: u32 = 10;
+
+#[contracttype]
+enum HoldKey {
+    Hold(u64),      // Credits earmarked for a school until a deadline
+    HoldCount,      // Last assigned hold id
+    Holds(Address), // Ids of a beneficiary's holds, live or lapsed
+}
+
+// Credits a beneficiary has set aside for a school
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Hold {
+    pub beneficiary: Address,
+    pub school: Address,
+    pub amount: i128,
+    pub release_at: u64, // The hold lapses and the credits are spendable again
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Earmark credits for a school until `release_at` (the beneficiary);
+    /// they can't be spent elsewhere meanwhile. Returns the hold id.
+    pub fn place_hold(
+        env: Env,
+        beneficiary: Address,
+        school: Address,
+        amount: i128,
+        release_at: u64,
+    ) -> u64 {
+        telemetry::record(&env, "place_hold");
+        beneficiary.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if beneficiary == school || release_at <= env.ledger().timestamp() {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+
+        // Lapsed holds are dropped to make room
+        let mut ids = Vec::new(&env);
+        for id in hold_ids(&env, &beneficiary).iter() {
+            match live_hold(&env, id) {
+                Some(_) => ids.push_back(id),
+                None => env.storage().persistent().remove(&HoldKey::Hold(id)),
+            }
+        }
+        if ids.len() >= MAX_HOLDS {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        let balance = demurrage::settle(&env, &beneficiary, is_beneficiary(&env, &beneficiary));
+        require_spendable(&env, &beneficiary, balance, amount);
+
+        let id = env
+            .storage()
+            .instance()
+            .get(&HoldKey::HoldCount)
+            .unwrap_or(0u64)
+            + 1;
+        env.storage().instance().set(&HoldKey::HoldCount, &id);
+
+        let hold = Hold {
+            beneficiary: beneficiary.clone(),
+            school: school.clone(),
+            amount,
+            release_at,
+        };
+        env.storage().persistent().set(&HoldKey::Hold(id), &hold);
+        ids.push_back(id);
+        env.storage()
+            .persistent()
+            .set(&HoldKey::Holds(beneficiary.clone()), &ids);
+        events::publish(
+            &env,
+            (symbol_short!("held"), beneficiary, school),
+            (id, amount, release_at),
+        );
+
+        id
+    }
+
+    /// Take the credits under a live hold (its school)
+    pub fn consume_hold(env: Env, school: Address, hold_id: u64) {
+        telemetry::record(&env, "consume_hold");
+        school.require_auth();
+
+        let hold =
+            live_hold(&env, hold_id).unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        if rotation::current(&env, &hold.school) != school {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        // Release the hold first so its credits are spendable
+        remove_hold(&env, &hold.beneficiary, hold_id);
+        transfer_credits(&env, &school, &hold.beneficiary, &school, hold.amount);
+        events::publish(
+            &env,
+            (symbol_short!("holdtaken"), hold.beneficiary, school),
+            (hold_id, hold.amount),
+        );
+    }
+
+    /// Get a hold, if it hasn't been consumed or lapsed
+    pub fn hold(env: Env, hold_id: u64) -> Option<Hold> {
+        live_hold(&env, hold_id)
+    }
+
+    /// Credits of an account under live holds
+    pub fn held_balance(env: Env, account: Address) -> i128 {
+        held(&env, &account)
+    }
+}
+
+/// Credits of an account under live holds
+pub(crate) fn held(env: &Env, account: &Address) -> i128 {
+    // Spares the lookup on every spend until the first hold is placed
+    if !env.storage().instance().has(&HoldKey::HoldCount) {
+        return 0;
+    }
+    let mut total = 0;
+    for id in hold_ids(env, account).iter() {
+        if let Some(hold) = live_hold(env, id) {
+            total = checked_add(env, total, hold.amount);
+        }
+    }
+    total
+}
+
+/// Refuse to spend `amount` of a `balance` the account holds, failing with
+/// InsufficientBalance if it doesn't hold that much and with CreditsOnHold
+/// if holds cover the rest
+pub(crate) fn require_spendable(env: &Env, account: &Address, balance: i128, amount: i128) {
+    if let Err(error) = check_spendable(balance, held(env, account), amount) {
+        panic_with_error!(env, error);
+    }
+}
+
+/// Dry-run form of require_spendable with the held amount already loaded
+pub(crate) fn check_spendable(balance: i128, held: i128, amount: i128) -> Result<(), Error> {
+    if balance < amount {
+        return Err(Error::InsufficientBalance);
+    }
+    if balance - held < amount {
+        return Err(Error::CreditsOnHold);
+    }
+    Ok(())
+}
+
+fn hold_ids(env: &Env, account: &Address) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&HoldKey::Holds(account.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn live_hold(env: &Env, hold_id: u64) -> Option<Hold> {
+    let hold: Hold = env.storage().persistent().get(&HoldKey::Hold(hold_id))?;
+    (env.ledger().timestamp() < hold.release_at).then_some(hold)
+}
+
+fn remove_hold(env: &Env, beneficiary: &Address, hold_id: u64) {
+    let mut ids = hold_ids(env, beneficiary);
+    if let Some(index) = ids.first_index_of(hold_id) {
+        ids.remove(index);
+    }
+    env.storage()
+        .persistent()
+        .set(&HoldKey::Holds(beneficiary.clone()), &ids);
+    env.storage().persistent().remove(&HoldKey::Hold(hold_id));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, event_payload, Scenario, DAY_SECS};
+    use soroban_sdk::testutils::{Address as _, Events};
+    use soroban_sdk::IntoVal;
+
+    fn setup() -> (Scenario, u64) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(2)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let id = s.client().place_hold(
+            &s.beneficiary(0),
+            &s.school(0),
+            &600,
+            &(s.now() + 30 * DAY_SECS),
+        );
+        (s, id)
+    }
+
+    #[test]
+    fn test_held_credits_only_go_to_their_school() {
+        let (s, id) = setup();
+        let client = s.client();
+        assert_eq!(client.held_balance(&s.beneficiary(0)), 600);
+        assert_eq!(client.balance(&s.beneficiary(0)), 1000);
+
+        let result = client.try_transfer(&s.beneficiary(0), &s.school(1), &500);
+        assert_eq!(result, Err(Ok(Error::CreditsOnHold.into())));
+        client.transfer(&s.beneficiary(0), &s.school(1), &400);
+
+        // Another school can't take the hold
+        let result = client.try_consume_hold(&s.school(1), &id);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        client.consume_hold(&s.school(0), &id);
+        assert_eq!(client.balance(&s.school(0)), 600);
+        // The school, not the beneficiary, authorized the move; its
+        // versioned transfer event comes just before holdtaken
+        let all = s.env.events().all();
+        let (_, _, data) = all.get_unchecked(all.len() - 2);
+        let (_, authorizer): (i128, Address) = event_payload(&s.env, &data).into_val(&s.env);
+        assert_eq!(authorizer, s.school(0));
+        assert_eq!(client.held_balance(&s.beneficiary(0)), 0);
+        assert_eq!(client.hold(&id), None);
+    }
+
+    #[test]
+    fn test_holds_lapse_at_their_deadline() {
+        let (s, id) = setup();
+        let client = s.client();
+        advance(&s.env, 30 * DAY_SECS);
+
+        assert_eq!(client.held_balance(&s.beneficiary(0)), 0);
+        let result = client.try_consume_hold(&s.school(0), &id);
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+        client.transfer(&s.beneficiary(0), &s.school(1), &1000);
+    }
+
+    #[test]
+    fn test_rotated_school_takes_hold_under_new_key_only() {
+        let (s, id) = setup();
+        let client = s.client();
+        client.set_registry_manager(&Some(Address::generate(&s.env)));
+        let new = Address::generate(&s.env);
+        client.rotate_address(&s.school(0), &new);
+
+        // The rotated-away key no longer speaks for the school
+        let result = client.try_consume_hold(&s.school(0), &id);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        client.consume_hold(&new, &id);
+        assert_eq!(client.balance(&new), 600);
+        assert_eq!(client.balance(&s.school(0)), 0);
+    }
+}
@@ -0,0 +1,297 @@
+// Households linking beneficiaries, for programs that cap support per
+// household rather than per child. An approved verifier creates a
+// household and attests each member's place in it; a beneficiary belongs
+// to at most one. Credits issued or granted to any member count toward the
+// household's total, and the admin's household cap bounds that total.
+// Deployments without households skip all of this.
+
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Vec};
+
+use crate::{
+    checked_add, demurrage, events, require_admin, telemetry, EduPassToken, EduPassTokenClient,
+    Error,
+};
+
+// Members of one household
+const MAX_MEMBERS: u32 = 16;
+
+#[contracttype]
+enum HouseholdKey {
+    Household(u64),    // A household's members and the credits issued to them
+    HouseholdCount,    // Last assigned household id
+    MemberOf(Address), // Household a beneficiary belongs to
+    HouseholdCap,      // Most credits issued to one household (absent when uncapped)
+}
+
+// Beneficiaries supported as one household
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Household {
+    pub verifier: Address, // Verifier that created it and attests its members
+    pub members: Vec<Address>,
+    pub issued: i128, // Credits issued or granted to members while they belonged
+}
+
+// A household's support at a glance
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HouseholdReport {
+    pub members: u32,
+    pub issued: i128,
+    pub balance: i128,  // Members' current balances combined
+    pub headroom: i128, // Credits still issuable under the cap (-1 when uncapped)
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Cap (Some) or uncap (None) the credits issued to one household
+    /// (admin only)
+    pub fn set_household_cap(env: Env, cap: Option<i128>) {
+        telemetry::record(&env, "set_household_cap");
+        require_admin(&env);
+
+        match cap {
+            Some(cap) => {
+                if cap <= 0 {
+                    panic_with_error!(&env, Error::InvalidAmount);
+                }
+                env.storage()
+                    .instance()
+                    .set(&HouseholdKey::HouseholdCap, &cap);
+            }
+            None => env.storage().instance().remove(&HouseholdKey::HouseholdCap),
+        }
+    }
+
+    /// Get the household cap, if set
+    pub fn household_cap(env: Env) -> Option<i128> {
+        env.storage().instance().get(&HouseholdKey::HouseholdCap)
+    }
+
+    /// Create an empty household (an approved verifier); returns its id
+    pub fn create_household(env: Env, verifier: Address) -> u64 {
+        telemetry::record(&env, "create_household");
+        require_verifier(&env, &verifier);
+
+        let id = household_count(&env) + 1;
+        env.storage()
+            .instance()
+            .set(&HouseholdKey::HouseholdCount, &id);
+        write_household(
+            &env,
+            id,
+            &Household {
+                verifier: verifier.clone(),
+                members: Vec::new(&env),
+                issued: 0,
+            },
+        );
+        events::publish(&env, (symbol_short!("household"), id), verifier);
+
+        id
+    }
+
+    /// Attest that a beneficiary belongs to a household (the household's
+    /// verifier). Credits issued to the beneficiary from now on count
+    /// toward the household.
+    pub fn add_member(env: Env, verifier: Address, household_id: u64, beneficiary: Address) {
+        telemetry::record(&env, "add_member");
+        let mut household = verified_household(&env, &verifier, household_id);
+        if Self::household_of(env.clone(), beneficiary.clone()).is_some() {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        if household.members.len() >= MAX_MEMBERS {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+
+        household.members.push_back(beneficiary.clone());
+        write_household(&env, household_id, &household);
+        env.storage()
+            .persistent()
+            .set(&HouseholdKey::MemberOf(beneficiary.clone()), &household_id);
+        events::publish(&env, (symbol_short!("hhjoin"), household_id), beneficiary);
+    }
+
+    /// Remove a beneficiary from a household (the household's verifier).
+    /// What it was issued stays counted.
+    pub fn remove_member(env: Env, verifier: Address, household_id: u64, beneficiary: Address) {
+        telemetry::record(&env, "remove_member");
+        let mut household = verified_household(&env, &verifier, household_id);
+        let Some(index) = household.members.first_index_of(&beneficiary) else {
+            panic_with_error!(&env, Error::NotFound);
+        };
+
+        household.members.remove(index);
+        write_household(&env, household_id, &household);
+        env.storage()
+            .persistent()
+            .remove(&HouseholdKey::MemberOf(beneficiary.clone()));
+        events::publish(&env, (symbol_short!("hhleave"), household_id), beneficiary);
+    }
+
+    /// Get a household
+    pub fn household(env: Env, household_id: u64) -> Option<Household> {
+        env.storage()
+            .persistent()
+            .get(&HouseholdKey::Household(household_id))
+    }
+
+    /// Get the household a beneficiary belongs to, if any
+    pub fn household_of(env: Env, beneficiary: Address) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&HouseholdKey::MemberOf(beneficiary))
+    }
+
+    /// Report a household's members, what they were issued and hold, and
+    /// the headroom left under the cap
+    pub fn household_report(env: Env, household_id: u64) -> HouseholdReport {
+        let household = read_household(&env, household_id);
+        let mut balance = 0;
+        for member in household.members.iter() {
+            balance = checked_add(&env, balance, demurrage::net_balance(&env, &member));
+        }
+        let headroom =
+            Self::household_cap(env.clone()).map_or(-1, |cap| (cap - household.issued).max(0));
+        HouseholdReport {
+            members: household.members.len(),
+            issued: household.issued,
+            balance,
+            headroom,
+        }
+    }
+}
+
+/// Fail with BalanceCapExceeded if issuing `amount` to `beneficiary` would
+/// take its household past the cap
+pub(crate) fn check_cap(env: &Env, beneficiary: &Address, amount: i128) -> Result<(), Error> {
+    let Some(cap) = EduPassToken::household_cap(env.clone()) else {
+        return Ok(());
+    };
+    let Some((_, household)) = membership(env, beneficiary) else {
+        return Ok(());
+    };
+    let issued = household
+        .issued
+        .checked_add(amount)
+        .ok_or(Error::Overflow)?;
+    if issued > cap {
+        return Err(Error::BalanceCapExceeded);
+    }
+    Ok(())
+}
+
+/// Count credits issued to `beneficiary` toward its household
+pub(crate) fn record_issued(env: &Env, beneficiary: &Address, amount: i128) {
+    if let Some((id, mut household)) = membership(env, beneficiary) {
+        household.issued = checked_add(env, household.issued, amount);
+        write_household(env, id, &household);
+    }
+}
+
+// A beneficiary's household, read only once any household exists so
+// deployments without them keep their issuance footprint
+fn membership(env: &Env, beneficiary: &Address) -> Option<(u64, Household)> {
+    if household_count(env) == 0 {
+        return None;
+    }
+    let id = EduPassToken::household_of(env.clone(), beneficiary.clone())?;
+    Some((id, read_household(env, id)))
+}
+
+fn require_verifier(env: &Env, verifier: &Address) {
+    verifier.require_auth();
+    if !EduPassToken::is_verifier(env.clone(), verifier.clone()) {
+        panic_with_error!(env, Error::Unauthorized);
+    }
+}
+
+// A household, once its own verifier has authorized the change
+fn verified_household(env: &Env, verifier: &Address, household_id: u64) -> Household {
+    require_verifier(env, verifier);
+    let household = read_household(env, household_id);
+    if household.verifier != *verifier {
+        panic_with_error!(env, Error::Unauthorized);
+    }
+    household
+}
+
+fn household_count(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&HouseholdKey::HouseholdCount)
+        .unwrap_or(0)
+}
+
+fn read_household(env: &Env, household_id: u64) -> Household {
+    EduPassToken::household(env.clone(), household_id)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound))
+}
+
+fn write_household(env: &Env, household_id: u64, household: &Household) {
+    env.storage()
+        .persistent()
+        .set(&HouseholdKey::Household(household_id), household);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::String;
+
+    #[test]
+    fn test_household_cap_spans_its_members() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(3);
+        let client = s.client();
+        let verifier = Address::generate(&s.env);
+        client.approve_verifier(&verifier, &true);
+        client.set_household_cap(&Some(1000));
+        let id = client.create_household(&verifier);
+        client.add_member(&verifier, &id, &s.beneficiary(0));
+        assert_eq!(s.env.auths()[0].0, verifier);
+        client.add_member(&verifier, &id, &s.beneficiary(1));
+        let result = client.try_add_member(&verifier, &id, &s.beneficiary(0));
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+
+        let purpose = String::from_str(&s.env, "Tuition");
+        let issue = |beneficiary: &Address, amount: i128| {
+            client.try_issue_credits(
+                &s.issuer(0),
+                beneficiary,
+                &amount,
+                &purpose,
+                &s.expires_at(),
+            )
+        };
+        assert!(issue(&s.beneficiary(0), 600).is_ok());
+        assert_eq!(
+            issue(&s.beneficiary(1), 401),
+            Err(Ok(Error::BalanceCapExceeded.into()))
+        );
+        let result = client.try_can_issue(&s.issuer(0), &s.beneficiary(1), &401, &purpose);
+        assert_eq!(result, Err(Ok(Error::BalanceCapExceeded)));
+        assert!(issue(&s.beneficiary(1), 400).is_ok());
+        // Beneficiaries outside the household aren't affected
+        assert!(issue(&s.beneficiary(2), 2000).is_ok());
+
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(
+            client.household_report(&id),
+            HouseholdReport {
+                members: 2,
+                issued: 1000,
+                balance: 900,
+                headroom: 0,
+            }
+        );
+        client.remove_member(&verifier, &id, &s.beneficiary(1));
+        assert_eq!(client.household_of(&s.beneficiary(1)), None);
+        assert_eq!(client.household_report(&id).issued, 1000);
+    }
+}
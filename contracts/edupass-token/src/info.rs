@@ -0,0 +1,172 @@
+use soroban_sdk::{contractimpl, contracttype, Address, Env, String, Symbol, Vec};
+
+use crate::{DataKey, EduPassToken, EduPassTokenClient};
+
+// What a deployment was built with and how it is configured
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractInfo {
+    pub version: String,             // Crate version the wasm was built from
+    pub event_schema: u32,           // Schema version carried by emitted events
+    pub instance_id: Option<Symbol>, // Deployment name carried by emitted events
+    pub features: Vec<Symbol>,       // Optional subsystems compiled in
+    pub enabled: Vec<Symbol>,        // Opt-in subsystems turned on by the admin
+    pub admin: Option<Address>,
+    pub governance: Option<Address>,
+    pub compliance: Option<Address>,
+    pub treasurer: Option<Address>,
+    pub reporter: Option<Address>,
+    pub program_manager: Option<Address>,
+    pub appellate: Option<Address>,
+    pub registry_manager: Option<Address>,
+    pub proof_verifier: Option<Address>, // Always None without the confidential feature
+    pub bridge_relayer: Option<Address>,
+    pub wrap_asset: Option<Address>,
+    pub bond_asset: Option<Address>,
+    pub settlement_asset: Option<Address>,
+    pub min_transfer: Option<i128>,
+    pub max_balance: Option<i128>, // Global per-beneficiary holding cap
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Describe this deployment: version, compiled features, opt-in
+    /// subsystems, configured addresses and limits
+    pub fn info(env: Env) -> ContractInfo {
+        let mut features = Vec::new(&env);
+        for (name, compiled) in [
+            ("acceptance", cfg!(feature = "acceptance")),
+            ("confidential", cfg!(feature = "confidential")),
+            ("disputes", cfg!(feature = "disputes")),
+            ("escrow", cfg!(feature = "escrow")),
+            ("exams", cfg!(feature = "exams")),
+            ("stipends", cfg!(feature = "stipends")),
+            ("telemetry", cfg!(feature = "telemetry")),
+        ] {
+            if compiled {
+                features.push_back(Symbol::new(&env, name));
+            }
+        }
+
+        let bond_config = Self::bond_config(env.clone());
+        let settlement_config = Self::settlement_config(env.clone());
+        let mut enabled = Vec::new(&env);
+        for (name, on) in [
+            ("bonding", bond_config.is_some()),
+            ("demurrage", Self::demurrage_config(env.clone()).is_some()),
+            ("disputes", disputes_enabled(&env)),
+            ("insurance", insurance_enabled(&env)),
+            ("referrals", Self::referral_config(env.clone()).is_some()),
+            ("registry", Self::registry_config(env.clone()).is_some()),
+            ("reputation", Self::reputation_config(env.clone()).is_some()),
+            ("screening", Self::screening_contract(env.clone()).is_some()),
+            ("settlement", settlement_config.is_some()),
+        ] {
+            if on {
+                enabled.push_back(Symbol::new(&env, name));
+            }
+        }
+
+        #[cfg(feature = "confidential")]
+        let proof_verifier = Self::proof_verifier(env.clone());
+        #[cfg(not(feature = "confidential"))]
+        let proof_verifier = None;
+        #[cfg(feature = "disputes")]
+        let appellate = Self::appellate(env.clone());
+        #[cfg(not(feature = "disputes"))]
+        let appellate = None;
+
+        ContractInfo {
+            version: String::from_str(&env, env!("CARGO_PKG_VERSION")),
+            event_schema: Self::event_schema_version(env.clone()),
+            instance_id: Self::instance_id(env.clone()),
+            features,
+            enabled,
+            admin: env.storage().instance().get(&DataKey::Admin),
+            governance: Self::governance(env.clone()),
+            compliance: Self::compliance(env.clone()),
+            treasurer: Self::treasurer(env.clone()),
+            reporter: Self::reporter(env.clone()),
+            program_manager: Self::program_manager(env.clone()),
+            appellate,
+            registry_manager: Self::registry_manager(env.clone()),
+            proof_verifier,
+            bridge_relayer: Self::bridge_relayer(env.clone()),
+            wrap_asset: Self::wrap_asset(env.clone()),
+            bond_asset: bond_config.map(|config| config.asset),
+            settlement_asset: settlement_config.map(|config| config.asset),
+            min_transfer: Self::min_transfer(env.clone()),
+            max_balance: Self::max_balance_per_beneficiary(env),
+        }
+    }
+}
+
+#[cfg(feature = "disputes")]
+fn disputes_enabled(env: &Env) -> bool {
+    EduPassToken::dispute_config(env.clone()).is_some()
+}
+
+#[cfg(feature = "disputes")]
+fn insurance_enabled(env: &Env) -> bool {
+    EduPassToken::insurance_share(env.clone()).is_some()
+}
+
+#[cfg(not(feature = "disputes"))]
+fn disputes_enabled(_env: &Env) -> bool {
+    false
+}
+
+#[cfg(not(feature = "disputes"))]
+fn insurance_enabled(_env: &Env) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use crate::{DemurrageConfig, SettlementConfig};
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_info_reflects_configuration() {
+        let s = Scenario::new().with_admin();
+        let client = s.client();
+
+        let info = client.info();
+        assert_eq!(info.version, String::from_str(&s.env, "0.1.0"));
+        assert_eq!(info.admin, Some(s.admin()));
+        assert_eq!(
+            info.features.contains(Symbol::new(&s.env, "acceptance")),
+            cfg!(feature = "acceptance")
+        );
+        assert!(info.enabled.is_empty());
+        assert_eq!(info.settlement_asset, None);
+        assert_eq!(info.min_transfer, None);
+
+        let asset = Address::generate(&s.env);
+        client.set_settlement_config(&Some(SettlementConfig {
+            treasurer: Address::generate(&s.env),
+            asset: asset.clone(),
+        }));
+        client.set_demurrage(&Some(DemurrageConfig {
+            rate_bps: 100,
+            epoch_secs: 30 * 24 * 60 * 60,
+        }));
+        client.set_min_transfer(&Some(10));
+
+        let info = client.info();
+        assert_eq!(
+            info.enabled,
+            Vec::from_array(
+                &s.env,
+                [
+                    Symbol::new(&s.env, "demurrage"),
+                    Symbol::new(&s.env, "settlement")
+                ]
+            )
+        );
+        assert_eq!(info.settlement_asset, Some(asset));
+        assert_eq!(info.min_transfer, Some(10));
+    }
+}
@@ -0,0 +1,218 @@
+// Insurance for disputes a beneficiary wins after the school was already
+// paid. The admin sets the share of every fee the contract collects (dust
+// sweeps and instant settlement haircuts) that goes into the insurance
+// bucket instead of the treasury. When a dispute over a redemption receipt
+// resolves against the school, the bucket pays the beneficiary back the
+// receipt's amount, or as much of it as the bucket holds, and keeps a
+// record of the payout and any shortfall.
+
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, symbol_short, Address, Env};
+
+use crate::{
+    checked_add, credit, events,
+    math::{self, Rounding},
+    require_admin, telemetry, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum InsuranceKey {
+    FeeShare,             // Bps of collected fees the bucket takes (absent when none)
+    InsuranceBucket,      // Credits held to make beneficiaries whole
+    InsurancePayout(u64), // A payout made under a dispute
+    PayoutCount,          // Last assigned payout id
+}
+
+// A beneficiary made whole after a dispute over a paid redemption
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsurancePayout {
+    pub case_id: u64, // The dispute that ordered it
+    pub receipt_id: u64,
+    pub beneficiary: Address,
+    pub paid: i128,
+    pub shortfall: i128, // Part of the receipt the bucket couldn't cover
+    pub paid_at: u64,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the share of collected fees, in bps, that goes
+    /// into the insurance bucket (admin only)
+    pub fn set_insurance_share(env: Env, share_bps: Option<u32>) {
+        telemetry::record(&env, "set_insurance_share");
+        require_admin(&env);
+
+        match share_bps {
+            Some(share_bps) => {
+                if share_bps == 0 || share_bps > 10_000 {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage()
+                    .instance()
+                    .set(&InsuranceKey::FeeShare, &share_bps);
+            }
+            None => env.storage().instance().remove(&InsuranceKey::FeeShare),
+        }
+    }
+
+    /// Get the insurance bucket's share of collected fees, if set
+    pub fn insurance_share(env: Env) -> Option<u32> {
+        env.storage().instance().get(&InsuranceKey::FeeShare)
+    }
+
+    /// Get credits held in the insurance bucket
+    pub fn insurance_balance(env: Env) -> i128 {
+        bucket_balance(&env)
+    }
+
+    /// Get a payout made from the insurance bucket
+    pub fn insurance_payout(env: Env, payout_id: u64) -> Option<InsurancePayout> {
+        env.storage()
+            .persistent()
+            .get(&InsuranceKey::InsurancePayout(payout_id))
+    }
+
+    /// Get the number of payouts made from the insurance bucket (the last
+    /// payout id)
+    pub fn insurance_payout_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&InsuranceKey::PayoutCount)
+            .unwrap_or(0)
+    }
+}
+
+/// Move the bucket's share of a collected fee into it; returns the share.
+/// The rest of the fee is the treasury's.
+pub(crate) fn take_share(env: &Env, fee: i128) -> i128 {
+    let Some(share_bps) = EduPassToken::insurance_share(env.clone()) else {
+        return 0;
+    };
+    let share = math::mul_div(env, fee, share_bps as i128, 10_000, Rounding::Down);
+    if share > 0 {
+        set_bucket_balance(env, checked_add(env, bucket_balance(env), share));
+    }
+    share
+}
+
+/// Pay a beneficiary back a disputed receipt's amount from the bucket, as
+/// far as it holds enough; returns the amount paid
+pub(crate) fn cover(
+    env: &Env,
+    case_id: u64,
+    receipt_id: u64,
+    beneficiary: &Address,
+    amount: i128,
+) -> i128 {
+    let balance = bucket_balance(env);
+    let paid = amount.min(balance);
+    set_bucket_balance(env, balance - paid);
+    if paid > 0 {
+        credit(env, beneficiary, paid);
+    }
+
+    let id = EduPassToken::insurance_payout_count(env.clone()) + 1;
+    env.storage()
+        .instance()
+        .set(&InsuranceKey::PayoutCount, &id);
+    env.storage().persistent().set(
+        &InsuranceKey::InsurancePayout(id),
+        &InsurancePayout {
+            case_id,
+            receipt_id,
+            beneficiary: beneficiary.clone(),
+            paid,
+            shortfall: amount - paid,
+            paid_at: env.ledger().timestamp(),
+        },
+    );
+    events::publish(
+        env,
+        (symbol_short!("inspayout"), id, beneficiary.clone()),
+        (case_id, paid, amount - paid),
+    );
+
+    paid
+}
+
+pub(crate) fn bucket_balance(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&InsuranceKey::InsuranceBucket)
+        .unwrap_or(0)
+}
+
+fn set_bucket_balance(env: &Env, balance: i128) {
+    env.storage()
+        .instance()
+        .set(&InsuranceKey::InsuranceBucket, &balance);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{Scenario, DAY_SECS};
+    use crate::{reputation, DisputeConfig, DisputeOutcome, ReputationConfig};
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_bucket_covers_disputed_redemption() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(2)
+            .issue(0, 0, 10)
+            .issue(0, 1, 9);
+        let client = s.client();
+        client.set_reputation_config(&Some(ReputationConfig {
+            settlement_window_secs: 7 * DAY_SECS,
+            large_redemption: 10_000,
+            min_score: 0,
+        }));
+        client.set_dispute_config(&Some(DisputeConfig {
+            voting_secs: 7 * DAY_SECS,
+            default_outcome: DisputeOutcome::ToSchool,
+            appeal_secs: 0,
+        }));
+        client.set_insurance_share(&Some(5_000));
+        client.set_min_transfer(&Some(10));
+
+        // Half of each fee, rounded down, goes to the bucket
+        client.sweep_dust(&s.beneficiary(1));
+        assert_eq!(client.insurance_balance(), 4);
+        assert_eq!(client.treasury_balance(), 5);
+
+        client.transfer(&s.beneficiary(0), &s.school(0), &10);
+        let receipt_id = s
+            .env
+            .as_contract(&s.contract_id, || reputation::receipt_count(&s.env));
+        let result = client.try_dispute_receipt(&s.beneficiary(1), &receipt_id);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        let case_id = client.dispute_receipt(&s.beneficiary(0), &receipt_id);
+        let arbiter = Address::generate(&s.env);
+        client.set_arbiter(&arbiter, &true);
+        client.assign_arbiter(&case_id, &arbiter);
+        client.vote_dispute(&arbiter, &case_id, &DisputeOutcome::ToIssuer);
+
+        // The school keeps its payment; the bucket pays what it holds
+        assert_eq!(client.balance(&s.school(0)), 10);
+        assert_eq!(client.balance(&s.beneficiary(0)), 4);
+        assert_eq!(client.insurance_balance(), 0);
+        assert_eq!(client.insurance_payout_count(), 1);
+        assert_eq!(
+            client.insurance_payout(&1),
+            Some(InsurancePayout {
+                case_id,
+                receipt_id,
+                beneficiary: s.beneficiary(0),
+                paid: 4,
+                shortfall: 6,
+                paid_at: s.now(),
+            })
+        );
+
+        let result = client.try_dispute_receipt(&s.beneficiary(0), &receipt_id);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+}
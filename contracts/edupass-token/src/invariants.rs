@@ -0,0 +1,139 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, Address, Env, Vec};
+
+use crate::{
+    bonus, bridge, commitments, community, demurrage, dust, referrals, total_burned, total_issued,
+    wrap, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[cfg(feature = "confidential")]
+use crate::confidential;
+#[cfg(feature = "escrow")]
+use crate::escrow;
+#[cfg(feature = "disputes")]
+use crate::insurance;
+
+// Bounds the work done by one check_invariants call
+const MAX_INVARIANT_SAMPLE: u32 = 100;
+
+// Result of a self-audit over the global counters and a sample of accounts
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvariantReport {
+    pub ok: bool,
+    pub negative_balances: Vec<Address>, // Sampled accounts with a stored balance below zero
+    pub outstanding: i128,               // Issued less burned and demurraged
+    pub held_in_pools: i128,             // Pools, budgets, escrow and unbound commitments
+    pub sampled_total: i128,             // Sum of sampled stored balances
+    pub supply_consistent: bool,         // Pools plus sampled balances fit in outstanding
+    pub reserve_backed: bool,            // Wrap asset held covers the wrap reserve
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Check contract invariants against up to 100 sampled accounts; a
+    /// read-only health check for monitoring and tests
+    pub fn check_invariants(env: Env, sample: Vec<Address>) -> InvariantReport {
+        if sample.len() > MAX_INVARIANT_SAMPLE {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+
+        let mut negative_balances = Vec::new(&env);
+        let mut seen = Vec::new(&env);
+        let mut sampled_total = 0i128;
+        for account in sample.iter() {
+            if seen.contains(&account) {
+                continue;
+            }
+            let balance = demurrage::stored_balance(&env, &account);
+            if balance < 0 {
+                negative_balances.push_back(account.clone());
+            }
+            sampled_total = sampled_total.saturating_add(balance);
+            seen.push_back(account);
+        }
+
+        let outstanding = total_issued(&env)
+            .saturating_sub(total_burned(&env))
+            .saturating_sub(demurrage::total_demurraged(&env));
+        let held_in_pools = bonus::pool_balance(&env)
+            .saturating_add(community::pool_balance(&env))
+            .saturating_add(dust::dust_balance(&env))
+            .saturating_add(referrals::budget_balance(&env))
+            .saturating_add(bridge::escrow_balance(&env))
+            .saturating_add(commitments::committed_total(&env));
+        #[cfg(feature = "escrow")]
+        let held_in_pools = held_in_pools.saturating_add(escrow::escrowed_total(&env));
+        #[cfg(feature = "disputes")]
+        let held_in_pools = held_in_pools.saturating_add(insurance::bucket_balance(&env));
+        #[cfg(feature = "confidential")]
+        let held_in_pools = held_in_pools.saturating_add(confidential::supply(&env));
+
+        let supply_consistent =
+            outstanding >= 0 && held_in_pools.saturating_add(sampled_total) <= outstanding;
+
+        let reserve_backed = wrap::reserve_backed(&env);
+
+        InvariantReport {
+            ok: negative_balances.is_empty() && supply_consistent && reserve_backed,
+            negative_balances,
+            outstanding,
+            held_in_pools,
+            sampled_total,
+            supply_consistent,
+            reserve_backed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ExpiryPolicy;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::{vec, String};
+
+    #[test]
+    fn test_invariants_hold_across_flows() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_700_000_000);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let other = Address::generate(&env);
+        let school = Address::generate(&env);
+        let purpose = String::from_str(&env, "Tuition");
+        let sample = vec![
+            &env,
+            issuer.clone(),
+            beneficiary.clone(),
+            other.clone(),
+            school.clone(),
+        ];
+
+        client.initialize(&Address::generate(&env));
+        client.set_expiry_policy(&ExpiryPolicy::Escheat);
+        client.fund_bonus_pool(&issuer, &500);
+        client.issue_credits(&issuer, &beneficiary, &1000, &purpose, &1_700_000_100);
+        client.issue_credits(&issuer, &other, &300, &purpose, &1_800_000_000);
+        client.transfer(&beneficiary, &school, &400);
+        client.burn(&school, &150);
+
+        let report = client.check_invariants(&sample);
+        assert!(report.ok);
+        assert_eq!(report.outstanding, 1650);
+        assert_eq!(report.held_in_pools, 500);
+        assert_eq!(report.sampled_total, 1150);
+
+        env.ledger().set_timestamp(1_700_000_100);
+        client.sweep_expired(&beneficiary);
+
+        let report = client.check_invariants(&sample);
+        assert!(report.ok);
+        assert_eq!(report.held_in_pools, 1100);
+        assert_eq!(report.sampled_total, 550);
+    }
+}
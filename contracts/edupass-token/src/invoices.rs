@@ -0,0 +1,477 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, BytesN, Env, Symbol,
+};
+
+use crate::{
+    burn_credits, checked_add, events,
+    math::{self, Rounding, BPS},
+    move_credits, require_admin, rotation, telemetry, transfer_credits, Allocation, DataKey,
+    EduPassToken, EduPassTokenClient, Error,
+};
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+#[contracttype]
+enum InvoiceKey {
+    Invoice(Address, BytesN<32>), // Payment a school received against one of its invoices
+    LateFeePolicy(Address),       // Late-fee terms a school bills under
+    LateFeeCap(Symbol), // Most a program's beneficiaries can be charged, in bps of the bill
+}
+
+// Late-fee terms: a daily rate on the unpaid bill once the grace period
+// after the due date has passed
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LateFeePolicy {
+    pub grace_secs: u64,
+    pub daily_rate_bps: u32, // Charged per day late, prorated by the second
+}
+
+// A beneficiary's payment against a school invoice, and how it was settled
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Invoice {
+    pub payer: Address,
+    pub billed: i128, // Amount the school billed (0 for unbilled payments)
+    pub due_at: u64,  // 0 for unbilled payments
+    pub late_fees: LateFeePolicy, // The school's terms when it billed
+    pub late_fee: i128, // Late fee accrued up to accrued_to
+    pub accrued_to: u64,
+    pub paid: i128,
+    pub redeemed: i128,       // Amount the school burned (0 until redeemed)
+    pub change: i128,         // Overage returned to the payer
+    pub redeemed_at: u64,     // 0 until redeemed
+    pub doc_hash: BytesN<32>, // Hash of the scanned invoice given at redemption (zero if none)
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the late-fee terms a school bills under (the
+    /// school). Invoices keep the terms they were billed under.
+    pub fn set_late_fee_policy(env: Env, school: Address, policy: Option<LateFeePolicy>) {
+        telemetry::record(&env, "set_late_fee_policy");
+        school.require_auth();
+
+        let key = InvoiceKey::LateFeePolicy(school);
+        match policy {
+            Some(policy) => {
+                if policy.daily_rate_bps as i128 > BPS {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage().persistent().set(&key, &policy);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Get a school's late-fee terms, if set
+    pub fn late_fee_policy(env: Env, school: Address) -> Option<LateFeePolicy> {
+        env.storage()
+            .persistent()
+            .get(&InvoiceKey::LateFeePolicy(school))
+    }
+
+    /// Cap (Some, in bps of the bill) or uncap (None) the late fees a
+    /// program's beneficiaries can be charged (admin only). Fees never
+    /// exceed the bill itself.
+    pub fn set_program_late_fee_cap(env: Env, program_id: Symbol, cap_bps: Option<u32>) {
+        telemetry::record(&env, "set_program_late_fee_cap");
+        require_admin(&env);
+
+        let key = InvoiceKey::LateFeeCap(program_id);
+        match cap_bps {
+            Some(cap_bps) => {
+                if cap_bps as i128 > BPS {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage().persistent().set(&key, &cap_bps);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Get a program's late-fee cap, if set
+    pub fn program_late_fee_cap(env: Env, program_id: Symbol) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get(&InvoiceKey::LateFeeCap(program_id))
+    }
+
+    /// Bill a beneficiary under the school's current late-fee terms (the
+    /// school)
+    pub fn bill_invoice(
+        env: Env,
+        school: Address,
+        invoice_ref: BytesN<32>,
+        beneficiary: Address,
+        amount: i128,
+        due_at: u64,
+    ) {
+        telemetry::record(&env, "bill_invoice");
+        school.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if due_at <= env.ledger().timestamp() {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        let key = invoice_key(&env, &school, &invoice_ref);
+        if env.storage().persistent().has(&key) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+
+        let invoice = Invoice {
+            late_fees: Self::late_fee_policy(env.clone(), school.clone()).unwrap_or_default(),
+            billed: amount,
+            due_at,
+            ..unbilled(&env, &beneficiary)
+        };
+        env.storage().persistent().set(&key, &invoice);
+        events::publish(
+            &env,
+            (symbol_short!("billed"), school, beneficiary),
+            (invoice_ref, amount, due_at),
+        );
+    }
+
+    /// Get what a beneficiary still owes on an invoice if paid now,
+    /// including late fees
+    pub fn invoice_due(env: Env, school: Address, invoice_ref: BytesN<32>) -> i128 {
+        let invoice: Invoice = env
+            .storage()
+            .persistent()
+            .get(&invoice_key(&env, &school, &invoice_ref))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        let owed = checked_add(&env, invoice.billed, accrued_fee(&env, &invoice));
+        (owed - invoice.paid).max(0)
+    }
+
+    /// Pay credits to a school against one of its invoices, identified by
+    /// the hash of the school's invoice reference. Repeat payments by the
+    /// same beneficiary add up.
+    pub fn pay_invoice(
+        env: Env,
+        beneficiary: Address,
+        school: Address,
+        invoice_ref: BytesN<32>,
+        amount: i128,
+    ) {
+        telemetry::record(&env, "pay_invoice");
+        beneficiary.require_auth();
+
+        let key = invoice_key(&env, &school, &invoice_ref);
+        let mut invoice = match env.storage().persistent().get::<_, Invoice>(&key) {
+            Some(invoice) if invoice.payer != beneficiary || invoice.redeemed_at != 0 => {
+                panic_with_error!(&env, Error::AlreadyExists)
+            }
+            Some(invoice) => invoice,
+            None => unbilled(&env, &beneficiary),
+        };
+
+        // Fees accrue on the bill as it stood up to this payment
+        let late_fee = accrued_fee(&env, &invoice);
+        if late_fee > invoice.late_fee {
+            events::publish(
+                &env,
+                (
+                    symbol_short!("latefee"),
+                    school.clone(),
+                    beneficiary.clone(),
+                ),
+                (invoice_ref.clone(), late_fee),
+            );
+        }
+        invoice.late_fee = late_fee;
+        invoice.accrued_to = env.ledger().timestamp();
+        invoice.paid = checked_add(&env, invoice.paid, amount);
+
+        transfer_credits(&env, &beneficiary, &beneficiary, &school, amount);
+        env.storage().persistent().set(&key, &invoice);
+        events::publish(
+            &env,
+            (symbol_short!("invpaid"), beneficiary, school),
+            (invoice_ref, amount),
+        );
+    }
+
+    /// Redeem exactly `amount` of an invoice's payment (the school) and
+    /// return the overage to the payer, optionally recording the hash of
+    /// the scanned invoice. The change doesn't count as a refund against
+    /// the school's reputation.
+    pub fn redeem_exact(
+        env: Env,
+        school: Address,
+        invoice_ref: BytesN<32>,
+        amount: i128,
+        overpayer: Address,
+        doc_hash: Option<BytesN<32>>,
+    ) {
+        telemetry::record(&env, "redeem_exact");
+        school.require_auth();
+
+        let key = invoice_key(&env, &school, &invoice_ref);
+        let mut invoice: Invoice = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        if invoice.payer != overpayer {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if invoice.redeemed_at != 0 {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        if amount <= 0 || amount > invoice.paid {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        let change = invoice.paid - amount;
+        if change > 0 {
+            move_credits(&env, &school, &school, &overpayer, change, false, None);
+        }
+        burn_credits(&env, &school, &school, amount);
+
+        invoice.redeemed = amount;
+        invoice.change = change;
+        invoice.redeemed_at = env.ledger().timestamp();
+        if let Some(doc_hash) = doc_hash {
+            invoice.doc_hash = doc_hash;
+        }
+        env.storage().persistent().set(&key, &invoice);
+        events::publish(
+            &env,
+            (symbol_short!("redeemed"), school, overpayer),
+            (invoice_ref, amount, change),
+        );
+    }
+
+    /// Get the payment a school received against an invoice
+    pub fn invoice(env: Env, school: Address, invoice_ref: BytesN<32>) -> Option<Invoice> {
+        env.storage()
+            .persistent()
+            .get(&invoice_key(&env, &school, &invoice_ref))
+    }
+}
+
+/// Move a school's late-fee terms to its new key. Its invoices can't be
+/// enumerated, so they stay under the old key and are found from the new
+/// one.
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &InvoiceKey::LateFeePolicy(old.clone()),
+        &InvoiceKey::LateFeePolicy(new.clone()),
+    );
+}
+
+// Where a school's invoice is kept, under its current key or one it
+// rotated from
+fn invoice_key(env: &Env, school: &Address, invoice_ref: &BytesN<32>) -> InvoiceKey {
+    let key = |school: &Address| InvoiceKey::Invoice(school.clone(), invoice_ref.clone());
+    let holder = rotation::holder(env, school, |school| {
+        env.storage().persistent().has(&key(school))
+    });
+    key(&holder)
+}
+
+fn unbilled(env: &Env, payer: &Address) -> Invoice {
+    Invoice {
+        payer: payer.clone(),
+        billed: 0,
+        due_at: 0,
+        late_fees: LateFeePolicy::default(),
+        late_fee: 0,
+        accrued_to: 0,
+        paid: 0,
+        redeemed: 0,
+        change: 0,
+        redeemed_at: 0,
+        doc_hash: BytesN::from_array(env, &[0; 32]),
+    }
+}
+
+// The invoice's late fee as of now. Payments go to the bill before the
+// fee, and the total is capped by the payer's program.
+fn accrued_fee(env: &Env, invoice: &Invoice) -> i128 {
+    let now = env.ledger().timestamp();
+    let start = invoice
+        .due_at
+        .saturating_add(invoice.late_fees.grace_secs)
+        .max(invoice.accrued_to);
+    let unpaid = invoice.billed - invoice.paid.min(invoice.billed);
+    if unpaid <= 0 || invoice.late_fees.daily_rate_bps == 0 || now <= start {
+        return invoice.late_fee;
+    }
+
+    let rate_secs = invoice.late_fees.daily_rate_bps as i128 * (now - start) as i128;
+    let accrued = math::mul_div(
+        env,
+        unpaid,
+        rate_secs,
+        BPS * DAY_SECS as i128,
+        Rounding::Down,
+    );
+    let cap_bps = env
+        .storage()
+        .persistent()
+        .get::<_, Allocation>(&DataKey::Allocations(invoice.payer.clone()))
+        .and_then(|allocation| allocation.program_id)
+        .and_then(|program_id| {
+            env.storage()
+                .persistent()
+                .get::<_, u32>(&InvoiceKey::LateFeeCap(program_id))
+        })
+        .map_or(BPS, |cap_bps| cap_bps as i128);
+    let cap = math::mul_div(env, invoice.billed, cap_bps, BPS, Rounding::Down);
+    checked_add(env, invoice.late_fee, accrued).min(cap)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+
+    fn setup() -> (Scenario, BytesN<32>) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(2)
+            .issue(0, 0, 1000);
+        s.client()
+            .set_reputation_config(&Some(crate::ReputationConfig {
+                settlement_window_secs: DAY_SECS,
+                large_redemption: 10_000,
+                min_score: 0,
+            }));
+        let invoice_ref = BytesN::from_array(&s.env, &[7; 32]);
+        s.client()
+            .pay_invoice(&s.beneficiary(0), &s.school(0), &invoice_ref, &300);
+        (s, invoice_ref)
+    }
+
+    #[test]
+    fn test_overage_returned_without_refund_penalty() {
+        let (s, invoice_ref) = setup();
+        let client = s.client();
+        let doc_hash = BytesN::from_array(&s.env, &[9; 32]);
+        client.redeem_exact(
+            &s.school(0),
+            &invoice_ref,
+            &280,
+            &s.beneficiary(0),
+            &Some(doc_hash.clone()),
+        );
+
+        assert_eq!(client.balance(&s.beneficiary(0)), 720);
+        assert_eq!(client.balance(&s.school(0)), 0);
+        assert_eq!(client.total_burned(), 280);
+        let invoice = client.invoice(&s.school(0), &invoice_ref).unwrap();
+        assert_eq!(
+            (invoice.paid, invoice.redeemed, invoice.change),
+            (300, 280, 20)
+        );
+        assert_eq!(invoice.doc_hash, doc_hash);
+        let stats = client.reputation(&s.school(0)).stats;
+        assert_eq!(
+            (stats.redemptions, stats.refunds, stats.settlements),
+            (1, 0, 1)
+        );
+
+        // An invoice is redeemed once
+        let result =
+            client.try_redeem_exact(&s.school(0), &invoice_ref, &280, &s.beneficiary(0), &None);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+
+    #[test]
+    fn test_redemption_bounded_by_the_payers_payment() {
+        let (s, invoice_ref) = setup();
+        let client = s.client();
+
+        let result =
+            client.try_redeem_exact(&s.school(0), &invoice_ref, &301, &s.beneficiary(0), &None);
+        assert_eq!(result, Err(Ok(Error::InvalidAmount.into())));
+        let result =
+            client.try_redeem_exact(&s.school(0), &invoice_ref, &300, &s.beneficiary(1), &None);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        // Only the original payer can add to an invoice
+        s.client().issue_credits(
+            &s.issuer(0),
+            &s.beneficiary(1),
+            &100,
+            &soroban_sdk::String::from_str(&s.env, "Tuition"),
+            &s.expires_at(),
+        );
+        let result = client.try_pay_invoice(&s.beneficiary(1), &s.school(0), &invoice_ref, &50);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+
+    // Bill 500 due in 10 days, with a 5-day grace and 1% a day after it
+    fn bill(s: &Scenario, beneficiary: u32) -> BytesN<32> {
+        let client = s.client();
+        client.set_late_fee_policy(
+            &s.school(0),
+            &Some(LateFeePolicy {
+                grace_secs: 5 * DAY_SECS,
+                daily_rate_bps: 100,
+            }),
+        );
+        let invoice_ref = BytesN::from_array(&s.env, &[beneficiary as u8; 32]);
+        client.bill_invoice(
+            &s.school(0),
+            &invoice_ref,
+            &s.beneficiary(beneficiary),
+            &500,
+            &(s.now() + 10 * DAY_SECS),
+        );
+        invoice_ref
+    }
+
+    #[test]
+    fn test_late_fees_accrue_on_the_unpaid_bill() {
+        let (s, _) = setup();
+        let client = s.client();
+        let invoice_ref = bill(&s, 0);
+
+        // Terms changed after billing don't apply
+        client.set_late_fee_policy(&s.school(0), &None);
+        advance(&s.env, 15 * DAY_SECS);
+        assert_eq!(client.invoice_due(&s.school(0), &invoice_ref), 500);
+        advance(&s.env, 4 * DAY_SECS);
+        assert_eq!(client.invoice_due(&s.school(0), &invoice_ref), 520);
+
+        client.pay_invoice(&s.beneficiary(0), &s.school(0), &invoice_ref, &200);
+        assert_eq!(
+            client.invoice(&s.school(0), &invoice_ref).unwrap().late_fee,
+            20
+        );
+        // Only the 300 still unpaid keeps accruing
+        advance(&s.env, 2 * DAY_SECS);
+        assert_eq!(client.invoice_due(&s.school(0), &invoice_ref), 326);
+    }
+
+    #[test]
+    fn test_program_caps_late_fees() {
+        let (s, _) = setup();
+        let client = s.client();
+        client.save_template(
+            &s.issuer(0),
+            &symbol_short!("uni"),
+            &soroban_sdk::String::from_str(&s.env, "Tuition"),
+            &600,
+            &(365 * DAY_SECS),
+            &symbol_short!("uni"),
+        );
+        client.issue_from_template(&s.issuer(0), &symbol_short!("uni"), &s.beneficiary(1));
+        client.set_program_late_fee_cap(&symbol_short!("uni"), &Some(300));
+        let invoice_ref = bill(&s, 1);
+
+        advance(&s.env, 60 * DAY_SECS);
+        assert_eq!(client.invoice_due(&s.school(0), &invoice_ref), 515);
+        client.pay_invoice(&s.beneficiary(1), &s.school(0), &invoice_ref, &515);
+        assert_eq!(client.invoice_due(&s.school(0), &invoice_ref), 0);
+    }
+}
@@ -0,0 +1,186 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Symbol,
+};
+
+use crate::{
+    accounts, checked_mul, events, require_admin, telemetry, treasury, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum KeeperKey {
+    KeeperBounty,          // Bounty terms for maintenance (absent when unpaid)
+    KeeperPaidAt(Address), // When a keeper was last paid a bounty
+}
+
+// What keepers are paid, in credits from the treasury, for maintenance
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeeperConfig {
+    pub bounty_per_item: i128, // Paid per account collected, receipt folded or balance swept
+    pub max_bounty: i128,      // Most paid for one call
+    pub cooldown_secs: u64,    // Between two bounties to the same keeper
+}
+
+// A maintenance task run through keep
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KeeperTask {
+    GcAccounts(u32, u32), // Cursor and limit, as for gc_accounts
+    FoldReceipts(u32),    // Limit, as for fold_receipts
+    SweepExpired(Address),
+    SweepDust(Address),
+    ProcessQueue(Symbol, u32), // Program and limit, as for process_queue
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the keeper bounty terms (admin only)
+    pub fn set_keeper_config(env: Env, config: Option<KeeperConfig>) {
+        telemetry::record(&env, "set_keeper_config");
+        require_admin(&env);
+
+        match config {
+            Some(config) => {
+                if config.bounty_per_item <= 0 || config.max_bounty < config.bounty_per_item {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage()
+                    .instance()
+                    .set(&KeeperKey::KeeperBounty, &config);
+            }
+            None => env.storage().instance().remove(&KeeperKey::KeeperBounty),
+        }
+    }
+
+    /// Get the keeper bounty terms, if set
+    pub fn keeper_config(env: Env) -> Option<KeeperConfig> {
+        env.storage().instance().get(&KeeperKey::KeeperBounty)
+    }
+
+    /// Run a maintenance task on behalf of `keeper` (the keeper) and pay it
+    /// a bounty for each item processed. Bounties come out of the treasury,
+    /// are capped per call and skipped while the keeper is cooling down or
+    /// the treasury is empty; the task runs either way. Returns the bounty
+    /// paid.
+    pub fn keep(env: Env, keeper: Address, task: KeeperTask) -> i128 {
+        telemetry::record(&env, "keep");
+        keeper.require_auth();
+
+        let items = match task {
+            KeeperTask::GcAccounts(cursor, limit) => {
+                accounts::collect_accounts(&env, cursor, limit).1
+            }
+            KeeperTask::FoldReceipts(limit) => Self::fold_receipts(env.clone(), limit),
+            KeeperTask::SweepExpired(beneficiary) => {
+                (Self::sweep_expired(env.clone(), beneficiary) > 0) as u32
+            }
+            KeeperTask::SweepDust(account) => (Self::sweep_dust(env.clone(), account) > 0) as u32,
+            KeeperTask::ProcessQueue(program_id, limit) => {
+                Self::process_queue(env.clone(), program_id, limit)
+            }
+        };
+
+        let Some(config) = Self::keeper_config(env.clone()) else {
+            return 0;
+        };
+        let now = env.ledger().timestamp();
+        let key = KeeperKey::KeeperPaidAt(keeper.clone());
+        let cooling = env
+            .storage()
+            .persistent()
+            .get::<_, u64>(&key)
+            .is_some_and(|paid_at| now < paid_at.saturating_add(config.cooldown_secs));
+        if items == 0 || cooling {
+            return 0;
+        }
+
+        let bounty = checked_mul(&env, config.bounty_per_item, items as i128)
+            .min(config.max_bounty)
+            .min(Self::treasury_balance(env.clone()));
+        if bounty <= 0 {
+            return 0;
+        }
+        treasury::pay_out(&env, &keeper, bounty);
+        env.storage().persistent().set(&key, &now);
+        events::publish(&env, (symbol_short!("bounty"), keeper), (items, bounty));
+
+        bounty
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+    use soroban_sdk::testutils::Address as _;
+
+    fn setup() -> (Scenario, Address) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(3);
+        let client = s.client();
+        client.set_min_transfer(&Some(10));
+        for beneficiary in 0..3 {
+            s.issue_allocation(0, beneficiary, 108);
+            client.transfer(&s.beneficiary(beneficiary), &s.school(0), &100);
+        }
+        client.set_keeper_config(&Some(KeeperConfig {
+            bounty_per_item: 3,
+            max_bounty: 5,
+            cooldown_secs: DAY_SECS,
+        }));
+        let keeper = Address::generate(&s.env);
+        (s, keeper)
+    }
+
+    #[test]
+    fn test_bounty_paid_per_item_from_treasury() {
+        let (s, keeper) = setup();
+        let client = s.client();
+        // The swept dust funds the keeper's own bounty
+        let task = KeeperTask::SweepDust(s.beneficiary(0));
+        assert_eq!(client.keep(&keeper, &task), 3);
+        assert_eq!(client.balance(&keeper), 3);
+        assert_eq!(client.treasury_balance(), 5);
+
+        // Cooling down: the sweep happens, the bounty doesn't
+        let task = KeeperTask::SweepDust(s.beneficiary(1));
+        assert_eq!(client.keep(&keeper, &task), 0);
+        assert_eq!(client.treasury_balance(), 13);
+
+        // Work that processes nothing earns nothing
+        advance(&s.env, DAY_SECS);
+        assert_eq!(client.keep(&keeper, &task), 0);
+        let task = KeeperTask::SweepDust(s.beneficiary(2));
+        assert_eq!(client.keep(&keeper, &task), 3);
+        assert_eq!(client.treasury_balance(), 18);
+    }
+
+    #[test]
+    fn test_bounty_capped_per_call() {
+        let (s, keeper) = setup();
+        let client = s.client();
+        for beneficiary in 0..3 {
+            client.sweep_dust(&s.beneficiary(beneficiary));
+        }
+
+        // Three emptied, expired accounts collected in one call
+        advance(&s.env, 400 * DAY_SECS);
+        client.set_gc_dormancy(&DAY_SECS);
+        let task = KeeperTask::GcAccounts(0, 10);
+        assert_eq!(client.keep(&keeper, &task), 5);
+        assert_eq!(client.treasury_balance(), 19);
+        let statement = client.treasury_statement(&(s.now() / (30 * DAY_SECS)));
+        assert_eq!(statement.withdrawn, 5);
+
+        let result = client.try_set_keeper_config(&Some(KeeperConfig {
+            bounty_per_item: 3,
+            max_bounty: 2,
+            cooldown_secs: 0,
+        }));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+    }
+}
@@ -1,24 +1,230 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Vec};
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short, Address,
+    Env, Map, String, Symbol, Vec,
+};
 
-// Storage keys
+#[cfg(feature = "acceptance")]
+mod acceptance;
+mod accounts;
+mod advances;
+mod allowances;
+mod approvals;
+mod attestations;
+mod audit;
+#[cfg(test)]
+mod benchmarks;
+mod bonding;
+mod bonus;
+mod bridge;
+mod budgets;
+mod cases;
+mod clawbacks;
+mod closure;
+mod commitments;
+mod community;
+#[cfg(feature = "confidential")]
+mod confidential;
+mod consent;
+mod conversions;
+mod demurrage;
+#[cfg(feature = "disputes")]
+mod disputes;
+mod dust;
+mod emergency;
+#[cfg(feature = "escrow")]
+mod escrow;
+mod events;
+#[cfg(feature = "exams")]
+mod exams;
+mod forecast;
+mod freeze;
+#[cfg(any(test, feature = "testutils"))]
+pub mod fuzz;
+mod grace;
+mod holds;
+mod households;
+mod info;
+#[cfg(feature = "disputes")]
+mod insurance;
+mod invariants;
+mod invoices;
+mod keepers;
+mod math;
+mod payloads;
+#[cfg(test)]
+mod property_tests;
+mod queue;
+mod quotas;
+mod redemptions;
+#[cfg(test)]
+mod reentrancy_tests;
+mod referrals;
+mod refunds;
+mod registry;
+mod replay;
+mod reporting;
+mod reputation;
+mod reserves;
+mod restrictions;
+mod rotation;
+mod savings;
+#[cfg(feature = "stipends")]
+mod schedule;
+mod screening;
+mod settlement;
+mod storage;
+mod subaccounts;
+mod subscriptions;
+mod tags;
+mod telemetry;
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
+#[cfg(test)]
+mod time_travel_tests;
+mod treasury;
+mod validity;
+mod wiring;
+mod wrap;
+
+#[cfg(feature = "acceptance")]
+pub use acceptance::PendingAllocation;
+pub use approvals::DrawProposal;
+pub use attestations::Attestation;
+pub use audit::ReceiptRoot;
+pub use bonding::{Bond, BondConfig};
+pub use bridge::{BridgeChain, BridgeFlow, BridgeProof};
+pub use budgets::{BudgetRebalance, PurposeBudget};
+pub use cases::{CaseAction, CaseStatus, FraudCase};
+pub use clawbacks::ClawbackBatch;
+pub use commitments::CommittedAllocation;
+pub use conversions::{ConversionRate, ProgramConversions};
+pub use demurrage::{BalanceBreakdown, DemurrageConfig};
+#[cfg(feature = "disputes")]
+pub use disputes::{DisputeCase, DisputeConfig, DisputeOutcome, DisputeStatus};
+pub use emergency::EmergencyWithdrawal;
+#[cfg(feature = "escrow")]
+pub use escrow::Escrow;
+#[cfg(feature = "exams")]
+pub use exams::ExamBonus;
+pub use forecast::{Commitments, FundingGap};
+pub use holds::Hold;
+pub use households::{Household, HouseholdReport};
+pub use info::ContractInfo;
+#[cfg(feature = "disputes")]
+pub use insurance::InsurancePayout;
+pub use invariants::InvariantReport;
+pub use invoices::{Invoice, LateFeePolicy};
+pub use keepers::{KeeperConfig, KeeperTask};
+pub use payloads::PayloadDomain;
+pub use queue::{PendingIssuance, QueueOrder};
+pub use redemptions::RedemptionPolicy;
+pub use referrals::{Referral, ReferralConfig};
+pub use refunds::{Contribution, ExpiryPolicy};
+pub use registry::{MerchantPolicy, MerchantType, RegistryConfig, RegistryFallback, SchoolStatus};
+pub use reporting::{FilingAdjustment, FilingField, FilingRecord, SpendingReport};
+pub use reputation::{Receipt, ReputationConfig, SchoolStats, Score};
+pub use reserves::AcceptedAsset;
+pub use restrictions::RestrictedDeposit;
+pub use savings::SavingsTerms;
+#[cfg(feature = "stipends")]
+pub use schedule::{MissedMonthPolicy, StipendSchedule, TrancheSummary};
+pub use settlement::{SettlementBatch, SettlementConfig, SettlementLine};
+pub use subaccounts::SubaccountReport;
+pub use tags::MetadataLimits;
+pub use treasury::{TreasuryStatement, TreasuryWithdrawal};
+pub use validity::ValidityConfig;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidAmount = 3,
+    InsufficientBalance = 4,
+    TemplateNotFound = 5,
+    BalanceCapExceeded = 6,
+    InvalidConfig = 7,
+    Unauthorized = 8,
+    ScheduleNotFound = 9,
+    InvalidMonth = 10,
+    NotFound = 11,
+    AlreadyExists = 12,
+    BudgetExceeded = 13,
+    NotExpired = 14,
+    NotConfigured = 15,
+    BelowMinTransfer = 16,
+    OfferExpired = 17,
+    Overflow = 18,
+    SelfTransfer = 19,
+    BondRequired = 20,
+    BondLocked = 21,
+    ReputationTooLow = 22,
+    Paused = 23,
+    AttestationRequired = 24,
+    InvalidAttestation = 25,
+    NonceAlreadyUsed = 26,
+    SignatureExpired = 27,
+    CreditsOnHold = 28,
+    RedemptionTooSoon = 29,
+    InsufficientAllowance = 30,
+    ExpiryTooSoon = 31,
+    ExpiryTooLate = 32,
+    ConsentRequired = 33,
+    AllocationExpired = 34,
+    InterfaceMismatch = 35,
+    NotMatured = 36,
+    ProposalExpired = 37,
+    RegistryUnavailable = 38,
+    ScreeningUnavailable = 39,
+}
+
+// Allocations one allocations_bulk call returns at most
+const MAX_BULK_ALLOCATIONS: u32 = 100;
+
+// Longest lifetime a template may give its allocations
+const MAX_TEMPLATE_VALIDITY_SECS: u64 = 10 * 365 * 24 * 60 * 60;
+
+// Storage keys. Subsystem modules define their own key enums; variant names
+// must stay unique across all of them since only the name is encoded. See
+// the storage module for which tier each kind of record belongs in.
 #[contracttype]
 pub enum DataKey {
     Admin,
-    Credits(Address),      // Credits balance for beneficiary
-    Allocations(Address),  // Allocation metadata
+    Credits(Address),     // Credits balance for beneficiary
+    Allocations(Address), // Allocation metadata
     TotalIssued,
+    Template(Address, Symbol), // Issuer-owned allocation template
+    MaxBalance,                // Global per-beneficiary holding cap
+    ProgramMaxBalance(Symbol), // Per-program holding cap override
+    Governance,                // Governance contract for governed entrypoints
+    TotalBurned,               // Credits redeemed through burn
 }
 
 // Credit allocation metadata
 #[contracttype]
-#[derive(Clone)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Allocation {
     pub beneficiary: Address,
     pub issuer: Address,
     pub amount: i128,
     pub purpose: String,
     pub expires_at: u64,
+    pub program_id: Option<Symbol>,
+    pub metadata: Map<Symbol, String>, // Issuer annotations (case file, school year)
+    pub remaining: i128,               // Credits of this allocation not yet spent
+    pub effective_expires_at: u64,     // End of any grace period for paying schools
+}
+
+// Reusable issuance parameters for repeat programs
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AllocationTemplate {
+    pub purpose: String,
+    pub amount: i128,
+    pub validity_secs: u64,
+    pub program_id: Symbol,
 }
 
 #[contract]
@@ -28,8 +234,9 @@ pub struct EduPassToken;
 impl EduPassToken {
     /// Initialize the contract with an admin
     pub fn initialize(env: Env, admin: Address) {
+        telemetry::record(&env, "initialize");
         if env.storage().instance().has(&DataKey::Admin) {
-            panic!("Contract already initialized");
+            panic_with_error!(&env, Error::AlreadyInitialized);
         }
         env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage().instance().set(&DataKey::TotalIssued, &0i128);
@@ -45,136 +252,798 @@ impl EduPassToken {
         expires_at: u64,
     ) -> Allocation {
         issuer.require_auth();
+        let expires_at = validity::resolve_expiry(&env, expires_at);
+        let allocation = issue(
+            &env,
+            issuer,
+            beneficiary,
+            amount,
+            purpose,
+            expires_at,
+            None,
+            Map::new(&env),
+        );
+        telemetry::record(&env, "issue_credits");
+        allocation
+    }
+
+    /// Save (or overwrite) an issuance template owned by the issuer
+    pub fn save_template(
+        env: Env,
+        issuer: Address,
+        template_id: Symbol,
+        purpose: String,
+        amount: i128,
+        validity_secs: u64,
+        program_id: Symbol,
+    ) {
+        telemetry::record(&env, "save_template");
+        issuer.require_auth();
 
         if amount <= 0 {
-            panic!("Amount must be positive");
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if validity_secs == 0 || validity_secs > MAX_TEMPLATE_VALIDITY_SECS {
+            panic_with_error!(&env, Error::InvalidConfig);
         }
 
-        // Get current balance
-        let current: i128 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Credits(beneficiary.clone()))
-            .unwrap_or(0);
-
-        // Update balance
+        let template = AllocationTemplate {
+            purpose,
+            amount,
+            validity_secs,
+            program_id,
+        };
         env.storage()
             .persistent()
-            .set(&DataKey::Credits(beneficiary.clone()), &(current + amount));
+            .set(&DataKey::Template(issuer, template_id), &template);
+    }
 
-        // Update total issued
-        let total: i128 = env
+    /// Issue credits to a beneficiary using a saved template
+    pub fn issue_from_template(
+        env: Env,
+        issuer: Address,
+        template_id: Symbol,
+        beneficiary: Address,
+    ) -> Allocation {
+        issuer.require_auth();
+
+        let template: AllocationTemplate = env
             .storage()
-            .instance()
-            .get(&DataKey::TotalIssued)
-            .unwrap_or(0);
-        env.storage()
-            .instance()
-            .set(&DataKey::TotalIssued, &(total + amount));
+            .persistent()
+            .get(&template_key(&env, &issuer, template_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::TemplateNotFound));
 
-        // Create allocation record
-        let allocation = Allocation {
-            beneficiary: beneficiary.clone(),
-            issuer: issuer.clone(),
-            amount,
-            purpose: purpose.clone(),
+        // Expiry is relative to the time of issuance
+        let expires_at = env
+            .ledger()
+            .timestamp()
+            .checked_add(template.validity_secs)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::Overflow));
+        let expires_at = validity::resolve_expiry(&env, expires_at);
+
+        let allocation = issue(
+            &env,
+            issuer,
+            beneficiary,
+            template.amount,
+            template.purpose,
             expires_at,
-        };
+            Some(template.program_id),
+            Map::new(&env),
+        );
+        telemetry::record(&env, "issue_from_template");
+        allocation
+    }
 
-        // Store allocation
+    /// Get a saved template
+    pub fn get_template(
+        env: Env,
+        issuer: Address,
+        template_id: Symbol,
+    ) -> Option<AllocationTemplate> {
         env.storage()
             .persistent()
-            .set(&DataKey::Allocations(beneficiary.clone()), &allocation);
-
-        allocation
+            .get(&template_key(&env, &issuer, template_id))
     }
 
     /// Transfer credits from beneficiary to school
     pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
         from.require_auth();
 
-        if amount <= 0 {
-            panic!("Amount must be positive");
-        }
-
-        // Get sender balance
-        let from_balance: i128 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Credits(from.clone()))
-            .unwrap_or(0);
+        transfer_credits(&env, &from, &from, &to, amount);
+        telemetry::record(&env, "transfer");
+    }
 
-        if from_balance < amount {
-            panic!("Insufficient balance");
+    /// Dry-run a transfer: run every check `transfer` would, without
+    /// requiring authorization or changing state
+    pub fn can_transfer(env: Env, from: Address, to: Address, amount: i128) -> Result<(), Error> {
+        if from == to {
+            return Err(Error::SelfTransfer);
+        }
+        let sender = storage::load_account(&env, &from);
+        let recipient = storage::load_account(&env, &to);
+        freeze::check_unfrozen(&env, &program_of(&sender.allocation))?;
+        grace::check_payable(&env, &sender.allocation, recipient.is_beneficiary())?;
+        screening::check_parties(&env, &from, &to)?;
+        if sender.is_beneficiary() && !recipient.is_beneficiary() {
+            registry::check_purpose(&env, &to, None)?;
+            registry::check_payee(&env, &to, amount, false)?;
         }
+        check_transfer(
+            &env,
+            demurrage::net_balance_of(&env, &from, &sender),
+            holds::held(&env, &from),
+            demurrage::net_balance_of(&env, &to, &recipient),
+            allocation_cap(&env, &recipient.allocation),
+            amount,
+        )?;
+        reputation::check_transfer(&env, &sender, &to, &recipient, amount)
+    }
 
-        // Get recipient balance
-        let to_balance: i128 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Credits(to.clone()))
-            .unwrap_or(0);
+    /// Dry-run a direct issuance: run every check `issue_credits` would,
+    /// without requiring authorization or changing state
+    pub fn can_issue(
+        env: Env,
+        issuer: Address,
+        beneficiary: Address,
+        amount: i128,
+        purpose: String,
+    ) -> Result<(), Error> {
+        let _ = purpose;
+        attestations::check_unattested(&env)?;
+        bonding::check_bonded(&env, &issuer)?;
+        check_allocation(&env, &beneficiary, amount, &None)?;
+        total_issued(&env)
+            .checked_add(amount)
+            .map(|_| ())
+            .ok_or(Error::Overflow)
+    }
 
-        // Update balances
-        env.storage()
-            .persistent()
-            .set(&DataKey::Credits(from.clone()), &(from_balance - amount));
-        env.storage()
-            .persistent()
-            .set(&DataKey::Credits(to.clone()), &(to_balance + amount));
+    /// Transfer credits tagged with a spending purpose, within the sender's
+    /// program budget for it. Priority purposes earn the sender a matching
+    /// bonus; returns the bonus paid.
+    pub fn transfer_for_purpose(
+        env: Env,
+        from: Address,
+        to: Address,
+        amount: i128,
+        purpose: Symbol,
+    ) -> i128 {
+        from.require_auth();
+
+        move_credits(&env, &from, &from, &to, amount, true, Some(&purpose));
+        budgets::spend(&env, &from, amount, &purpose);
+        quotas::spend(&env, &from, amount, &purpose);
+        reporting::record_purpose(&env, &purpose, amount);
+        let bonus = bonus::match_priority_spend(&env, &from, amount, &purpose);
+        telemetry::record(&env, "transfer_for_purpose");
+        bonus
     }
 
     /// Burn credits (redeem)
     pub fn burn(env: Env, account: Address, amount: i128) {
         account.require_auth();
 
-        if amount <= 0 {
-            panic!("Amount must be positive");
+        burn_credits(&env, &account, &account, amount);
+        telemetry::record(&env, "burn");
+    }
+
+    /// Get balance for an account
+    pub fn balance(env: Env, account: Address) -> i128 {
+        demurrage::net_balance(&env, &account)
+    }
+
+    /// Get allocation details in the single-record shape existing
+    /// integrators expect (the beneficiary's most recent allocation)
+    pub fn get_allocation(env: Env, beneficiary: Address) -> Option<Allocation> {
+        read_allocation(&env, &beneficiary)
+    }
+
+    /// Get all of a beneficiary's allocation records, most recent first.
+    /// Beneficiaries currently hold at most one allocation; integrators
+    /// should move to this shape ahead of multi-allocation storage.
+    pub fn get_allocation_v2(env: Env, beneficiary: Address) -> Vec<Allocation> {
+        let mut allocations = Vec::new(&env);
+        if let Some(allocation) = read_allocation(&env, &beneficiary) {
+            allocations.push_back(allocation);
         }
+        allocations
+    }
 
-        let balance: i128 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Credits(account.clone()))
-            .unwrap_or(0);
+    /// Get the allocation records of many beneficiaries at once, as
+    /// get_allocation_v2 does, returning at most 100 allocations. The
+    /// result lists the beneficiaries in order, each with its allocations
+    /// (none for some), and stops before the one that would pass the cap;
+    /// continue with those not listed.
+    pub fn allocations_bulk(
+        env: Env,
+        beneficiaries: Vec<Address>,
+    ) -> Vec<(Address, Vec<Allocation>)> {
+        let mut result = Vec::new(&env);
+        let mut returned = 0;
+        for beneficiary in beneficiaries.iter() {
+            let allocations = Self::get_allocation_v2(env.clone(), beneficiary.clone());
+            returned += allocations.len();
+            if returned > MAX_BULK_ALLOCATIONS {
+                break;
+            }
+            result.push_back((beneficiary, allocations));
+        }
+        result
+    }
+
+    /// Credits of a beneficiary's allocation not yet spent (0 without one)
+    pub fn allocation_remaining(env: Env, beneficiary: Address) -> i128 {
+        read_allocation(&env, &beneficiary).map_or(0, |allocation| allocation.remaining)
+    }
+
+    /// Get total credits issued
+    pub fn total_issued(env: Env) -> i128 {
+        total_issued(&env)
+    }
 
-        if balance < amount {
-            panic!("Insufficient balance to burn");
+    /// Get total credits burned
+    pub fn total_burned(env: Env) -> i128 {
+        total_burned(&env)
+    }
+
+    /// Set the global per-beneficiary holding cap (admin only, None removes it)
+    pub fn set_max_balance_per_beneficiary(env: Env, cap: Option<i128>) {
+        telemetry::record(&env, "set_max_balance_per_beneficiary");
+        require_admin(&env);
+
+        match cap {
+            Some(cap) => {
+                if cap <= 0 {
+                    panic_with_error!(&env, Error::InvalidAmount);
+                }
+                env.storage().instance().set(&DataKey::MaxBalance, &cap);
+            }
+            None => env.storage().instance().remove(&DataKey::MaxBalance),
         }
+    }
 
-        env.storage()
-            .persistent()
-            .set(&DataKey::Credits(account), &(balance - amount));
+    /// Override the holding cap for one program (admin only, None removes it)
+    pub fn set_program_max_balance(env: Env, program_id: Symbol, cap: Option<i128>) {
+        telemetry::record(&env, "set_program_max_balance");
+        require_admin(&env);
+
+        let key = DataKey::ProgramMaxBalance(program_id);
+        match cap {
+            Some(cap) => {
+                if cap <= 0 {
+                    panic_with_error!(&env, Error::InvalidAmount);
+                }
+                env.storage().persistent().set(&key, &cap);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
     }
 
-    /// Get balance for an account
-    pub fn balance(env: Env, account: Address) -> i128 {
+    /// Get the global per-beneficiary holding cap
+    pub fn max_balance_per_beneficiary(env: Env) -> Option<i128> {
+        global_balance_cap(&env)
+    }
+
+    /// Get the effective holding cap for a program (override or global)
+    pub fn program_max_balance(env: Env, program_id: Symbol) -> Option<i128> {
+        program_balance_cap(&env, &program_id)
+    }
+
+    /// Set the governance contract (admin only)
+    pub fn set_governance(env: Env, governance: Address) {
+        telemetry::record(&env, "set_governance");
+        require_admin(&env);
         env.storage()
-            .persistent()
-            .get(&DataKey::Credits(account))
-            .unwrap_or(0)
+            .instance()
+            .set(&DataKey::Governance, &governance);
     }
 
-    /// Get allocation details
-    pub fn get_allocation(env: Env, beneficiary: Address) -> Option<Allocation> {
+    /// Get the governance contract, if set
+    pub fn governance(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Governance)
+    }
+}
+
+fn read_admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotInitialized))
+}
+
+// Load the governance contract and require its authorization
+fn require_governance(env: &Env) -> Address {
+    let governance: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Governance)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotConfigured));
+    governance.require_auth();
+    governance
+}
+
+// Load the admin and require its authorization
+fn require_admin(env: &Env) -> Address {
+    let admin = read_admin(env);
+    admin.require_auth();
+    admin
+}
+
+// Holding cap for a program: its override, falling back to the global cap
+fn program_balance_cap(env: &Env, program_id: &Symbol) -> Option<i128> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ProgramMaxBalance(program_id.clone()))
+        .or_else(|| global_balance_cap(env))
+}
+
+fn global_balance_cap(env: &Env) -> Option<i128> {
+    env.storage().instance().get(&DataKey::MaxBalance)
+}
+
+// Beneficiaries are accounts holding an allocation. Schools receiving payments
+// are not beneficiaries, so holding caps and demurrage never apply to them.
+fn is_beneficiary(env: &Env, account: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Allocations(account.clone()))
+}
+
+// Holding cap for an account, if it is a beneficiary
+fn balance_cap(env: &Env, account: &Address) -> Option<i128> {
+    let allocation: Option<Allocation> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Allocations(account.clone()));
+    allocation_cap(env, &allocation)
+}
+
+// Holding cap implied by an account's allocation (none for non-beneficiaries)
+fn allocation_cap(env: &Env, allocation: &Option<Allocation>) -> Option<i128> {
+    match &allocation.as_ref()?.program_id {
+        Some(program_id) => program_balance_cap(env, program_id),
+        None => global_balance_cap(env),
+    }
+}
+
+// Where an issuer's template is kept, under its current key or one it
+// rotated from
+fn template_key(env: &Env, issuer: &Address, template_id: Symbol) -> DataKey {
+    let holder = rotation::holder(env, issuer, |issuer| {
         env.storage()
             .persistent()
-            .get(&DataKey::Allocations(beneficiary))
+            .has(&DataKey::Template(issuer.clone(), template_id.clone()))
+    });
+    DataKey::Template(holder, template_id)
+}
+
+// Program an account's allocation belongs to, if any
+fn program_of(allocation: &Option<Allocation>) -> Option<Symbol> {
+    allocation.as_ref()?.program_id.clone()
+}
+
+// Checks shared by transfer_credits and the can_transfer dry run, on
+// balances net of demurrage
+fn check_transfer(
+    env: &Env,
+    from_balance: i128,
+    from_held: i128,
+    to_balance: i128,
+    to_cap: Option<i128>,
+    amount: i128,
+) -> Result<(), Error> {
+    if amount <= 0 {
+        return Err(Error::InvalidAmount);
     }
+    dust::check_min_transfer(env, amount)?;
 
-    /// Get total credits issued
-    pub fn total_issued(env: Env) -> i128 {
-        env.storage()
-            .instance()
-            .get(&DataKey::TotalIssued)
-            .unwrap_or(0)
+    // Check sender balance, less any credits on hold
+    holds::check_spendable(from_balance, from_held, amount)?;
+
+    // Enforce the recipient's holding cap
+    let to_balance = to_balance.checked_add(amount).ok_or(Error::Overflow)?;
+    if let Some(cap) = to_cap {
+        if to_balance > cap {
+            return Err(Error::BalanceCapExceeded);
+        }
+    }
+
+    Ok(())
+}
+
+// Shared balance movement for all transfer entrypoints. Each account is
+// read once; a failed check reverts any demurrage settled beforehand.
+// `authorizer` is whoever authorized the move: `from` itself, or a spender
+// or school acting on its behalf.
+fn transfer_credits(env: &Env, authorizer: &Address, from: &Address, to: &Address, amount: i128) {
+    move_credits(env, authorizer, from, to, amount, true, None);
+}
+
+// Move credits between accounts. Moves that only hand back change are
+// left out of the school's reputation (`counted` false). A payment to a
+// school restricted to some purposes must be tagged with one of them.
+fn move_credits(
+    env: &Env,
+    authorizer: &Address,
+    from: &Address,
+    to: &Address,
+    amount: i128,
+    counted: bool,
+    purpose: Option<&Symbol>,
+) {
+    // Both balances are read before either is written, so a self-transfer
+    // would credit the amount without debiting it
+    if from == to {
+        panic_with_error!(env, Error::SelfTransfer);
+    }
+    let sender = storage::load_account(env, from);
+    let recipient = storage::load_account(env, to);
+    if let Err(error) = freeze::check_unfrozen(env, &program_of(&sender.allocation)) {
+        panic_with_error!(env, error);
+    }
+    if let Err(error) = grace::check_payable(env, &sender.allocation, recipient.is_beneficiary()) {
+        panic_with_error!(env, error);
+    }
+    if let Err(error) = screening::check_parties(env, from, to) {
+        panic_with_error!(env, error);
+    }
+    if sender.is_beneficiary() && !recipient.is_beneficiary() {
+        if let Err(error) = registry::check_purpose(env, to, purpose) {
+            panic_with_error!(env, error);
+        }
+        registry::require_payee(env, to, amount);
+    }
+
+    let from_balance = demurrage::settle_loaded(env, from, &sender);
+    let to_balance = demurrage::settle_loaded(env, to, &recipient);
+
+    let to_cap = allocation_cap(env, &recipient.allocation);
+    let from_held = holds::held(env, from);
+    if let Err(error) = check_transfer(env, from_balance, from_held, to_balance, to_cap, amount) {
+        panic_with_error!(env, error);
+    }
+
+    if counted {
+        reputation::record_transfer(env, authorizer, from, &sender, to, &recipient, amount);
+    }
+
+    // Update balances
+    let from_remaining = checked_sub(env, from_balance, amount);
+    write_balance(env, from, from_remaining);
+    write_balance(env, to, checked_add(env, to_balance, amount));
+    spend_allocation(env, from, sender.allocation.clone(), from_remaining);
+    events::publish_token(
+        env,
+        (symbol_short!("transfer"), from.clone(), to.clone()),
+        amount,
+        (amount, authorizer.clone()),
+    );
+    referrals::on_redemption(env, from, &sender, &recipient);
+    if sender.is_beneficiary() && !recipient.is_beneficiary() {
+        reporting::record_payment(env, to, amount);
+    }
+}
+
+// Shared redemption path: burn credits from an account, authorized by
+// `authorizer` (the account or a spender)
+fn burn_credits(env: &Env, authorizer: &Address, account: &Address, amount: i128) {
+    redeem(env, authorizer, account, amount, true);
+}
+
+// Burn credits from an account, leaving a school's redemption out of the
+// settlement batches (`batched` false) when it is paid out some other way
+fn redeem(env: &Env, authorizer: &Address, account: &Address, amount: i128, batched: bool) {
+    if amount <= 0 {
+        panic_with_error!(env, Error::InvalidAmount);
+    }
+
+    let allocation: Option<Allocation> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Allocations(account.clone()));
+    if let Err(error) = freeze::check_unfrozen(env, &program_of(&allocation)) {
+        panic_with_error!(env, error);
+    }
+    let beneficiary = allocation.is_some();
+    let balance = demurrage::settle(env, account, beneficiary);
+    holds::require_spendable(env, account, balance, amount);
+    if !beneficiary {
+        registry::require_redeemable(env, account);
+        redemptions::record_redemption(env, account, amount);
+    }
+
+    let remaining = checked_sub(env, balance, amount);
+    write_balance(env, account, remaining);
+    spend_allocation(env, account, allocation, remaining);
+    add_total_burned(env, amount);
+    reputation::record_burn(env, account, remaining);
+    reporting::record_redeemed(env, amount);
+    if batched && !beneficiary {
+        settlement::record_redemption(env, authorizer, account, amount);
+    }
+    events::publish_token(
+        env,
+        (symbol_short!("burn"), account.clone()),
+        amount,
+        (amount, authorizer.clone()),
+    );
+}
+
+// Shared issuance path for direct and template-based issuance
+#[allow(clippy::too_many_arguments)]
+fn issue(
+    env: &Env,
+    issuer: Address,
+    beneficiary: Address,
+    amount: i128,
+    purpose: String,
+    expires_at: u64,
+    program_id: Option<Symbol>,
+    metadata: Map<Symbol, String>,
+) -> Allocation {
+    attestations::require_unattested_allowed(env);
+    mint_allocation(
+        env,
+        issuer,
+        beneficiary,
+        amount,
+        purpose,
+        expires_at,
+        program_id,
+        metadata,
+    )
+}
+
+// Mint and allocate credits once any attestation has been checked
+#[allow(clippy::too_many_arguments)]
+fn mint_allocation(
+    env: &Env,
+    issuer: Address,
+    beneficiary: Address,
+    amount: i128,
+    purpose: String,
+    expires_at: u64,
+    program_id: Option<Symbol>,
+    metadata: Map<Symbol, String>,
+) -> Allocation {
+    bonding::require_bonded(env, &issuer);
+    let allocation = allocate(
+        env,
+        issuer,
+        beneficiary,
+        amount,
+        purpose,
+        expires_at,
+        program_id,
+        metadata,
+    );
+    add_total_issued(env, &allocation.program_id, amount);
+    events::publish(
+        env,
+        (symbol_short!("issued"), allocation.beneficiary.clone()),
+        (
+            allocation.issuer.clone(),
+            amount,
+            allocation.metadata.clone(),
+        ),
+    );
+    advances::repay(env, &allocation.beneficiary, amount);
+
+    allocation
+}
+
+// Credit a beneficiary and record the allocation without minting, for
+// credits that were already issued (e.g. grants from a pool)
+#[allow(clippy::too_many_arguments)]
+fn allocate(
+    env: &Env,
+    issuer: Address,
+    beneficiary: Address,
+    amount: i128,
+    purpose: String,
+    expires_at: u64,
+    program_id: Option<Symbol>,
+    metadata: Map<Symbol, String>,
+) -> Allocation {
+    if let Err(error) = check_allocation(env, &beneficiary, amount, &program_id) {
+        panic_with_error!(env, error);
+    }
+    tags::check_metadata(env, &metadata);
+    households::record_issued(env, &beneficiary, amount);
+
+    // Update balance
+    let current = demurrage::settle(env, &beneficiary, true);
+    write_balance(env, &beneficiary, checked_add(env, current, amount));
+
+    // Create allocation record
+    let allocation = Allocation {
+        beneficiary: beneficiary.clone(),
+        issuer,
+        amount,
+        purpose,
+        expires_at,
+        program_id,
+        metadata,
+        remaining: amount,
+        effective_expires_at: grace::effective_expiry(env, expires_at),
+    };
+
+    // Store allocation, superseding any co-funding, tags and savings of the
+    // previous one
+    tags::clear_index(env, &beneficiary);
+    savings::clear(env, &beneficiary);
+    env.storage()
+        .persistent()
+        .set(&DataKey::Allocations(beneficiary.clone()), &allocation);
+    refunds::clear_funders(env, &beneficiary);
+
+    allocation
+}
+
+// Checks shared by allocate and the can_issue dry run
+fn check_allocation(
+    env: &Env,
+    beneficiary: &Address,
+    amount: i128,
+    program_id: &Option<Symbol>,
+) -> Result<(), Error> {
+    if amount <= 0 {
+        return Err(Error::InvalidAmount);
+    }
+    freeze::check_unfrozen(env, program_id)?;
+    households::check_cap(env, beneficiary, amount)?;
+
+    // Enforce the beneficiary's holding cap
+    let balance = demurrage::net_balance(env, beneficiary)
+        .checked_add(amount)
+        .ok_or(Error::Overflow)?;
+    let cap = match program_id {
+        Some(program_id) => program_balance_cap(env, program_id),
+        None => global_balance_cap(env),
+    };
+    if let Some(cap) = cap {
+        if balance > cap {
+            return Err(Error::BalanceCapExceeded);
+        }
+    }
+
+    Ok(())
+}
+
+// Spend from an allocation once the rest of the balance is gone. Credits
+// carried over from earlier allocations or received later are spent first,
+// so the allocation only shrinks when the balance drops below what is left.
+fn spend_allocation(env: &Env, account: &Address, allocation: Option<Allocation>, balance: i128) {
+    if let Some(mut allocation) = allocation {
+        if balance < allocation.remaining {
+            savings::accrue(env, account, allocation.remaining);
+            allocation.remaining = balance;
+            env.storage()
+                .persistent()
+                .set(&DataKey::Allocations(account.clone()), &allocation);
+        }
+    }
+}
+
+// A beneficiary's allocation as reported to callers: pending demurrage and
+// other debits are reflected by never reporting more than the net balance
+fn read_allocation(env: &Env, beneficiary: &Address) -> Option<Allocation> {
+    let mut allocation: Allocation = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Allocations(beneficiary.clone()))?;
+    allocation.remaining = allocation
+        .remaining
+        .min(demurrage::net_balance(env, beneficiary));
+    Some(allocation)
+}
+
+// Add credits to an account's balance outside of a transfer (refunds,
+// returns), screening the recipient
+fn credit(env: &Env, account: &Address, amount: i128) {
+    screening::require_clear(env, account);
+    let balance = demurrage::settle(env, account, is_beneficiary(env, account));
+    write_balance(env, account, checked_add(env, balance, amount));
+}
+
+// Pay credits into an account out of a contract-held pool, failing with
+// BalanceCapExceeded past the holding cap a transfer would enforce
+fn credit_capped(env: &Env, account: &Address, amount: i128) {
+    screening::require_clear(env, account);
+    let state = storage::load_account(env, account);
+    let balance = checked_add(env, demurrage::settle_loaded(env, account, &state), amount);
+    if let Some(cap) = allocation_cap(env, &state.allocation) {
+        if balance > cap {
+            panic_with_error!(env, Error::BalanceCapExceeded);
+        }
     }
+    write_balance(env, account, balance);
+}
+
+// Take credits out of an account into a contract-held pool, with the
+// checks a transfer applies to its sender
+fn debit(env: &Env, account: &Address, amount: i128) {
+    if amount <= 0 {
+        panic_with_error!(env, Error::InvalidAmount);
+    }
+    let state = storage::load_account(env, account);
+    if let Err(error) = freeze::check_unfrozen(env, &program_of(&state.allocation)) {
+        panic_with_error!(env, error);
+    }
+    if let Err(error) = grace::check_payable(env, &state.allocation, false) {
+        panic_with_error!(env, error);
+    }
+    screening::require_clear(env, account);
+    let balance = demurrage::settle_loaded(env, account, &state);
+    holds::require_spendable(env, account, balance, amount);
+    let remaining = checked_sub(env, balance, amount);
+    write_balance(env, account, remaining);
+    spend_allocation(env, account, state.allocation, remaining);
+}
+
+// Persist an account's balance, recording the activity for storage hygiene
+fn write_balance(env: &Env, account: &Address, balance: i128) {
+    subscriptions::notify(env, account, balance);
+    env.storage()
+        .persistent()
+        .set(&DataKey::Credits(account.clone()), &balance);
+    accounts::touch(env, account);
+}
+
+fn total_issued(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::TotalIssued)
+        .unwrap_or(0)
+}
+
+// Count minted credits toward total_issued and the running quarter's
+// filing, under their program if they have one
+fn add_total_issued(env: &Env, program_id: &Option<Symbol>, amount: i128) {
+    let total = total_issued(env);
+    env.storage()
+        .instance()
+        .set(&DataKey::TotalIssued, &checked_add(env, total, amount));
+    reporting::record_issued(env, program_id, amount);
+}
+
+fn total_burned(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::TotalBurned)
+        .unwrap_or(0)
+}
+
+fn add_total_burned(env: &Env, amount: i128) {
+    let total = total_burned(env);
+    env.storage()
+        .instance()
+        .set(&DataKey::TotalBurned, &checked_add(env, total, amount));
+}
+
+// Checked arithmetic for balances and aggregates, failing with a typed
+// error instead of wrapping or trapping near the i128 limits
+fn checked_add(env: &Env, a: i128, b: i128) -> i128 {
+    a.checked_add(b)
+        .unwrap_or_else(|| panic_with_error!(env, Error::Overflow))
+}
+
+fn checked_sub(env: &Env, a: i128, b: i128) -> i128 {
+    a.checked_sub(b)
+        .unwrap_or_else(|| panic_with_error!(env, Error::Overflow))
+}
+
+fn checked_mul(env: &Env, a: i128, b: i128) -> i128 {
+    a.checked_mul(b)
+        .unwrap_or_else(|| panic_with_error!(env, Error::Overflow))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::symbol_short;
+    use soroban_sdk::testutils::{Address as _, Ledger};
 
     #[test]
     fn test_initialize() {
@@ -247,4 +1116,338 @@ mod test {
 
         assert_eq!(client.balance(&school), 500);
     }
+
+    #[test]
+    fn test_issue_from_template() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_700_000_000);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+
+        client.initialize(&admin);
+
+        client.save_template(
+            &issuer,
+            &symbol_short!("term1"),
+            &String::from_str(&env, "Term 1 Tuition"),
+            &250,
+            &(90 * 24 * 60 * 60),
+            &symbol_short!("primary"),
+        );
+
+        let allocation = client.issue_from_template(&issuer, &symbol_short!("term1"), &beneficiary);
+
+        assert_eq!(allocation.amount, 250);
+        assert_eq!(allocation.expires_at, 1_700_000_000 + 90 * 24 * 60 * 60);
+        assert_eq!(allocation.program_id, Some(symbol_short!("primary")));
+        assert_eq!(client.balance(&beneficiary), 250);
+        assert_eq!(client.total_issued(), 250);
+    }
+
+    #[test]
+    fn test_template_validity_is_bounded() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let issuer = Address::generate(&env);
+        let purpose = String::from_str(&env, "Term 1 Tuition");
+
+        client.initialize(&Address::generate(&env));
+
+        for validity_secs in [0, MAX_TEMPLATE_VALIDITY_SECS + 1, u64::MAX] {
+            let result = client.try_save_template(
+                &issuer,
+                &symbol_short!("term1"),
+                &purpose,
+                &250,
+                &validity_secs,
+                &symbol_short!("primary"),
+            );
+            assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+        }
+        client.save_template(
+            &issuer,
+            &symbol_short!("term1"),
+            &purpose,
+            &250,
+            &MAX_TEMPLATE_VALIDITY_SECS,
+            &symbol_short!("primary"),
+        );
+    }
+
+    #[test]
+    fn test_issue_from_missing_template() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+
+        client.initialize(&admin);
+
+        let result = client.try_issue_from_template(&issuer, &symbol_short!("none"), &beneficiary);
+        assert_eq!(result, Err(Ok(Error::TemplateNotFound.into())));
+    }
+
+    #[test]
+    fn test_balance_cap() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let school = Address::generate(&env);
+        let purpose = String::from_str(&env, "Tuition");
+
+        client.initialize(&admin);
+        client.set_max_balance_per_beneficiary(&Some(1000));
+
+        client.issue_credits(&issuer, &alice, &800, &purpose, &1735689600);
+        let result = client.try_issue_credits(&issuer, &alice, &300, &purpose, &1735689600);
+        assert_eq!(result, Err(Ok(Error::BalanceCapExceeded.into())));
+
+        // Receiver-side failure on transfer between beneficiaries
+        client.issue_credits(&issuer, &bob, &500, &purpose, &1735689600);
+        let result = client.try_transfer(&bob, &alice, &300);
+        assert_eq!(result, Err(Ok(Error::BalanceCapExceeded.into())));
+
+        // Sender-side failure is reported separately
+        let result = client.try_transfer(&bob, &school, &600);
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+
+        // Schools are not capped
+        client.transfer(&alice, &school, &800);
+        client.transfer(&bob, &school, &500);
+        assert_eq!(client.balance(&school), 1300);
+    }
+
+    #[test]
+    fn test_program_balance_cap_override() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+
+        client.initialize(&admin);
+        client.set_max_balance_per_beneficiary(&Some(100));
+        client.set_program_max_balance(&symbol_short!("uni"), &Some(5000));
+
+        client.save_template(
+            &issuer,
+            &symbol_short!("uni"),
+            &String::from_str(&env, "University Fees"),
+            &3000,
+            &86400,
+            &symbol_short!("uni"),
+        );
+        client.issue_from_template(&issuer, &symbol_short!("uni"), &beneficiary);
+        assert_eq!(client.balance(&beneficiary), 3000);
+
+        let result = client.try_issue_from_template(&issuer, &symbol_short!("uni"), &beneficiary);
+        assert_eq!(result, Err(Ok(Error::BalanceCapExceeded.into())));
+
+        assert_eq!(
+            client.program_max_balance(&symbol_short!("uni")),
+            Some(5000)
+        );
+        assert_eq!(
+            client.program_max_balance(&symbol_short!("other")),
+            Some(100)
+        );
+    }
+
+    #[test]
+    fn test_overflow_at_i128_max() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+        let purpose = String::from_str(&env, "Tuition");
+
+        client.initialize(&admin);
+        client.issue_credits(&issuer, &beneficiary, &i128::MAX, &purpose, &1735689600);
+        assert_eq!(client.total_issued(), i128::MAX);
+
+        // The issued total cannot grow past the limit
+        let result = client.try_issue_credits(&issuer, &school, &1, &purpose, &1735689600);
+        assert_eq!(result, Err(Ok(Error::Overflow.into())));
+        let result = client.try_fund_bonus_pool(&issuer, &1);
+        assert_eq!(result, Err(Ok(Error::Overflow.into())));
+
+        // Matching a maximal spend is taken at full width; the empty pool
+        // pays nothing
+        client.set_priority_purpose(&symbol_short!("books"), &Some(5000));
+        let bonus =
+            client.transfer_for_purpose(&beneficiary, &school, &i128::MAX, &symbol_short!("books"));
+        assert_eq!(bonus, 0);
+        assert_eq!(client.balance(&school), i128::MAX);
+        client.burn(&school, &i128::MAX);
+        assert_eq!(client.balance(&school), 0);
+    }
+
+    #[test]
+    fn test_dry_run_validators() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+        let purpose = String::from_str(&env, "Tuition");
+
+        client.initialize(&admin);
+        client.set_max_balance_per_beneficiary(&Some(1000));
+
+        client.can_issue(&issuer, &beneficiary, &1000, &purpose);
+        let result = client.try_can_issue(&issuer, &beneficiary, &1001, &purpose);
+        assert_eq!(result, Err(Ok(Error::BalanceCapExceeded)));
+        let result = client.try_can_issue(&issuer, &beneficiary, &0, &purpose);
+        assert_eq!(result, Err(Ok(Error::InvalidAmount)));
+
+        client.issue_credits(&issuer, &beneficiary, &600, &purpose, &1735689600);
+        client.can_transfer(&beneficiary, &school, &600);
+        let result = client.try_can_transfer(&beneficiary, &school, &601);
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+        let result = client.try_can_transfer(&beneficiary, &beneficiary, &100);
+        assert_eq!(result, Err(Ok(Error::SelfTransfer)));
+
+        // Recipients that are beneficiaries are held to their cap
+        client.transfer(&beneficiary, &school, &100);
+        client.issue_credits(&issuer, &beneficiary, &500, &purpose, &1735689600);
+        let result = client.try_can_transfer(&school, &beneficiary, &100);
+        assert_eq!(result, Err(Ok(Error::BalanceCapExceeded)));
+
+        // Dry runs leave state untouched
+        assert_eq!(client.balance(&beneficiary), 1000);
+        assert_eq!(client.balance(&school), 100);
+    }
+
+    #[test]
+    fn test_allocation_remaining() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+        let purpose = String::from_str(&env, "Tuition");
+
+        client.initialize(&admin);
+        assert_eq!(client.allocation_remaining(&beneficiary), 0);
+
+        client.issue_credits(&issuer, &beneficiary, &600, &purpose, &1735689600);
+        client.transfer(&beneficiary, &school, &200);
+        assert_eq!(client.allocation_remaining(&beneficiary), 400);
+
+        // A new allocation starts whole; what was carried over goes first
+        client.issue_credits(&issuer, &beneficiary, &500, &purpose, &1735689600);
+        client.transfer(&beneficiary, &school, &300);
+        assert_eq!(client.allocation_remaining(&beneficiary), 500);
+        client.burn(&beneficiary, &300);
+        assert_eq!(client.allocation_remaining(&beneficiary), 300);
+
+        let allocation = client.get_allocation(&beneficiary).unwrap();
+        assert_eq!((allocation.amount, allocation.remaining), (500, 300));
+        let listed = client.get_allocation_v2(&beneficiary);
+        assert_eq!(listed.get(0).unwrap().remaining, 300);
+    }
+
+    #[test]
+    fn test_get_allocation_v2_lists_the_allocation() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+
+        client.initialize(&Address::generate(&env));
+        client.issue_credits(
+            &issuer,
+            &beneficiary,
+            &1000,
+            &String::from_str(&env, "Tuition"),
+            &1735689600,
+        );
+
+        assert_eq!(
+            client.get_allocation_v2(&beneficiary),
+            Vec::from_array(&env, [client.get_allocation(&beneficiary).unwrap()])
+        );
+        assert!(client.get_allocation_v2(&school).is_empty());
+    }
+
+    #[test]
+    fn test_allocations_bulk_stops_at_the_cap() {
+        let s = testutils::Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(MAX_BULK_ALLOCATIONS + 1);
+        s.env.budget().reset_unlimited();
+        let client = s.client();
+        let mut caseload = Vec::new(&s.env);
+        let unfunded = Address::generate(&s.env);
+        caseload.push_back(unfunded.clone());
+        for index in 0..MAX_BULK_ALLOCATIONS + 1 {
+            s.issue_allocation(0, index, 10);
+            caseload.push_back(s.beneficiary(index));
+        }
+
+        // The cap counts allocations; beneficiaries without one don't use it
+        let synced = client.allocations_bulk(&caseload);
+        assert_eq!(synced.len(), MAX_BULK_ALLOCATIONS + 1);
+        assert_eq!(synced.get(0).unwrap(), (unfunded, Vec::new(&s.env)));
+        let (beneficiary, allocations) = synced.get(1).unwrap();
+        assert_eq!(beneficiary, s.beneficiary(0));
+        assert_eq!(allocations, client.get_allocation_v2(&s.beneficiary(0)));
+
+        // Continue from the first beneficiary not listed
+        let rest = caseload.slice(synced.len()..);
+        let synced = client.allocations_bulk(&rest);
+        assert_eq!(synced.len(), 1);
+        assert_eq!(
+            synced.get(0).unwrap().0,
+            s.beneficiary(MAX_BULK_ALLOCATIONS)
+        );
+    }
 }
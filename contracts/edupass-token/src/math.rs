@@ -0,0 +1,174 @@
+// Fixed-point helpers shared by every percentage, rate and pro-rata
+// computation. Products are taken at full 256-bit width before dividing,
+// so only the final result has to fit an i128, and each call states which
+// way it rounds.
+
+use soroban_sdk::{panic_with_error, Env};
+
+use crate::Error;
+
+/// Basis points in one whole
+pub(crate) const BPS: i128 = 10_000;
+
+/// Direction to round a quotient that isn't exact
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Rounding {
+    Down, // Toward zero: shares paid out, fees charged, limits allowed
+    Up,   // Away from zero: amounts owed to the contract
+}
+
+/// `a * b / denominator` rounded as asked, failing with Overflow if the
+/// result doesn't fit and InvalidAmount on negative operands or a
+/// non-positive denominator
+pub(crate) fn mul_div(env: &Env, a: i128, b: i128, denominator: i128, rounding: Rounding) -> i128 {
+    checked_mul_div(a, b, denominator, rounding)
+        .unwrap_or_else(|error| panic_with_error!(env, error))
+}
+
+/// Fallible form of mul_div, for callers without an Env to hand
+pub(crate) fn checked_mul_div(
+    a: i128,
+    b: i128,
+    denominator: i128,
+    rounding: Rounding,
+) -> Result<i128, Error> {
+    if a < 0 || b < 0 || denominator <= 0 {
+        return Err(Error::InvalidAmount);
+    }
+
+    let (hi, lo) = mul_wide(a as u128, b as u128);
+    let (mut quotient, remainder) = div_wide(hi, lo, denominator as u128).ok_or(Error::Overflow)?;
+    if rounding == Rounding::Up && remainder != 0 {
+        quotient = quotient.checked_add(1).ok_or(Error::Overflow)?;
+    }
+    i128::try_from(quotient).map_err(|_| Error::Overflow)
+}
+
+// Full product of two u128s as (high, low) halves
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_lo, a_hi) = (a & MASK, a >> 64);
+    let (b_lo, b_hi) = (b & MASK, b >> 64);
+
+    let ll = a_lo * b_lo;
+    let lh = a_lo * b_hi;
+    let hl = a_hi * b_lo;
+    let hh = a_hi * b_hi;
+
+    let mid = (ll >> 64) + (lh & MASK) + (hl & MASK);
+    let lo = (ll & MASK) | (mid << 64);
+    let hi = hh + (lh >> 64) + (hl >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+// Quotient and remainder of (hi, lo) by `d`, or None if the quotient
+// doesn't fit a u128
+fn div_wide(hi: u128, lo: u128, d: u128) -> Option<(u128, u128)> {
+    if hi == 0 {
+        return Some((lo / d, lo % d));
+    }
+    if hi >= d {
+        return None;
+    }
+
+    // Long division one bit at a time; the remainder stays below `d`
+    let mut remainder = hi;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((lo >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= d {
+            remainder = remainder.wrapping_sub(d);
+            quotient |= 1;
+        }
+    }
+    Some((quotient, remainder))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn down(a: i128, b: i128, d: i128) -> Result<i128, Error> {
+        checked_mul_div(a, b, d, Rounding::Down)
+    }
+
+    fn up(a: i128, b: i128, d: i128) -> Result<i128, Error> {
+        checked_mul_div(a, b, d, Rounding::Up)
+    }
+
+    #[test]
+    fn test_matches_exact_division_on_small_operands() {
+        for a in 0..48 {
+            for b in 0..48 {
+                for d in 1..40 {
+                    let product = a * b;
+                    let floor = product / d;
+                    let ceil = floor + (product % d != 0) as i128;
+                    assert_eq!(down(a, b, d), Ok(floor), "{a} * {b} / {d}");
+                    assert_eq!(up(a, b, d), Ok(ceil), "{a} * {b} / {d}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rounding_direction_on_bps() {
+        // 1 bps of 9,999 is 0.9999
+        assert_eq!(down(9_999, 1, BPS), Ok(0));
+        assert_eq!(up(9_999, 1, BPS), Ok(1));
+        // Exact results don't round either way
+        assert_eq!(down(10_000, 2_500, BPS), Ok(2_500));
+        assert_eq!(up(10_000, 2_500, BPS), Ok(2_500));
+        // Zero stays zero
+        assert_eq!(up(0, 2_500, BPS), Ok(0));
+        assert_eq!(up(2_500, 0, BPS), Ok(0));
+    }
+
+    #[test]
+    fn test_intermediate_products_beyond_i128() {
+        let max = i128::MAX;
+        // max * max / max needs the full 256-bit product
+        assert_eq!(down(max, max, max), Ok(max));
+        assert_eq!(up(max, max, max), Ok(max));
+        assert_eq!(down(max, BPS, BPS), Ok(max));
+        assert_eq!(
+            down(max, 9_999, BPS),
+            Ok(max / BPS * 9_999 + max % BPS * 9_999 / BPS)
+        );
+        assert_eq!(
+            down(max, 3, 7),
+            Ok(72_917_650_054_486_813_599_294_558_735_378_902_454)
+        );
+        assert_eq!(
+            up(max, 3, 7),
+            Ok(72_917_650_054_486_813_599_294_558_735_378_902_455)
+        );
+        assert_eq!(down(1 << 100, 1 << 100, 1 << 90), Ok(1 << 110));
+        assert_eq!(up(1 << 100, 1 << 100, 1 << 90), Ok(1 << 110));
+        assert_eq!(
+            down((1 << 100) + 1, 1 << 100, 1 << 90),
+            Ok((1 << 110) + (1 << 10))
+        );
+    }
+
+    #[test]
+    fn test_results_that_do_not_fit_fail() {
+        let max = i128::MAX;
+        assert_eq!(down(max, 2, 1), Err(Error::Overflow));
+        assert_eq!(down(max, max, 1), Err(Error::Overflow));
+        // Exactly i128::MAX fits, one more doesn't
+        assert_eq!(down(max, 1, 1), Ok(max));
+        assert_eq!(up(max, 2, 2), Ok(max));
+        assert_eq!(up(max, 3, 2), Err(Error::Overflow));
+    }
+
+    #[test]
+    fn test_invalid_operands_fail() {
+        assert_eq!(down(1, 1, 0), Err(Error::InvalidAmount));
+        assert_eq!(down(1, 1, -1), Err(Error::InvalidAmount));
+        assert_eq!(down(-1, 1, 1), Err(Error::InvalidAmount));
+        assert_eq!(up(1, -1, 1), Err(Error::InvalidAmount));
+    }
+}
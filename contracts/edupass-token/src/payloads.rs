@@ -0,0 +1,322 @@
+// Signed payloads, for entrypoints that authenticate a signer by an
+// ed25519 signature rather than transaction auth. Every digest is bound to
+// a domain: the network, this contract, the entrypoint and the payload
+// layout version, so a signature made for one entrypoint, deployment or
+// network can't be replayed against another, and a new layout version
+// retires signatures made under the old one. Entrypoints establish their
+// signer through require_signature; none hashes payloads of its own.
+//
+// So far this lets a student with no XLM accept an allocation or claim
+// committed credits through a relayer that pays the fees (typically the
+// NGO running the program). The beneficiary signs with the key behind
+// their account address, and the account needn't exist on the ledger yet.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol,
+    Val,
+};
+
+use crate::{replay, EduPassToken, EduPassTokenClient, Error};
+
+// Layout of the signed message; bumping it invalidates outstanding
+// signatures
+const PAYLOAD_VERSION: u32 = 1;
+
+// XDR of an address ScVal up to its ed25519 account key: the ScVal
+// discriminant (address), the address type (account) and the key type
+// (ed25519)
+const ACCOUNT_KEY_PREFIX: [u8; 12] = [0, 0, 0, 18, 0, 0, 0, 0, 0, 0, 0, 0];
+
+// What a signature is bound to besides its arguments
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayloadDomain {
+    pub network_id: BytesN<32>, // The network passphrase's hash
+    pub contract: Address,
+    pub tag: Symbol, // Names the entrypoint, e.g. `accept`
+    pub version: u32,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Get the domain payloads signed for an entrypoint tag are bound to
+    pub fn payload_domain(env: Env, tag: Symbol) -> PayloadDomain {
+        domain(&env, tag)
+    }
+
+    /// Get the digest a signer signs for an entrypoint tag, its arguments
+    /// (as a tuple) and a deadline
+    pub fn payload_digest(env: Env, tag: Symbol, args: Val, valid_until: u64) -> BytesN<32> {
+        digest(&env, &domain(&env, tag), args, valid_until)
+    }
+}
+
+/// Authenticate `signer` by a signature over an entrypoint's arguments
+/// rather than transaction auth. Contract addresses can't sign and fail
+/// with Unauthorized, a bad signature traps, and reused or expired
+/// payloads fail as the replay registry decides.
+pub(crate) fn require_signature(
+    env: &Env,
+    signer: &Address,
+    tag: Symbol,
+    args: Val,
+    valid_until: u64,
+    signature: &BytesN<64>,
+) {
+    let key =
+        account_key(env, signer).unwrap_or_else(|| panic_with_error!(env, Error::Unauthorized));
+    let digest = digest(env, &domain(env, tag), args, valid_until);
+    env.crypto()
+        .ed25519_verify(&key, &Bytes::from_array(env, &digest.to_array()), signature);
+    replay::consume(env, &digest, Some(valid_until));
+}
+
+fn domain(env: &Env, tag: Symbol) -> PayloadDomain {
+    PayloadDomain {
+        network_id: env.ledger().network_id(),
+        contract: env.current_contract_address(),
+        tag,
+        version: PAYLOAD_VERSION,
+    }
+}
+
+// SHA-256 of the XDR encoding of (domain, arguments, deadline)
+fn digest(env: &Env, domain: &PayloadDomain, args: Val, valid_until: u64) -> BytesN<32> {
+    let payload = (domain.clone(), args, valid_until).to_xdr(env);
+    env.crypto().sha256(&payload).to_bytes()
+}
+
+// The ed25519 key behind an account address; None for contracts
+fn account_key(env: &Env, address: &Address) -> Option<BytesN<32>> {
+    let xdr = address.clone().to_xdr(env);
+    if xdr.len() != 44 || xdr.slice(..12) != Bytes::from_array(env, &ACCOUNT_KEY_PREFIX) {
+        return None;
+    }
+    let mut key = [0u8; 32];
+    xdr.slice(12..).copy_into_slice(&mut key);
+    Some(BytesN::from_array(env, &key))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{Scenario, DAY_SECS};
+    use ed25519_dalek::{Signer, SigningKey};
+    use soroban_sdk::{symbol_short, xdr::FromXdr, IntoVal};
+
+    // A key pair seeded by `seed` and the account address it signs for
+    fn signer(env: &Env, seed: u8) -> (SigningKey, Address) {
+        let key = SigningKey::from_bytes(&[seed; 32]);
+        let mut xdr = Bytes::from_array(env, &ACCOUNT_KEY_PREFIX);
+        xdr.extend_from_array(key.verifying_key().as_bytes());
+        (key, Address::from_xdr(env, &xdr).unwrap())
+    }
+
+    fn sign(env: &Env, key: &SigningKey, digest: &BytesN<32>) -> BytesN<64> {
+        BytesN::from_array(env, &key.sign(&digest.to_array()).to_bytes())
+    }
+
+    #[test]
+    fn test_relayer_submits_signed_claim() {
+        let s = Scenario::new().with_admin().with_issuers(1);
+        let client = s.client();
+        let (key, beneficiary) = signer(&s.env, 1);
+        let salt = BytesN::from_array(&s.env, &[9; 32]);
+        let commitment = s.env.as_contract(&s.contract_id, || {
+            crate::commitments::commitment_of(&s.env, &salt, &beneficiary)
+        });
+        client.issue_to_commitment(&s.issuer(0), &commitment, &500, &s.expires_at());
+
+        let valid_until = s.now() + DAY_SECS;
+        let args = (commitment.clone(), salt.clone(), beneficiary.clone()).into_val(&s.env);
+        let digest = client.payload_digest(&symbol_short!("bind"), &args, &valid_until);
+        let signature = sign(&s.env, &key, &digest);
+
+        // Signed by someone else, or for other arguments
+        let (other, _) = signer(&s.env, 2);
+        let forged = sign(&s.env, &other, &digest);
+        let result = client.try_bind_commitment_signed(
+            &commitment,
+            &salt,
+            &beneficiary,
+            &valid_until,
+            &forged,
+        );
+        assert!(result.is_err());
+        let result = client.try_bind_commitment_signed(
+            &commitment,
+            &salt,
+            &beneficiary,
+            &(valid_until + 1),
+            &signature,
+        );
+        assert!(result.is_err());
+        // Contracts have no key to sign with
+        let result = client.try_bind_commitment_signed(
+            &commitment,
+            &salt,
+            &s.contract_id,
+            &valid_until,
+            &signature,
+        );
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        client.bind_commitment_signed(&commitment, &salt, &beneficiary, &valid_until, &signature);
+        // No transaction auth was asked of anyone
+        assert!(s.env.auths().is_empty());
+        assert_eq!(client.balance(&beneficiary), 500);
+        assert!(client.is_consumed(&digest));
+        let result = client.try_bind_commitment_signed(
+            &commitment,
+            &salt,
+            &beneficiary,
+            &valid_until,
+            &signature,
+        );
+        assert_eq!(result, Err(Ok(Error::NonceAlreadyUsed.into())));
+    }
+
+    #[test]
+    fn test_replays_refused_until_the_deadline_however_fast_ledgers_close() {
+        use crate::testutils::{advance_ledgers, set_time};
+        use soroban_sdk::testutils::Ledger;
+        // Entries written from here on outlive the ledgers advanced below
+        let env = Env::default();
+        env.ledger()
+            .with_mut(|ledger| ledger.min_persistent_entry_ttl = 100_000);
+        let s = Scenario::with_env(env).with_admin().with_issuers(1);
+        let client = s.client();
+        let (key, beneficiary) = signer(&s.env, 1);
+        let salt = BytesN::from_array(&s.env, &[9; 32]);
+        let commitment = s.env.as_contract(&s.contract_id, || {
+            crate::commitments::commitment_of(&s.env, &salt, &beneficiary)
+        });
+        client.issue_to_commitment(&s.issuer(0), &commitment, &500, &s.expires_at());
+        let valid_until = s.now() + DAY_SECS;
+        let args = (commitment.clone(), salt.clone(), beneficiary.clone()).into_val(&s.env);
+        let digest = client.payload_digest(&symbol_short!("bind"), &args, &valid_until);
+        let signature = sign(&s.env, &key, &digest);
+        client.bind_commitment_signed(&commitment, &salt, &beneficiary, &valid_until, &signature);
+
+        // A day's worth of four-second ledgers outlasts a TTL converted at
+        // five seconds a ledger
+        advance_ledgers(&s.env, (DAY_SECS / 4) as u32);
+        set_time(&s.env, valid_until);
+        let result = client.try_bind_commitment_signed(
+            &commitment,
+            &salt,
+            &beneficiary,
+            &valid_until,
+            &signature,
+        );
+        assert_eq!(result, Err(Ok(Error::NonceAlreadyUsed.into())));
+    }
+
+    #[test]
+    fn test_signatures_dont_cross_domains() {
+        let s = Scenario::new().with_admin().with_issuers(1);
+        let client = s.client();
+        let (key, beneficiary) = signer(&s.env, 1);
+        let salt = BytesN::from_array(&s.env, &[9; 32]);
+        let commitment = s.env.as_contract(&s.contract_id, || {
+            crate::commitments::commitment_of(&s.env, &salt, &beneficiary)
+        });
+        client.issue_to_commitment(&s.issuer(0), &commitment, &500, &s.expires_at());
+
+        let valid_until = s.now() + DAY_SECS;
+        let args = (commitment.clone(), salt.clone(), beneficiary.clone()).into_val(&s.env);
+        let domain = client.payload_domain(&symbol_short!("bind"));
+        assert_eq!(domain.network_id, s.env.ledger().network_id());
+        assert_eq!(domain.contract, s.contract_id);
+        assert_eq!(domain.version, PAYLOAD_VERSION);
+
+        // The same arguments signed for another network, entrypoint or
+        // layout version
+        let foreign = [
+            PayloadDomain {
+                network_id: BytesN::from_array(&s.env, &[7; 32]),
+                ..domain.clone()
+            },
+            PayloadDomain {
+                tag: symbol_short!("accept"),
+                ..domain.clone()
+            },
+            PayloadDomain {
+                version: PAYLOAD_VERSION - 1,
+                ..domain.clone()
+            },
+        ];
+        for domain in foreign {
+            let digest = digest(&s.env, &domain, args, valid_until);
+            let signature = sign(&s.env, &key, &digest);
+            let result = client.try_bind_commitment_signed(
+                &commitment,
+                &salt,
+                &beneficiary,
+                &valid_until,
+                &signature,
+            );
+            assert!(result.is_err());
+        }
+        assert_eq!(client.balance(&beneficiary), 0);
+
+        // A signature made here doesn't carry over to another deployment
+        // holding the same commitment
+        let signature = sign(&s.env, &key, &digest(&s.env, &domain, args, valid_until));
+        let other = Scenario::with_env(s.env.clone())
+            .with_admin()
+            .with_issuers(1);
+        other.client().issue_to_commitment(
+            &other.issuer(0),
+            &commitment,
+            &500,
+            &other.expires_at(),
+        );
+        let result = other.client().try_bind_commitment_signed(
+            &commitment,
+            &salt,
+            &beneficiary,
+            &valid_until,
+            &signature,
+        );
+        assert!(result.is_err());
+        client.bind_commitment_signed(&commitment, &salt, &beneficiary, &valid_until, &signature);
+        assert_eq!(client.balance(&beneficiary), 500);
+    }
+
+    #[cfg(feature = "acceptance")]
+    #[test]
+    fn test_relayer_submits_signed_acceptance() {
+        use crate::testutils;
+        use soroban_sdk::String;
+
+        let s = Scenario::new().with_admin().with_issuers(1);
+        let client = s.client();
+        let (key, beneficiary) = signer(&s.env, 1);
+        let id = client.offer_credits(
+            &s.issuer(0),
+            &beneficiary,
+            &1000,
+            &String::from_str(&s.env, "Tuition"),
+            &s.expires_at(),
+            &BytesN::from_array(&s.env, &[1; 32]),
+        );
+
+        let valid_until = s.now() + DAY_SECS;
+        let args = (id.clone(),).into_val(&s.env);
+        let digest = client.payload_digest(&symbol_short!("accept"), &args, &valid_until);
+        let signature = sign(&s.env, &key, &digest);
+        // Signatures past their deadline are refused
+        let now = s.now();
+        testutils::set_time(&s.env, valid_until + 1);
+        let result = client.try_accept_allocation_signed(&id, &valid_until, &signature);
+        assert_eq!(result, Err(Ok(Error::SignatureExpired.into())));
+        testutils::set_time(&s.env, now);
+
+        let allocation = client.accept_allocation_signed(&id, &valid_until, &signature);
+        assert!(s.env.auths().is_empty());
+        assert_eq!(allocation.beneficiary, beneficiary);
+        assert_eq!(client.balance(&beneficiary), 1000);
+        assert_eq!(client.pending_allocation(&id), None);
+    }
+}
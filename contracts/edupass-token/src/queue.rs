@@ -0,0 +1,416 @@
+// Queued issuance for programs whose budget can't cover everyone at once.
+// Issuers queue allocations with priority metadata, and keepers issue them
+// with process_queue in the program's configured order: highest need score
+// first, highest grade level first, or first come first served, with ties
+// going to the earliest queued. Processing stops at the first entry the
+// remaining budget can't cover rather than skipping to smaller ones, so the
+// order holds however the budget runs out. Entries that no longer pass the
+// issuance checks are dropped with a `qdrop` event instead of blocking the
+// queue.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Map, String, Symbol,
+    Vec,
+};
+
+use crate::{
+    attestations, bonding, check_allocation, checked_add, events, issue, require_admin, telemetry,
+    validity, EduPassToken, EduPassTokenClient, Error,
+};
+
+// Pending issuances one program can queue
+const MAX_QUEUED: u32 = 64;
+
+#[contracttype]
+enum QueueKey {
+    PendingIssuance(u64),   // A queued allocation
+    PendingCount,           // Last assigned pending issuance id
+    IssuanceQueue(Symbol),  // A program's queue, in queuing order
+    QueueOrder(Symbol),     // How a program's queue is ordered (absent for Fifo)
+    IssuanceBudget(Symbol), // Credits a program's queue may still issue (absent when unlimited)
+    QueuedAmount(Symbol),   // Credits pending in a program's queue
+    QueuedTotal,            // Credits pending in all queues
+}
+
+// How a program's queue is ordered
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QueueOrder {
+    Fifo,       // Earliest queued first (default)
+    NeedScore,  // Highest need score first
+    GradeLevel, // Highest grade level first
+}
+
+// An allocation waiting in a program's queue
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingIssuance {
+    pub issuer: Address,
+    pub beneficiary: Address,
+    pub amount: i128,
+    pub purpose: String,
+    pub expires_at: u64,
+    pub program_id: Symbol,
+    pub need_score: u32,
+    pub grade_level: u32,
+}
+
+// A queue slot: the pending issuance and the metadata it is ordered by
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct QueueSlot {
+    id: u64,
+    need_score: u32,
+    grade_level: u32,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set how a program's queue is ordered (admin only)
+    pub fn set_queue_order(env: Env, program_id: Symbol, order: QueueOrder) {
+        telemetry::record(&env, "set_queue_order");
+        require_admin(&env);
+
+        let key = QueueKey::QueueOrder(program_id);
+        if order == QueueOrder::Fifo {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &order);
+        }
+    }
+
+    /// Get how a program's queue is ordered
+    pub fn queue_order(env: Env, program_id: Symbol) -> QueueOrder {
+        env.storage()
+            .persistent()
+            .get(&QueueKey::QueueOrder(program_id))
+            .unwrap_or(QueueOrder::Fifo)
+    }
+
+    /// Set or clear (None, unlimited) what a program's queue may still
+    /// issue (admin only)
+    pub fn set_issuance_budget(env: Env, program_id: Symbol, budget: Option<i128>) {
+        telemetry::record(&env, "set_issuance_budget");
+        require_admin(&env);
+
+        let key = QueueKey::IssuanceBudget(program_id);
+        match budget {
+            Some(budget) => {
+                if budget < 0 {
+                    panic_with_error!(&env, Error::InvalidAmount);
+                }
+                env.storage().persistent().set(&key, &budget);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Get what a program's queue may still issue, if limited
+    pub fn issuance_budget(env: Env, program_id: Symbol) -> Option<i128> {
+        env.storage()
+            .persistent()
+            .get(&QueueKey::IssuanceBudget(program_id))
+    }
+
+    /// Queue an allocation in its program's queue (the issuer); returns
+    /// the pending issuance id
+    pub fn queue_issuance(env: Env, pending: PendingIssuance) -> u64 {
+        telemetry::record(&env, "queue_issuance");
+        pending.issuer.require_auth();
+
+        if pending.amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        let mut queue = read_queue(&env, &pending.program_id);
+        if queue.len() >= MAX_QUEUED {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+
+        let id = env
+            .storage()
+            .instance()
+            .get(&QueueKey::PendingCount)
+            .unwrap_or(0u64)
+            + 1;
+        env.storage().instance().set(&QueueKey::PendingCount, &id);
+
+        let pending = PendingIssuance {
+            expires_at: validity::resolve_expiry(&env, pending.expires_at),
+            ..pending
+        };
+        queue.push_back(QueueSlot {
+            id,
+            need_score: pending.need_score,
+            grade_level: pending.grade_level,
+        });
+        write_queue(&env, &pending.program_id, &queue);
+        add_queued(&env, &pending.program_id, pending.amount);
+        env.storage()
+            .persistent()
+            .set(&QueueKey::PendingIssuance(id), &pending);
+        events::publish(
+            &env,
+            (symbol_short!("queued"), pending.program_id),
+            (id, pending.beneficiary, pending.amount),
+        );
+
+        id
+    }
+
+    /// Take a pending issuance out of its queue (its issuer)
+    pub fn cancel_issuance(env: Env, issuer: Address, pending_id: u64) {
+        telemetry::record(&env, "cancel_issuance");
+        issuer.require_auth();
+
+        let pending = read_pending(&env, pending_id);
+        if pending.issuer != issuer {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        let mut queue = read_queue(&env, &pending.program_id);
+        if let Some(index) = queue.iter().position(|slot| slot.id == pending_id) {
+            queue.remove(index as u32);
+        }
+        write_queue(&env, &pending.program_id, &queue);
+        add_queued(&env, &pending.program_id, -pending.amount);
+        env.storage()
+            .persistent()
+            .remove(&QueueKey::PendingIssuance(pending_id));
+    }
+
+    /// Issue up to `limit` queued allocations of a program in priority
+    /// order, within its budget (anyone). Returns the entries processed,
+    /// issued or dropped.
+    pub fn process_queue(env: Env, program_id: Symbol, limit: u32) -> u32 {
+        telemetry::record(&env, "process_queue");
+        let order = Self::queue_order(env.clone(), program_id.clone());
+        let mut budget = Self::issuance_budget(env.clone(), program_id.clone());
+        let mut queue = read_queue(&env, &program_id);
+
+        let mut processed = 0;
+        let mut dequeued = 0;
+        while processed < limit && !queue.is_empty() {
+            let index = next(&queue, order);
+            let id = queue.get(index).unwrap().id;
+            let pending = read_pending(&env, id);
+            if budget.is_some_and(|budget| pending.amount > budget) {
+                break;
+            }
+
+            queue.remove(index);
+            env.storage()
+                .persistent()
+                .remove(&QueueKey::PendingIssuance(id));
+            processed += 1;
+            dequeued = checked_add(&env, dequeued, pending.amount);
+            if let Err(error) = check_issuable(&env, &pending) {
+                events::publish(&env, (symbol_short!("qdrop"), id), error as u32);
+                continue;
+            }
+            budget = budget.map(|budget| budget - pending.amount);
+            events::publish(
+                &env,
+                (symbol_short!("qissued"), id),
+                (pending.beneficiary.clone(), pending.amount),
+            );
+            issue(
+                &env,
+                pending.issuer,
+                pending.beneficiary,
+                pending.amount,
+                pending.purpose,
+                pending.expires_at,
+                Some(program_id.clone()),
+                Map::new(&env),
+            );
+        }
+
+        write_queue(&env, &program_id, &queue);
+        add_queued(&env, &program_id, -dequeued);
+        if let Some(budget) = budget {
+            env.storage()
+                .persistent()
+                .set(&QueueKey::IssuanceBudget(program_id), &budget);
+        }
+        processed
+    }
+
+    /// Get a pending issuance, until it's processed or cancelled
+    pub fn pending_issuance(env: Env, pending_id: u64) -> Option<PendingIssuance> {
+        env.storage()
+            .persistent()
+            .get(&QueueKey::PendingIssuance(pending_id))
+    }
+
+    /// Get the ids of a program's pending issuances, in queuing order
+    pub fn issuance_queue(env: Env, program_id: Symbol) -> Vec<u64> {
+        let mut ids = Vec::new(&env);
+        for slot in read_queue(&env, &program_id).iter() {
+            ids.push_back(slot.id);
+        }
+        ids
+    }
+}
+
+// Index of the slot to process next; the queue is in queuing order, so
+// the first best slot is the earliest queued among equals
+fn next(queue: &Vec<QueueSlot>, order: QueueOrder) -> u32 {
+    let rank = |slot: &QueueSlot| match order {
+        QueueOrder::Fifo => 0,
+        QueueOrder::NeedScore => slot.need_score,
+        QueueOrder::GradeLevel => slot.grade_level,
+    };
+    let mut best = 0;
+    let mut best_rank = rank(&queue.get(0).unwrap());
+    for (index, slot) in queue.iter().enumerate().skip(1) {
+        if rank(&slot) > best_rank {
+            best = index as u32;
+            best_rank = rank(&slot);
+        }
+    }
+    best
+}
+
+// Every check issuing the entry would run
+fn check_issuable(env: &Env, pending: &PendingIssuance) -> Result<(), Error> {
+    attestations::check_unattested(env)?;
+    bonding::check_bonded(env, &pending.issuer)?;
+    if env.ledger().timestamp() >= pending.expires_at {
+        return Err(Error::OfferExpired);
+    }
+    check_allocation(
+        env,
+        &pending.beneficiary,
+        pending.amount,
+        &Some(pending.program_id.clone()),
+    )
+}
+
+/// Credits pending in a program's queue
+pub(crate) fn queued_amount(env: &Env, program_id: &Symbol) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&QueueKey::QueuedAmount(program_id.clone()))
+        .unwrap_or(0)
+}
+
+/// Credits pending in all queues
+pub(crate) fn queued_total(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&QueueKey::QueuedTotal)
+        .unwrap_or(0)
+}
+
+// Adjust the queued counters by `amount`, negative as entries leave
+fn add_queued(env: &Env, program_id: &Symbol, amount: i128) {
+    if amount == 0 {
+        return;
+    }
+    let key = QueueKey::QueuedAmount(program_id.clone());
+    let queued = checked_add(env, queued_amount(env, program_id), amount);
+    if queued == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &queued);
+    }
+    let total = checked_add(env, queued_total(env), amount);
+    env.storage().instance().set(&QueueKey::QueuedTotal, &total);
+}
+
+fn read_pending(env: &Env, pending_id: u64) -> PendingIssuance {
+    EduPassToken::pending_issuance(env.clone(), pending_id)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound))
+}
+
+fn read_queue(env: &Env, program_id: &Symbol) -> Vec<QueueSlot> {
+    env.storage()
+        .persistent()
+        .get(&QueueKey::IssuanceQueue(program_id.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn write_queue(env: &Env, program_id: &Symbol, queue: &Vec<QueueSlot>) {
+    let key = QueueKey::IssuanceQueue(program_id.clone());
+    if queue.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, queue);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+    use soroban_sdk::{symbol_short, vec};
+
+    fn setup(order: QueueOrder) -> (Scenario, Symbol, Vec<u64>) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(4);
+        let client = s.client();
+        let program = symbol_short!("bursary");
+        client.set_queue_order(&program, &order);
+        client.set_issuance_budget(&program, &Some(1000));
+        let mut ids = Vec::new(&s.env);
+        for (beneficiary, amount, need_score, grade_level) in [
+            (0, 400, 2, 12),
+            (1, 300, 9, 8),
+            (2, 500, 9, 10),
+            (3, 100, 1, 12),
+        ] {
+            ids.push_back(client.queue_issuance(&PendingIssuance {
+                issuer: s.issuer(0),
+                beneficiary: s.beneficiary(beneficiary),
+                amount,
+                purpose: String::from_str(&s.env, "Tuition"),
+                expires_at: s.expires_at(),
+                program_id: program.clone(),
+                need_score,
+                grade_level,
+            }));
+        }
+        (s, program, ids)
+    }
+
+    #[test]
+    fn test_queue_issued_by_need_within_budget() {
+        let (s, program, ids) = setup(QueueOrder::NeedScore);
+        let client = s.client();
+        // Ties in need go to the earliest queued
+        assert_eq!(client.process_queue(&program, &1), 1);
+        assert_eq!(client.balance(&s.beneficiary(1)), 300);
+        assert_eq!(client.process_queue(&program, &10), 1);
+        assert_eq!(client.balance(&s.beneficiary(2)), 500);
+        // 200 left can't cover the next 400, and smaller entries wait
+        // behind it
+        assert_eq!(client.process_queue(&program, &10), 0);
+        assert_eq!(client.balance(&s.beneficiary(3)), 0);
+        assert_eq!(client.issuance_budget(&program), Some(200));
+        assert_eq!(
+            client.issuance_queue(&program),
+            vec![&s.env, ids.get(0).unwrap(), ids.get(3).unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_orders_are_deterministic() {
+        let (s, program, _) = setup(QueueOrder::GradeLevel);
+        let client = s.client();
+        client.set_issuance_budget(&program, &None);
+        assert_eq!(client.process_queue(&program, &2), 2);
+        assert_eq!(client.balance(&s.beneficiary(0)), 400);
+        assert_eq!(client.balance(&s.beneficiary(3)), 100);
+
+        let (s, program, ids) = setup(QueueOrder::Fifo);
+        let client = s.client();
+        client.cancel_issuance(&s.issuer(0), &ids.get(0).unwrap());
+        // Entries failing the issuance checks are dropped, not stuck
+        advance(&s.env, 400 * DAY_SECS);
+        assert_eq!(client.process_queue(&program, &10), 3);
+        assert_eq!(client.balance(&s.beneficiary(1)), 0);
+        assert!(client.issuance_queue(&program).is_empty());
+        assert_eq!(client.issuance_budget(&program), Some(1000));
+    }
+}
@@ -0,0 +1,152 @@
+// Daily spending quotas for daily-living purposes such as meals and
+// transport, where payments are small and frequent and a per-day cap
+// matters more than a program budget. The admin sets the most one account
+// may spend on a purpose per UTC day, and transfer_for_purpose enforces it.
+// Days are derived from the ledger timestamp, so quotas reset at 00:00 UTC.
+// Each account's spend for the day lives in temporary storage and lapses
+// on its own once the day is over.
+
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, Address, Env, Map, Symbol};
+
+use crate::{
+    checked_add, require_admin, storage, telemetry, EduPassToken, EduPassTokenClient, Error,
+};
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+// Purposes that can carry a daily quota at once
+const MAX_QUOTA_PURPOSES: u32 = 20;
+
+#[contracttype]
+enum QuotaKey {
+    DailyQuotas,                 // Daily cap per purpose (absent when none are set)
+    DailySpent(Address, Symbol), // An account's spend on a purpose today (temporary)
+}
+
+// What an account has spent on a purpose during one UTC day
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct DailySpend {
+    day: u64, // Days since the Unix epoch
+    spent: i128,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the most one account may spend on a purpose
+    /// per UTC day (admin only)
+    pub fn set_daily_quota(env: Env, purpose: Symbol, quota: Option<i128>) {
+        telemetry::record(&env, "set_daily_quota");
+        require_admin(&env);
+
+        let mut quotas = Self::daily_quotas(env.clone());
+        match quota {
+            Some(quota) => {
+                if quota <= 0 {
+                    panic_with_error!(&env, Error::InvalidAmount);
+                }
+                quotas.set(purpose, quota);
+                if quotas.len() > MAX_QUOTA_PURPOSES {
+                    panic_with_error!(&env, Error::BudgetExceeded);
+                }
+            }
+            None => {
+                quotas.remove(purpose);
+            }
+        }
+        if quotas.is_empty() {
+            env.storage().instance().remove(&QuotaKey::DailyQuotas);
+        } else {
+            env.storage()
+                .instance()
+                .set(&QuotaKey::DailyQuotas, &quotas);
+        }
+    }
+
+    /// Get the daily quotas, by purpose
+    pub fn daily_quotas(env: Env) -> Map<Symbol, i128> {
+        env.storage()
+            .instance()
+            .get(&QuotaKey::DailyQuotas)
+            .unwrap_or_else(|| Map::new(&env))
+    }
+
+    /// Get what an account has spent on a purpose so far today
+    pub fn spent_today(env: Env, account: Address, purpose: Symbol) -> i128 {
+        read_spend(&env, &account, &purpose).spent
+    }
+}
+
+/// Count `amount` spent by `account` on `purpose` today, failing with
+/// BudgetExceeded past the purpose's daily quota
+pub(crate) fn spend(env: &Env, account: &Address, amount: i128, purpose: &Symbol) {
+    let Some(quota) = EduPassToken::daily_quotas(env.clone()).get(purpose.clone()) else {
+        return;
+    };
+
+    let mut spend = read_spend(env, account, purpose);
+    spend.spent = checked_add(env, spend.spent, amount);
+    if spend.spent > quota {
+        panic_with_error!(env, Error::BudgetExceeded);
+    }
+    // Kept until the day ends
+    let left_today = (spend.day + 1) * DAY_SECS - env.ledger().timestamp();
+    storage::set_temporary(
+        env,
+        &QuotaKey::DailySpent(account.clone(), purpose.clone()),
+        &spend,
+        left_today,
+    );
+}
+
+// Today's spend, starting from zero on a new day
+fn read_spend(env: &Env, account: &Address, purpose: &Symbol) -> DailySpend {
+    let today = env.ledger().timestamp() / DAY_SECS;
+    env.storage()
+        .temporary()
+        .get(&QuotaKey::DailySpent(account.clone(), purpose.clone()))
+        .filter(|spend: &DailySpend| spend.day == today)
+        .unwrap_or(DailySpend {
+            day: today,
+            spent: 0,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario};
+    use soroban_sdk::symbol_short;
+
+    #[test]
+    fn test_quota_resets_at_utc_midnight() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        let (meals, books) = (symbol_short!("meals"), symbol_short!("books"));
+        client.set_daily_quota(&meals, &Some(30));
+        // Start an hour before midnight
+        let now = s.env.ledger().timestamp();
+        advance(&s.env, DAY_SECS - now % DAY_SECS - 3600);
+
+        let (beneficiary, merchant) = (s.beneficiary(0), s.school(0));
+        client.transfer_for_purpose(&beneficiary, &merchant, &20, &meals);
+        let result = client.try_transfer_for_purpose(&beneficiary, &merchant, &11, &meals);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+        assert_eq!(client.spent_today(&beneficiary, &meals), 20);
+        // Other purposes and plain transfers don't count
+        client.transfer_for_purpose(&beneficiary, &merchant, &100, &books);
+        client.transfer(&beneficiary, &merchant, &100);
+
+        advance(&s.env, 3600);
+        assert_eq!(client.spent_today(&beneficiary, &meals), 0);
+        client.transfer_for_purpose(&beneficiary, &merchant, &30, &meals);
+
+        client.set_daily_quota(&meals, &None);
+        assert!(client.daily_quotas().is_empty());
+        client.transfer_for_purpose(&beneficiary, &merchant, &30, &meals);
+    }
+}
@@ -0,0 +1,242 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, Address, Env};
+
+use crate::{
+    checked_add, require_admin, rotation, telemetry, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum RedemptionKey {
+    RedemptionPolicy(Address), // Pacing terms for a school's redemptions
+    RedemptionWindow(Address), // A school's redemptions under its policy so far
+    PolicedSchools,            // Schools with a redemption policy
+}
+
+// How often, how small and how much a school may redeem
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedemptionPolicy {
+    pub min_interval_secs: u64, // Least time between two redemptions
+    pub min_amount: i128,       // Smallest redemption
+    pub max_per_period: i128,   // Most redeemed per period (0 for no limit)
+    pub period_secs: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct RedemptionWindow {
+    last_redeemed_at: u64,
+    period_started_at: u64,
+    redeemed: i128, // Redeemed since period_started_at
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) a school's redemption policy (admin only)
+    pub fn set_redemption_policy(env: Env, school: Address, policy: Option<RedemptionPolicy>) {
+        telemetry::record(&env, "set_redemption_policy");
+        require_admin(&env);
+
+        let key = RedemptionKey::RedemptionPolicy(school);
+        let had_policy = env.storage().persistent().has(&key);
+        let policed: u32 = env
+            .storage()
+            .instance()
+            .get(&RedemptionKey::PolicedSchools)
+            .unwrap_or(0);
+        match policy {
+            Some(policy) => {
+                if policy.min_amount < 0
+                    || policy.max_per_period < 0
+                    || (policy.max_per_period > 0
+                        && (policy.period_secs == 0 || policy.max_per_period < policy.min_amount))
+                {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage().persistent().set(&key, &policy);
+                if !had_policy {
+                    set_policed(&env, policed + 1);
+                }
+            }
+            None => {
+                env.storage().persistent().remove(&key);
+                if had_policy {
+                    set_policed(&env, policed - 1);
+                }
+            }
+        }
+    }
+
+    /// Get a school's redemption policy, if set
+    pub fn redemption_policy(env: Env, school: Address) -> Option<RedemptionPolicy> {
+        env.storage()
+            .persistent()
+            .get(&RedemptionKey::RedemptionPolicy(school))
+    }
+
+    /// Earliest time a school's next redemption is allowed (0 if it can
+    /// redeem now without limits)
+    pub fn next_redemption_at(env: Env, school: Address) -> u64 {
+        let Some(policy) = Self::redemption_policy(env.clone(), school.clone()) else {
+            return 0;
+        };
+        let window = current_window(&env, &school, &policy);
+        let mut next = match window.last_redeemed_at {
+            0 => 0,
+            last => last.saturating_add(policy.min_interval_secs),
+        };
+        // A full period waits for the next one
+        if policy.max_per_period > 0
+            && policy.max_per_period - window.redeemed < policy.min_amount.max(1)
+        {
+            next = next.max(window.period_started_at.saturating_add(policy.period_secs));
+        }
+        next
+    }
+}
+
+/// Enforce a school's redemption policy on a redemption of `amount`
+pub(crate) fn record_redemption(env: &Env, school: &Address, amount: i128) {
+    // Spares the lookup on every redemption until a policy is set
+    if !env.storage().instance().has(&RedemptionKey::PolicedSchools) {
+        return;
+    }
+    let Some(policy) = EduPassToken::redemption_policy(env.clone(), school.clone()) else {
+        return;
+    };
+
+    let now = env.ledger().timestamp();
+    let mut window = current_window(env, school, &policy);
+    let earliest = window
+        .last_redeemed_at
+        .saturating_add(policy.min_interval_secs);
+    if window.last_redeemed_at != 0 && now < earliest {
+        panic_with_error!(env, Error::RedemptionTooSoon);
+    }
+    if amount < policy.min_amount {
+        panic_with_error!(env, Error::BelowMinTransfer);
+    }
+    window.redeemed = checked_add(env, window.redeemed, amount);
+    if policy.max_per_period > 0 && window.redeemed > policy.max_per_period {
+        panic_with_error!(env, Error::BudgetExceeded);
+    }
+
+    window.last_redeemed_at = now;
+    env.storage()
+        .persistent()
+        .set(&RedemptionKey::RedemptionWindow(school.clone()), &window);
+}
+
+/// Move a school's redemption policy and its window under it to the
+/// school's new key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    if rotation::move_persistent(
+        env,
+        &RedemptionKey::RedemptionPolicy(old.clone()),
+        &RedemptionKey::RedemptionPolicy(new.clone()),
+    ) {
+        let policed: u32 = env
+            .storage()
+            .instance()
+            .get(&RedemptionKey::PolicedSchools)
+            .unwrap_or(0);
+        set_policed(env, policed - 1);
+    }
+    rotation::move_persistent(
+        env,
+        &RedemptionKey::RedemptionWindow(old.clone()),
+        &RedemptionKey::RedemptionWindow(new.clone()),
+    );
+}
+
+// A school's window, restarted once its period has run out
+fn current_window(env: &Env, school: &Address, policy: &RedemptionPolicy) -> RedemptionWindow {
+    let now = env.ledger().timestamp();
+    let mut window: RedemptionWindow = env
+        .storage()
+        .persistent()
+        .get(&RedemptionKey::RedemptionWindow(school.clone()))
+        .unwrap_or_default();
+    if now >= window.period_started_at.saturating_add(policy.period_secs) {
+        window.period_started_at = now;
+        window.redeemed = 0;
+    }
+    window
+}
+
+fn set_policed(env: &Env, count: u32) {
+    if count == 0 {
+        env.storage()
+            .instance()
+            .remove(&RedemptionKey::PolicedSchools);
+    } else {
+        env.storage()
+            .instance()
+            .set(&RedemptionKey::PolicedSchools, &count);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+
+    // Weekly redemptions of at least 100, at most 500 per 30 days
+    fn setup() -> Scenario {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        client.set_redemption_policy(
+            &s.school(0),
+            &Some(RedemptionPolicy {
+                min_interval_secs: 7 * DAY_SECS,
+                min_amount: 100,
+                max_per_period: 500,
+                period_secs: 30 * DAY_SECS,
+            }),
+        );
+        client.transfer(&s.beneficiary(0), &s.school(0), &1000);
+        s
+    }
+
+    #[test]
+    fn test_redemptions_paced_by_interval_and_size() {
+        let s = setup();
+        let client = s.client();
+        assert_eq!(client.next_redemption_at(&s.school(0)), 0);
+
+        let result = client.try_burn(&s.school(0), &99);
+        assert_eq!(result, Err(Ok(Error::BelowMinTransfer.into())));
+        client.burn(&s.school(0), &100);
+        let next = s.now() + 7 * DAY_SECS;
+        assert_eq!(client.next_redemption_at(&s.school(0)), next);
+        let result = client.try_burn(&s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::RedemptionTooSoon.into())));
+
+        advance(&s.env, 7 * DAY_SECS);
+        client.burn(&s.school(0), &100);
+    }
+
+    #[test]
+    fn test_period_limit() {
+        let s = setup();
+        let client = s.client();
+        let period_start = s.now();
+        client.burn(&s.school(0), &300);
+        advance(&s.env, 7 * DAY_SECS);
+        let result = client.try_burn(&s.school(0), &201);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+        client.burn(&s.school(0), &200);
+
+        // The period is full, so the next redemption waits for it to end
+        assert_eq!(
+            client.next_redemption_at(&s.school(0)),
+            period_start + 30 * DAY_SECS
+        );
+        client.set_redemption_policy(&s.school(0), &None);
+        client.burn(&s.school(0), &1);
+    }
+}
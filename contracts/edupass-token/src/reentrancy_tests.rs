@@ -0,0 +1,86 @@
+// Re-entry through cross-contract callbacks. The host refuses any call
+// into a contract that is already on the call stack, so a callback can
+// never observe or modify state mid-update; these tests pin that down
+// with a malicious recipient that tries to re-enter `transfer`.
+
+use soroban_sdk::xdr::{ScErrorCode, ScErrorType};
+use soroban_sdk::{contract, contractimpl, vec, Address, Env, Error, IntoVal, Symbol};
+
+use crate::testutils::Scenario;
+
+// A recipient whose callback tries to pull credits back out of the
+// token contract that is calling it
+#[contract]
+struct MaliciousRecipient;
+
+#[contractimpl]
+impl MaliciousRecipient {
+    pub fn on_receive(env: Env, token: Address, from: Address, amount: i128) {
+        let me = env.current_contract_address();
+        env.invoke_contract::<()>(
+            &token,
+            &Symbol::new(&env, "transfer"),
+            (from, me, amount).into_val(&env),
+        );
+    }
+}
+
+// What a transfer hook would do: call the recipient from inside the
+// token contract while it is mid-invocation
+fn notify(s: &Scenario, recipient: &Address, from: &Address, amount: i128) -> Option<Error> {
+    let client = MaliciousRecipientClient::new(&s.env, recipient);
+    s.env.as_contract(&s.contract_id, || {
+        match client.try_on_receive(&s.contract_id, from, &amount) {
+            Err(Ok(error)) => Some(error),
+            _ => None,
+        }
+    })
+}
+
+#[test]
+fn test_callback_cannot_reenter_transfer() {
+    let s = Scenario::new()
+        .with_admin()
+        .with_issuers(1)
+        .with_beneficiaries(1)
+        .issue(0, 0, 1000);
+    // The callback's transfer needs the victim's auth deep in the stack;
+    // grant it so only the re-entry itself can stop the call
+    s.env.mock_all_auths_allowing_non_root_auth();
+    let recipient = s.env.register_contract(None, MaliciousRecipient);
+    let client = s.client();
+    client.transfer(&s.beneficiary(0), &recipient, &100);
+
+    // The host aborts the nested call before `transfer` runs
+    let reentry = Error::from_type_and_code(ScErrorType::Context, ScErrorCode::InvalidAction);
+    assert_eq!(
+        notify(&s, &recipient, &s.beneficiary(0), 900),
+        Some(reentry)
+    );
+    assert_eq!(client.balance(&s.beneficiary(0)), 900);
+    assert_eq!(client.balance(&recipient), 100);
+    let sample = vec![&s.env, s.beneficiary(0), recipient];
+    assert!(client.check_invariants(&sample).ok);
+}
+
+#[test]
+fn test_direct_call_from_recipient_is_allowed() {
+    let s = Scenario::new()
+        .with_admin()
+        .with_issuers(1)
+        .with_beneficiaries(1)
+        .issue(0, 0, 1000);
+    s.env.mock_all_auths_allowing_non_root_auth();
+    let recipient = s.env.register_contract(None, MaliciousRecipient);
+    let client = s.client();
+    client.transfer(&s.beneficiary(0), &recipient, &100);
+
+    // Outside a token invocation the same call is an ordinary transfer,
+    // so the failure above comes from the re-entry and nothing else
+    MaliciousRecipientClient::new(&s.env, &recipient).on_receive(
+        &s.contract_id,
+        &s.beneficiary(0),
+        &50,
+    );
+    assert_eq!(client.balance(&recipient), 150);
+}
@@ -0,0 +1,294 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, symbol_short, Address, Env};
+
+use crate::{
+    add_total_issued, checked_add, credit, events, is_beneficiary, require_admin, rotation,
+    storage::AccountState, telemetry, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum ReferralKey {
+    ReferralConfig,           // Referral bonus terms (absent when disabled)
+    ReferralBudget,           // Credits set aside for referral bonuses
+    Referral(Address),        // Referrer of a beneficiary awaiting its first redemption
+    ReferralsPaid(Address),   // Bonuses paid to a referrer
+    FlaggedReferrer(Address), // Referrer suspected of fraud; earns nothing
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReferralConfig {
+    pub bonus: i128,           // Paid per beneficiary's first redemption
+    pub max_per_referrer: u32, // Bonuses one referrer can earn in total
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Referral {
+    pub referrer: Address,
+    pub registered_at: u64,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Enable, update or disable (None) referral bonuses (admin only)
+    pub fn set_referral_config(env: Env, config: Option<ReferralConfig>) {
+        telemetry::record(&env, "set_referral_config");
+        require_admin(&env);
+
+        match config {
+            Some(config) => {
+                if config.bonus <= 0 || config.max_per_referrer == 0 {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage()
+                    .instance()
+                    .set(&ReferralKey::ReferralConfig, &config);
+            }
+            None => env
+                .storage()
+                .instance()
+                .remove(&ReferralKey::ReferralConfig),
+        }
+    }
+
+    /// Get the referral bonus terms, if enabled
+    pub fn referral_config(env: Env) -> Option<ReferralConfig> {
+        referral_config(&env)
+    }
+
+    /// Mint credits into the budget that pays referral bonuses
+    pub fn fund_referral_budget(env: Env, issuer: Address, amount: i128) {
+        telemetry::record(&env, "fund_referral_budget");
+        issuer.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        set_budget_balance(&env, checked_add(&env, budget_balance(&env), amount));
+        add_total_issued(&env, &None, amount);
+    }
+
+    /// Credits left in the referral budget
+    pub fn referral_budget(env: Env) -> i128 {
+        budget_balance(&env)
+    }
+
+    /// Record who referred a verified enrollment (admin only). Must happen
+    /// before the beneficiary's first issuance.
+    pub fn register_referral(env: Env, referrer: Address, beneficiary: Address) {
+        telemetry::record(&env, "register_referral");
+        require_admin(&env);
+
+        if referrer == beneficiary {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        if is_flagged(&env, &referrer) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        let key = ReferralKey::Referral(beneficiary.clone());
+        if is_beneficiary(&env, &beneficiary) || env.storage().persistent().has(&key) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+
+        let referral = Referral {
+            referrer,
+            registered_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&key, &referral);
+    }
+
+    /// Get the pending referral for a beneficiary
+    pub fn referral(env: Env, beneficiary: Address) -> Option<Referral> {
+        env.storage()
+            .persistent()
+            .get(&ReferralKey::Referral(beneficiary))
+    }
+
+    /// Number of bonuses paid to a referrer
+    pub fn referrals_paid(env: Env, referrer: Address) -> u32 {
+        referrals_paid(&env, &referrer)
+    }
+
+    /// Flag or clear a referrer suspected of fraud (admin only). Flagged
+    /// referrers can't register referrals and earn no bonuses.
+    pub fn flag_referrer(env: Env, referrer: Address, flagged: bool) {
+        telemetry::record(&env, "flag_referrer");
+        require_admin(&env);
+
+        let key = ReferralKey::FlaggedReferrer(referrer.clone());
+        if flagged {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        events::publish(&env, (symbol_short!("refflag"), referrer), flagged);
+    }
+
+    /// Check whether a referrer is flagged
+    pub fn is_referrer_flagged(env: Env, referrer: Address) -> bool {
+        is_flagged(&env, &referrer)
+    }
+}
+
+/// Settle a beneficiary's referral on its first redemption: the referrer
+/// is paid unless flagged, capped or the budget has run dry. Called after
+/// the transfer's balances are written.
+pub(crate) fn on_redemption(
+    env: &Env,
+    beneficiary: &Address,
+    sender: &AccountState,
+    recipient: &AccountState,
+) {
+    if !sender.is_beneficiary() || recipient.is_beneficiary() {
+        return;
+    }
+    let Some(config) = referral_config(env) else {
+        return;
+    };
+    let key = ReferralKey::Referral(beneficiary.clone());
+    let Some(referral) = env.storage().persistent().get::<_, Referral>(&key) else {
+        return;
+    };
+    env.storage().persistent().remove(&key);
+
+    let referrer = referral.referrer;
+    let paid = referrals_paid(env, &referrer);
+    let budget = budget_balance(env);
+    let bonus =
+        if is_flagged(env, &referrer) || paid >= config.max_per_referrer || budget < config.bonus {
+            0
+        } else {
+            set_budget_balance(env, budget - config.bonus);
+            credit(env, &referrer, config.bonus);
+            env.storage()
+                .persistent()
+                .set(&ReferralKey::ReferralsPaid(referrer.clone()), &(paid + 1));
+            config.bonus
+        };
+    events::publish(
+        env,
+        (symbol_short!("referral"), referrer, beneficiary.clone()),
+        bonus,
+    );
+}
+
+/// Move a referrer's record, flag included, to its rotated key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &ReferralKey::ReferralsPaid(old.clone()),
+        &ReferralKey::ReferralsPaid(new.clone()),
+    );
+    rotation::move_persistent(
+        env,
+        &ReferralKey::FlaggedReferrer(old.clone()),
+        &ReferralKey::FlaggedReferrer(new.clone()),
+    );
+}
+
+/// Credits held in the referral budget
+pub(crate) fn budget_balance(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&ReferralKey::ReferralBudget)
+        .unwrap_or(0)
+}
+
+fn set_budget_balance(env: &Env, balance: i128) {
+    env.storage()
+        .instance()
+        .set(&ReferralKey::ReferralBudget, &balance);
+}
+
+fn referral_config(env: &Env) -> Option<ReferralConfig> {
+    env.storage().instance().get(&ReferralKey::ReferralConfig)
+}
+
+fn referrals_paid(env: &Env, referrer: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&ReferralKey::ReferralsPaid(referrer.clone()))
+        .unwrap_or(0)
+}
+
+fn is_flagged(env: &Env, referrer: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&ReferralKey::FlaggedReferrer(referrer.clone()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::testutils::Address as _;
+
+    fn setup() -> (Scenario, Address) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(4);
+        let referrer = Address::generate(&s.env);
+        let client = s.client();
+        client.set_referral_config(&Some(ReferralConfig {
+            bonus: 50,
+            max_per_referrer: 2,
+        }));
+        client.fund_referral_budget(&s.issuer(0), &1000);
+        for i in 0..4 {
+            client.register_referral(&referrer, &s.beneficiary(i));
+        }
+        (s, referrer)
+    }
+
+    #[test]
+    fn test_bonus_paid_on_first_redemption() {
+        let (s, referrer) = setup();
+        let s = s.issue(0, 0, 1000);
+        let client = s.client();
+
+        // Registration has to come before the first issuance
+        let result = client.try_register_referral(&Address::generate(&s.env), &s.beneficiary(0));
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(client.balance(&referrer), 50);
+        assert_eq!(client.referral_budget(), 950);
+        assert_eq!(client.referral(&s.beneficiary(0)), None);
+
+        // Only the first redemption pays
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(client.balance(&referrer), 50);
+        assert!(
+            client
+                .check_invariants(&soroban_sdk::vec![&s.env, referrer])
+                .ok
+        );
+    }
+
+    #[test]
+    fn test_caps_and_flags_withhold_bonus() {
+        let (s, referrer) = setup();
+        let s = (0..4).fold(s, |s, i| s.issue(0, i, 1000));
+        let client = s.client();
+
+        client.flag_referrer(&referrer, &true);
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(client.balance(&referrer), 0);
+        let result = client.try_register_referral(&referrer, &Address::generate(&s.env));
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        client.flag_referrer(&referrer, &false);
+        client.transfer(&s.beneficiary(1), &s.school(0), &100);
+        client.transfer(&s.beneficiary(2), &s.school(0), &100);
+        assert_eq!(client.balance(&referrer), 100);
+
+        // The per-referrer cap is reached
+        client.transfer(&s.beneficiary(3), &s.school(0), &100);
+        assert_eq!(client.balance(&referrer), 100);
+        assert_eq!(client.referrals_paid(&referrer), 2);
+        assert_eq!(client.referral_budget(), 900);
+    }
+}
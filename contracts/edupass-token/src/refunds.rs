@@ -0,0 +1,381 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, vec, Address, Env, Map, String, Vec,
+};
+
+use crate::{
+    checked_add, community, credit, demurrage, events, grace, issue,
+    math::{self, Rounding},
+    require_admin, telemetry, validity, write_balance, Allocation, DataKey, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum RefundKey {
+    Funders(Address),               // Contributions behind a beneficiary's allocation
+    FunderRefund(Address, Address), // Total refunded to (beneficiary, funder)
+    ExpiryPolicy,                   // What sweep_expired does with remainders
+}
+
+// Destination of an expired allocation's remaining credits
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExpiryPolicy {
+    Refund,  // Back to the funders, pro rata (default)
+    Escheat, // Into the community pool
+}
+
+// One funder's share of a co-funded allocation
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Contribution {
+    pub funder: Address,
+    pub amount: i128,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Issue an allocation funded by several funders; the first funder is
+    /// recorded as the allocation's issuer
+    pub fn issue_cofunded(
+        env: Env,
+        beneficiary: Address,
+        contributions: Vec<Contribution>,
+        purpose: String,
+        expires_at: u64,
+    ) -> Allocation {
+        telemetry::record(&env, "issue_cofunded");
+        if contributions.is_empty() {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+
+        let mut total = 0i128;
+        for contribution in contributions.iter() {
+            contribution.funder.require_auth();
+            if contribution.amount <= 0 {
+                panic_with_error!(&env, Error::InvalidAmount);
+            }
+            total = checked_add(&env, total, contribution.amount);
+        }
+
+        let lead = contributions.get_unchecked(0).funder;
+        let expires_at = validity::resolve_expiry(&env, expires_at);
+        let allocation = issue(
+            &env,
+            lead,
+            beneficiary.clone(),
+            total,
+            purpose,
+            expires_at,
+            None,
+            Map::new(&env),
+        );
+
+        // Attribute each funder's share; the issued event names only the lead
+        for contribution in contributions.iter() {
+            events::publish(
+                &env,
+                (
+                    symbol_short!("funded"),
+                    beneficiary.clone(),
+                    contribution.funder,
+                ),
+                contribution.amount,
+            );
+        }
+        env.storage()
+            .persistent()
+            .set(&RefundKey::Funders(beneficiary), &contributions);
+
+        allocation
+    }
+
+    /// Claw back a beneficiary's remaining credits to its funders (admin only);
+    /// returns the amount refunded
+    pub fn clawback(env: Env, beneficiary: Address) -> i128 {
+        require_admin(&env);
+
+        let refunded = refund_funders(&env, &beneficiary);
+        // Counting the funders costs a read, so only telemetry builds do
+        #[cfg(feature = "telemetry")]
+        let funders = funders_of(&env, &beneficiary).len();
+        events::publish(&env, (symbol_short!("clawback"), beneficiary), refunded);
+        #[cfg(feature = "telemetry")]
+        telemetry::record_sized(&env, "clawback", funders);
+
+        refunded
+    }
+
+    /// Set what happens to expired remainders (admin only)
+    pub fn set_expiry_policy(env: Env, policy: ExpiryPolicy) {
+        telemetry::record(&env, "set_expiry_policy");
+        require_admin(&env);
+        env.storage()
+            .instance()
+            .set(&RefundKey::ExpiryPolicy, &policy);
+    }
+
+    /// Get the expiry policy
+    pub fn expiry_policy(env: Env) -> ExpiryPolicy {
+        expiry_policy(&env)
+    }
+
+    /// Sweep an expired allocation's remaining credits according to the
+    /// expiry policy; callable by anyone once the allocation has expired
+    pub fn sweep_expired(env: Env, beneficiary: Address) -> i128 {
+        let allocation: Allocation = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Allocations(beneficiary.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+
+        if !grace::is_expired(&env, &allocation) {
+            panic_with_error!(&env, Error::NotExpired);
+        }
+
+        let swept = sweep(&env, &beneficiary);
+        events::publish(&env, (symbol_short!("expired"), beneficiary), swept);
+        telemetry::record(&env, "sweep_expired");
+
+        swept
+    }
+
+    /// Get the contributions behind a beneficiary's allocation
+    pub fn funders(env: Env, beneficiary: Address) -> Vec<Contribution> {
+        funders_of(&env, &beneficiary)
+    }
+
+    /// Get the total refunded to a funder from a beneficiary's allocations
+    pub fn refunded_to(env: Env, beneficiary: Address, funder: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&RefundKey::FunderRefund(beneficiary, funder))
+            .unwrap_or(0)
+    }
+}
+
+/// Dispose of an expired allocation's remainder according to the expiry policy
+pub(crate) fn sweep(env: &Env, beneficiary: &Address) -> i128 {
+    match expiry_policy(env) {
+        ExpiryPolicy::Refund => refund_funders(env, beneficiary),
+        ExpiryPolicy::Escheat => community::escheat(env, beneficiary),
+    }
+}
+
+fn expiry_policy(env: &Env) -> ExpiryPolicy {
+    env.storage()
+        .instance()
+        .get(&RefundKey::ExpiryPolicy)
+        .unwrap_or(ExpiryPolicy::Refund)
+}
+
+/// Forget the funders of a beneficiary's previous allocation
+pub(crate) fn clear_funders(env: &Env, beneficiary: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&RefundKey::Funders(beneficiary.clone()));
+}
+
+/// Move the beneficiary's whole remaining balance back to its funders.
+/// Accounts without an allocation have no funders and keep their balance.
+pub(crate) fn refund_funders(env: &Env, beneficiary: &Address) -> i128 {
+    refund_part(env, beneficiary, i128::MAX)
+}
+
+/// Move up to `amount` of the beneficiary's balance back to its funders,
+/// pro rata; returns the amount refunded. The funders are forgotten once
+/// nothing is left.
+pub(crate) fn refund_part(env: &Env, beneficiary: &Address, amount: i128) -> i128 {
+    let balance = demurrage::settle(env, beneficiary, true);
+    let remaining = balance.min(amount);
+    if remaining <= 0 {
+        return 0;
+    }
+
+    let contributions = funders_of(env, beneficiary);
+    if contributions.is_empty() {
+        return 0;
+    }
+    let shares = split_pro_rata(env, remaining, &contributions);
+
+    write_balance(env, beneficiary, balance - remaining);
+
+    for (contribution, share) in contributions.iter().zip(shares.iter()) {
+        if share == 0 {
+            continue;
+        }
+        let funder = contribution.funder;
+        credit(env, &funder, share);
+
+        let key = RefundKey::FunderRefund(beneficiary.clone(), funder.clone());
+        let refunded: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&key, &checked_add(env, refunded, share));
+
+        events::publish(
+            env,
+            (symbol_short!("refund"), beneficiary.clone(), funder),
+            share,
+        );
+    }
+
+    if remaining == balance {
+        clear_funders(env, beneficiary);
+    }
+
+    remaining
+}
+
+// Recorded contributions, or the issuer as sole funder of a plain allocation
+fn funders_of(env: &Env, beneficiary: &Address) -> Vec<Contribution> {
+    if let Some(contributions) = env
+        .storage()
+        .persistent()
+        .get(&RefundKey::Funders(beneficiary.clone()))
+    {
+        return contributions;
+    }
+
+    match env
+        .storage()
+        .persistent()
+        .get::<_, Allocation>(&DataKey::Allocations(beneficiary.clone()))
+    {
+        Some(allocation) => vec![
+            env,
+            Contribution {
+                funder: allocation.issuer,
+                amount: allocation.amount,
+            },
+        ],
+        None => Vec::new(env),
+    }
+}
+
+// Split `amount` in proportion to contributions, rounding each share down and
+// giving the remainder to the largest funder (the first one on ties)
+fn split_pro_rata(env: &Env, amount: i128, contributions: &Vec<Contribution>) -> Vec<i128> {
+    let mut shares = Vec::new(env);
+
+    let mut total = 0i128;
+    let mut largest = 0u32;
+    for (i, contribution) in contributions.iter().enumerate() {
+        total = checked_add(env, total, contribution.amount);
+        if contribution.amount > contributions.get_unchecked(largest).amount {
+            largest = i as u32;
+        }
+    }
+    if total == 0 {
+        return shares;
+    }
+
+    let mut allocated = 0i128;
+    for contribution in contributions.iter() {
+        let share = math::mul_div(env, amount, contribution.amount, total, Rounding::Down);
+        allocated += share;
+        shares.push_back(share);
+    }
+
+    let remainder = amount - allocated;
+    shares.set(largest, shares.get_unchecked(largest) + remainder);
+
+    shares
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    fn contribution(funder: &Address, amount: i128) -> Contribution {
+        Contribution {
+            funder: funder.clone(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_split_remainder_to_largest() {
+        let env = Env::default();
+        let a = Address::generate(&env);
+        let b = Address::generate(&env);
+        let c = Address::generate(&env);
+
+        let contributions = vec![
+            &env,
+            contribution(&a, 100),
+            contribution(&b, 600),
+            contribution(&c, 300),
+        ];
+        assert_eq!(
+            split_pro_rata(&env, 667, &contributions),
+            vec![&env, 66, 401, 200]
+        );
+
+        // Ties go to the first of the largest funders
+        let contributions = vec![&env, contribution(&a, 1), contribution(&b, 1)];
+        assert_eq!(split_pro_rata(&env, 3, &contributions), vec![&env, 2, 1]);
+    }
+
+    #[test]
+    fn test_clawback_refunds_cofunders() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let ngo = Address::generate(&env);
+        let ministry = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+
+        client.initialize(&admin);
+        client.issue_cofunded(
+            &beneficiary,
+            &vec![&env, contribution(&ngo, 300), contribution(&ministry, 700)],
+            &String::from_str(&env, "Tuition"),
+            &1735689600,
+        );
+        assert_eq!(client.funders(&beneficiary).len(), 2);
+        client.transfer(&beneficiary, &school, &501);
+
+        assert_eq!(client.clawback(&beneficiary), 499);
+        assert_eq!(client.balance(&beneficiary), 0);
+        assert_eq!(client.balance(&ngo), 149);
+        assert_eq!(client.balance(&ministry), 350);
+        assert_eq!(client.refunded_to(&beneficiary, &ministry), 350);
+    }
+
+    #[test]
+    fn test_sweep_expired_refunds_issuer() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(1_700_000_000);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+
+        client.initialize(&admin);
+        client.issue_credits(
+            &issuer,
+            &beneficiary,
+            &1000,
+            &String::from_str(&env, "Tuition"),
+            &1_700_000_100,
+        );
+
+        let result = client.try_sweep_expired(&beneficiary);
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+
+        env.ledger().set_timestamp(1_700_000_100);
+        assert_eq!(client.sweep_expired(&beneficiary), 1000);
+        assert_eq!(client.balance(&issuer), 1000);
+        assert_eq!(client.sweep_expired(&beneficiary), 0);
+    }
+}
@@ -0,0 +1,754 @@
+// Opt-in school registry. While configured, every payment from a
+// beneficiary to a non-beneficiary asks the registry contract's
+// `is_school(school) -> bool` whether the payee is a listed school. When
+// the registry is unreachable or misconfigured (missing, trapping or
+// answering something else), the fallback policy decides: fail closed
+// with RegistryUnavailable, or trust the school's last answer from the
+// registry for up to `max_staleness_secs`. Schools the registry doesn't
+// list fail with Unauthorized either way.
+//
+// Answers can be cached in temporary storage for `cache_ttl_secs` so busy
+// schools don't cost a cross-contract call per payment. The registry
+// pushes changes by calling `invalidate_school_status`, which drops the
+// cached and last-known answers for the schools it names.
+//
+// Separately, the registry manager can restrict a school to a set of
+// spending purposes, so a driving school can't take `books` credits.
+// Purpose-tagged payments to such a school must carry one of its purposes.
+//
+// Programs also pay bookshops, transport providers and clinics. The
+// registry manager types payees as merchants, and the admin gives each
+// type a policy bounding the payments it takes and whether it may redeem.
+// The registry contract speaks for schools only, so merchants of the other
+// types are payable without asking it.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, vec, Address, Env, IntoVal, Symbol,
+    Vec,
+};
+
+use crate::{
+    events, require_admin, rotation, storage, telemetry, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum RegistryKey {
+    RegistryConfig,               // School registry and fallback (absent when disabled)
+    KnownSchool(Address),         // A school's last answer from the registry
+    CachedSchool(Address),        // A recent answer payments reuse (temporary)
+    RegistryManager,              // Address allowed to edit school purpose sets
+    SchoolPurposes(Address),      // Purposes a school accepts (absent when unrestricted)
+    RestrictedSchools,            // Schools with a purpose set
+    MerchantKind(Address),        // A payee's merchant type (absent when untyped)
+    MerchantCount,                // Payees with a merchant type
+    MerchantPolicy(MerchantType), // Payment and redemption policy of a merchant type
+}
+
+// Kinds of payee a program can pay
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MerchantType {
+    School,
+    Bookshop,
+    Transport,
+    Clinic,
+}
+
+// What merchants of one type may take and do
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MerchantPolicy {
+    pub payable: bool,     // Whether beneficiaries may pay them at all
+    pub max_payment: i128, // Largest single payment from a beneficiary (0 for no limit)
+    pub redeemable: bool,  // Whether they may burn (redeem) the credits they hold
+}
+
+// Purposes one school can accept
+const MAX_SCHOOL_PURPOSES: u32 = 16;
+
+// What a school payment does when the registry can't answer
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RegistryFallback {
+    FailClosed,    // Fail with RegistryUnavailable
+    LastKnownGood, // Use the school's last answer, if recent enough
+}
+
+// The registry consulted on school payments
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryConfig {
+    pub registry: Address,
+    pub fallback: RegistryFallback,
+    pub max_staleness_secs: u64, // Oldest last-known answer LastKnownGood trusts
+    pub cache_ttl_secs: u64,     // How long payments reuse an answer (0 disables caching)
+}
+
+// The registry's last answer for a school
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SchoolStatus {
+    pub listed: bool,
+    pub checked_at: u64,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Enable, update or disable (None) the school registry (admin only).
+    /// The registry isn't probed here, so it can be configured while down.
+    pub fn set_registry_config(env: Env, config: Option<RegistryConfig>) {
+        telemetry::record(&env, "set_registry_config");
+        require_admin(&env);
+
+        match config {
+            Some(config) => env
+                .storage()
+                .instance()
+                .set(&RegistryKey::RegistryConfig, &config),
+            None => env
+                .storage()
+                .instance()
+                .remove(&RegistryKey::RegistryConfig),
+        }
+    }
+
+    /// Get the registry config, if enabled
+    pub fn registry_config(env: Env) -> Option<RegistryConfig> {
+        env.storage().instance().get(&RegistryKey::RegistryConfig)
+    }
+
+    /// Get a school's last answer from the registry. Answers are only kept
+    /// under the LastKnownGood fallback.
+    pub fn known_school_status(env: Env, school: Address) -> Option<SchoolStatus> {
+        env.storage()
+            .persistent()
+            .get(&RegistryKey::KnownSchool(school))
+    }
+
+    /// Get the answer payments to a school reuse, while it is cached
+    pub fn cached_school_status(env: Env, school: Address) -> Option<SchoolStatus> {
+        let config = Self::registry_config(env.clone())?;
+        cached(&env, &config, &school)
+    }
+
+    /// Drop the cached and last-known answers for schools whose status
+    /// changed (the configured registry only), so the next payment asks
+    /// the registry again
+    pub fn invalidate_school_status(env: Env, schools: Vec<Address>) {
+        telemetry::record(&env, "invalidate_school_status");
+        let config = Self::registry_config(env.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        config.registry.require_auth();
+
+        for school in schools.iter() {
+            env.storage()
+                .temporary()
+                .remove(&RegistryKey::CachedSchool(school.clone()));
+            env.storage()
+                .persistent()
+                .remove(&RegistryKey::KnownSchool(school.clone()));
+            events::publish(&env, (symbol_short!("reginval"), school), ());
+        }
+    }
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the registry manager (admin only)
+    pub fn set_registry_manager(env: Env, manager: Option<Address>) {
+        telemetry::record(&env, "set_registry_manager");
+        require_admin(&env);
+
+        match manager {
+            Some(manager) => env
+                .storage()
+                .instance()
+                .set(&RegistryKey::RegistryManager, &manager),
+            None => env
+                .storage()
+                .instance()
+                .remove(&RegistryKey::RegistryManager),
+        }
+    }
+
+    /// Get the registry manager, if set
+    pub fn registry_manager(env: Env) -> Option<Address> {
+        env.storage().instance().get(&RegistryKey::RegistryManager)
+    }
+
+    /// Restrict a school to the given spending purposes, or lift the
+    /// restriction with an empty list (registry manager only)
+    pub fn set_school_purposes(env: Env, school: Address, purposes: Vec<Symbol>) {
+        telemetry::record(&env, "set_school_purposes");
+        require_manager(&env);
+
+        if purposes.len() > MAX_SCHOOL_PURPOSES {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        let key = RegistryKey::SchoolPurposes(school.clone());
+        let was_restricted = env.storage().persistent().has(&key);
+        let restricted = restricted_schools(&env);
+        if purposes.is_empty() {
+            env.storage().persistent().remove(&key);
+            if was_restricted {
+                set_restricted_schools(&env, restricted - 1);
+            }
+        } else {
+            env.storage().persistent().set(&key, &purposes);
+            if !was_restricted {
+                set_restricted_schools(&env, restricted + 1);
+            }
+        }
+        events::publish(&env, (symbol_short!("schpurp"), school), purposes);
+    }
+
+    /// Get the purposes a school accepts (empty when unrestricted)
+    pub fn school_purposes(env: Env, school: Address) -> Vec<Symbol> {
+        env.storage()
+            .persistent()
+            .get(&RegistryKey::SchoolPurposes(school))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Check whether a school accepts payments tagged with a purpose, for
+    /// wallets filtering their payee lists
+    pub fn accepts_purpose(env: Env, school: Address, purpose: Symbol) -> bool {
+        check_purpose(&env, &school, Some(&purpose)).is_ok()
+    }
+
+    /// Type a payee as a merchant, or untype it (None) (registry manager
+    /// only)
+    pub fn set_merchant_type(env: Env, merchant: Address, kind: Option<MerchantType>) {
+        telemetry::record(&env, "set_merchant_type");
+        require_manager(&env);
+
+        let key = RegistryKey::MerchantKind(merchant.clone());
+        let was_typed = env.storage().persistent().has(&key);
+        let count = merchant_count(&env);
+        match kind {
+            Some(kind) => {
+                env.storage().persistent().set(&key, &kind);
+                if !was_typed {
+                    set_merchant_count(&env, count + 1);
+                }
+                events::publish(&env, (symbol_short!("merchant"), merchant), kind);
+            }
+            None => {
+                env.storage().persistent().remove(&key);
+                if was_typed {
+                    set_merchant_count(&env, count - 1);
+                }
+                events::publish(&env, (symbol_short!("untyped"), merchant), ());
+            }
+        }
+    }
+
+    /// Get a payee's merchant type, if typed
+    pub fn merchant_type(env: Env, merchant: Address) -> Option<MerchantType> {
+        env.storage()
+            .persistent()
+            .get(&RegistryKey::MerchantKind(merchant))
+    }
+
+    /// Set or clear (None) the policy of a merchant type (admin only).
+    /// Types without a policy are unrestricted.
+    pub fn set_merchant_policy(env: Env, kind: MerchantType, policy: Option<MerchantPolicy>) {
+        telemetry::record(&env, "set_merchant_policy");
+        require_admin(&env);
+
+        let key = RegistryKey::MerchantPolicy(kind);
+        match policy {
+            Some(policy) => {
+                if policy.max_payment < 0 {
+                    panic_with_error!(&env, Error::InvalidAmount);
+                }
+                env.storage().instance().set(&key, &policy);
+            }
+            None => env.storage().instance().remove(&key),
+        }
+    }
+
+    /// Get the policy of a merchant type, if set
+    pub fn merchant_policy(env: Env, kind: MerchantType) -> Option<MerchantPolicy> {
+        env.storage()
+            .instance()
+            .get(&RegistryKey::MerchantPolicy(kind))
+    }
+}
+
+/// Check that `payee` may take a payment of `amount` from a beneficiary:
+/// its merchant type's policy allows it, and, unless it's typed as a
+/// merchant other than a school, the registry lists it as a school. With
+/// `record` set the registry answer is recorded, as for `check_school`.
+pub(crate) fn check_payee(
+    env: &Env,
+    payee: &Address,
+    amount: i128,
+    record: bool,
+) -> Result<(), Error> {
+    if let Some(kind) = typed(env, payee) {
+        if let Some(policy) = EduPassToken::merchant_policy(env.clone(), kind) {
+            if !policy.payable || (policy.max_payment > 0 && amount > policy.max_payment) {
+                return Err(Error::Unauthorized);
+            }
+        }
+        if kind != MerchantType::School {
+            return Ok(());
+        }
+    }
+    check_school(env, payee, record)
+}
+
+/// `check_payee` for a payment, failing with its error
+pub(crate) fn require_payee(env: &Env, payee: &Address, amount: i128) {
+    if let Err(error) = check_payee(env, payee, amount, true) {
+        panic_with_error!(env, error);
+    }
+}
+
+/// Fail with Unauthorized if `merchant`'s type may not redeem
+pub(crate) fn require_redeemable(env: &Env, merchant: &Address) {
+    let Some(kind) = typed(env, merchant) else {
+        return;
+    };
+    if let Some(policy) = EduPassToken::merchant_policy(env.clone(), kind) {
+        if !policy.redeemable {
+            panic_with_error!(env, Error::Unauthorized);
+        }
+    }
+}
+
+// A payee's merchant type, read only once any payee is typed so
+// deployments without merchants keep their payment footprint
+fn typed(env: &Env, payee: &Address) -> Option<MerchantType> {
+    if merchant_count(env) == 0 {
+        return None;
+    }
+    EduPassToken::merchant_type(env.clone(), payee.clone())
+}
+
+fn merchant_count(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&RegistryKey::MerchantCount)
+        .unwrap_or(0)
+}
+
+fn set_merchant_count(env: &Env, count: u32) {
+    env.storage()
+        .instance()
+        .set(&RegistryKey::MerchantCount, &count);
+}
+
+/// Require the registry manager's authorization, failing with
+/// NotConfigured while there is none
+pub(crate) fn require_manager(env: &Env) {
+    let manager = EduPassToken::registry_manager(env.clone())
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotConfigured));
+    manager.require_auth();
+}
+
+/// Move a school's registry answer, purposes and merchant type to its new
+/// key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &RegistryKey::KnownSchool(old.clone()),
+        &RegistryKey::KnownSchool(new.clone()),
+    );
+    if rotation::move_persistent(
+        env,
+        &RegistryKey::SchoolPurposes(old.clone()),
+        &RegistryKey::SchoolPurposes(new.clone()),
+    ) {
+        set_restricted_schools(env, restricted_schools(env) - 1);
+    }
+    if rotation::move_persistent(
+        env,
+        &RegistryKey::MerchantKind(old.clone()),
+        &RegistryKey::MerchantKind(new.clone()),
+    ) {
+        set_merchant_count(env, merchant_count(env) - 1);
+    }
+}
+
+/// Fail with Unauthorized if `school` is restricted to purposes that
+/// don't include `purpose`; a restricted school takes no untagged (None)
+/// payments. Purpose sets are only read once some school has one, so
+/// deployments without them keep their payment footprint.
+pub(crate) fn check_purpose(
+    env: &Env,
+    school: &Address,
+    purpose: Option<&Symbol>,
+) -> Result<(), Error> {
+    if restricted_schools(env) == 0 {
+        return Ok(());
+    }
+    let purposes = EduPassToken::school_purposes(env.clone(), school.clone());
+    match purpose {
+        _ if purposes.is_empty() => Ok(()),
+        Some(purpose) if purposes.contains(purpose) => Ok(()),
+        _ => Err(Error::Unauthorized),
+    }
+}
+
+fn restricted_schools(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&RegistryKey::RestrictedSchools)
+        .unwrap_or(0)
+}
+
+fn set_restricted_schools(env: &Env, count: u32) {
+    env.storage()
+        .instance()
+        .set(&RegistryKey::RestrictedSchools, &count);
+}
+
+/// Check that the registry, if configured, lists `school` as a payee.
+/// With `record` set (a real payment rather than a dry run) a fresh answer
+/// is cached and kept for the LastKnownGood fallback, and falling back
+/// emits a `regstale` event so the outage shows up in the event stream.
+fn check_school(env: &Env, school: &Address, record: bool) -> Result<(), Error> {
+    let Some(config) = EduPassToken::registry_config(env.clone()) else {
+        return Ok(());
+    };
+    if let Some(status) = cached(env, &config, school) {
+        return listed(status.listed);
+    }
+
+    let now = env.ledger().timestamp();
+    let listed_now = match query(env, &config.registry, school) {
+        Some(listed) => {
+            let status = SchoolStatus {
+                listed,
+                checked_at: now,
+            };
+            if record && config.cache_ttl_secs > 0 {
+                storage::set_temporary(
+                    env,
+                    &RegistryKey::CachedSchool(school.clone()),
+                    &status,
+                    config.cache_ttl_secs,
+                );
+            }
+            if record && config.fallback == RegistryFallback::LastKnownGood {
+                env.storage()
+                    .persistent()
+                    .set(&RegistryKey::KnownSchool(school.clone()), &status);
+            }
+            listed
+        }
+        None => {
+            let known = match config.fallback {
+                RegistryFallback::FailClosed => None,
+                RegistryFallback::LastKnownGood => {
+                    EduPassToken::known_school_status(env.clone(), school.clone())
+                        .filter(|status| now - status.checked_at <= config.max_staleness_secs)
+                }
+            };
+            let Some(status) = known else {
+                return Err(Error::RegistryUnavailable);
+            };
+            if record {
+                events::publish(
+                    env,
+                    (symbol_short!("regstale"), school.clone()),
+                    (status.listed, status.checked_at),
+                );
+            }
+            status.listed
+        }
+    };
+    listed(listed_now)
+}
+
+// A cached answer still within the cache TTL
+fn cached(env: &Env, config: &RegistryConfig, school: &Address) -> Option<SchoolStatus> {
+    if config.cache_ttl_secs == 0 {
+        return None;
+    }
+    let status: SchoolStatus = env
+        .storage()
+        .temporary()
+        .get(&RegistryKey::CachedSchool(school.clone()))?;
+    (env.ledger().timestamp() - status.checked_at < config.cache_ttl_secs).then_some(status)
+}
+
+fn listed(listed: bool) -> Result<(), Error> {
+    if listed {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
+// Ask the registry about a school; None if it can't answer
+fn query(env: &Env, registry: &Address, school: &Address) -> Option<bool> {
+    match env.try_invoke_contract::<bool, soroban_sdk::Error>(
+        registry,
+        &Symbol::new(env, "is_school"),
+        vec![env, school.into_val(env)],
+    ) {
+        Ok(Ok(listed)) => Some(listed),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{contract, contractimpl};
+
+    #[contract]
+    struct Registry;
+
+    #[contractimpl]
+    impl Registry {
+        pub fn set_listed(env: Env, school: Address, listed: bool) {
+            env.storage().instance().set(&school, &listed);
+        }
+
+        pub fn set_down(env: Env, down: bool) {
+            env.storage().instance().set(&symbol_short!("down"), &down);
+        }
+
+        pub fn is_school(env: Env, school: Address) -> bool {
+            if env
+                .storage()
+                .instance()
+                .get(&symbol_short!("down"))
+                .unwrap_or(false)
+            {
+                panic!("registry down");
+            }
+            let calls: u32 = Self::calls(env.clone());
+            env.storage()
+                .instance()
+                .set(&symbol_short!("calls"), &(calls + 1));
+            env.storage().instance().get(&school).unwrap_or(false)
+        }
+
+        pub fn calls(env: Env) -> u32 {
+            env.storage()
+                .instance()
+                .get(&symbol_short!("calls"))
+                .unwrap_or(0)
+        }
+    }
+
+    fn setup(fallback: RegistryFallback) -> (Scenario, RegistryClient<'static>) {
+        setup_cached(fallback, 0)
+    }
+
+    fn setup_cached(
+        fallback: RegistryFallback,
+        cache_ttl_secs: u64,
+    ) -> (Scenario, RegistryClient<'static>) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(2)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let registry = RegistryClient::new(&s.env, &s.env.register_contract(None, Registry));
+        registry.set_listed(&s.school(0), &true);
+        s.client().set_registry_config(&Some(RegistryConfig {
+            registry: registry.address.clone(),
+            fallback,
+            max_staleness_secs: DAY_SECS,
+            cache_ttl_secs,
+        }));
+        (s, registry)
+    }
+
+    #[test]
+    fn test_payments_need_a_listed_school() {
+        let (s, _) = setup(RegistryFallback::FailClosed);
+        let client = s.client();
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        let result = client.try_transfer(&s.beneficiary(0), &s.school(1), &100);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        let result = client.try_can_transfer(&s.beneficiary(0), &s.school(1), &100);
+        assert_eq!(result, Err(Ok(Error::Unauthorized)));
+        // Refunds and beneficiary payments don't consult the registry
+        client.transfer(&s.school(0), &s.beneficiary(0), &50);
+        // Nor are answers kept when failing closed
+        assert_eq!(client.known_school_status(&s.school(0)), None);
+    }
+
+    #[test]
+    fn test_fail_closed_reports_the_outage() {
+        let (s, registry) = setup(RegistryFallback::FailClosed);
+        let client = s.client();
+        registry.set_down(&true);
+        let result = client.try_transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::RegistryUnavailable.into())));
+
+        // An address without the registry interface is misconfigured
+        let mut config = client.registry_config().unwrap();
+        config.registry = Address::generate(&s.env);
+        client.set_registry_config(&Some(config));
+        let result = client.try_transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::RegistryUnavailable.into())));
+    }
+
+    #[test]
+    fn test_last_known_good_is_bounded_by_staleness() {
+        let (s, registry) = setup(RegistryFallback::LastKnownGood);
+        let client = s.client();
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        let known = client.known_school_status(&s.school(0)).unwrap();
+        assert!(known.listed);
+
+        registry.set_down(&true);
+        advance(&s.env, DAY_SECS);
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(client.balance(&s.school(0)), 200);
+        // Schools never seen fail closed
+        let result = client.try_transfer(&s.beneficiary(0), &s.school(1), &100);
+        assert_eq!(result, Err(Ok(Error::RegistryUnavailable.into())));
+
+        advance(&s.env, 1);
+        let result = client.try_transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::RegistryUnavailable.into())));
+    }
+
+    #[test]
+    fn test_cached_answers_save_registry_calls() {
+        let (s, registry) = setup_cached(RegistryFallback::FailClosed, 60);
+        let client = s.client();
+        for _ in 0..3 {
+            client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        }
+        assert_eq!(registry.calls(), 1);
+        assert!(client.cached_school_status(&s.school(0)).unwrap().listed);
+
+        advance(&s.env, 60);
+        assert_eq!(client.cached_school_status(&s.school(0)), None);
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(registry.calls(), 2);
+    }
+
+    #[test]
+    fn test_cached_and_fresh_paths_agree() {
+        let outcomes = |cache_ttl_secs: u64| {
+            let (s, registry) = setup_cached(RegistryFallback::LastKnownGood, cache_ttl_secs);
+            let client = s.client();
+            let school = s.school(0);
+            let pay = || client.try_transfer(&s.beneficiary(0), &school, &10).is_ok();
+            let mut outcomes = [pay(); 5];
+            // The registry delists the school and pushes the change
+            registry.set_listed(&school, &false);
+            client.invalidate_school_status(&vec![&s.env, school.clone()]);
+            assert_eq!(s.env.auths()[0].0, registry.address);
+            outcomes[1] = pay();
+            registry.set_listed(&school, &true);
+            client.invalidate_school_status(&vec![&s.env, school.clone()]);
+            outcomes[2] = pay();
+            advance(&s.env, 30);
+            outcomes[3] = pay();
+            // An invalidation also drops the last-known answer, so an
+            // outage right after a delisting can't fall back to it
+            registry.set_listed(&school, &false);
+            client.invalidate_school_status(&vec![&s.env, school.clone()]);
+            registry.set_down(&true);
+            outcomes[4] = pay();
+            outcomes
+        };
+        assert_eq!(outcomes(0), outcomes(60));
+        assert_eq!(outcomes(60), [true, false, true, true, false]);
+    }
+
+    #[test]
+    fn test_school_purpose_sets() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(2)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        let manager = Address::generate(&s.env);
+        let (driving, books) = (symbol_short!("driving"), symbol_short!("books"));
+        let result = client.try_set_school_purposes(&s.school(0), &vec![&s.env, driving.clone()]);
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+        client.set_registry_manager(&Some(manager.clone()));
+        client.set_school_purposes(&s.school(0), &vec![&s.env, driving.clone()]);
+        assert_eq!(s.env.auths()[0].0, manager);
+
+        assert!(!client.accepts_purpose(&s.school(0), &books));
+        assert!(client.accepts_purpose(&s.school(1), &books));
+        let result = client.try_transfer_for_purpose(&s.beneficiary(0), &s.school(0), &100, &books);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        client.transfer_for_purpose(&s.beneficiary(0), &s.school(0), &100, &driving);
+        client.transfer_for_purpose(&s.beneficiary(0), &s.school(1), &100, &books);
+        // Untagged payments can't sidestep the restriction, whichever
+        // entrypoint makes them
+        let result = client.try_transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        let result = client.try_can_transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::Unauthorized)));
+        let hold_id = client.place_hold(&s.beneficiary(0), &s.school(0), &100, &(s.now() + 60));
+        let result = client.try_consume_hold(&s.school(0), &hold_id);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        client.transfer(&s.beneficiary(0), &s.school(1), &100);
+
+        client.set_school_purposes(&s.school(0), &Vec::new(&s.env));
+        assert!(client.school_purposes(&s.school(0)).is_empty());
+        client.transfer_for_purpose(&s.beneficiary(0), &s.school(0), &100, &books);
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(client.balance(&s.school(0)), 300);
+    }
+
+    #[test]
+    fn test_merchant_types_and_policies() {
+        let (s, registry) = setup(RegistryFallback::FailClosed);
+        let client = s.client();
+        client.set_registry_manager(&Some(Address::generate(&s.env)));
+        let (bookshop, clinic) = (Address::generate(&s.env), Address::generate(&s.env));
+        client.set_merchant_type(&bookshop, &Some(MerchantType::Bookshop));
+        client.set_merchant_type(&clinic, &Some(MerchantType::Clinic));
+        client.set_merchant_type(&s.school(0), &Some(MerchantType::School));
+        client.set_merchant_policy(
+            &MerchantType::Bookshop,
+            &Some(MerchantPolicy {
+                payable: true,
+                max_payment: 50,
+                redeemable: false,
+            }),
+        );
+        client.set_merchant_policy(
+            &MerchantType::Clinic,
+            &Some(MerchantPolicy {
+                payable: false,
+                max_payment: 0,
+                redeemable: true,
+            }),
+        );
+
+        // Typed merchants other than schools don't need the registry
+        let beneficiary = s.beneficiary(0);
+        client.transfer(&beneficiary, &bookshop, &50);
+        let result = client.try_transfer(&beneficiary, &bookshop, &51);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        let result = client.try_can_transfer(&beneficiary, &clinic, &1);
+        assert_eq!(result, Err(Ok(Error::Unauthorized)));
+        let result = client.try_burn(&bookshop, &10);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        // Schools still go through the registry
+        registry.set_listed(&s.school(0), &false);
+        let result = client.try_transfer(&beneficiary, &s.school(0), &10);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        assert_eq!(
+            client.merchant_type(&s.school(0)),
+            Some(MerchantType::School)
+        );
+
+        client.set_merchant_type(&bookshop, &None);
+        let result = client.try_transfer(&beneficiary, &bookshop, &10);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    }
+}
@@ -0,0 +1,89 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, BytesN, Env};
+
+use crate::{storage, EduPassToken, EduPassTokenClient, Error};
+
+#[contracttype]
+enum ReplayKey {
+    Consumed(BytesN<32>), // Hash of a signed payload that has already been acted on
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Check whether a signed payload hash has been consumed
+    pub fn is_consumed(env: Env, hash: BytesN<32>) -> bool {
+        is_consumed(&env, &hash)
+    }
+}
+
+/// Consume the hash of a signed payload, failing with NonceAlreadyUsed if
+/// it was seen before. Payloads valid until a deadline are remembered at
+/// least until then, however fast ledgers close, and refused with
+/// SignatureExpired after; payloads without one are remembered for good.
+pub(crate) fn consume(env: &Env, hash: &BytesN<32>, valid_until: Option<u64>) {
+    if is_consumed(env, hash) {
+        panic_with_error!(env, Error::NonceAlreadyUsed);
+    }
+
+    let key = ReplayKey::Consumed(hash.clone());
+    match valid_until {
+        Some(valid_until) => {
+            let now = env.ledger().timestamp();
+            if now > valid_until {
+                panic_with_error!(env, Error::SignatureExpired);
+            }
+            storage::set_outlasting(env, &key, &true, valid_until - now);
+        }
+        None => env.storage().persistent().set(&key, &true),
+    }
+}
+
+fn is_consumed(env: &Env, hash: &BytesN<32>) -> bool {
+    let key = ReplayKey::Consumed(hash.clone());
+    env.storage().temporary().has(&key) || env.storage().persistent().has(&key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance_ledgers, Scenario, DAY_SECS};
+
+    #[test]
+    fn test_consumed_hashes_kept_until_deadline() {
+        let s = Scenario::new().with_admin();
+        let client = s.client();
+        let expiring = BytesN::from_array(&s.env, &[1; 32]);
+        let permanent = BytesN::from_array(&s.env, &[2; 32]);
+        let deadline = s.now() + DAY_SECS;
+        let max_ttl = s.env.storage().max_ttl();
+        s.env.as_contract(&s.contract_id, || {
+            consume(&s.env, &expiring, Some(deadline));
+            consume(&s.env, &permanent, None);
+            s.env.storage().instance().extend_ttl(max_ttl, max_ttl);
+            let key = ReplayKey::Consumed(permanent.clone());
+            s.env
+                .storage()
+                .persistent()
+                .extend_ttl(&key, max_ttl, max_ttl);
+        });
+        assert!(client.is_consumed(&expiring));
+
+        // Once the deadline has long passed the record lapses; the payload
+        // is refused as expired from then on
+        advance_ledgers(&s.env, (2 * DAY_SECS) as u32);
+        assert!(!client.is_consumed(&expiring));
+        assert!(client.is_consumed(&permanent));
+    }
+
+    #[test]
+    fn test_deadlines_past_the_max_ttl_are_kept_persistently() {
+        let s = Scenario::new().with_admin();
+        let hash = BytesN::from_array(&s.env, &[1; 32]);
+        let deadline = s.now() + s.env.storage().max_ttl() as u64;
+        s.env.as_contract(&s.contract_id, || {
+            consume(&s.env, &hash, Some(deadline));
+            let key = ReplayKey::Consumed(hash.clone());
+            assert!(s.env.storage().persistent().has(&key));
+            assert!(!s.env.storage().temporary().has(&key));
+        });
+    }
+}
@@ -0,0 +1,603 @@
+// Reporting on individual beneficiaries. Detailed spending reports are
+// only returned to the approved reporter's authenticated calls, while the
+// aggregate totals stay public. This keeps the privacy policy in the
+// contract's interface; it doesn't hide ledger entries, which anyone
+// running a node can still read.
+//
+// While a reporter is set, the contract also compiles the quarterly filing
+// a ministry asks for: credits issued, spent and redeemed in each quarter,
+// with counts, broken down by program, spending purpose and the region of
+// the school paid. Quarters are the 13-week periods receipt audit roots
+// use. Each breakdown lists at most 32 keys a quarter, in key order, and
+// folds any further keys into `other`, so a filing keeps a fixed layout. A
+// quarter's activity only ever lands in its own filing. Once it has ended
+// the reporter closes it. After a timelock, during which the admin can
+// cancel the close, the quarter is locked and its filing no longer changes.
+//
+// Errors found later are corrected with adjustment entries rather than
+// edits. The reporter posts an entry against the quarter and total it
+// corrects, with a hash of the reasoning held off chain. The entry is
+// listed in that quarter's filing while the quarter is unlocked, and in
+// the running quarter's once it is locked. Totals are never rewritten.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, BytesN, Env, Map, Symbol,
+    Vec,
+};
+
+use crate::{
+    advances, checked_add, events, read_allocation, require_admin, rotation, telemetry,
+    EduPassToken, EduPassTokenClient, Error,
+};
+
+// Filing quarters: 13-week periods counted from the Unix epoch
+const QUARTER_SECS: u64 = 91 * 24 * 60 * 60;
+// Delay between closing a quarter and its lock, long enough for the admin
+// to catch a premature close
+const CLOSE_TIMELOCK_SECS: u64 = 3 * 24 * 60 * 60;
+// Keys one breakdown of a filing lists before folding the rest into `other`
+const MAX_FILING_KEYS: u32 = 32;
+// Adjustment entries one filing can list
+const MAX_ADJUSTMENTS: u32 = 32;
+
+#[contracttype]
+enum ReportingKey {
+    Reporter,              // Address allowed to read per-beneficiary reports
+    Filing(u64),           // A quarter's filing
+    SchoolRegion(Address), // Region a school's payments are filed under
+    RegionedSchools,       // Schools with a region
+}
+
+// A quarter's totals in the layout filed with the ministry
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FilingRecord {
+    pub quarter: u64,
+    pub issued: i128, // Minted, as counted in total_issued
+    pub issuances: u32,
+    pub spent: i128, // Paid by beneficiaries to schools and merchants
+    pub payments: u32,
+    pub redeemed: i128, // Burned
+    pub redemptions: u32,
+    pub issued_by_program: Map<Symbol, i128>, // Issuance outside programs isn't broken down
+    pub spent_by_purpose: Map<Symbol, i128>,  // Spending tagged with transfer_for_purpose
+    pub spent_by_region: Map<Symbol, i128>,   // Payments to schools with a region
+    pub adjustments: Vec<FilingAdjustment>,   // Corrections booked into this quarter
+    pub closed_at: u64,                       // When the quarter locks (0 while open)
+}
+
+// A filing total an adjustment corrects
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FilingField {
+    Issued,
+    Spent,
+    Redeemed,
+}
+
+// A correction to a quarter's filing, listed apart from its totals
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FilingAdjustment {
+    pub period: u64, // The quarter corrected, which may be earlier than the filing's
+    pub program_id: Symbol,
+    pub field: FilingField,
+    pub delta: i128,
+    pub reason_hash: BytesN<32>, // Hash of the explanation held off chain
+    pub posted_at: u64,
+}
+
+// What a beneficiary has been given and spent
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpendingReport {
+    pub allocated: i128, // Amount of the current allocation
+    pub remaining: i128, // Of it, not yet spent
+    pub spent: i128,
+    pub bonus_received: i128,
+    pub advance: i128, // Outstanding advance
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the reporter (admin only)
+    pub fn set_reporter(env: Env, reporter: Option<Address>) {
+        telemetry::record(&env, "set_reporter");
+        require_admin(&env);
+
+        match reporter {
+            Some(reporter) => env
+                .storage()
+                .instance()
+                .set(&ReportingKey::Reporter, &reporter),
+            None => env.storage().instance().remove(&ReportingKey::Reporter),
+        }
+    }
+
+    /// Get the reporter, if set
+    pub fn reporter(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ReportingKey::Reporter)
+    }
+
+    /// Report what a beneficiary has been given and spent (the reporter)
+    pub fn spending_report(env: Env, reporter: Address, beneficiary: Address) -> SpendingReport {
+        let Some(approved) = Self::reporter(env.clone()) else {
+            panic_with_error!(&env, Error::NotConfigured);
+        };
+        if reporter != approved {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        reporter.require_auth();
+
+        let (allocated, remaining) = read_allocation(&env, &beneficiary)
+            .map_or((0, 0), |allocation| {
+                (allocation.amount, allocation.remaining)
+            });
+        SpendingReport {
+            allocated,
+            remaining,
+            spent: allocated - remaining,
+            bonus_received: Self::bonus_received(env.clone(), beneficiary.clone()),
+            advance: advances::outstanding(&env, &beneficiary),
+        }
+    }
+
+    /// Set or clear (None) the region a school's payments are filed under
+    /// (admin only)
+    pub fn set_school_region(env: Env, school: Address, region: Option<Symbol>) {
+        telemetry::record(&env, "set_school_region");
+        require_admin(&env);
+
+        let key = ReportingKey::SchoolRegion(school);
+        let had_region = env.storage().persistent().has(&key);
+        match region {
+            Some(region) => {
+                env.storage().persistent().set(&key, &region);
+                if !had_region {
+                    set_regioned_schools(&env, regioned_schools(&env) + 1);
+                }
+            }
+            None => {
+                if had_region {
+                    env.storage().persistent().remove(&key);
+                    set_regioned_schools(&env, regioned_schools(&env) - 1);
+                }
+            }
+        }
+    }
+
+    /// Get the region a school's payments are filed under, if set
+    pub fn school_region(env: Env, school: Address) -> Option<Symbol> {
+        env.storage()
+            .persistent()
+            .get(&ReportingKey::SchoolRegion(school))
+    }
+
+    /// Get a quarter's filing, numbered from the Unix epoch (the current
+    /// one is `now / 7_862_400`). The running quarter's is still being
+    /// compiled; a closed one no longer changes.
+    pub fn filing(env: Env, quarter: u64) -> Option<FilingRecord> {
+        env.storage()
+            .persistent()
+            .get(&ReportingKey::Filing(quarter))
+    }
+
+    /// Close a quarter that has ended (the reporter). The quarter locks
+    /// after the timelock unless the admin cancels the close.
+    pub fn close_period(env: Env, quarter: u64) {
+        telemetry::record(&env, "close_period");
+        let reporter = Self::reporter(env.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        reporter.require_auth();
+
+        if quarter >= current_quarter(&env) {
+            panic_with_error!(&env, Error::NotExpired);
+        }
+        let mut filing = Self::filing(env.clone(), quarter).unwrap_or(new_filing(&env, quarter));
+        if filing.closed_at != 0 {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        filing.closed_at = env.ledger().timestamp() + CLOSE_TIMELOCK_SECS;
+        write_filing(&env, &filing);
+        events::publish(
+            &env,
+            (symbol_short!("filed"), quarter),
+            (
+                filing.closed_at,
+                filing.issued,
+                filing.spent,
+                filing.redeemed,
+            ),
+        );
+    }
+
+    /// Cancel a quarter's close before it locks (admin only)
+    pub fn cancel_close(env: Env, quarter: u64) {
+        telemetry::record(&env, "cancel_close");
+        require_admin(&env);
+
+        let mut filing = Self::filing(env.clone(), quarter)
+            .filter(|filing| filing.closed_at != 0)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        if Self::period_locked(env.clone(), quarter) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        filing.closed_at = 0;
+        write_filing(&env, &filing);
+        events::publish(&env, (symbol_short!("unfiled"), quarter), ());
+    }
+
+    /// Post a correction to a quarter's filing (the reporter). It is listed
+    /// in that quarter's filing while the quarter is unlocked and in the
+    /// running quarter's once it is locked; returns the quarter it was
+    /// booked into.
+    pub fn post_adjustment(
+        env: Env,
+        period: u64,
+        program_id: Symbol,
+        field: FilingField,
+        delta: i128,
+        reason_hash: BytesN<32>,
+    ) -> u64 {
+        telemetry::record(&env, "post_adjustment");
+        let reporter = Self::reporter(env.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        reporter.require_auth();
+
+        if delta == 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        let current = current_quarter(&env);
+        if period > current {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        let booked_in = if Self::period_locked(env.clone(), period) {
+            current
+        } else {
+            period
+        };
+
+        let mut filing =
+            Self::filing(env.clone(), booked_in).unwrap_or_else(|| new_filing(&env, booked_in));
+        if filing.adjustments.len() >= MAX_ADJUSTMENTS {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        filing.adjustments.push_back(FilingAdjustment {
+            period,
+            program_id: program_id.clone(),
+            field,
+            delta,
+            reason_hash,
+            posted_at: env.ledger().timestamp(),
+        });
+        write_filing(&env, &filing);
+        events::publish(
+            &env,
+            (symbol_short!("adjusted"), booked_in),
+            (period, program_id, field, delta),
+        );
+
+        booked_in
+    }
+
+    /// Check whether a quarter is locked: closed, with the timelock passed
+    pub fn period_locked(env: Env, quarter: u64) -> bool {
+        Self::filing(env.clone(), quarter).is_some_and(|filing| {
+            filing.closed_at != 0 && env.ledger().timestamp() >= filing.closed_at
+        })
+    }
+}
+
+/// File minted credits under the current quarter
+pub(crate) fn record_issued(env: &Env, program_id: &Option<Symbol>, amount: i128) {
+    update_filing(env, |filing| {
+        filing.issued = checked_add(env, filing.issued, amount);
+        filing.issuances += 1;
+        if let Some(program_id) = program_id {
+            add_to(env, &mut filing.issued_by_program, program_id, amount);
+        }
+    });
+}
+
+/// File a beneficiary's payment to a school or merchant under the current
+/// quarter
+pub(crate) fn record_payment(env: &Env, payee: &Address, amount: i128) {
+    update_filing(env, |filing| {
+        filing.spent = checked_add(env, filing.spent, amount);
+        filing.payments += 1;
+        // Only look the payee up once some school has a region
+        if regioned_schools(env) > 0 {
+            if let Some(region) = EduPassToken::school_region(env.clone(), payee.clone()) {
+                add_to(env, &mut filing.spent_by_region, &region, amount);
+            }
+        }
+    });
+}
+
+/// File spending tagged with a purpose under the current quarter. The
+/// payment itself is filed by record_payment.
+pub(crate) fn record_purpose(env: &Env, purpose: &Symbol, amount: i128) {
+    update_filing(env, |filing| {
+        add_to(env, &mut filing.spent_by_purpose, purpose, amount);
+    });
+}
+
+/// File burned credits under the current quarter
+pub(crate) fn record_redeemed(env: &Env, amount: i128) {
+    update_filing(env, |filing| {
+        filing.redeemed = checked_add(env, filing.redeemed, amount);
+        filing.redemptions += 1;
+    });
+}
+
+/// Move a school's region to its rotated key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &ReportingKey::SchoolRegion(old.clone()),
+        &ReportingKey::SchoolRegion(new.clone()),
+    );
+}
+
+// Apply a change to the current quarter's filing; filings are only
+// compiled while a reporter is set
+fn update_filing(env: &Env, change: impl FnOnce(&mut FilingRecord)) {
+    if !env.storage().instance().has(&ReportingKey::Reporter) {
+        return;
+    }
+    let quarter = current_quarter(env);
+    let mut filing =
+        EduPassToken::filing(env.clone(), quarter).unwrap_or_else(|| new_filing(env, quarter));
+    change(&mut filing);
+    write_filing(env, &filing);
+}
+
+// Add to a breakdown, folding keys past the limit into `other`
+fn add_to(env: &Env, breakdown: &mut Map<Symbol, i128>, key: &Symbol, amount: i128) {
+    let key = if breakdown.contains_key(key.clone()) || breakdown.len() < MAX_FILING_KEYS {
+        key.clone()
+    } else {
+        symbol_short!("other")
+    };
+    let total = breakdown.get(key.clone()).unwrap_or(0);
+    breakdown.set(key, checked_add(env, total, amount));
+}
+
+fn current_quarter(env: &Env) -> u64 {
+    env.ledger().timestamp() / QUARTER_SECS
+}
+
+fn new_filing(env: &Env, quarter: u64) -> FilingRecord {
+    FilingRecord {
+        quarter,
+        issued: 0,
+        issuances: 0,
+        spent: 0,
+        payments: 0,
+        redeemed: 0,
+        redemptions: 0,
+        issued_by_program: Map::new(env),
+        spent_by_purpose: Map::new(env),
+        spent_by_region: Map::new(env),
+        adjustments: Vec::new(env),
+        closed_at: 0,
+    }
+}
+
+fn write_filing(env: &Env, filing: &FilingRecord) {
+    env.storage()
+        .persistent()
+        .set(&ReportingKey::Filing(filing.quarter), filing);
+}
+
+fn regioned_schools(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&ReportingKey::RegionedSchools)
+        .unwrap_or(0)
+}
+
+fn set_regioned_schools(env: &Env, count: u32) {
+    env.storage()
+        .instance()
+        .set(&ReportingKey::RegionedSchools, &count);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{map, vec, String};
+
+    #[test]
+    fn test_reports_only_for_the_reporter() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        client.transfer(&s.beneficiary(0), &s.school(0), &300);
+        let reporter = Address::generate(&s.env);
+        let result = client.try_spending_report(&reporter, &s.beneficiary(0));
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+
+        client.set_reporter(&Some(reporter.clone()));
+        let report = client.spending_report(&reporter, &s.beneficiary(0));
+        assert_eq!(s.env.auths()[0].0, reporter);
+        assert_eq!(
+            (report.allocated, report.remaining, report.spent),
+            (1000, 700, 300)
+        );
+        let result = client.try_spending_report(&s.admin(), &s.beneficiary(0));
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        // Aggregates stay public
+        assert_eq!(client.total_issued(), 1000);
+    }
+
+    #[test]
+    fn test_quarterly_filing_freezes_on_close() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(2)
+            .with_beneficiaries(2);
+        let client = s.client();
+        let reporter = Address::generate(&s.env);
+        client.set_reporter(&Some(reporter.clone()));
+        let north = symbol_short!("north");
+        client.set_school_region(&s.school(0), &Some(north.clone()));
+        let uni = symbol_short!("uni");
+        client.save_template(
+            &s.issuer(0),
+            &uni,
+            &String::from_str(&s.env, "Tuition"),
+            &1000,
+            &(200 * 24 * 60 * 60),
+            &uni,
+        );
+        client.issue_from_template(&s.issuer(0), &uni, &s.beneficiary(0));
+        s.issue_allocation(0, 1, 500);
+
+        let books = symbol_short!("books");
+        client.transfer_for_purpose(&s.beneficiary(0), &s.school(0), &300, &books);
+        client.transfer(&s.beneficiary(1), &s.school(1), &200);
+        client.transfer(&s.beneficiary(0), &s.beneficiary(1), &100);
+        client.burn(&s.school(0), &300);
+
+        let quarter = s.now() / QUARTER_SECS;
+        let result = client.try_close_period(&quarter);
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+        advance(&s.env, QUARTER_SECS);
+        // Activity after the quarter ends goes to the next filing
+        client.transfer(&s.beneficiary(1), &s.school(1), &50);
+        client.close_period(&quarter);
+        assert_eq!(s.env.auths()[0].0, reporter);
+        assert!(!client.period_locked(&quarter));
+        // The admin can take a close back within the timelock
+        client.cancel_close(&quarter);
+        client.close_period(&quarter);
+        advance(&s.env, CLOSE_TIMELOCK_SECS);
+        assert!(client.period_locked(&quarter));
+        let result = client.try_cancel_close(&quarter);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+
+        assert_eq!(
+            client.filing(&quarter),
+            Some(FilingRecord {
+                quarter,
+                issued: 1500,
+                issuances: 2,
+                spent: 500,
+                payments: 2,
+                redeemed: 300,
+                redemptions: 1,
+                issued_by_program: map![&s.env, (uni, 1000)],
+                spent_by_purpose: map![&s.env, (books, 300)],
+                spent_by_region: map![&s.env, (north, 300)],
+                adjustments: Vec::new(&s.env),
+                closed_at: s.now(),
+            })
+        );
+        assert_eq!(client.filing(&(quarter + 1)).unwrap().spent, 50);
+        let result = client.try_close_period(&quarter);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+
+    #[test]
+    fn test_filed_issuance_reconciles_with_total_issued() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(3);
+        let client = s.client();
+        client.set_reporter(&Some(Address::generate(&s.env)));
+        client.set_governance(&Address::generate(&s.env));
+        let (food, tuition) = (symbol_short!("food"), symbol_short!("tuition"));
+        client.save_template(
+            &s.issuer(0),
+            &food,
+            &String::from_str(&s.env, "Meals"),
+            &1000,
+            &(200 * 24 * 60 * 60),
+            &food,
+        );
+        client.issue_from_template(&s.issuer(0), &food, &s.beneficiary(0));
+        s.issue_allocation(0, 1, 500);
+
+        // Advances mint; grants and conversions move credits already issued
+        client.set_advance_limit(&Some(100));
+        client.request_advance(&s.beneficiary(1), &100);
+        client.deposit_to_pool(&s.beneficiary(1), &200, &Vec::new(&s.env));
+        let purpose = String::from_str(&s.env, "Emergency Grant");
+        client.grant_from_pool(&s.beneficiary(2), &200, &purpose, &s.expires_at(), &None);
+        client.set_conversion_rate(
+            &food,
+            &tuition,
+            &Some(crate::ConversionRate {
+                rate_bps: 10_000,
+                max_amount: 0,
+                route_cap: 0,
+            }),
+        );
+        client.convert(&s.beneficiary(0), &food, &tuition, &400);
+
+        let filing = client.filing(&(s.now() / QUARTER_SECS)).unwrap();
+        assert_eq!(client.total_issued(), 1600);
+        assert_eq!((filing.issued, filing.issuances), (1600, 3));
+        assert_eq!(filing.issued_by_program, map![&s.env, (food, 1000)]);
+    }
+
+    #[test]
+    fn test_adjustments_to_locked_quarters_book_forward() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        let reporter = Address::generate(&s.env);
+        client.set_reporter(&Some(reporter.clone()));
+        s.issue_allocation(0, 0, 400);
+        let quarter = s.now() / QUARTER_SECS;
+        let (uni, reason) = (symbol_short!("uni"), BytesN::from_array(&s.env, &[3; 32]));
+
+        // An open quarter lists its own corrections
+        let booked = client.post_adjustment(&quarter, &uni, &FilingField::Issued, &-50, &reason);
+        assert_eq!(s.env.auths()[0].0, reporter);
+        assert_eq!(booked, quarter);
+        let result =
+            client.try_post_adjustment(&(quarter + 1), &uni, &FilingField::Issued, &5, &reason);
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+
+        advance(&s.env, QUARTER_SECS);
+        client.close_period(&quarter);
+        advance(&s.env, CLOSE_TIMELOCK_SECS);
+        let booked = client.post_adjustment(&quarter, &uni, &FilingField::Spent, &20, &reason);
+        assert_eq!(booked, quarter + 1);
+
+        // The locked filing keeps its totals and entries; the correction
+        // is listed in the running quarter's
+        let filing = client.filing(&quarter).unwrap();
+        assert_eq!(filing.issued, 400);
+        assert_eq!(filing.adjustments.len(), 1);
+        let next = client.filing(&(quarter + 1)).unwrap();
+        assert_eq!(
+            next.adjustments,
+            vec![
+                &s.env,
+                FilingAdjustment {
+                    period: quarter,
+                    program_id: uni,
+                    field: FilingField::Spent,
+                    delta: 20,
+                    reason_hash: reason,
+                    posted_at: s.now(),
+                }
+            ]
+        );
+    }
+}
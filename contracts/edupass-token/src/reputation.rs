@@ -0,0 +1,482 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, BytesN, Env,
+};
+
+use crate::{
+    events,
+    math::{self, Rounding, BPS},
+    require_admin, rotation,
+    storage::AccountState,
+    telemetry, EduPassToken, EduPassTokenClient, Error,
+};
+
+const MAX_RATING: u32 = 5;
+
+#[contracttype]
+enum ReputationKey {
+    ReputationConfig,     // Reputation tracking terms (absent when disabled)
+    SchoolStats(Address), // Running counters for a school
+    Receipt(u64),         // A redemption, open for the beneficiary's feedback
+    ReceiptCount,         // Last assigned receipt id
+}
+
+// Opt-in reputation tracking and the redemption gate it drives
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReputationConfig {
+    pub settlement_window_secs: u64, // Longest a school should hold credits before burning them
+    pub large_redemption: i128,      // Redemptions this size or larger are gated
+    pub min_score: u32,              // Score (bps) a school needs to accept a large redemption
+}
+
+// Signals behind a school's score, updated as it redeems, refunds and settles
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SchoolStats {
+    pub redemptions: u32,      // Transfers received from beneficiaries
+    pub refunds: u32,          // Transfers sent back to beneficiaries
+    pub settlements: u32,      // Burns
+    pub late_settlements: u32, // Burns after the settlement window
+    pub unsettled_since: u64,  // First redemption not yet settled (0 when settled)
+    pub ratings: u32,          // Feedback records left by beneficiaries
+    pub rating_total: u32,     // Sum of their ratings
+}
+
+// Score in bps: 10_000 less the average of the refund rate, the
+// late-settlement rate and the rating shortfall
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Score {
+    pub score: u32,
+    pub refund_rate_bps: u32, // Refunds per redemption
+    pub late_rate_bps: u32,   // Late settlements per settlement
+    pub rating_bps: u32,      // Average rating as a share of the top rating (10_000 when unrated)
+    pub stats: SchoolStats,
+}
+
+// A redemption recorded while reputation tracking is enabled
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Receipt {
+    pub beneficiary: Address,
+    pub school: Address,
+    pub amount: i128,
+    pub redeemed_at: u64,
+    pub rating: u32,              // 1 to 5 once rated, 0 before
+    pub comment_hash: BytesN<32>, // Hash of the off-chain comment (zero before rating)
+    pub authorized_by: Address,   // The beneficiary, or a spender or school acting for it
+    pub doc_hash: BytesN<32>,     // Hash of the scanned invoice (zero until attached)
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Enable, update or disable (None) school reputation tracking (admin
+    /// only). Counters only move while tracking is enabled.
+    pub fn set_reputation_config(env: Env, config: Option<ReputationConfig>) {
+        telemetry::record(&env, "set_reputation_config");
+        require_admin(&env);
+
+        match config {
+            Some(config) => {
+                if config.large_redemption <= 0 || config.min_score as i128 > BPS {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage()
+                    .instance()
+                    .set(&ReputationKey::ReputationConfig, &config);
+            }
+            None => env
+                .storage()
+                .instance()
+                .remove(&ReputationKey::ReputationConfig),
+        }
+    }
+
+    /// Get the reputation config, if enabled
+    pub fn reputation_config(env: Env) -> Option<ReputationConfig> {
+        reputation_config(&env)
+    }
+
+    /// Get a school's reputation score and the signals behind it
+    pub fn reputation(env: Env, school: Address) -> Score {
+        score(&env, read_stats(&env, &school))
+    }
+
+    /// Get a redemption receipt
+    pub fn receipt(env: Env, receipt_id: u64) -> Option<Receipt> {
+        env.storage()
+            .persistent()
+            .get(&ReputationKey::Receipt(receipt_id))
+    }
+
+    /// Rate a redemption from 1 to 5 with the hash of an off-chain comment
+    /// (the receipt's beneficiary, once per receipt)
+    pub fn rate_redemption(env: Env, receipt_id: u64, rating: u32, comment_hash: BytesN<32>) {
+        telemetry::record(&env, "rate_redemption");
+        let key = ReputationKey::Receipt(receipt_id);
+        let mut receipt: Receipt = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        receipt.beneficiary.require_auth();
+
+        if rating == 0 || rating > MAX_RATING {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if receipt.rating != 0 {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        receipt.rating = rating;
+        receipt.comment_hash = comment_hash;
+        env.storage().persistent().set(&key, &receipt);
+
+        let school = rotation::current(&env, &receipt.school);
+        let mut stats = read_stats(&env, &school);
+        stats.ratings = stats.ratings.saturating_add(1);
+        stats.rating_total = stats.rating_total.saturating_add(rating);
+        write_stats(&env, &school, &stats);
+        events::publish(&env, (symbol_short!("rated"), school), (receipt_id, rating));
+    }
+
+    /// Attach the hash of the scanned invoice behind a redemption (the
+    /// receipt's school, once per receipt), so auditors can check the
+    /// document against it
+    pub fn attach_document(env: Env, receipt_id: u64, doc_hash: BytesN<32>) {
+        telemetry::record(&env, "attach_document");
+        let key = ReputationKey::Receipt(receipt_id);
+        let mut receipt: Receipt = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        let school = rotation::current(&env, &receipt.school);
+        school.require_auth();
+
+        if receipt.doc_hash != zero_hash(&env) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        receipt.doc_hash = doc_hash.clone();
+        env.storage().persistent().set(&key, &receipt);
+        events::publish(
+            &env,
+            (symbol_short!("document"), school),
+            (receipt_id, doc_hash),
+        );
+    }
+}
+
+/// Gate and count a transfer: beneficiary to school is a redemption,
+/// school to beneficiary a refund. Called before balances are written.
+pub(crate) fn record_transfer(
+    env: &Env,
+    authorizer: &Address,
+    from: &Address,
+    sender: &AccountState,
+    to: &Address,
+    recipient: &AccountState,
+    amount: i128,
+) {
+    let Some(config) = reputation_config(env) else {
+        return;
+    };
+
+    match (sender.is_beneficiary(), recipient.is_beneficiary()) {
+        (true, false) => {
+            let mut stats = read_stats(env, to);
+            if blocks_redemption(env, &config, &stats, amount) {
+                panic_with_error!(env, Error::ReputationTooLow);
+            }
+            stats.redemptions = stats.redemptions.saturating_add(1);
+            if stats.unsettled_since == 0 {
+                stats.unsettled_since = env.ledger().timestamp();
+            }
+            write_stats(env, to, &stats);
+            write_receipt(env, authorizer, from, to, amount);
+        }
+        (false, true) => {
+            let mut stats = read_stats(env, from);
+            stats.refunds = stats.refunds.saturating_add(1);
+            write_stats(env, from, &stats);
+        }
+        _ => {}
+    }
+}
+
+/// Dry-run form of record_transfer's gate, for can_transfer
+pub(crate) fn check_transfer(
+    env: &Env,
+    sender: &AccountState,
+    to: &Address,
+    recipient: &AccountState,
+    amount: i128,
+) -> Result<(), Error> {
+    let Some(config) = reputation_config(env) else {
+        return Ok(());
+    };
+    if sender.is_beneficiary()
+        && !recipient.is_beneficiary()
+        && blocks_redemption(env, &config, &read_stats(env, to), amount)
+    {
+        return Err(Error::ReputationTooLow);
+    }
+    Ok(())
+}
+
+// Large redemptions are refused at schools scoring below the minimum
+fn blocks_redemption(
+    env: &Env,
+    config: &ReputationConfig,
+    stats: &SchoolStats,
+    amount: i128,
+) -> bool {
+    amount >= config.large_redemption && score(env, stats.clone()).score < config.min_score
+}
+
+/// Count a school's burn as a settlement, late if it held unsettled
+/// credits past the window
+pub(crate) fn record_burn(env: &Env, account: &Address, remaining: i128) {
+    let Some(config) = reputation_config(env) else {
+        return;
+    };
+
+    let mut stats = read_stats(env, account);
+    if stats.unsettled_since == 0 {
+        return;
+    }
+    let now = env.ledger().timestamp();
+    stats.settlements = stats.settlements.saturating_add(1);
+    if now.saturating_sub(stats.unsettled_since) > config.settlement_window_secs {
+        stats.late_settlements = stats.late_settlements.saturating_add(1);
+    }
+    // Whatever is left over starts a new window
+    stats.unsettled_since = if remaining > 0 { now } else { 0 };
+    write_stats(env, account, &stats);
+}
+
+/// Last assigned receipt id
+pub(crate) fn receipt_count(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&ReputationKey::ReceiptCount)
+        .unwrap_or(0)
+}
+
+/// Load a receipt, failing with NotFound if there is none
+pub(crate) fn read_receipt(env: &Env, receipt_id: u64) -> Receipt {
+    env.storage()
+        .persistent()
+        .get(&ReputationKey::Receipt(receipt_id))
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound))
+}
+
+/// Move a school's counters to its rotated key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &ReputationKey::SchoolStats(old.clone()),
+        &ReputationKey::SchoolStats(new.clone()),
+    );
+}
+
+fn score(env: &Env, stats: SchoolStats) -> Score {
+    let rate = |count: u32, total: u32| match total {
+        0 => 0,
+        _ => math::mul_div(env, count as i128, BPS, total as i128, Rounding::Down).min(BPS) as u32,
+    };
+    let refund_rate_bps = rate(stats.refunds, stats.redemptions);
+    let late_rate_bps = rate(stats.late_settlements, stats.settlements);
+    let rating_bps = match stats.ratings {
+        0 => BPS as u32,
+        _ => rate(stats.rating_total, stats.ratings.saturating_mul(MAX_RATING)),
+    };
+    let penalty = (refund_rate_bps + late_rate_bps + (BPS as u32 - rating_bps)) / 3;
+
+    Score {
+        score: BPS as u32 - penalty,
+        refund_rate_bps,
+        late_rate_bps,
+        rating_bps,
+        stats,
+    }
+}
+
+// Record a redemption so the beneficiary can rate it later
+fn write_receipt(
+    env: &Env,
+    authorizer: &Address,
+    beneficiary: &Address,
+    school: &Address,
+    amount: i128,
+) {
+    let id = receipt_count(env) + 1;
+    env.storage()
+        .instance()
+        .set(&ReputationKey::ReceiptCount, &id);
+
+    let receipt = Receipt {
+        beneficiary: beneficiary.clone(),
+        school: school.clone(),
+        amount,
+        redeemed_at: env.ledger().timestamp(),
+        rating: 0,
+        comment_hash: zero_hash(env),
+        authorized_by: authorizer.clone(),
+        doc_hash: zero_hash(env),
+    };
+    env.storage()
+        .persistent()
+        .set(&ReputationKey::Receipt(id), &receipt);
+    events::publish(
+        env,
+        (
+            symbol_short!("receipt"),
+            beneficiary.clone(),
+            school.clone(),
+        ),
+        (id, amount),
+    );
+}
+
+fn zero_hash(env: &Env) -> BytesN<32> {
+    BytesN::from_array(env, &[0; 32])
+}
+
+fn reputation_config(env: &Env) -> Option<ReputationConfig> {
+    env.storage()
+        .instance()
+        .get(&ReputationKey::ReputationConfig)
+}
+
+fn read_stats(env: &Env, school: &Address) -> SchoolStats {
+    env.storage()
+        .persistent()
+        .get(&ReputationKey::SchoolStats(school.clone()))
+        .unwrap_or_default()
+}
+
+fn write_stats(env: &Env, school: &Address, stats: &SchoolStats) {
+    env.storage()
+        .persistent()
+        .set(&ReputationKey::SchoolStats(school.clone()), stats);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{Scenario, DAY_SECS};
+
+    fn setup() -> Scenario {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(2)
+            .issue(0, 0, 10_000)
+            .issue(0, 1, 10_000);
+        s.client().set_reputation_config(&Some(ReputationConfig {
+            settlement_window_secs: 7 * DAY_SECS,
+            large_redemption: 1000,
+            min_score: 8000,
+        }));
+        s
+    }
+
+    #[test]
+    fn test_score_tracks_refunds_and_late_settlements() {
+        let s = setup();
+        let client = s.client();
+        let school = s.school(0);
+        assert_eq!(client.reputation(&school).score, 10_000);
+
+        client.transfer(&s.beneficiary(0), &school, &100);
+        client.transfer(&s.beneficiary(1), &school, &100);
+        client.transfer(&school, &s.beneficiary(0), &50);
+
+        // Settled on time, then late
+        client.burn(&school, &50);
+        let s = s.advance_days(8);
+        let client = s.client();
+        client.burn(&school, &100);
+
+        let score = client.reputation(&school);
+        assert_eq!(score.refund_rate_bps, 5000);
+        assert_eq!(score.late_rate_bps, 5000);
+        assert_eq!(score.score, 6667);
+        assert_eq!(score.stats.unsettled_since, 0);
+    }
+
+    #[test]
+    fn test_rate_redemption_once() {
+        let s = setup();
+        let client = s.client();
+        let school = s.school(0);
+        client.transfer(&s.beneficiary(0), &school, &100);
+        client.transfer(&s.beneficiary(1), &school, &100);
+
+        let receipt = client.receipt(&1).unwrap();
+        assert_eq!(
+            (receipt.beneficiary, receipt.amount),
+            (s.beneficiary(0), 100)
+        );
+        assert_eq!(receipt.authorized_by, s.beneficiary(0));
+        let hash = BytesN::from_array(&s.env, &[7; 32]);
+        let result = client.try_rate_redemption(&1, &6, &hash);
+        assert_eq!(result, Err(Ok(Error::InvalidAmount.into())));
+
+        client.rate_redemption(&1, &5, &hash);
+        client.rate_redemption(&2, &2, &hash);
+        let result = client.try_rate_redemption(&1, &1, &hash);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+        assert_eq!(client.receipt(&1).unwrap().rating, 5);
+
+        // An average of 3.5 stars is 70% of the top rating
+        let score = client.reputation(&school);
+        assert_eq!(score.rating_bps, 7000);
+        assert_eq!(score.score, 9000);
+    }
+
+    #[test]
+    fn test_attach_document_once() {
+        let s = setup();
+        let client = s.client();
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        let zero = BytesN::from_array(&s.env, &[0; 32]);
+        assert_eq!(client.receipt(&1).unwrap().doc_hash, zero);
+
+        let doc_hash = BytesN::from_array(&s.env, &[3; 32]);
+        client.attach_document(&1, &doc_hash);
+        assert_eq!(s.env.auths()[0].0, s.school(0));
+        assert_eq!(client.receipt(&1).unwrap().doc_hash, doc_hash);
+        // The hash can't be replaced once attached
+        let other = BytesN::from_array(&s.env, &[4; 32]);
+        let result = client.try_attach_document(&1, &other);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+        let result = client.try_attach_document(&2, &other);
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+    }
+
+    #[test]
+    fn test_low_score_blocks_large_redemptions() {
+        let s = setup();
+        let client = s.client();
+        let school = s.school(0);
+        client.transfer(&s.beneficiary(0), &school, &100);
+        client.transfer(&school, &s.beneficiary(1), &100);
+        assert_eq!(client.reputation(&school).score, 6667);
+
+        let result = client.try_transfer(&s.beneficiary(0), &school, &1000);
+        assert_eq!(result, Err(Ok(Error::ReputationTooLow.into())));
+
+        let dry_run = client.try_can_transfer(&s.beneficiary(0), &school, &1000);
+        assert_eq!(dry_run, Err(Ok(Error::ReputationTooLow)));
+
+        // Small redemptions still go through
+        client.can_transfer(&s.beneficiary(0), &school, &999);
+        client.transfer(&s.beneficiary(0), &school, &999);
+
+        // Beneficiary-to-beneficiary transfers aren't redemptions
+        client.transfer(&s.beneficiary(0), &s.beneficiary(1), &1000);
+        assert_eq!(client.reputation(&school).stats.redemptions, 2);
+    }
+}
@@ -0,0 +1,227 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, Address, Env, Vec};
+
+use crate::{
+    checked_add, require_admin, telemetry, wiring, EduPassToken, EduPassTokenClient, Error,
+};
+
+// Bounds the accepted assets listed at once
+const MAX_ACCEPTED_ASSETS: u32 = 10;
+
+#[contracttype]
+enum ReserveKey {
+    AcceptedAssets,        // Assets deposits may be made in (absent when unchecked)
+    ReserveAsset(Address), // Cap and holdings of an accepted asset
+}
+
+// An asset accepted into the contract's reserves
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AcceptedAsset {
+    pub asset: Address,
+    pub cap: i128,  // Most of the asset held from deposits at once
+    pub held: i128, // Deposited (wraps and bonds) and not yet paid back out
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Accept an asset into the reserves with a cap on what may be held,
+    /// or stop accepting it (None) (admin only). Once any asset is listed,
+    /// wraps and bond postings in unlisted assets are refused; holdings of
+    /// a delisted asset can still be paid out.
+    pub fn set_accepted_asset(env: Env, asset: Address, cap: Option<i128>) {
+        telemetry::record(&env, "set_accepted_asset");
+        require_admin(&env);
+
+        let mut assets = listed(&env);
+        let index = assets.first_index_of(&asset);
+        let key = ReserveKey::ReserveAsset(asset.clone());
+        match cap {
+            Some(cap) => {
+                if cap <= 0 {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                if index.is_none() {
+                    wiring::require_asset(&env, &asset);
+                    if assets.len() >= MAX_ACCEPTED_ASSETS {
+                        panic_with_error!(&env, Error::BudgetExceeded);
+                    }
+                    assets.push_back(asset.clone());
+                }
+                let mut accepted = read_asset(&env, &asset);
+                accepted.cap = cap;
+                env.storage().persistent().set(&key, &accepted);
+            }
+            None => {
+                let Some(index) = index else {
+                    return;
+                };
+                assets.remove(index);
+                // Keep the holdings so later payouts are still accounted
+                let mut accepted = read_asset(&env, &asset);
+                accepted.cap = 0;
+                env.storage().persistent().set(&key, &accepted);
+            }
+        }
+        if assets.is_empty() {
+            env.storage().instance().remove(&ReserveKey::AcceptedAssets);
+        } else {
+            env.storage()
+                .instance()
+                .set(&ReserveKey::AcceptedAssets, &assets);
+        }
+    }
+
+    /// Get the assets currently accepted into the reserves, with their caps
+    /// and holdings
+    pub fn accepted_assets(env: Env) -> Vec<AcceptedAsset> {
+        let mut accepted = Vec::new(&env);
+        for asset in listed(&env).iter() {
+            accepted.push_back(read_asset(&env, &asset));
+        }
+        accepted
+    }
+}
+
+/// Account for a deposit of `amount` of `asset` into the reserves, failing
+/// with Unauthorized for an asset that isn't accepted and BudgetExceeded
+/// past its cap. Deposits are unchecked while no asset is listed.
+pub(crate) fn deposit(env: &Env, asset: &Address, amount: i128) {
+    let assets = listed(env);
+    if assets.is_empty() {
+        return;
+    }
+    if !assets.contains(asset) {
+        panic_with_error!(env, Error::Unauthorized);
+    }
+
+    let mut accepted = read_asset(env, asset);
+    accepted.held = checked_add(env, accepted.held, amount);
+    if accepted.held > accepted.cap {
+        panic_with_error!(env, Error::BudgetExceeded);
+    }
+    write_asset(env, &accepted);
+}
+
+/// Account for `amount` of `asset` paid back out of the reserves
+pub(crate) fn withdraw(env: &Env, asset: &Address, amount: i128) {
+    let key = ReserveKey::ReserveAsset(asset.clone());
+    // Only assets that were ever listed are accounted
+    if !env.storage().persistent().has(&key) {
+        return;
+    }
+    let mut accepted = read_asset(env, asset);
+    accepted.held = (accepted.held - amount).max(0);
+    write_asset(env, &accepted);
+}
+
+fn listed(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&ReserveKey::AcceptedAssets)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn read_asset(env: &Env, asset: &Address) -> AcceptedAsset {
+    env.storage()
+        .persistent()
+        .get(&ReserveKey::ReserveAsset(asset.clone()))
+        .unwrap_or(AcceptedAsset {
+            asset: asset.clone(),
+            cap: 0,
+            held: 0,
+        })
+}
+
+fn write_asset(env: &Env, accepted: &AcceptedAsset) {
+    env.storage()
+        .persistent()
+        .set(&ReserveKey::ReserveAsset(accepted.asset.clone()), accepted);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use crate::BondConfig;
+    use soroban_sdk::token::StellarAssetClient;
+
+    fn setup() -> (Scenario, Address, Address) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1);
+        let client = s.client();
+        let usdc = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        let other = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        for asset in [&usdc, &other] {
+            StellarAssetClient::new(&s.env, asset).mint(&s.beneficiary(0), &1000);
+            StellarAssetClient::new(&s.env, asset).mint(&s.issuer(0), &1000);
+        }
+        client.set_accepted_asset(&usdc, &Some(800));
+        (s, usdc, other)
+    }
+
+    #[test]
+    fn test_deposits_limited_to_accepted_assets() {
+        let (s, usdc, other) = setup();
+        let client = s.client();
+
+        client.set_wrap_asset(&Some(other.clone()));
+        let result = client.try_wrap(&s.beneficiary(0), &100);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        client.set_bond_config(&Some(BondConfig {
+            asset: other,
+            min_bond: 100,
+            cooldown_secs: 0,
+        }));
+        let result = client.try_post_bond(&s.issuer(0), &100);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        // Wraps and bonds in an accepted asset share its cap
+        client.set_wrap_asset(&Some(usdc.clone()));
+        client.set_bond_config(&Some(BondConfig {
+            asset: usdc.clone(),
+            min_bond: 100,
+            cooldown_secs: 0,
+        }));
+        client.wrap(&s.beneficiary(0), &500);
+        client.post_bond(&s.issuer(0), &300);
+        let result = client.try_post_bond(&s.issuer(0), &1);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+        assert_eq!(client.accepted_assets().get(0).unwrap().held, 800);
+
+        // Payouts free room under the cap
+        client.withdraw_bond(&s.issuer(0), &200);
+        client.wrap(&s.beneficiary(0), &200);
+        assert_eq!(client.accepted_assets().get(0).unwrap().held, 800);
+    }
+
+    #[test]
+    fn test_delisted_asset_keeps_its_accounting() {
+        let (s, usdc, other) = setup();
+        let client = s.client();
+        client.set_wrap_asset(&Some(usdc.clone()));
+        client.wrap(&s.beneficiary(0), &500);
+        let result = client.try_set_accepted_asset(&other, &Some(0));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+
+        client.set_accepted_asset(&other, &Some(100));
+        client.set_accepted_asset(&usdc, &None);
+        assert_eq!(client.accepted_assets().len(), 1);
+        let result = client.try_wrap(&s.beneficiary(0), &1);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        // Relisting picks up the holdings left from before
+        client.set_accepted_asset(&usdc, &Some(600));
+        let result = client.try_wrap(&s.beneficiary(0), &101);
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+        client.wrap(&s.beneficiary(0), &100);
+    }
+}
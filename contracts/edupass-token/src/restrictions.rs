@@ -0,0 +1,285 @@
+// Donor restrictions on community pool credits. A deposit can carry tags
+// such as `girls` and `secondary`, and its credits are then only granted to
+// programs the admin has tagged with every one of them. Grants use the
+// unrestricted credits (plain deposits and escheats) first, then matching
+// restricted deposits with the fewest restrictions, oldest first among
+// equals, so the most specific money is kept for the programs only it can
+// fund.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Symbol, Vec,
+};
+
+use crate::{
+    checked_add, events, require_admin, telemetry, EduPassToken, EduPassTokenClient, Error,
+};
+
+// Restricted deposits with credits left that a grant may have to scan
+const MAX_OPEN_DEPOSITS: u32 = 32;
+// Restriction tags on one deposit
+const MAX_RESTRICTIONS: u32 = 8;
+
+#[contracttype]
+enum RestrictionKey {
+    ProgramTags(Symbol),    // Tags a program satisfies restrictions with
+    RestrictedDeposit(u64), // A restricted deposit's terms and undrawn credits
+    OpenDeposits,           // Ids of restricted deposits with credits left, oldest first
+    DepositCount,           // Last assigned restricted deposit id
+    RestrictedTotal,        // Undrawn credits across restricted deposits
+}
+
+// A pool deposit its donor restricted to matching programs
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RestrictedDeposit {
+    pub donor: Address,
+    pub restrictions: Vec<Symbol>,
+    pub remaining: i128, // Credits not yet granted
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set the tags a program satisfies deposit restrictions with (admin
+    /// only); an empty list removes them
+    pub fn set_program_tags(env: Env, program_id: Symbol, tags: Vec<Symbol>) {
+        telemetry::record(&env, "set_program_tags");
+        require_admin(&env);
+
+        let key = RestrictionKey::ProgramTags(program_id);
+        if tags.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &tags);
+        }
+    }
+
+    /// Get a program's tags
+    pub fn program_tags(env: Env, program_id: Symbol) -> Vec<Symbol> {
+        env.storage()
+            .persistent()
+            .get(&RestrictionKey::ProgramTags(program_id))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Get a restricted pool deposit, while it has credits left
+    pub fn restricted_deposit(env: Env, deposit_id: u64) -> Option<RestrictedDeposit> {
+        env.storage()
+            .persistent()
+            .get(&RestrictionKey::RestrictedDeposit(deposit_id))
+    }
+
+    /// Get the ids of restricted deposits with credits left, oldest first
+    pub fn open_restricted_deposits(env: Env) -> Vec<u64> {
+        open_deposits(&env)
+    }
+
+    /// Pool credits held under donor restrictions
+    pub fn restricted_pool_balance(env: Env) -> i128 {
+        restricted_total(&env)
+    }
+}
+
+/// Record the restrictions a donor put on a pool deposit, if any
+pub(crate) fn add(env: &Env, donor: &Address, amount: i128, restrictions: Vec<Symbol>) {
+    if restrictions.is_empty() {
+        return;
+    }
+    if restrictions.len() > MAX_RESTRICTIONS {
+        panic_with_error!(env, Error::InvalidConfig);
+    }
+    let mut open = open_deposits(env);
+    if open.len() >= MAX_OPEN_DEPOSITS {
+        panic_with_error!(env, Error::BudgetExceeded);
+    }
+
+    let id = env
+        .storage()
+        .instance()
+        .get(&RestrictionKey::DepositCount)
+        .unwrap_or(0u64)
+        + 1;
+    env.storage()
+        .instance()
+        .set(&RestrictionKey::DepositCount, &id);
+    env.storage().persistent().set(
+        &RestrictionKey::RestrictedDeposit(id),
+        &RestrictedDeposit {
+            donor: donor.clone(),
+            restrictions,
+            remaining: amount,
+        },
+    );
+    open.push_back(id);
+    set_open_deposits(env, &open);
+    set_restricted_total(env, checked_add(env, restricted_total(env), amount));
+}
+
+/// Pool credits free of donor restrictions, out of the pool `balance`
+pub(crate) fn unrestricted(env: &Env, balance: i128) -> i128 {
+    balance - restricted_total(env)
+}
+
+/// Take a grant of `amount` out of a pool holding `balance`, drawing on
+/// restricted deposits the program satisfies once the unrestricted credits
+/// run out. Fails with InsufficientBalance if the matching credits fall
+/// short.
+pub(crate) fn draw(env: &Env, program_id: &Option<Symbol>, amount: i128, balance: i128) {
+    let mut needed = amount - unrestricted(env, balance).max(0);
+    if needed <= 0 {
+        return;
+    }
+
+    let tags = match program_id {
+        Some(program_id) => EduPassToken::program_tags(env.clone(), program_id.clone()),
+        None => Vec::new(env),
+    };
+    let mut matching = Vec::new(env);
+    for id in open_deposits(env).iter() {
+        let deposit = EduPassToken::restricted_deposit(env.clone(), id).unwrap();
+        if deposit
+            .restrictions
+            .iter()
+            .all(|restriction| tags.contains(restriction))
+        {
+            matching.push_back((id, deposit));
+        }
+    }
+
+    let mut drawn_total = 0;
+    let mut emptied = Vec::new(env);
+    for restrictions in 1..=MAX_RESTRICTIONS {
+        for (id, mut deposit) in matching.iter() {
+            if needed == 0 {
+                break;
+            }
+            if deposit.restrictions.len() != restrictions {
+                continue;
+            }
+            let drawn = deposit.remaining.min(needed);
+            needed -= drawn;
+            drawn_total += drawn;
+            deposit.remaining -= drawn;
+            if deposit.remaining == 0 {
+                env.storage()
+                    .persistent()
+                    .remove(&RestrictionKey::RestrictedDeposit(id));
+                emptied.push_back(id);
+            } else {
+                env.storage()
+                    .persistent()
+                    .set(&RestrictionKey::RestrictedDeposit(id), &deposit);
+            }
+            events::publish(
+                env,
+                (symbol_short!("pooldraw"), id),
+                (program_id.clone(), drawn),
+            );
+        }
+    }
+    if needed > 0 {
+        panic_with_error!(env, Error::InsufficientBalance);
+    }
+
+    if !emptied.is_empty() {
+        let mut open = Vec::new(env);
+        for id in open_deposits(env).iter() {
+            if !emptied.contains(id) {
+                open.push_back(id);
+            }
+        }
+        set_open_deposits(env, &open);
+    }
+    set_restricted_total(env, restricted_total(env) - drawn_total);
+}
+
+fn open_deposits(env: &Env) -> Vec<u64> {
+    env.storage()
+        .instance()
+        .get(&RestrictionKey::OpenDeposits)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn set_open_deposits(env: &Env, open: &Vec<u64>) {
+    env.storage()
+        .instance()
+        .set(&RestrictionKey::OpenDeposits, open);
+}
+
+fn restricted_total(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&RestrictionKey::RestrictedTotal)
+        .unwrap_or(0)
+}
+
+fn set_restricted_total(env: &Env, total: i128) {
+    env.storage()
+        .instance()
+        .set(&RestrictionKey::RestrictedTotal, &total);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{vec, String};
+
+    fn setup() -> (Scenario, Symbol) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(4)
+            .issue(0, 1, 300)
+            .issue(0, 2, 300)
+            .issue(0, 3, 100);
+        let client = s.client();
+        client.set_governance(&Address::generate(&s.env));
+        let program = symbol_short!("girls_sec");
+        client.set_program_tags(
+            &program,
+            &vec![&s.env, symbol_short!("girls"), symbol_short!("secondary")],
+        );
+        (s, program)
+    }
+
+    #[test]
+    fn test_draws_least_restricted_matching_deposits_first() {
+        let (s, program) = setup();
+        let client = s.client();
+        let env = &s.env;
+        let both = vec![env, symbol_short!("girls"), symbol_short!("secondary")];
+        client.deposit_to_pool(&s.beneficiary(1), &300, &both);
+        client.deposit_to_pool(&s.beneficiary(2), &300, &vec![env, symbol_short!("girls")]);
+        client.deposit_to_pool(&s.beneficiary(3), &100, &vec![env]);
+        assert_eq!(client.restricted_pool_balance(), 600);
+
+        // Unrestricted credits first, then the single-tag deposit
+        let purpose = String::from_str(env, "Scholarship");
+        let grant = |program: Option<Symbol>, amount: i128| {
+            client.try_grant_from_pool(
+                &s.beneficiary(0),
+                &amount,
+                &purpose,
+                &s.expires_at(),
+                &program,
+            )
+        };
+        assert!(grant(Some(program.clone()), 250).is_ok());
+        assert_eq!(client.restricted_deposit(&2).unwrap().remaining, 150);
+        assert_eq!(client.restricted_deposit(&1).unwrap().remaining, 300);
+        assert!(grant(Some(program.clone()), 200).is_ok());
+        assert_eq!(client.restricted_deposit(&2), None);
+        assert_eq!(client.restricted_deposit(&1).unwrap().remaining, 250);
+        assert_eq!(client.open_restricted_deposits(), vec![env, 1]);
+
+        // Other programs and share withdrawals can't touch restricted credits
+        assert_eq!(grant(None, 1), Err(Ok(Error::InsufficientBalance.into())));
+        let result = client.try_withdraw_from_pool(&s.beneficiary(1), &10);
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+        assert_eq!(
+            grant(Some(program), 251),
+            Err(Ok(Error::InsufficientBalance.into()))
+        );
+    }
+}
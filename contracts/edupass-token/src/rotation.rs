@@ -0,0 +1,308 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, IntoVal, Val,
+};
+
+use crate::{
+    attestations, bonding, demurrage, events, invoices, redemptions, referrals, registry,
+    reporting, reputation, settlement, storage, subaccounts, telemetry, wrap, write_balance,
+    EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum RotationKey {
+    RotatedTo(Address),   // Key an institution moved to from this one
+    RotatedFrom(Address), // Key an institution moved to this one from
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Move an issuer's or school's identity to a new key (the old key and
+    /// the registry manager). Its balance, bond, reputation, roles and
+    /// restrictions move with it, and records naming the old key resolve to
+    /// the new one.
+    pub fn rotate_address(env: Env, old: Address, new: Address) {
+        telemetry::record(&env, "rotate_address");
+        old.require_auth();
+        registry::require_manager(&env);
+
+        if old == new || rotated_to(&env, &old).is_some() {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        // Beneficiary identities are tied to their allocations
+        let from = storage::load_account(&env, &old);
+        if from.is_beneficiary() {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        let to = storage::load_account(&env, &new);
+        if to.is_beneficiary()
+            || to.balance != 0
+            || rotated_to(&env, &new).is_some()
+            || rotated_from(&env, &new).is_some()
+        {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+
+        let balance = demurrage::settle_loaded(&env, &old, &from);
+        write_balance(&env, &old, 0);
+        write_balance(&env, &new, balance);
+        bonding::rotate(&env, &old, &new);
+        reputation::rotate(&env, &old, &new);
+        referrals::rotate(&env, &old, &new);
+        attestations::rotate(&env, &old, &new);
+        wrap::rotate(&env, &old, &new);
+        settlement::rotate(&env, &old, &new);
+        reporting::rotate(&env, &old, &new);
+        registry::rotate(&env, &old, &new);
+        redemptions::rotate(&env, &old, &new);
+        invoices::rotate(&env, &old, &new);
+        subaccounts::rotate(&env, &old, &new);
+        #[cfg(feature = "exams")]
+        crate::exams::rotate(&env, &old, &new);
+        #[cfg(feature = "stipends")]
+        crate::schedule::rotate(&env, &old, &new);
+        #[cfg(feature = "confidential")]
+        crate::confidential::rotate(&env, &old, &new);
+        #[cfg(feature = "disputes")]
+        crate::disputes::rotate(&env, &old, &new);
+
+        env.storage()
+            .persistent()
+            .set(&RotationKey::RotatedTo(old.clone()), &new);
+        env.storage()
+            .persistent()
+            .set(&RotationKey::RotatedFrom(new.clone()), &old);
+        events::publish(&env, (symbol_short!("rotated"), old, new), balance);
+    }
+
+    /// Get the key an address was rotated to, if any
+    pub fn rotated_to(env: Env, old: Address) -> Option<Address> {
+        rotated_to(&env, &old)
+    }
+}
+
+/// The key currently held by the institution behind `address`, following
+/// any rotations
+pub(crate) fn current(env: &Env, address: &Address) -> Address {
+    let mut address = address.clone();
+    while let Some(next) = rotated_to(env, &address) {
+        address = next;
+    }
+    address
+}
+
+/// The key holding an institution's record that can't be moved on
+/// rotation (one of many keyed by the institution and an id): the first of
+/// `address` and the keys it was rotated from that `has` the record, or
+/// `address` when none does
+pub(crate) fn holder(env: &Env, address: &Address, has: impl Fn(&Address) -> bool) -> Address {
+    let mut holder = address.clone();
+    loop {
+        if has(&holder) {
+            return holder;
+        }
+        match rotated_from(env, &holder) {
+            Some(previous) => holder = previous,
+            None => return address.clone(),
+        }
+    }
+}
+
+/// Move a persistent record from one key to another, if it exists.
+/// Returns whether it replaced a record already under `to`, for callers
+/// keeping a count of them.
+pub(crate) fn move_persistent<K>(env: &Env, from: &K, to: &K) -> bool
+where
+    K: IntoVal<Env, Val>,
+{
+    let persistent = env.storage().persistent();
+    match persistent.get::<K, Val>(from) {
+        Some(value) => {
+            let replaced = persistent.has(to);
+            persistent.set(to, &value);
+            persistent.remove(from);
+            replaced
+        }
+        None => false,
+    }
+}
+
+fn rotated_to(env: &Env, old: &Address) -> Option<Address> {
+    env.storage()
+        .persistent()
+        .get(&RotationKey::RotatedTo(old.clone()))
+}
+
+fn rotated_from(env: &Env, new: &Address) -> Option<Address> {
+    env.storage()
+        .persistent()
+        .get(&RotationKey::RotatedFrom(new.clone()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::BytesN;
+
+    #[test]
+    fn test_school_history_follows_rotation() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        client.set_reputation_config(&Some(crate::ReputationConfig {
+            settlement_window_secs: 1000,
+            large_redemption: 10_000,
+            min_score: 0,
+        }));
+        client.approve_unwrap(&s.school(0), &true);
+        client.transfer(&s.beneficiary(0), &s.school(0), &300);
+        client.set_registry_manager(&Some(Address::generate(&s.env)));
+
+        let new = Address::generate(&s.env);
+        client.rotate_address(&s.school(0), &new);
+        assert_eq!(client.balance(&s.school(0)), 0);
+        assert_eq!(client.balance(&new), 300);
+        assert_eq!(client.reputation(&new).stats.redemptions, 1);
+        assert!(client.is_unwrap_approved(&new));
+        assert!(!client.is_unwrap_approved(&s.school(0)));
+        assert_eq!(client.rotated_to(&s.school(0)), Some(new.clone()));
+
+        // A rating on a receipt issued to the old key lands on the new one
+        client.rate_redemption(&1, &4, &BytesN::from_array(&s.env, &[0; 32]));
+        assert_eq!(client.reputation(&new).stats.ratings, 1);
+
+        // Neither key can be rotated onto again
+        let result = client.try_rotate_address(&s.school(0), &Address::generate(&s.env));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+        let result = client.try_rotate_address(&new, &s.beneficiary(0));
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+
+    #[test]
+    fn test_issuer_keeps_authority_over_its_allocations() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        client.set_registry_manager(&Some(Address::generate(&s.env)));
+        let new = Address::generate(&s.env);
+        client.rotate_address(&s.issuer(0), &new);
+
+        let tag = soroban_sdk::String::from_str(&s.env, "2026");
+        client.set_allocation_tag(&new, &s.beneficiary(0), &symbol_short!("year"), &Some(tag));
+        let result = client.try_set_allocation_tag(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &symbol_short!("year"),
+            &None,
+        );
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        // Beneficiaries can't be rotated
+        let result = client.try_rotate_address(&s.beneficiary(0), &Address::generate(&s.env));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+    }
+
+    #[test]
+    fn test_restrictions_and_records_follow_rotation() {
+        use crate::{MerchantType, RedemptionPolicy};
+        use soroban_sdk::{vec, String, Symbol};
+
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(2)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        let (school, payee) = (s.school(0), s.school(1));
+        let books = symbol_short!("books");
+        let tuition = symbol_short!("tuition");
+        let invoice_ref = BytesN::from_array(&s.env, &[7; 32]);
+        let policy = RedemptionPolicy {
+            min_interval_secs: 0,
+            min_amount: 100,
+            max_per_period: 0,
+            period_secs: 0,
+        };
+
+        // Rotation needs the registry manager's approval
+        let result = client.try_rotate_address(&school, &Address::generate(&s.env));
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+        client.set_registry_manager(&Some(Address::generate(&s.env)));
+
+        client.set_school_purposes(&school, &vec![&s.env, books.clone()]);
+        client.set_merchant_type(&school, &Some(MerchantType::Bookshop));
+        client.set_redemption_policy(&school, &Some(policy.clone()));
+        client.credit_subaccount(&school, &tuition);
+        client.bill_invoice(
+            &school,
+            &invoice_ref,
+            &s.beneficiary(0),
+            &200,
+            &(s.now() + 60),
+        );
+        let hold_id = client.place_hold(&s.beneficiary(0), &payee, &100, &(s.now() + 60));
+        let template_id = Symbol::new(&s.env, "uni");
+        let purpose = String::from_str(&s.env, "Tuition");
+        client.save_template(&s.issuer(0), &template_id, &purpose, &100, &60, &tuition);
+
+        let (new_school, new_payee, new_issuer) = (
+            Address::generate(&s.env),
+            Address::generate(&s.env),
+            Address::generate(&s.env),
+        );
+        client.rotate_address(&school, &new_school);
+        client.rotate_address(&payee, &new_payee);
+        client.rotate_address(&s.issuer(0), &new_issuer);
+
+        // The school's restrictions hold at its new key
+        assert_eq!(
+            client.school_purposes(&new_school),
+            vec![&s.env, books.clone()]
+        );
+        assert!(client.school_purposes(&school).is_empty());
+        let result = client.try_transfer(&s.beneficiary(0), &new_school, &100);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        assert_eq!(
+            client.merchant_type(&new_school),
+            Some(MerchantType::Bookshop)
+        );
+        assert_eq!(client.merchant_type(&school), None);
+        assert_eq!(client.redemption_policy(&new_school), Some(policy));
+        assert_eq!(client.redemption_policy(&school), None);
+        assert_eq!(
+            client.subaccounts(&new_school),
+            vec![&s.env, tuition.clone()]
+        );
+        assert!(client.subaccount_report(&new_school, &tuition).is_some());
+
+        // Records keyed by the old key are found from the new one
+        assert_eq!(client.invoice_due(&new_school, &invoice_ref), 200);
+        let result = client.try_bill_invoice(
+            &new_school,
+            &invoice_ref,
+            &s.beneficiary(0),
+            &200,
+            &(s.now() + 60),
+        );
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+        client.consume_hold(&new_payee, &hold_id);
+        assert_eq!(client.balance(&new_payee), 100);
+        assert!(client.get_template(&new_issuer, &template_id).is_some());
+        let allocation =
+            client.issue_from_template(&new_issuer, &template_id, &Address::generate(&s.env));
+        assert_eq!(allocation.issuer, new_issuer);
+
+        // A key another rotation already moved onto is taken
+        let result = client.try_rotate_address(&Address::generate(&s.env), &new_issuer);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+}
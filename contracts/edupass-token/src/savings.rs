@@ -0,0 +1,311 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, symbol_short, Address, Env};
+
+use crate::{
+    add_total_issued, checked_add, credit, demurrage, events,
+    math::{self, Rounding, BPS},
+    require_admin, telemetry, Allocation, DataKey, EduPassToken, EduPassTokenClient, Error,
+};
+
+// Seconds in the year savings rates are quoted over
+const YEAR_SECS: u64 = 365 * 24 * 60 * 60;
+
+#[contracttype]
+enum SavingsKey {
+    SavingsBudget,           // Credits that may still be minted as savings bonuses
+    SavingsAccrual(Address), // Terms and accrual of a beneficiary's savings allocation
+    Accruing,                // Number of allocations accruing savings bonuses
+}
+
+// Savings terms of an allocation: unspent credits earn `rate_bps` a year,
+// paid once the allocation is held to `matures_at`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SavingsTerms {
+    pub rate_bps: u32,
+    pub matures_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Accrual {
+    terms: SavingsTerms,
+    accrued: i128,   // Bonus earned up to accrued_at
+    accrued_at: u64, // When the accrual was last brought up to date
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set the credits that may be minted as savings bonuses (admin only)
+    pub fn set_savings_budget(env: Env, budget: i128) {
+        telemetry::record(&env, "set_savings_budget");
+        require_admin(&env);
+
+        if budget < 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        env.storage()
+            .instance()
+            .set(&SavingsKey::SavingsBudget, &budget);
+    }
+
+    /// Credits that may still be minted as savings bonuses
+    pub fn savings_budget(env: Env) -> i128 {
+        budget(&env)
+    }
+
+    /// Set or clear (None) the savings terms of a beneficiary's allocation
+    /// (its issuer). The allocation's unspent credits accrue from now
+    /// until it matures, which must be before it expires; clearing the
+    /// terms forfeits what has accrued.
+    pub fn set_savings_terms(
+        env: Env,
+        issuer: Address,
+        beneficiary: Address,
+        terms: Option<SavingsTerms>,
+    ) {
+        telemetry::record(&env, "set_savings_terms");
+        issuer.require_auth();
+
+        let allocation: Allocation = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Allocations(beneficiary.clone()))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        if allocation.issuer != issuer {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        let Some(terms) = terms else {
+            clear(&env, &beneficiary);
+            return;
+        };
+        let now = env.ledger().timestamp();
+        if terms.rate_bps == 0
+            || terms.rate_bps as i128 > BPS
+            || terms.matures_at <= now
+            || terms.matures_at > allocation.expires_at
+        {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        let key = SavingsKey::SavingsAccrual(beneficiary);
+        if !env.storage().persistent().has(&key) {
+            set_accounts(&env, accounts(&env) + 1);
+        }
+        env.storage().persistent().set(
+            &key,
+            &Accrual {
+                terms,
+                accrued: 0,
+                accrued_at: now,
+            },
+        );
+    }
+
+    /// Get the savings terms of a beneficiary's allocation, if any
+    pub fn savings_terms(env: Env, beneficiary: Address) -> Option<SavingsTerms> {
+        read(&env, &beneficiary).map(|accrual| accrual.terms)
+    }
+
+    /// Savings bonus a beneficiary's allocation has accrued so far
+    pub fn accrued(env: Env, beneficiary: Address) -> i128 {
+        read(&env, &beneficiary).map_or(0, |accrual| {
+            brought_up_to_date(&env, accrual, unspent(&env, &beneficiary)).accrued
+        })
+    }
+
+    /// Mint a matured allocation's savings bonus to its beneficiary against
+    /// the savings budget. Fails with NotMatured before maturity and
+    /// BudgetExceeded if the budget can't cover the bonus. Returns the
+    /// bonus paid.
+    pub fn claim_savings(env: Env, beneficiary: Address) -> i128 {
+        telemetry::record(&env, "claim_savings");
+        let Some(accrual) = read(&env, &beneficiary) else {
+            panic_with_error!(&env, Error::NotFound);
+        };
+        if env.ledger().timestamp() < accrual.terms.matures_at {
+            panic_with_error!(&env, Error::NotMatured);
+        }
+
+        let bonus = brought_up_to_date(&env, accrual, unspent(&env, &beneficiary)).accrued;
+        let budget = budget(&env);
+        if bonus > budget {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        clear(&env, &beneficiary);
+        if bonus > 0 {
+            env.storage()
+                .instance()
+                .set(&SavingsKey::SavingsBudget, &(budget - bonus));
+            add_total_issued(&env, &None, bonus);
+            credit(&env, &beneficiary, bonus);
+            events::publish(&env, (symbol_short!("savings"), beneficiary), bonus);
+        }
+        bonus
+    }
+}
+
+/// Bring a beneficiary's savings accrual up to date on the `unspent`
+/// credits its allocation had, before they change
+pub(crate) fn accrue(env: &Env, beneficiary: &Address, unspent: i128) {
+    // Spares the lookup on every spend until the first savings allocation
+    if accounts(env) == 0 {
+        return;
+    }
+    let Some(accrual) = read(env, beneficiary) else {
+        return;
+    };
+    let accrual = brought_up_to_date(env, accrual, unspent);
+    env.storage()
+        .persistent()
+        .set(&SavingsKey::SavingsAccrual(beneficiary.clone()), &accrual);
+}
+
+/// Forfeit the savings of a beneficiary's allocation, when it is
+/// superseded or its terms are cleared
+pub(crate) fn clear(env: &Env, beneficiary: &Address) {
+    if accounts(env) == 0 {
+        return;
+    }
+    let key = SavingsKey::SavingsAccrual(beneficiary.clone());
+    if env.storage().persistent().has(&key) {
+        env.storage().persistent().remove(&key);
+        set_accounts(env, accounts(env) - 1);
+    }
+}
+
+// Accrues simple interest on `unspent` credits since the last update, up
+// to maturity
+fn brought_up_to_date(env: &Env, mut accrual: Accrual, unspent: i128) -> Accrual {
+    let until = env.ledger().timestamp().min(accrual.terms.matures_at);
+    if until <= accrual.accrued_at {
+        return accrual;
+    }
+    let elapsed = (until - accrual.accrued_at) as i128;
+    let earned = math::mul_div(
+        env,
+        unspent,
+        accrual.terms.rate_bps as i128 * elapsed,
+        BPS * YEAR_SECS as i128,
+        Rounding::Down,
+    );
+    accrual.accrued = checked_add(env, accrual.accrued, earned);
+    accrual.accrued_at = until;
+    accrual
+}
+
+// Credits of a beneficiary's allocation still unspent, net of debits such
+// as demurrage and clawbacks
+fn unspent(env: &Env, beneficiary: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get::<_, Allocation>(&DataKey::Allocations(beneficiary.clone()))
+        .map_or(0, |allocation| allocation.remaining)
+        .min(demurrage::net_balance(env, beneficiary))
+        .max(0)
+}
+
+fn read(env: &Env, beneficiary: &Address) -> Option<Accrual> {
+    env.storage()
+        .persistent()
+        .get(&SavingsKey::SavingsAccrual(beneficiary.clone()))
+}
+
+fn budget(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&SavingsKey::SavingsBudget)
+        .unwrap_or(0)
+}
+
+fn accounts(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&SavingsKey::Accruing)
+        .unwrap_or(0)
+}
+
+fn set_accounts(env: &Env, count: u32) {
+    env.storage().instance().set(&SavingsKey::Accruing, &count);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+
+    fn setup() -> Scenario {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 10_000);
+        let client = s.client();
+        client.set_savings_budget(&1000);
+        client.set_savings_terms(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &Some(SavingsTerms {
+                rate_bps: 500,
+                matures_at: s.now() + YEAR_SECS,
+            }),
+        );
+        s
+    }
+
+    #[test]
+    fn test_unspent_credits_accrue_to_maturity() {
+        let s = setup();
+        let client = s.client();
+        let beneficiary = s.beneficiary(0);
+
+        // Half a year on 10_000, then half a year on the 6_000 left
+        advance(&s.env, YEAR_SECS / 2);
+        assert_eq!(client.accrued(&beneficiary), 250);
+        let result = client.try_claim_savings(&beneficiary);
+        assert_eq!(result, Err(Ok(Error::NotMatured.into())));
+        client.transfer(&beneficiary, &s.school(0), &4000);
+        advance(&s.env, YEAR_SECS);
+        assert_eq!(client.accrued(&beneficiary), 400);
+
+        // Nothing accrues past maturity
+        assert_eq!(client.claim_savings(&beneficiary), 400);
+        assert_eq!(client.balance(&beneficiary), 6400);
+        assert_eq!(client.savings_budget(), 600);
+        assert_eq!(client.total_issued(), 10_400);
+        assert_eq!(client.savings_terms(&beneficiary), None);
+    }
+
+    #[test]
+    fn test_bonus_limited_by_budget() {
+        let s = setup();
+        let client = s.client();
+        client.set_savings_budget(&100);
+        advance(&s.env, YEAR_SECS);
+        let result = client.try_claim_savings(&s.beneficiary(0));
+        assert_eq!(result, Err(Ok(Error::BudgetExceeded.into())));
+        client.set_savings_budget(&500);
+        assert_eq!(client.claim_savings(&s.beneficiary(0)), 500);
+    }
+
+    #[test]
+    fn test_terms_set_by_the_issuer_within_the_allocation() {
+        let s = setup();
+        let client = s.client();
+        let expires_at = client.get_allocation(&s.beneficiary(0)).unwrap().expires_at;
+        let terms = Some(SavingsTerms {
+            rate_bps: 500,
+            matures_at: expires_at + 1,
+        });
+        let result = client.try_set_savings_terms(&s.issuer(0), &s.beneficiary(0), &terms);
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+        let result = client.try_set_savings_terms(&s.admin(), &s.beneficiary(0), &None);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        // A new allocation forfeits the old one's savings
+        advance(&s.env, 30 * DAY_SECS);
+        let s = s.issue(0, 0, 100);
+        assert_eq!(s.client().accrued(&s.beneficiary(0)), 0);
+        assert_eq!(s.client().savings_terms(&s.beneficiary(0)), None);
+    }
+}
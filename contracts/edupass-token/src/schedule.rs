@@ -0,0 +1,459 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, Address, Env, Map, String};
+
+use crate::{
+    checked_add, checked_mul, issue, require_admin, rotation, telemetry, validity, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+// Tranche state is tracked in a bitmask, one bit per month
+const MAX_SCHEDULE_MONTHS: u32 = 64;
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+#[contracttype]
+enum ScheduleKey {
+    Attestor(Address),        // Approved attendance attestor
+    Attendance(Address, u32), // Attestation time for (beneficiary, YYYYMM)
+    Schedule(u64),            // Scheduled monthly allocation
+    ScheduleCount,            // Last assigned schedule id
+    TranchesSettled(u64),     // Tranches of a schedule taken out of the forecast
+    ScheduledOutstanding,     // Credits of all tranches not yet claimed or forfeited
+}
+
+// What happens to a tranche whose month ends without an attestation
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MissedMonthPolicy {
+    Forfeit, // Late attestations don't count; the tranche is lost
+    Defer,   // The tranche stays claimable once attendance is attested
+}
+
+// Monthly stipend released against attendance attestations
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StipendSchedule {
+    pub issuer: Address,
+    pub beneficiary: Address,
+    pub monthly_amount: i128,
+    pub start_month: u32, // First month as YYYYMM
+    pub months: u32,
+    pub purpose: String,
+    pub expires_at: u64,
+    pub policy: MissedMonthPolicy,
+    pub claimed_mask: u64, // Bit i set once month i has been claimed
+}
+
+// Tranche counts by state for a schedule
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrancheSummary {
+    pub claimed: u32,
+    pub claimable: u32,
+    pub forfeited: u32,
+    pub pending: u32,
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Approve or revoke an attendance attestor (admin only)
+    pub fn set_attestor(env: Env, attestor: Address, approved: bool) {
+        telemetry::record(&env, "set_attestor");
+        require_admin(&env);
+
+        let key = ScheduleKey::Attestor(attestor);
+        if approved {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    /// Check whether an address is an approved attestor
+    pub fn is_attestor(env: Env, attestor: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&ScheduleKey::Attestor(attestor))
+    }
+
+    /// Create a monthly stipend schedule; returns the schedule id
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_schedule(
+        env: Env,
+        issuer: Address,
+        beneficiary: Address,
+        monthly_amount: i128,
+        start_month: u32,
+        months: u32,
+        purpose: String,
+        expires_at: u64,
+        policy: MissedMonthPolicy,
+    ) -> u64 {
+        telemetry::record(&env, "create_schedule");
+        issuer.require_auth();
+
+        if monthly_amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        if !valid_month(start_month) {
+            panic_with_error!(&env, Error::InvalidMonth);
+        }
+        if months == 0 || months > MAX_SCHEDULE_MONTHS {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        let expires_at = validity::resolve_expiry(&env, expires_at);
+
+        let id: u64 = env
+            .storage()
+            .instance()
+            .get(&ScheduleKey::ScheduleCount)
+            .unwrap_or(0u64)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&ScheduleKey::ScheduleCount, &id);
+
+        let schedule = StipendSchedule {
+            issuer,
+            beneficiary,
+            monthly_amount,
+            start_month,
+            months,
+            purpose,
+            expires_at,
+            policy,
+            claimed_mask: 0,
+        };
+        let committed = checked_mul(&env, monthly_amount, months as i128);
+        set_outstanding(&env, checked_add(&env, outstanding(&env), committed));
+        env.storage()
+            .persistent()
+            .set(&ScheduleKey::Schedule(id), &schedule);
+
+        id
+    }
+
+    /// Attest that a beneficiary attended during a month (YYYYMM)
+    pub fn attest_attendance(env: Env, attestor: Address, beneficiary: Address, month: u32) {
+        telemetry::record(&env, "attest_attendance");
+        attestor.require_auth();
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&ScheduleKey::Attestor(attestor))
+        {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        // Attendance can't be attested for a month that hasn't started
+        let now = env.ledger().timestamp();
+        if !valid_month(month) || month_start(month) > now {
+            panic_with_error!(&env, Error::InvalidMonth);
+        }
+
+        // The first attestation is the one that counts
+        let key = ScheduleKey::Attendance(beneficiary, month);
+        if !env.storage().persistent().has(&key) {
+            env.storage().persistent().set(&key, &now);
+        }
+    }
+
+    /// Get when attendance was attested for a beneficiary and month
+    pub fn attendance(env: Env, beneficiary: Address, month: u32) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&ScheduleKey::Attendance(beneficiary, month))
+    }
+
+    /// Claim all attested, unclaimed tranches; returns the amount issued
+    pub fn claim_tranches(env: Env, beneficiary: Address, schedule_id: u64) -> i128 {
+        telemetry::record(&env, "claim_tranches");
+        beneficiary.require_auth();
+
+        let mut schedule = load_schedule(&env, schedule_id);
+        if schedule.beneficiary != beneficiary {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        let mut claimed = 0u32;
+        let mut month = schedule.start_month;
+        for i in 0..schedule.months {
+            let bit = 1u64 << i;
+            if schedule.claimed_mask & bit == 0 && claimable(&env, &schedule, month) {
+                schedule.claimed_mask |= bit;
+                claimed += 1;
+            }
+            month = next_month(month);
+        }
+
+        if claimed == 0 {
+            settle_tranches(&env, schedule_id, &schedule);
+            return 0;
+        }
+
+        env.storage()
+            .persistent()
+            .set(&ScheduleKey::Schedule(schedule_id), &schedule);
+        settle_tranches(&env, schedule_id, &schedule);
+
+        let amount = checked_mul(&env, schedule.monthly_amount, claimed as i128);
+        issue(
+            &env,
+            schedule.issuer,
+            beneficiary,
+            amount,
+            schedule.purpose,
+            schedule.expires_at,
+            None,
+            Map::new(&env),
+        );
+
+        amount
+    }
+
+    /// Get a stipend schedule
+    pub fn get_schedule(env: Env, schedule_id: u64) -> Option<StipendSchedule> {
+        env.storage()
+            .persistent()
+            .get(&ScheduleKey::Schedule(schedule_id))
+    }
+
+    /// Count a schedule's tranches by state
+    pub fn tranche_summary(env: Env, schedule_id: u64) -> TrancheSummary {
+        let schedule = load_schedule(&env, schedule_id);
+        let now = env.ledger().timestamp();
+
+        let mut summary = TrancheSummary {
+            claimed: 0,
+            claimable: 0,
+            forfeited: 0,
+            pending: 0,
+        };
+        let mut month = schedule.start_month;
+        for i in 0..schedule.months {
+            if schedule.claimed_mask & (1u64 << i) != 0 {
+                summary.claimed += 1;
+            } else if claimable(&env, &schedule, month) {
+                summary.claimable += 1;
+            } else if schedule.policy == MissedMonthPolicy::Forfeit
+                && month_start(next_month(month)) <= now
+            {
+                summary.forfeited += 1;
+            } else {
+                summary.pending += 1;
+            }
+            month = next_month(month);
+        }
+
+        summary
+    }
+}
+
+/// Credits of all stipend tranches not yet claimed or forfeited
+pub(crate) fn outstanding(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&ScheduleKey::ScheduledOutstanding)
+        .unwrap_or(0)
+}
+
+fn set_outstanding(env: &Env, amount: i128) {
+    env.storage()
+        .instance()
+        .set(&ScheduleKey::ScheduledOutstanding, &amount);
+}
+
+// Take a schedule's claimed and forfeited tranches out of the forecast.
+// Forfeits surface when the beneficiary next claims.
+fn settle_tranches(env: &Env, schedule_id: u64, schedule: &StipendSchedule) {
+    let summary = EduPassToken::tranche_summary(env.clone(), schedule_id);
+    let settled = summary.claimed + summary.forfeited;
+    let key = ScheduleKey::TranchesSettled(schedule_id);
+    let counted: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+    if settled > counted {
+        let amount = checked_mul(env, schedule.monthly_amount, (settled - counted) as i128);
+        set_outstanding(env, outstanding(env) - amount);
+        env.storage().persistent().set(&key, &settled);
+    }
+}
+
+/// Move an attestor's approval to its rotated key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &ScheduleKey::Attestor(old.clone()),
+        &ScheduleKey::Attestor(new.clone()),
+    );
+}
+
+fn load_schedule(env: &Env, schedule_id: u64) -> StipendSchedule {
+    env.storage()
+        .persistent()
+        .get(&ScheduleKey::Schedule(schedule_id))
+        .unwrap_or_else(|| panic_with_error!(env, Error::ScheduleNotFound))
+}
+
+// A tranche is claimable once attendance is attested; under the forfeit
+// policy the attestation must have happened before the month ended.
+fn claimable(env: &Env, schedule: &StipendSchedule, month: u32) -> bool {
+    let attested_at: Option<u64> = env.storage().persistent().get(&ScheduleKey::Attendance(
+        schedule.beneficiary.clone(),
+        month,
+    ));
+    match (attested_at, schedule.policy) {
+        (None, _) => false,
+        (Some(_), MissedMonthPolicy::Defer) => true,
+        (Some(attested_at), MissedMonthPolicy::Forfeit) => {
+            attested_at < month_start(next_month(month))
+        }
+    }
+}
+
+fn valid_month(month: u32) -> bool {
+    let m = month % 100;
+    (1..=12).contains(&m) && month / 100 >= 1970
+}
+
+fn next_month(month: u32) -> u32 {
+    if month % 100 == 12 {
+        (month / 100 + 1) * 100 + 1
+    } else {
+        month + 1
+    }
+}
+
+// Unix timestamp of 00:00 UTC on the first day of a YYYYMM month
+pub(crate) fn month_start(month: u32) -> u64 {
+    days_from_civil((month / 100) as i64, month % 100, 1) as u64 * DAY_SECS
+}
+
+// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    // 2026-01-15 00:00 UTC
+    const JAN_15_2026: u64 = 1_768_435_200;
+
+    fn setup(env: &Env) -> (EduPassTokenClient<'_>, Address, Address, Address) {
+        env.mock_all_auths();
+        env.ledger().set_timestamp(JAN_15_2026);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(env, &contract_id);
+
+        let admin = Address::generate(env);
+        let issuer = Address::generate(env);
+        let attestor = Address::generate(env);
+        let beneficiary = Address::generate(env);
+
+        client.initialize(&admin);
+        client.set_attestor(&attestor, &true);
+
+        (client, issuer, attestor, beneficiary)
+    }
+
+    #[test]
+    fn test_month_math() {
+        assert_eq!(month_start(197001), 0);
+        assert_eq!(month_start(202601), 1_767_225_600);
+        assert_eq!(month_start(202403) - month_start(202402), 29 * DAY_SECS);
+        assert_eq!(next_month(202512), 202601);
+        assert!(!valid_month(202613));
+    }
+
+    #[test]
+    fn test_tranches_released_on_attendance() {
+        let env = Env::default();
+        let (client, issuer, attestor, beneficiary) = setup(&env);
+
+        let id = client.create_schedule(
+            &issuer,
+            &beneficiary,
+            &100,
+            &202601,
+            &3,
+            &String::from_str(&env, "Stipend"),
+            &1_800_000_000,
+            &MissedMonthPolicy::Defer,
+        );
+
+        // Nothing to claim before attendance is attested
+        assert_eq!(client.claim_tranches(&beneficiary, &id), 0);
+
+        client.attest_attendance(&attestor, &beneficiary, &202601);
+        assert_eq!(client.claim_tranches(&beneficiary, &id), 100);
+        assert_eq!(client.balance(&beneficiary), 100);
+
+        // Already claimed tranches are not paid twice
+        assert_eq!(client.claim_tranches(&beneficiary, &id), 0);
+
+        // Future months can't be attested
+        let result = client.try_attest_attendance(&attestor, &beneficiary, &202602);
+        assert_eq!(result, Err(Ok(Error::InvalidMonth.into())));
+    }
+
+    #[test]
+    fn test_missed_month_policies() {
+        let env = Env::default();
+        let (client, issuer, attestor, beneficiary) = setup(&env);
+        let purpose = String::from_str(&env, "Stipend");
+
+        let forfeit = client.create_schedule(
+            &issuer,
+            &beneficiary,
+            &100,
+            &202601,
+            &2,
+            &purpose,
+            &1_800_000_000,
+            &MissedMonthPolicy::Forfeit,
+        );
+        let defer = client.create_schedule(
+            &issuer,
+            &beneficiary,
+            &50,
+            &202601,
+            &2,
+            &purpose,
+            &1_800_000_000,
+            &MissedMonthPolicy::Defer,
+        );
+
+        assert_eq!(client.commitments(&None).stipends, 300);
+
+        // January passes without an attestation, which arrives in February
+        env.ledger().set_timestamp(month_start(202602) + DAY_SECS);
+        client.attest_attendance(&attestor, &beneficiary, &202601);
+
+        let summary = client.tranche_summary(&forfeit);
+        assert_eq!(summary.forfeited, 1);
+        assert_eq!(summary.pending, 1);
+        assert_eq!(client.claim_tranches(&beneficiary, &forfeit), 0);
+
+        assert_eq!(client.tranche_summary(&defer).claimable, 1);
+        assert_eq!(client.claim_tranches(&beneficiary, &defer), 50);
+        // The forfeited and claimed tranches are no longer committed
+        assert_eq!(client.commitments(&None).stipends, 150);
+    }
+
+    #[test]
+    fn test_unapproved_attestor() {
+        let env = Env::default();
+        let (client, _, _, beneficiary) = setup(&env);
+
+        let stranger = Address::generate(&env);
+        let result = client.try_attest_attendance(&stranger, &beneficiary, &202601);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    }
+}
@@ -0,0 +1,303 @@
+// Opt-in counterparty screening. Deployments that must screen against a
+// sanctions list configure a screening contract, and every transfer first
+// asks its `is_sanctioned(account) -> bool` about the sender and the
+// recipient. Credits paid out of the contract's pools and escrows (bridge
+// releases, refunds, payouts, grants) screen their recipient, credits moved
+// into them (deposits, bridge locks) their sender, and wrapping and
+// unwrapping the account trading the asset. Flagged parties fail with
+// Unauthorized. The policy is fail closed: when the screening contract
+// can't answer (missing, trapping or answering something else), the
+// transfer fails with ScreeningUnavailable. The admin exempts
+// program-internal addresses, such as the contract's own pools or a
+// program's operational accounts, which are never asked about.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, vec, Address, Env, IntoVal, Symbol,
+};
+
+use crate::{require_admin, telemetry, EduPassToken, EduPassTokenClient, Error};
+
+#[contracttype]
+enum ScreeningKey {
+    Screener,                 // Screening contract (absent when disabled)
+    ScreeningExempt(Address), // Set for addresses transfers don't screen
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the screening contract (admin only). It isn't
+    /// probed here, so it can be configured while down.
+    pub fn set_screening_contract(env: Env, screener: Option<Address>) {
+        telemetry::record(&env, "set_screening_contract");
+        require_admin(&env);
+
+        match screener {
+            Some(screener) => env
+                .storage()
+                .instance()
+                .set(&ScreeningKey::Screener, &screener),
+            None => env.storage().instance().remove(&ScreeningKey::Screener),
+        }
+    }
+
+    /// Get the screening contract, if configured
+    pub fn screening_contract(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ScreeningKey::Screener)
+    }
+
+    /// Exempt an address from screening, or lift the exemption (admin
+    /// only)
+    pub fn set_screening_exempt(env: Env, account: Address, exempt: bool) {
+        telemetry::record(&env, "set_screening_exempt");
+        require_admin(&env);
+
+        let key = ScreeningKey::ScreeningExempt(account);
+        if exempt {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    /// Check whether an address is exempt from screening
+    pub fn is_screening_exempt(env: Env, account: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&ScreeningKey::ScreeningExempt(account))
+    }
+}
+
+/// Screen both parties to a transfer while a screening contract is
+/// configured: Unauthorized for a flagged party, ScreeningUnavailable when
+/// the contract can't answer
+pub(crate) fn check_parties(env: &Env, from: &Address, to: &Address) -> Result<(), Error> {
+    check(env, &[from, to])
+}
+
+/// Screen the one account credits are paid to or taken from outside a
+/// transfer, failing as check_parties does
+pub(crate) fn require_clear(env: &Env, account: &Address) {
+    if let Err(error) = check(env, &[account]) {
+        panic_with_error!(env, error);
+    }
+}
+
+fn check(env: &Env, accounts: &[&Address]) -> Result<(), Error> {
+    let Some(screener) = EduPassToken::screening_contract(env.clone()) else {
+        return Ok(());
+    };
+    for &account in accounts {
+        if EduPassToken::is_screening_exempt(env.clone(), account.clone()) {
+            continue;
+        }
+        match query(env, &screener, account) {
+            Some(false) => {}
+            Some(true) => return Err(Error::Unauthorized),
+            None => return Err(Error::ScreeningUnavailable),
+        }
+    }
+    Ok(())
+}
+
+// Ask the screening contract about an account; None if it can't answer
+fn query(env: &Env, screener: &Address, account: &Address) -> Option<bool> {
+    match env.try_invoke_contract::<bool, soroban_sdk::Error>(
+        screener,
+        &Symbol::new(env, "is_sanctioned"),
+        vec![env, account.into_val(env)],
+    ) {
+        Ok(Ok(sanctioned)) => Some(sanctioned),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{contract, contractimpl, symbol_short};
+
+    #[contract]
+    struct Screener;
+
+    #[contractimpl]
+    impl Screener {
+        pub fn set_sanctioned(env: Env, account: Address, sanctioned: bool) {
+            env.storage().instance().set(&account, &sanctioned);
+        }
+
+        pub fn set_down(env: Env, down: bool) {
+            env.storage().instance().set(&symbol_short!("down"), &down);
+        }
+
+        pub fn is_sanctioned(env: Env, account: Address) -> bool {
+            if env
+                .storage()
+                .instance()
+                .get(&symbol_short!("down"))
+                .unwrap_or(false)
+            {
+                panic!("screening down");
+            }
+            env.storage().instance().get(&account).unwrap_or(false)
+        }
+    }
+
+    #[test]
+    fn test_transfers_screen_both_parties_and_fail_closed() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(2)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        let screener = ScreenerClient::new(&s.env, &s.env.register_contract(None, Screener));
+        client.set_screening_contract(&Some(screener.address.clone()));
+        let beneficiary = s.beneficiary(0);
+
+        client.transfer(&beneficiary, &s.school(0), &100);
+        screener.set_sanctioned(&s.school(1), &true);
+        let result = client.try_transfer(&beneficiary, &s.school(1), &100);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        assert_eq!(
+            client.try_can_transfer(&beneficiary, &s.school(1), &100),
+            Err(Ok(Error::Unauthorized))
+        );
+
+        // Nothing moves while screening is down, except between exempt
+        // addresses
+        screener.set_down(&true);
+        let result = client.try_transfer(&beneficiary, &s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::ScreeningUnavailable.into())));
+        client.set_screening_exempt(&beneficiary, &true);
+        client.set_screening_exempt(&s.school(0), &true);
+        client.transfer(&beneficiary, &s.school(0), &100);
+        assert_eq!(client.balance(&s.school(0)), 200);
+
+        // An account that isn't a contract can't answer either
+        client.set_screening_contract(&Some(Address::generate(&s.env)));
+        client.set_screening_exempt(&s.school(0), &false);
+        let result = client.try_transfer(&beneficiary, &s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::ScreeningUnavailable.into())));
+    }
+
+    #[test]
+    fn test_payouts_and_deposits_screen_their_account() {
+        use crate::{BridgeChain, BridgeProof};
+        use soroban_sdk::{Bytes, BytesN};
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(2)
+            .issue(0, 0, 1000)
+            .issue(0, 1, 1000);
+        let client = s.client();
+        let screener = ScreenerClient::new(&s.env, &s.env.register_contract(None, Screener));
+        let (flagged, clear) = (s.beneficiary(0), s.beneficiary(1));
+        let chain = symbol_short!("partner");
+        let relayer = Address::generate(&s.env);
+        client.set_bridge_relayer(&Some(relayer));
+        client.set_bridge_chain(
+            &chain,
+            &Some(BridgeChain {
+                outbound_limit: 1000,
+                inbound_limit: 1000,
+                period_secs: crate::testutils::DAY_SECS,
+            }),
+        );
+        let dest = Bytes::from_slice(&s.env, b"0xabc");
+        client.lock_for_bridge(&clear, &200, &chain, &dest);
+        let shares = client.deposit_to_pool(&flagged, &100, &soroban_sdk::Vec::new(&s.env));
+        client.set_screening_contract(&Some(screener.address.clone()));
+        screener.set_sanctioned(&flagged, &true);
+
+        let unauthorized = Some(Ok(Error::Unauthorized.into()));
+        let result = client.try_lock_for_bridge(&flagged, &100, &chain, &dest);
+        assert_eq!(result.err(), unauthorized);
+        let result = client.try_deposit_to_pool(&flagged, &100, &soroban_sdk::Vec::new(&s.env));
+        assert_eq!(result.err(), unauthorized);
+        let proof = BridgeProof {
+            source_chain: chain.clone(),
+            source_tx: BytesN::from_array(&s.env, &[1; 32]),
+            recipient: flagged.clone(),
+            amount: 100,
+            valid_until: s.now() + 1,
+        };
+        assert_eq!(client.try_release(&proof).err(), unauthorized);
+        let result = client.try_withdraw_from_pool(&flagged, &shares);
+        assert_eq!(result.err(), unauthorized);
+        let result = client.try_close_account(&clear, &Some(flagged.clone()));
+        assert_eq!(result.err(), unauthorized);
+
+        // A clear recipient is paid as before
+        client.release(&BridgeProof {
+            recipient: clear.clone(),
+            ..proof
+        });
+        assert_eq!(client.balance(&clear), 900);
+    }
+
+    #[test]
+    fn test_wrapping_screens_the_account() {
+        use soroban_sdk::token::StellarAssetClient;
+        let s = Scenario::new()
+            .with_admin()
+            .with_schools(1)
+            .with_beneficiaries(1);
+        let client = s.client();
+        let asset = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        StellarAssetClient::new(&s.env, &asset).mint(&s.beneficiary(0), &1000);
+        client.set_wrap_asset(&Some(asset));
+        client.approve_unwrap(&s.school(0), &true);
+        client.wrap(&s.beneficiary(0), &500);
+        client.transfer(&s.beneficiary(0), &s.school(0), &500);
+        let screener = ScreenerClient::new(&s.env, &s.env.register_contract(None, Screener));
+        client.set_screening_contract(&Some(screener.address.clone()));
+        screener.set_sanctioned(&s.beneficiary(0), &true);
+        screener.set_sanctioned(&s.school(0), &true);
+
+        let result = client.try_wrap(&s.beneficiary(0), &100);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        let result = client.try_unwrap(&s.school(0), &100);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        screener.set_sanctioned(&s.school(0), &false);
+        client.unwrap(&s.school(0), &100);
+        assert_eq!(client.wrap_reserve(), 400);
+    }
+
+    #[test]
+    #[cfg(feature = "escrow")]
+    fn test_escrow_payouts_screen_the_payee() {
+        use soroban_sdk::String;
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1);
+        let client = s.client();
+        let screener = ScreenerClient::new(&s.env, &s.env.register_contract(None, Screener));
+        let escrow_id = client.issue_to_escrow(
+            &s.issuer(0),
+            &s.beneficiary(0),
+            &s.school(0),
+            &100,
+            &String::from_str(&s.env, "Tuition"),
+            &s.expires_at(),
+        );
+        client.set_screening_contract(&Some(screener.address.clone()));
+        screener.set_sanctioned(&s.school(0), &true);
+
+        let result = client.try_release_escrow(&s.beneficiary(0), &escrow_id);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+        screener.set_sanctioned(&s.school(0), &false);
+        client.release_escrow(&s.beneficiary(0), &escrow_id);
+        assert_eq!(client.balance(&s.school(0)), 100);
+    }
+}
@@ -0,0 +1,415 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, token, Address, Env, Vec,
+};
+
+use crate::{
+    checked_add, demurrage, dust, events, holds,
+    math::{self, Rounding},
+    redeem, reputation, require_admin, rotation, telemetry, write_balance, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+// Redemptions are batched per school by week, counted from the Unix epoch
+const SETTLEMENT_PERIOD_SECS: u64 = 7 * 24 * 60 * 60;
+// Bounds the line items returned by one batch_lines call
+const MAX_LINES_PAGE: u32 = 50;
+// Highest haircut instant settlement can charge
+const MAX_HAIRCUT_BPS: u32 = 1_000;
+
+#[contracttype]
+enum SettlementKey {
+    TreasuryConfig,                    // Treasurer and payout asset (absent when disabled)
+    RedemptionBatch(Address, u64),     // A school's redemptions in one period
+    RedemptionLine(Address, u64, u32), // One redemption in a batch, by position
+    PayoutAddress(Address),            // Where a school's settlements are paid, if not to it
+    InstantHaircut,                    // Bps instant settlement keeps (absent when unavailable)
+    InstantSettled(u64),               // Set once a receipt has been settled instantly
+}
+
+// Who settles batches, and in what
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementConfig {
+    pub treasurer: Address,
+    pub asset: Address, // Paid out one unit per credit redeemed
+}
+
+// A school's redemptions in one period, paid out together
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SettlementBatch {
+    pub total: i128,
+    pub lines: u32,
+    pub settled_at: u64, // 0 until settled
+}
+
+// A redemption included in a batch
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementLine {
+    pub amount: i128,
+    pub redeemed_at: u64,
+    pub authorized_by: Address, // The school, or a spender acting for it
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Enable, update or disable (None) batched settlement (admin only).
+    /// Redemptions are only batched while it is enabled.
+    pub fn set_settlement_config(env: Env, config: Option<SettlementConfig>) {
+        telemetry::record(&env, "set_settlement_config");
+        require_admin(&env);
+
+        match config {
+            Some(config) => env
+                .storage()
+                .instance()
+                .set(&SettlementKey::TreasuryConfig, &config),
+            None => env
+                .storage()
+                .instance()
+                .remove(&SettlementKey::TreasuryConfig),
+        }
+    }
+
+    /// Get the settlement config, if enabled
+    pub fn settlement_config(env: Env) -> Option<SettlementConfig> {
+        settlement_config(&env)
+    }
+
+    /// Set or clear (None) the address a school's settlements are paid to
+    /// (the school and the admin). Redemptions are still credited to the
+    /// school itself; only settle_batch pays the payout address.
+    pub fn set_payout_address(env: Env, school: Address, payout: Option<Address>) {
+        telemetry::record(&env, "set_payout_address");
+        school.require_auth();
+        require_admin(&env);
+
+        let key = SettlementKey::PayoutAddress(school.clone());
+        match &payout {
+            Some(payout) => env.storage().persistent().set(&key, payout),
+            None => env.storage().persistent().remove(&key),
+        }
+        events::publish(&env, (symbol_short!("payout"), school), payout);
+    }
+
+    /// Get the address a school's settlements are paid to, if not to it
+    pub fn payout_address(env: Env, school: Address) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&SettlementKey::PayoutAddress(school))
+    }
+
+    /// Get a school's batch for a period. Periods are 7 days long,
+    /// numbered from the Unix epoch.
+    pub fn settlement_batch(env: Env, school: Address, period: u64) -> Option<SettlementBatch> {
+        env.storage()
+            .persistent()
+            .get(&SettlementKey::RedemptionBatch(school, period))
+    }
+
+    /// Get up to `limit` line items of a batch, from position `start`
+    pub fn batch_lines(
+        env: Env,
+        school: Address,
+        period: u64,
+        start: u32,
+        limit: u32,
+    ) -> Vec<SettlementLine> {
+        let batch = Self::settlement_batch(env.clone(), school.clone(), period).unwrap_or_default();
+        let end = batch
+            .lines
+            .min(start.saturating_add(limit.min(MAX_LINES_PAGE)));
+
+        let mut lines = Vec::new(&env);
+        for index in start..end {
+            if let Some(line) = env
+                .storage()
+                .persistent()
+                .get(&SettlementKey::RedemptionLine(
+                    school.clone(),
+                    period,
+                    index,
+                ))
+            {
+                lines.push_back(line);
+            }
+        }
+        lines
+    }
+
+    /// Pay out a school's batch for an ended period in one transfer of the
+    /// settlement asset (the treasurer). The school's payout address is
+    /// paid if it has one; otherwise a rotated school is paid at its
+    /// current key.
+    pub fn settle_batch(env: Env, school: Address, period: u64) -> i128 {
+        telemetry::record(&env, "settle_batch");
+        let config = settlement_config(&env)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        config.treasurer.require_auth();
+
+        if period >= period_of(env.ledger().timestamp()) {
+            panic_with_error!(&env, Error::NotExpired);
+        }
+        let key = SettlementKey::RedemptionBatch(school.clone(), period);
+        let mut batch: SettlementBatch = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        if batch.settled_at != 0 {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+
+        batch.settled_at = env.ledger().timestamp();
+        env.storage().persistent().set(&key, &batch);
+        let payee = rotation::current(&env, &school);
+        let payout = Self::payout_address(env.clone(), payee.clone()).unwrap_or(payee.clone());
+        token::Client::new(&env, &config.asset).transfer(&config.treasurer, &payout, &batch.total);
+        events::publish(
+            &env,
+            (symbol_short!("settled"), payee, period),
+            (batch.total, batch.lines),
+        );
+
+        batch.total
+    }
+
+    /// Offer instant settlement at a haircut in basis points, at most
+    /// 1000, or withdraw the offer (None) (admin only)
+    pub fn set_instant_haircut(env: Env, haircut_bps: Option<u32>) {
+        telemetry::record(&env, "set_instant_haircut");
+        require_admin(&env);
+
+        match haircut_bps {
+            Some(bps) => {
+                if bps > MAX_HAIRCUT_BPS {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage()
+                    .instance()
+                    .set(&SettlementKey::InstantHaircut, &bps);
+            }
+            None => env
+                .storage()
+                .instance()
+                .remove(&SettlementKey::InstantHaircut),
+        }
+    }
+
+    /// Get the instant settlement haircut, if offered
+    pub fn instant_haircut(env: Env) -> Option<u32> {
+        env.storage().instance().get(&SettlementKey::InstantHaircut)
+    }
+
+    /// Settle a redemption receipt now instead of in its weekly batch (the
+    /// receipt's school). The school's credits for it are redeemed: the
+    /// haircut goes to the treasury, and the rest is paid in the
+    /// settlement asset from the treasurer's allowance to this contract.
+    /// Returns the amount paid.
+    pub fn instant_settle(env: Env, receipt_id: u64) -> i128 {
+        telemetry::record(&env, "instant_settle");
+        let config = settlement_config(&env)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        let bps = Self::instant_haircut(env.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotConfigured));
+        let receipt = reputation::read_receipt(&env, receipt_id);
+        let school = rotation::current(&env, &receipt.school);
+        school.require_auth();
+
+        let key = SettlementKey::InstantSettled(receipt_id);
+        if env.storage().persistent().has(&key) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        env.storage().persistent().set(&key, &true);
+
+        let haircut = math::mul_div(&env, receipt.amount, bps as i128, 10_000, Rounding::Down);
+        let paid = receipt.amount - haircut;
+        let balance = demurrage::settle(&env, &school, false);
+        holds::require_spendable(&env, &school, balance, receipt.amount);
+        write_balance(&env, &school, balance - haircut);
+        dust::collect(&env, haircut);
+        redeem(&env, &school, &school, paid, false);
+
+        let payout = Self::payout_address(env.clone(), school.clone()).unwrap_or(school.clone());
+        token::Client::new(&env, &config.asset).transfer_from(
+            &env.current_contract_address(),
+            &config.treasurer,
+            &payout,
+            &paid,
+        );
+        events::publish(
+            &env,
+            (symbol_short!("instant"), school, receipt_id),
+            (paid, haircut),
+        );
+
+        paid
+    }
+}
+
+/// Add a school's redemption to its batch for the current period, while
+/// settlement is enabled
+pub(crate) fn record_redemption(env: &Env, authorizer: &Address, school: &Address, amount: i128) {
+    if settlement_config(env).is_none() {
+        return;
+    }
+
+    let now = env.ledger().timestamp();
+    let period = period_of(now);
+    let key = SettlementKey::RedemptionBatch(school.clone(), period);
+    let mut batch: SettlementBatch = env.storage().persistent().get(&key).unwrap_or_default();
+    env.storage().persistent().set(
+        &SettlementKey::RedemptionLine(school.clone(), period, batch.lines),
+        &SettlementLine {
+            amount,
+            redeemed_at: now,
+            authorized_by: authorizer.clone(),
+        },
+    );
+    batch.total = checked_add(env, batch.total, amount);
+    batch.lines += 1;
+    env.storage().persistent().set(&key, &batch);
+}
+
+/// Move a school's payout address to its rotated key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &SettlementKey::PayoutAddress(old.clone()),
+        &SettlementKey::PayoutAddress(new.clone()),
+    );
+}
+
+fn settlement_config(env: &Env) -> Option<SettlementConfig> {
+    env.storage().instance().get(&SettlementKey::TreasuryConfig)
+}
+
+fn period_of(timestamp: u64) -> u64 {
+    timestamp / SETTLEMENT_PERIOD_SECS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{advance, Scenario, DAY_SECS};
+    use crate::ReputationConfig;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    // Two redemptions by the school in the current period
+    fn setup() -> (Scenario, SettlementConfig, u64) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 1000);
+        let treasurer = Address::generate(&s.env);
+        let asset = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        StellarAssetClient::new(&s.env, &asset).mint(&treasurer, &10_000);
+        let config = SettlementConfig { treasurer, asset };
+        let client = s.client();
+        client.set_settlement_config(&Some(config.clone()));
+
+        client.transfer(&s.beneficiary(0), &s.school(0), &500);
+        client.burn(&s.school(0), &200);
+        client.burn(&s.school(0), &300);
+        let period = period_of(s.now());
+        (s, config, period)
+    }
+
+    #[test]
+    fn test_batch_settled_in_one_transfer() {
+        let (s, config, period) = setup();
+        let client = s.client();
+        let lines = client.batch_lines(&s.school(0), &period, &0, &10);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines.get_unchecked(1).amount, 300);
+        assert_eq!(lines.get_unchecked(1).authorized_by, s.school(0));
+
+        // The period has to end first
+        let result = client.try_settle_batch(&s.school(0), &period);
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+        advance(&s.env, SETTLEMENT_PERIOD_SECS);
+        assert_eq!(client.settle_batch(&s.school(0), &period), 500);
+
+        let token = TokenClient::new(&s.env, &config.asset);
+        assert_eq!(token.balance(&s.school(0)), 500);
+        assert_eq!(token.balance(&config.treasurer), 9_500);
+        let batch = client.settlement_batch(&s.school(0), &period).unwrap();
+        assert_eq!(batch.settled_at, s.now());
+        let result = client.try_settle_batch(&s.school(0), &period);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+
+    #[test]
+    fn test_batch_paid_to_payout_address() {
+        let (s, config, period) = setup();
+        let client = s.client();
+        let bursar = s.school(0);
+        let anchor = Address::generate(&s.env);
+        client.set_payout_address(&bursar, &Some(anchor.clone()));
+
+        // The address follows the school through a key rotation
+        let rotated = Address::generate(&s.env);
+        client.set_registry_manager(&Some(Address::generate(&s.env)));
+        client.rotate_address(&bursar, &rotated);
+        assert_eq!(client.payout_address(&rotated), Some(anchor.clone()));
+        assert_eq!(client.payout_address(&bursar), None);
+
+        advance(&s.env, SETTLEMENT_PERIOD_SECS);
+        client.settle_batch(&bursar, &period);
+        let token = TokenClient::new(&s.env, &config.asset);
+        assert_eq!(token.balance(&anchor), 500);
+        assert_eq!(token.balance(&rotated), 0);
+    }
+
+    #[test]
+    fn test_only_school_redemptions_are_batched() {
+        let (s, _, period) = setup();
+        let client = s.client();
+        client.burn(&s.beneficiary(0), &100);
+        assert_eq!(client.settlement_batch(&s.beneficiary(0), &period), None);
+        assert_eq!(client.batch_lines(&s.school(0), &period, &1, &10).len(), 1);
+    }
+
+    #[test]
+    fn test_instant_settlement_takes_a_haircut() {
+        let (s, config, period) = setup();
+        let client = s.client();
+        client.set_reputation_config(&Some(ReputationConfig {
+            settlement_window_secs: 7 * DAY_SECS,
+            large_redemption: 10_000,
+            min_score: 0,
+        }));
+        client.transfer(&s.beneficiary(0), &s.school(0), &400);
+        let receipt_id = s
+            .env
+            .as_contract(&s.contract_id, || reputation::receipt_count(&s.env));
+        let result = client.try_instant_settle(&receipt_id);
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+
+        client.set_instant_haircut(&Some(150));
+        let token = TokenClient::new(&s.env, &config.asset);
+        token.approve(&config.treasurer, &s.contract_id, &1000, &1000);
+        assert_eq!(client.instant_settle(&receipt_id), 394);
+        assert_eq!(s.env.auths()[0].0, s.school(0));
+        assert_eq!(token.balance(&s.school(0)), 394);
+        assert_eq!(client.balance(&s.school(0)), 0);
+        assert_eq!(client.treasury_balance(), 6);
+        // Left out of the weekly batch, which pays in full
+        assert_eq!(
+            client
+                .settlement_batch(&s.school(0), &period)
+                .unwrap()
+                .total,
+            500
+        );
+        let result = client.try_instant_settle(&receipt_id);
+        assert_eq!(result, Err(Ok(Error::AlreadyExists.into())));
+    }
+}
@@ -0,0 +1,195 @@
+use soroban_sdk::{Address, Env, IntoVal, Val};
+
+use crate::{Allocation, DataKey};
+
+// Storage tier policy:
+// - instance: contract-wide configuration and counters
+// - persistent: balances, allocations and any record that carries value or
+//   history past every deadline
+// - temporary: records that are meaningless once a deadline passes (e.g.
+//   a day's spend against a quota); written with `set_temporary` so
+//   they expire instead of paying rent forever, or with `set_outlasting`
+//   when losing one early would be unsafe (e.g. replay records)
+// DataKey tiers are pinned by the exhaustive match in the tests below.
+
+// Approximate ledger close time, for converting lifetimes to TTLs
+const LEDGER_SECS: u64 = 5;
+
+// Fastest ledger close time `set_outlasting` allows for, well under the
+// usual rate so a record outlives its lifetime even while ledgers close
+// faster than expected
+const MIN_LEDGER_SECS: u64 = 1;
+
+// Balance-affecting state of one account, read once per operation so hot
+// paths don't go back to storage for each check
+pub(crate) struct AccountState {
+    pub balance: i128, // Stored (gross) balance
+    pub allocation: Option<Allocation>,
+}
+
+impl AccountState {
+    // Beneficiaries are accounts holding an allocation
+    pub(crate) fn is_beneficiary(&self) -> bool {
+        self.allocation.is_some()
+    }
+}
+
+/// Load an account's stored balance and allocation in one pass
+pub(crate) fn load_account(env: &Env, account: &Address) -> AccountState {
+    let persistent = env.storage().persistent();
+    AccountState {
+        balance: persistent
+            .get(&DataKey::Credits(account.clone()))
+            .unwrap_or(0),
+        allocation: persistent.get(&DataKey::Allocations(account.clone())),
+    }
+}
+
+/// Store a short-lived record in temporary storage, kept alive for at
+/// least `lifetime_secs` (capped at the network's maximum TTL)
+pub(crate) fn set_temporary<K, V>(env: &Env, key: &K, value: &V, lifetime_secs: u64)
+where
+    K: IntoVal<Env, Val>,
+    V: IntoVal<Env, Val>,
+{
+    let temporary = env.storage().temporary();
+    temporary.set(key, value);
+
+    let ledgers = (lifetime_secs / LEDGER_SECS + 1).min(env.storage().max_ttl() as u64) as u32;
+    temporary.extend_ttl(key, ledgers, ledgers);
+}
+
+/// Store a record that must not lapse before `lifetime_secs` pass: in
+/// temporary storage for a TTL converted at the fastest ledger close time,
+/// or in persistent storage when that TTL would pass the network's maximum
+pub(crate) fn set_outlasting<K, V>(env: &Env, key: &K, value: &V, lifetime_secs: u64)
+where
+    K: IntoVal<Env, Val>,
+    V: IntoVal<Env, Val>,
+{
+    let ledgers = lifetime_secs / MIN_LEDGER_SECS + 1;
+    if ledgers > env.storage().max_ttl() as u64 {
+        env.storage().persistent().set(key, value);
+        return;
+    }
+    let temporary = env.storage().temporary();
+    temporary.set(key, value);
+    temporary.extend_ttl(key, ledgers as u32, ledgers as u32);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{DataKey, EduPassToken, EduPassTokenClient};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{symbol_short, Address, Env, String};
+
+    // Guard against regressions in the cost of the hottest path. A
+    // beneficiary's transfer also writes back its allocation's remainder,
+    // and each versioned event reads the deployment's instance id. Paying a
+    // school also checks whether a school registry is configured and
+    // whether any payee has a merchant type. Every transfer checks whether
+    // a screening contract is configured.
+    const MAX_TRANSFER_CPU: u64 = 215_000;
+    const MAX_TRANSFER_MEM: u64 = 25_000;
+
+    #[derive(Debug, PartialEq)]
+    enum Tier {
+        Instance,
+        Persistent,
+    }
+
+    // Every DataKey variant must be assigned a tier here
+    fn tier(key: &DataKey) -> Tier {
+        match key {
+            DataKey::Admin
+            | DataKey::TotalIssued
+            | DataKey::MaxBalance
+            | DataKey::Governance
+            | DataKey::TotalBurned => Tier::Instance,
+            DataKey::Credits(_)
+            | DataKey::Allocations(_)
+            | DataKey::Template(_, _)
+            | DataKey::ProgramMaxBalance(_) => Tier::Persistent,
+        }
+    }
+
+    #[test]
+    fn test_data_keys_follow_tier_policy() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+
+        client.initialize(&Address::generate(&env));
+        client.set_governance(&Address::generate(&env));
+        client.set_max_balance_per_beneficiary(&Some(10_000));
+        client.set_program_max_balance(&symbol_short!("uni"), &Some(5000));
+        client.save_template(
+            &issuer,
+            &symbol_short!("uni"),
+            &String::from_str(&env, "University Fees"),
+            &100,
+            &86400,
+            &symbol_short!("uni"),
+        );
+        client.issue_from_template(&issuer, &symbol_short!("uni"), &beneficiary);
+        client.burn(&beneficiary, &10);
+
+        let keys = [
+            DataKey::Admin,
+            DataKey::TotalIssued,
+            DataKey::MaxBalance,
+            DataKey::Governance,
+            DataKey::TotalBurned,
+            DataKey::Credits(beneficiary.clone()),
+            DataKey::Allocations(beneficiary.clone()),
+            DataKey::Template(issuer.clone(), symbol_short!("uni")),
+            DataKey::ProgramMaxBalance(symbol_short!("uni")),
+        ];
+        env.as_contract(&contract_id, || {
+            for key in keys.iter() {
+                let stored = match tier(key) {
+                    Tier::Instance => env.storage().instance().has(key),
+                    Tier::Persistent => env.storage().persistent().has(key),
+                };
+                assert!(stored, "{:?} not in its tier", tier(key));
+            }
+        });
+    }
+
+    #[test]
+    #[cfg_attr(
+        feature = "telemetry",
+        ignore = "budgets are for builds without telemetry"
+    )]
+    fn test_transfer_budget() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let beneficiary = Address::generate(&env);
+        let school = Address::generate(&env);
+
+        client.initialize(&Address::generate(&env));
+        client.issue_credits(
+            &Address::generate(&env),
+            &beneficiary,
+            &1000,
+            &String::from_str(&env, "Tuition"),
+            &1735689600,
+        );
+        client.transfer(&beneficiary, &school, &10);
+
+        env.budget().reset_default();
+        client.transfer(&beneficiary, &school, &10);
+
+        assert!(env.budget().cpu_instruction_cost() <= MAX_TRANSFER_CPU);
+        assert!(env.budget().memory_bytes_cost() <= MAX_TRANSFER_MEM);
+    }
+}
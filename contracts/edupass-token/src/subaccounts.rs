@@ -0,0 +1,207 @@
+// Named sub-accounts under a school, such as `tuition`, `boarding` and
+// `exams`, so a large school can track its departments' receipts
+// separately. Credits still sit in the school's one balance; a sub-account
+// attributes the payments made to it and the settlements the school burns
+// against it. Plain transfers and burns leave the sub-accounts untouched.
+
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Symbol, Vec,
+};
+
+use crate::{
+    burn_credits, checked_add, events, rotation, telemetry, transfer_credits, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+// Sub-accounts one school can open
+const MAX_SUBACCOUNTS: u32 = 16;
+
+#[contracttype]
+enum SubaccountKey {
+    Subaccounts(Address), // Names of a school's sub-accounts, in opening order
+    Subaccount(Address, Symbol), // A sub-account's receipts and settlements
+}
+
+// What a school sub-account has received and settled
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SubaccountReport {
+    pub redeemed: i128,   // Paid in by beneficiaries
+    pub redemptions: u32, // Payments received
+    pub settled: i128,    // Burned by the school against the sub-account
+    pub balance: i128,    // Redeemed and not yet settled
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Open a named sub-account that beneficiaries can pay into (the
+    /// school)
+    pub fn credit_subaccount(env: Env, school: Address, subaccount: Symbol) {
+        telemetry::record(&env, "credit_subaccount");
+        school.require_auth();
+
+        let mut names = Self::subaccounts(env.clone(), school.clone());
+        if names.contains(&subaccount) {
+            panic_with_error!(&env, Error::AlreadyExists);
+        }
+        if names.len() >= MAX_SUBACCOUNTS {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        names.push_back(subaccount.clone());
+        env.storage()
+            .persistent()
+            .set(&SubaccountKey::Subaccounts(school.clone()), &names);
+        write_report(&env, &school, &subaccount, &SubaccountReport::default());
+    }
+
+    /// Get the names of a school's sub-accounts, in opening order
+    pub fn subaccounts(env: Env, school: Address) -> Vec<Symbol> {
+        env.storage()
+            .persistent()
+            .get(&SubaccountKey::Subaccounts(school))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Pay a school, attributing the payment to one of its sub-accounts.
+    /// Runs every check `transfer` does.
+    pub fn transfer_to_subaccount(
+        env: Env,
+        from: Address,
+        school: Address,
+        subaccount: Symbol,
+        amount: i128,
+    ) {
+        telemetry::record(&env, "transfer_to_subaccount");
+        from.require_auth();
+
+        let mut report = read_report(&env, &school, &subaccount);
+        transfer_credits(&env, &from, &from, &school, amount);
+        report.redeemed = checked_add(&env, report.redeemed, amount);
+        report.redemptions = report.redemptions.saturating_add(1);
+        report.balance = checked_add(&env, report.balance, amount);
+        write_report(&env, &school, &subaccount, &report);
+        events::publish(
+            &env,
+            (symbol_short!("subpay"), school, subaccount),
+            (from, amount),
+        );
+    }
+
+    /// Burn credits a sub-account received, settling them (the school).
+    /// Fails with InsufficientBalance beyond the sub-account's unsettled
+    /// receipts.
+    pub fn burn_from_subaccount(env: Env, school: Address, subaccount: Symbol, amount: i128) {
+        telemetry::record(&env, "burn_from_subaccount");
+        school.require_auth();
+
+        let mut report = read_report(&env, &school, &subaccount);
+        if amount > report.balance {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+        burn_credits(&env, &school, &school, amount);
+        report.settled = checked_add(&env, report.settled, amount);
+        report.balance -= amount;
+        write_report(&env, &school, &subaccount, &report);
+        events::publish(&env, (symbol_short!("subburn"), school, subaccount), amount);
+    }
+
+    /// Report what a school's sub-account has received and settled
+    pub fn subaccount_report(
+        env: Env,
+        school: Address,
+        subaccount: Symbol,
+    ) -> Option<SubaccountReport> {
+        env.storage()
+            .persistent()
+            .get(&SubaccountKey::Subaccount(school, subaccount))
+    }
+}
+
+/// Move a school's sub-accounts and their reports to its new key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    for name in EduPassToken::subaccounts(env.clone(), old.clone()).iter() {
+        rotation::move_persistent(
+            env,
+            &SubaccountKey::Subaccount(old.clone(), name.clone()),
+            &SubaccountKey::Subaccount(new.clone(), name),
+        );
+    }
+    rotation::move_persistent(
+        env,
+        &SubaccountKey::Subaccounts(old.clone()),
+        &SubaccountKey::Subaccounts(new.clone()),
+    );
+}
+
+fn read_report(env: &Env, school: &Address, subaccount: &Symbol) -> SubaccountReport {
+    EduPassToken::subaccount_report(env.clone(), school.clone(), subaccount.clone())
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound))
+}
+
+fn write_report(env: &Env, school: &Address, subaccount: &Symbol, report: &SubaccountReport) {
+    env.storage().persistent().set(
+        &SubaccountKey::Subaccount(school.clone(), subaccount.clone()),
+        report,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_receipts_tracked_per_subaccount() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(2)
+            .issue(0, 0, 1000)
+            .issue(0, 1, 1000);
+        let client = s.client();
+        let school = s.school(0);
+        let (tuition, boarding) = (symbol_short!("tuition"), symbol_short!("boarding"));
+        client.credit_subaccount(&school, &tuition);
+        assert_eq!(s.env.auths()[0].0, school);
+        client.credit_subaccount(&school, &boarding);
+        assert_eq!(
+            client.subaccounts(&school),
+            vec![&s.env, tuition.clone(), boarding.clone()]
+        );
+
+        client.transfer_to_subaccount(&s.beneficiary(0), &school, &tuition, &600);
+        client.transfer_to_subaccount(&s.beneficiary(1), &school, &tuition, &200);
+        client.transfer_to_subaccount(&s.beneficiary(1), &school, &boarding, &300);
+        assert_eq!(client.balance(&school), 1100);
+        let result = client.try_transfer_to_subaccount(
+            &s.beneficiary(0),
+            &school,
+            &symbol_short!("exams"),
+            &1,
+        );
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+
+        client.burn_from_subaccount(&school, &tuition, &500);
+        let result = client.try_burn_from_subaccount(&school, &boarding, &301);
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+        assert_eq!(
+            client.subaccount_report(&school, &tuition),
+            Some(SubaccountReport {
+                redeemed: 800,
+                redemptions: 2,
+                settled: 500,
+                balance: 300,
+            })
+        );
+        assert_eq!(
+            client
+                .subaccount_report(&school, &boarding)
+                .unwrap()
+                .balance,
+            300
+        );
+        assert_eq!(client.total_burned(), 500);
+    }
+}
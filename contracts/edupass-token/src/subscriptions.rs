@@ -0,0 +1,159 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, symbol_short, Address, Env, Vec};
+
+use crate::{events, telemetry, DataKey, EduPassToken, EduPassTokenClient, Error};
+
+// Bounds the watchers stored per account
+const MAX_WATCHERS: u32 = 10;
+
+#[contracttype]
+enum SubscriptionKey {
+    Watchers(Address), // Watchers registered for an account's balance changes
+    WatchedAccounts,   // Number of accounts with at least one watcher
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Register `watcher`'s interest in `account` (the watcher). Every
+    /// change to the account's balance then also emits a `balchange` event
+    /// under the account's own topic.
+    pub fn watch(env: Env, watcher: Address, account: Address) {
+        telemetry::record(&env, "watch");
+        watcher.require_auth();
+
+        let mut watchers = watchers_of(&env, &account);
+        if watchers.contains(&watcher) {
+            return;
+        }
+        if watchers.len() >= MAX_WATCHERS {
+            panic_with_error!(&env, Error::BudgetExceeded);
+        }
+        if watchers.is_empty() {
+            set_watched_accounts(&env, watched_accounts(&env) + 1);
+        }
+        watchers.push_back(watcher);
+        env.storage()
+            .persistent()
+            .set(&SubscriptionKey::Watchers(account), &watchers);
+    }
+
+    /// Withdraw `watcher`'s interest in `account` (the watcher)
+    pub fn unwatch(env: Env, watcher: Address, account: Address) {
+        telemetry::record(&env, "unwatch");
+        watcher.require_auth();
+
+        let mut watchers = watchers_of(&env, &account);
+        let Some(index) = watchers.first_index_of(&watcher) else {
+            return;
+        };
+        watchers.remove(index);
+        let key = SubscriptionKey::Watchers(account);
+        if watchers.is_empty() {
+            env.storage().persistent().remove(&key);
+            set_watched_accounts(&env, watched_accounts(&env) - 1);
+        } else {
+            env.storage().persistent().set(&key, &watchers);
+        }
+    }
+
+    /// Get the watchers registered for an account
+    pub fn watchers(env: Env, account: Address) -> Vec<Address> {
+        watchers_of(&env, &account)
+    }
+}
+
+/// Announce a balance about to be written for a watched account, with the
+/// change from its stored balance
+pub(crate) fn notify(env: &Env, account: &Address, balance: i128) {
+    // Spares the lookup on every balance write until the first watch
+    if watched_accounts(env) == 0 {
+        return;
+    }
+    if !env
+        .storage()
+        .persistent()
+        .has(&SubscriptionKey::Watchers(account.clone()))
+    {
+        return;
+    }
+    let previous: i128 = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Credits(account.clone()))
+        .unwrap_or(0);
+    if balance != previous {
+        events::publish(
+            env,
+            (symbol_short!("balchange"), account.clone()),
+            (balance.saturating_sub(previous), balance),
+        );
+    }
+}
+
+fn watchers_of(env: &Env, account: &Address) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&SubscriptionKey::Watchers(account.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn watched_accounts(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&SubscriptionKey::WatchedAccounts)
+        .unwrap_or(0)
+}
+
+fn set_watched_accounts(env: &Env, count: u32) {
+    env.storage()
+        .instance()
+        .set(&SubscriptionKey::WatchedAccounts, &count);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{event_payload, Scenario};
+    use soroban_sdk::testutils::{Address as _, Events};
+    use soroban_sdk::{vec, IntoVal, Val};
+
+    // balchange events so far for an account, as (delta, balance)
+    fn changes(s: &Scenario, account: &Address) -> Vec<(i128, i128)> {
+        let topics: Vec<Val> = (symbol_short!("balchange"), 5u32, account.clone()).into_val(&s.env);
+        let mut changes = Vec::new(&s.env);
+        for (_, t, data) in s.env.events().all().iter() {
+            if t == topics {
+                changes.push_back(event_payload(&s.env, &data).into_val(&s.env));
+            }
+        }
+        changes
+    }
+
+    #[test]
+    fn test_watched_accounts_announce_balance_changes() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1);
+        let client = s.client();
+        let watcher = Address::generate(&s.env);
+        client.watch(&watcher, &s.beneficiary(0));
+        client.watch(&watcher, &s.beneficiary(0));
+        assert_eq!(client.watchers(&s.beneficiary(0)).len(), 1);
+
+        s.issue_allocation(0, 0, 1000);
+        assert_eq!(changes(&s, &s.beneficiary(0)), vec![&s.env, (1000, 1000)]);
+        client.transfer(&s.beneficiary(0), &s.school(0), &300);
+        assert_eq!(
+            changes(&s, &s.beneficiary(0)),
+            vec![&s.env, (1000, 1000), (-300, 700)]
+        );
+        // Unwatched accounts stay quiet
+        assert!(changes(&s, &s.school(0)).is_empty());
+
+        client.unwatch(&watcher, &s.beneficiary(0));
+        client.transfer(&s.beneficiary(0), &s.school(0), &300);
+        assert_eq!(changes(&s, &s.beneficiary(0)).len(), 2);
+        assert!(client.watchers(&s.beneficiary(0)).is_empty());
+    }
+}
@@ -0,0 +1,535 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, Address, Env, Map, String, Symbol, Vec,
+};
+
+use crate::{
+    issue, require_admin, rotation, telemetry, validity, Allocation, DataKey, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+// Bounds on issuer annotations so allocation records stay small: the
+// defaults apply until the admin sets limits, which can't exceed the ceilings
+const DEFAULT_MAX_TAGS: u32 = 8;
+const DEFAULT_MAX_TAG_VALUE_LEN: u32 = 64;
+const MAX_TAGS_CEILING: u32 = 32;
+const MAX_TAG_VALUE_LEN_CEILING: u32 = 256;
+const MAX_TAG_PAGE: u32 = 100;
+
+// Each tag's index is kept one entry per beneficiary, so it grows without
+// bound at a fixed cost per write: a count, the beneficiary at each
+// position, and each beneficiary's position for removal in place
+#[contracttype]
+enum TagKey {
+    TagCount(Address, Symbol, String), // Beneficiaries tagged (issuer, key, value)
+    TagAt(Address, Symbol, String, u32), // Beneficiary at a position of the index
+    TagSlot(Address, Symbol, String, Address), // Position of a tagged beneficiary
+    MetadataLimits,                    // Admin-set bounds on allocation metadata
+}
+
+// How much metadata one allocation may carry
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetadataLimits {
+    pub max_entries: u32,
+    pub max_value_len: u32, // Longest value, in bytes
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Issue credits with issuer annotations (e.g. case file number)
+    pub fn issue_tagged(
+        env: Env,
+        issuer: Address,
+        beneficiary: Address,
+        amount: i128,
+        purpose: String,
+        expires_at: u64,
+        metadata: Map<Symbol, String>,
+    ) -> Allocation {
+        issuer.require_auth();
+        let expires_at = validity::resolve_expiry(&env, expires_at);
+
+        let allocation = issue(
+            &env,
+            issuer,
+            beneficiary,
+            amount,
+            purpose,
+            expires_at,
+            None,
+            metadata,
+        );
+        for (key, value) in allocation.metadata.iter() {
+            index_add(&env, &allocation, &key, &value);
+        }
+        telemetry::record_sized(&env, "issue_tagged", allocation.metadata.len());
+
+        allocation
+    }
+
+    /// Set or reset (None) the bounds on allocation metadata (admin only).
+    /// Limits can't exceed 32 entries or 256-byte values.
+    pub fn set_metadata_limits(env: Env, limits: Option<MetadataLimits>) {
+        telemetry::record(&env, "set_metadata_limits");
+        require_admin(&env);
+
+        match limits {
+            Some(limits) => {
+                if limits.max_entries > MAX_TAGS_CEILING
+                    || limits.max_value_len > MAX_TAG_VALUE_LEN_CEILING
+                {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage()
+                    .instance()
+                    .set(&TagKey::MetadataLimits, &limits);
+            }
+            None => env.storage().instance().remove(&TagKey::MetadataLimits),
+        }
+    }
+
+    /// Get the bounds on allocation metadata in effect
+    pub fn metadata_limits(env: Env) -> MetadataLimits {
+        env.storage()
+            .instance()
+            .get(&TagKey::MetadataLimits)
+            .unwrap_or(MetadataLimits {
+                max_entries: DEFAULT_MAX_TAGS,
+                max_value_len: DEFAULT_MAX_TAG_VALUE_LEN,
+            })
+    }
+
+    /// Set or remove (None) an annotation on an allocation (issuer only)
+    pub fn set_allocation_tag(
+        env: Env,
+        issuer: Address,
+        beneficiary: Address,
+        key: Symbol,
+        value: Option<String>,
+    ) -> Allocation {
+        telemetry::record(&env, "set_allocation_tag");
+        issuer.require_auth();
+
+        let mut allocation: Allocation = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Allocations(beneficiary))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotFound));
+        if rotation::current(&env, &allocation.issuer) != issuer {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        if let Some(previous) = allocation.metadata.get(key.clone()) {
+            index_remove(&env, &allocation, &key, &previous);
+        }
+        match value {
+            Some(value) => {
+                allocation.metadata.set(key.clone(), value.clone());
+                check_metadata(&env, &allocation.metadata);
+                index_add(&env, &allocation, &key, &value);
+            }
+            None => {
+                allocation.metadata.remove(key);
+            }
+        }
+        save_allocation(&env, &allocation);
+
+        allocation
+    }
+
+    /// List beneficiaries whose allocation from `issuer` carries the tag,
+    /// paging through at most 100 entries from `cursor`. Removals move the
+    /// last entry into the gap, so the order isn't that of tagging.
+    pub fn allocations_by_tag(
+        env: Env,
+        issuer: Address,
+        key: Symbol,
+        value: String,
+        cursor: u32,
+        limit: u32,
+    ) -> Vec<Address> {
+        let issuer = index_issuer(&env, &issuer, &key, &value);
+        let count = tag_count(&env, &issuer, &key, &value);
+        let end = cursor.saturating_add(limit.min(MAX_TAG_PAGE)).min(count);
+
+        let persistent = env.storage().persistent();
+        let mut page = Vec::new(&env);
+        for i in cursor..end {
+            let at = TagKey::TagAt(issuer.clone(), key.clone(), value.clone(), i);
+            if let Some(beneficiary) = persistent.get(&at) {
+                page.push_back(beneficiary);
+            }
+        }
+        page
+    }
+}
+
+/// Drop a beneficiary's current allocation from the tag index before the
+/// allocation is superseded or removed
+pub(crate) fn clear_index(env: &Env, beneficiary: &Address) {
+    let allocation: Option<Allocation> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Allocations(beneficiary.clone()));
+    if let Some(allocation) = allocation {
+        for (key, value) in allocation.metadata.iter() {
+            index_remove(env, &allocation, &key, &value);
+        }
+    }
+}
+
+/// Refuse metadata beyond the limits in effect
+pub(crate) fn check_metadata(env: &Env, metadata: &Map<Symbol, String>) {
+    // Untagged issuance skips the limits lookup
+    if metadata.is_empty() {
+        return;
+    }
+    let limits = EduPassToken::metadata_limits(env.clone());
+    if metadata.len() > limits.max_entries {
+        panic_with_error!(env, Error::InvalidConfig);
+    }
+    for (_, value) in metadata.iter() {
+        if value.len() > limits.max_value_len {
+            panic_with_error!(env, Error::InvalidConfig);
+        }
+    }
+}
+
+fn save_allocation(env: &Env, allocation: &Allocation) {
+    env.storage().persistent().set(
+        &DataKey::Allocations(allocation.beneficiary.clone()),
+        allocation,
+    );
+}
+
+fn tag_count(env: &Env, issuer: &Address, key: &Symbol, value: &String) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&TagKey::TagCount(
+            issuer.clone(),
+            key.clone(),
+            value.clone(),
+        ))
+        .unwrap_or(0)
+}
+
+// The key an issuer's tag index is kept under: indexes stay where they
+// were first written when the issuer rotates, and later keys add to them
+fn index_issuer(env: &Env, issuer: &Address, key: &Symbol, value: &String) -> Address {
+    rotation::holder(env, &rotation::current(env, issuer), |issuer| {
+        env.storage().persistent().has(&TagKey::TagCount(
+            issuer.clone(),
+            key.clone(),
+            value.clone(),
+        ))
+    })
+}
+
+fn index_add(env: &Env, allocation: &Allocation, key: &Symbol, value: &String) {
+    let issuer = index_issuer(env, &allocation.issuer, key, value);
+    let beneficiary = &allocation.beneficiary;
+    let persistent = env.storage().persistent();
+    let slot = TagKey::TagSlot(
+        issuer.clone(),
+        key.clone(),
+        value.clone(),
+        beneficiary.clone(),
+    );
+    if persistent.has(&slot) {
+        return;
+    }
+
+    let count = tag_count(env, &issuer, key, value);
+    persistent.set(
+        &TagKey::TagAt(issuer.clone(), key.clone(), value.clone(), count),
+        beneficiary,
+    );
+    persistent.set(&slot, &count);
+    persistent.set(
+        &TagKey::TagCount(issuer, key.clone(), value.clone()),
+        &(count + 1),
+    );
+}
+
+fn index_remove(env: &Env, allocation: &Allocation, key: &Symbol, value: &String) {
+    let issuer = index_issuer(env, &allocation.issuer, key, value);
+    let persistent = env.storage().persistent();
+    let slot = TagKey::TagSlot(
+        issuer.clone(),
+        key.clone(),
+        value.clone(),
+        allocation.beneficiary.clone(),
+    );
+    let Some(position) = persistent.get::<_, u32>(&slot) else {
+        return;
+    };
+    persistent.remove(&slot);
+
+    // Fill the gap with the last entry
+    let last = tag_count(env, &issuer, key, value) - 1;
+    let last_at = TagKey::TagAt(issuer.clone(), key.clone(), value.clone(), last);
+    if position != last {
+        let moved: Address = persistent
+            .get(&last_at)
+            .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+        persistent.set(
+            &TagKey::TagAt(issuer.clone(), key.clone(), value.clone(), position),
+            &moved,
+        );
+        persistent.set(
+            &TagKey::TagSlot(issuer.clone(), key.clone(), value.clone(), moved),
+            &position,
+        );
+    }
+    persistent.remove(&last_at);
+
+    let count_key = TagKey::TagCount(issuer, key.clone(), value.clone());
+    if last == 0 {
+        persistent.remove(&count_key);
+    } else {
+        persistent.set(&count_key, &last);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{Address as _, Events};
+    use soroban_sdk::{map, symbol_short, vec, IntoVal};
+
+    #[test]
+    fn test_tags_indexed_and_amended() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let issuer = Address::generate(&env);
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+        let year = symbol_short!("year");
+        let y2025 = String::from_str(&env, "2025");
+        let y2026 = String::from_str(&env, "2026");
+
+        client.initialize(&Address::generate(&env));
+        for beneficiary in [&first, &second] {
+            client.issue_tagged(
+                &issuer,
+                beneficiary,
+                &100,
+                &String::from_str(&env, "Tuition"),
+                &1735689600,
+                &map![&env, (year.clone(), y2025.clone())],
+            );
+        }
+        assert_eq!(
+            client.allocations_by_tag(&issuer, &year, &y2025, &0, &10),
+            vec![&env, first.clone(), second.clone()]
+        );
+        assert_eq!(
+            client.allocations_by_tag(&issuer, &year, &y2025, &1, &10),
+            vec![&env, second.clone()]
+        );
+
+        let allocation = client.set_allocation_tag(&issuer, &first, &year, &Some(y2026.clone()));
+        assert_eq!(allocation.metadata.get(year.clone()), Some(y2026.clone()));
+        assert_eq!(
+            client.allocations_by_tag(&issuer, &year, &y2025, &0, &10),
+            vec![&env, second.clone()]
+        );
+        assert_eq!(
+            client.allocations_by_tag(&issuer, &year, &y2026, &0, &10),
+            vec![&env, first.clone()]
+        );
+
+        // A new allocation supersedes the old annotations
+        client.issue_credits(
+            &issuer,
+            &second,
+            &50,
+            &String::from_str(&env, "Books"),
+            &1735689600,
+        );
+        assert!(client
+            .allocations_by_tag(&issuer, &year, &y2025, &0, &10)
+            .is_empty());
+
+        let result = client.try_set_allocation_tag(&Address::generate(&env), &first, &year, &None);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    }
+
+    #[test]
+    fn test_tag_index_fills_gaps_on_removal() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let issuer = Address::generate(&env);
+        let year = symbol_short!("year");
+        let y2025 = String::from_str(&env, "2025");
+        let tagged = [
+            Address::generate(&env),
+            Address::generate(&env),
+            Address::generate(&env),
+        ];
+
+        client.initialize(&Address::generate(&env));
+        for beneficiary in tagged.iter() {
+            client.issue_tagged(
+                &issuer,
+                beneficiary,
+                &100,
+                &String::from_str(&env, "Tuition"),
+                &1735689600,
+                &map![&env, (year.clone(), y2025.clone())],
+            );
+        }
+
+        // The last entry moves into the gap
+        client.set_allocation_tag(&issuer, &tagged[0], &year, &None);
+        assert_eq!(
+            client.allocations_by_tag(&issuer, &year, &y2025, &0, &10),
+            vec![&env, tagged[2].clone(), tagged[1].clone()]
+        );
+        assert_eq!(
+            client.allocations_by_tag(&issuer, &year, &y2025, &1, &1),
+            vec![&env, tagged[1].clone()]
+        );
+        client.set_allocation_tag(&issuer, &tagged[1], &year, &None);
+        client.set_allocation_tag(&issuer, &tagged[0], &year, &Some(y2025.clone()));
+        assert_eq!(
+            client.allocations_by_tag(&issuer, &year, &y2025, &0, &10),
+            vec![&env, tagged[2].clone(), tagged[0].clone()]
+        );
+        client.set_allocation_tag(&issuer, &tagged[2], &year, &None);
+        client.set_allocation_tag(&issuer, &tagged[0], &year, &None);
+        assert!(client
+            .allocations_by_tag(&issuer, &year, &y2025, &0, &10)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tag_index_follows_rotation() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let issuer = Address::generate(&env);
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+        let year = symbol_short!("year");
+        let y2025 = String::from_str(&env, "2025");
+        let tags = map![&env, (year.clone(), y2025.clone())];
+
+        client.initialize(&Address::generate(&env));
+        client.set_registry_manager(&Some(Address::generate(&env)));
+        client.issue_tagged(
+            &issuer,
+            &first,
+            &100,
+            &String::from_str(&env, "Tuition"),
+            &1735689600,
+            &tags,
+        );
+
+        let new = Address::generate(&env);
+        client.rotate_address(&issuer, &new);
+        assert_eq!(
+            client.allocations_by_tag(&new, &year, &y2025, &0, &10),
+            vec![&env, first.clone()]
+        );
+
+        // Tags written under the new key join the same list
+        client.issue_tagged(
+            &new,
+            &second,
+            &100,
+            &String::from_str(&env, "Tuition"),
+            &1735689600,
+            &tags,
+        );
+        assert_eq!(
+            client.allocations_by_tag(&new, &year, &y2025, &0, &10),
+            vec![&env, first.clone(), second.clone()]
+        );
+        client.set_allocation_tag(&new, &first, &year, &None);
+        assert_eq!(
+            client.allocations_by_tag(&new, &year, &y2025, &0, &10),
+            vec![&env, second]
+        );
+    }
+
+    #[test]
+    fn test_metadata_limits_and_issued_event() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        let client = EduPassTokenClient::new(&env, &contract_id);
+
+        let issuer = Address::generate(&env);
+        let beneficiary = Address::generate(&env);
+        let purpose = String::from_str(&env, "Tuition");
+        let metadata = map![
+            &env,
+            (symbol_short!("course"), String::from_str(&env, "CS101")),
+            (symbol_short!("grant"), String::from_str(&env, "G-2025-17")),
+        ];
+
+        client.initialize(&Address::generate(&env));
+        assert_eq!(client.metadata_limits().max_entries, 8);
+        client.set_metadata_limits(&Some(MetadataLimits {
+            max_entries: 1,
+            max_value_len: 16,
+        }));
+        let result = client.try_issue_tagged(
+            &issuer,
+            &beneficiary,
+            &100,
+            &purpose,
+            &1735689600,
+            &metadata,
+        );
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+        let result = client.try_set_metadata_limits(&Some(MetadataLimits {
+            max_entries: 33,
+            max_value_len: 16,
+        }));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+
+        client.set_metadata_limits(&Some(MetadataLimits {
+            max_entries: 2,
+            max_value_len: 16,
+        }));
+        client.issue_tagged(
+            &issuer,
+            &beneficiary,
+            &100,
+            &purpose,
+            &1735689600,
+            &metadata,
+        );
+        let issued = env
+            .events()
+            .all()
+            .iter()
+            .find(|(_, topics, _)| {
+                topics
+                    .get_unchecked(0)
+                    .shallow_eq(&symbol_short!("issued").to_val())
+            })
+            .unwrap();
+        let data: (Address, i128, Map<Symbol, String>) =
+            crate::testutils::event_payload(&env, &issued.2).into_val(&env);
+        assert_eq!(data, (issuer, 100, metadata.clone()));
+        assert_eq!(
+            client.get_allocation(&beneficiary).unwrap().metadata,
+            metadata
+        );
+    }
+}
@@ -0,0 +1,88 @@
+// Resource telemetry for operators tuning fees. Built with the `telemetry`
+// feature, every mutating entrypoint emits a ("telemetry", entrypoint)
+// event carrying the data size its cost grows with (0 where it is fixed),
+// and the indexer counts calls and sizes per entrypoint. A contract can't
+// read its own budget or footprint, so the events carry no cost figures:
+// dashboards price the counts with the measured benchmarks/budget.txt or
+// with transaction simulation. Production builds leave the feature off and
+// `record` compiles to nothing.
+
+use soroban_sdk::Env;
+
+/// Emit the telemetry event of an entrypoint whose cost doesn't depend on
+/// the size of its input
+pub(crate) fn record(env: &Env, entrypoint: &str) {
+    record_sized(env, entrypoint, 0);
+}
+
+/// Emit the telemetry event of an entrypoint along with the data size its
+/// cost grows with, such as metadata entries indexed or funders refunded
+#[cfg(feature = "telemetry")]
+pub(crate) fn record_sized(env: &Env, entrypoint: &str, size: u32) {
+    use soroban_sdk::{symbol_short, Symbol};
+
+    crate::events::publish(
+        env,
+        (symbol_short!("telemetry"), Symbol::new(env, entrypoint)),
+        size,
+    );
+}
+
+#[cfg(not(feature = "telemetry"))]
+#[inline(always)]
+pub(crate) fn record_sized(_env: &Env, _entrypoint: &str, _size: u32) {}
+
+#[cfg(all(test, feature = "telemetry"))]
+mod test {
+    use crate::testutils::{event_payload, Scenario};
+    use soroban_sdk::testutils::Events;
+    use soroban_sdk::{symbol_short, FromVal, Map, String, Symbol};
+
+    // Entrypoint and size of the last telemetry event
+    fn last_telemetry(s: &Scenario) -> (Symbol, u32) {
+        let (_, topics, data) = s.env.events().all().last().unwrap();
+        let (name, version, entrypoint) =
+            <(Symbol, u32, Symbol)>::from_val(&s.env, &topics.to_val());
+        assert_eq!((name, version), (symbol_short!("telemetry"), 5));
+        (
+            entrypoint,
+            u32::from_val(&s.env, &event_payload(&s.env, &data)),
+        )
+    }
+
+    #[test]
+    fn test_mutating_entrypoints_emit_telemetry() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(2)
+            .issue(0, 0, 1000);
+        let client = s.client();
+        let name = |name: &str| Symbol::new(&s.env, name);
+
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        assert_eq!(last_telemetry(&s), (name("transfer"), 0));
+
+        client.set_daily_quota(&symbol_short!("meals"), &Some(30));
+        assert_eq!(last_telemetry(&s), (name("set_daily_quota"), 0));
+
+        let mut metadata: Map<Symbol, String> = Map::new(&s.env);
+        metadata.set(symbol_short!("school"), String::from_str(&s.env, "a"));
+        metadata.set(symbol_short!("grade"), String::from_str(&s.env, "b"));
+        client.issue_tagged(
+            &s.issuer(0),
+            &s.beneficiary(1),
+            &100,
+            &String::from_str(&s.env, "Tuition"),
+            &s.expires_at(),
+            &metadata,
+        );
+        assert_eq!(last_telemetry(&s), (name("issue_tagged"), 2));
+
+        // Views don't
+        let events = s.env.events().all().len();
+        client.balance(&s.beneficiary(0));
+        assert_eq!(s.env.events().all().len(), events);
+    }
+}
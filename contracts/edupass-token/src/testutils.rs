@@ -0,0 +1,214 @@
+//! Scenario builder for multi-party tests, available to downstream crates
+//! through the `testutils` feature.
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{Address, Env, FromVal, String, Symbol, Val, Vec};
+
+use crate::{Allocation, EduPassToken, EduPassTokenClient};
+
+// Ledger time scenarios start at
+pub const START_TIMESTAMP: u64 = 1_700_000_000;
+
+pub const DAY_SECS: u64 = 24 * 60 * 60;
+
+// Lifetime of allocations issued by a scenario unless overridden
+pub const DEFAULT_VALIDITY_SECS: u64 = 365 * DAY_SECS;
+
+/// Move the ledger clock to `timestamp`
+pub fn set_time(env: &Env, timestamp: u64) {
+    env.ledger().set_timestamp(timestamp);
+}
+
+/// Move the ledger clock forward by `secs`
+pub fn advance(env: &Env, secs: u64) {
+    set_time(env, env.ledger().timestamp() + secs);
+}
+
+/// Advance the ledger sequence without touching the clock, for TTL-driven
+/// behavior such as temporary entries expiring
+pub fn advance_ledgers(env: &Env, ledgers: u32) {
+    env.ledger()
+        .set_sequence_number(env.ledger().sequence() + ledgers);
+}
+
+/// Data of a versioned event without the instance id it carries
+pub fn event_payload(env: &Env, data: &Val) -> Val {
+    let (_, payload): (Option<Symbol>, Val) = FromVal::from_val(env, data);
+    payload
+}
+
+/// Unix timestamp of 00:00 UTC on the first day of a YYYYMM month
+#[cfg(feature = "stipends")]
+pub fn month_start(month: u32) -> u64 {
+    crate::schedule::month_start(month)
+}
+
+/// A registered contract with generated parties. Auths are mocked and the
+/// ledger starts at `START_TIMESTAMP`; parties are addressed by index in
+/// the order they were added.
+pub struct Scenario {
+    pub env: Env,
+    pub contract_id: Address,
+    admin: Option<Address>,
+    issuers: Vec<Address>,
+    schools: Vec<Address>,
+    beneficiaries: Vec<Address>,
+    validity_secs: u64,
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        Scenario::with_env(Env::default())
+    }
+
+    /// Start from a caller-configured env (e.g. with snapshots disabled)
+    pub fn with_env(env: Env) -> Self {
+        env.mock_all_auths();
+        env.ledger().set_timestamp(START_TIMESTAMP);
+
+        let contract_id = env.register_contract(None, EduPassToken);
+        Scenario {
+            admin: None,
+            issuers: Vec::new(&env),
+            schools: Vec::new(&env),
+            beneficiaries: Vec::new(&env),
+            validity_secs: DEFAULT_VALIDITY_SECS,
+            contract_id,
+            env,
+        }
+    }
+
+    /// Initialize the contract with a generated admin
+    pub fn with_admin(mut self) -> Self {
+        let admin = Address::generate(&self.env);
+        self.client().initialize(&admin);
+        self.admin = Some(admin);
+        self
+    }
+
+    pub fn with_issuers(mut self, count: u32) -> Self {
+        generate(&self.env, &mut self.issuers, count);
+        self
+    }
+
+    pub fn with_schools(mut self, count: u32) -> Self {
+        generate(&self.env, &mut self.schools, count);
+        self
+    }
+
+    pub fn with_beneficiaries(mut self, count: u32) -> Self {
+        generate(&self.env, &mut self.beneficiaries, count);
+        self
+    }
+
+    /// Lifetime of allocations issued from here on
+    pub fn with_validity(mut self, secs: u64) -> Self {
+        self.validity_secs = secs;
+        self
+    }
+
+    /// Issue credits from one issuer to one beneficiary
+    pub fn issue(self, issuer: u32, beneficiary: u32, amount: i128) -> Self {
+        self.issue_allocation(issuer, beneficiary, amount);
+        self
+    }
+
+    /// Issue credits and return the stored allocation
+    pub fn issue_allocation(&self, issuer: u32, beneficiary: u32, amount: i128) -> Allocation {
+        self.client().issue_credits(
+            &self.issuer(issuer),
+            &self.beneficiary(beneficiary),
+            &amount,
+            &String::from_str(&self.env, "Tuition"),
+            &self.expires_at(),
+        )
+    }
+
+    /// Expiry of an allocation issued now
+    pub fn expires_at(&self) -> u64 {
+        self.env.ledger().timestamp() + self.validity_secs
+    }
+
+    pub fn advance_time(self, secs: u64) -> Self {
+        advance(&self.env, secs);
+        self
+    }
+
+    pub fn advance_days(self, days: u64) -> Self {
+        self.advance_time(days * DAY_SECS)
+    }
+
+    /// Jump to an absolute time, e.g. one second before a deadline
+    pub fn set_time(self, timestamp: u64) -> Self {
+        set_time(&self.env, timestamp);
+        self
+    }
+
+    pub fn now(&self) -> u64 {
+        self.env.ledger().timestamp()
+    }
+
+    pub fn client(&self) -> EduPassTokenClient<'_> {
+        EduPassTokenClient::new(&self.env, &self.contract_id)
+    }
+
+    pub fn admin(&self) -> Address {
+        self.admin.clone().expect("scenario has no admin")
+    }
+
+    pub fn issuer(&self, index: u32) -> Address {
+        self.issuers.get(index).expect("no such issuer")
+    }
+
+    pub fn school(&self, index: u32) -> Address {
+        self.schools.get(index).expect("no such school")
+    }
+
+    pub fn beneficiary(&self, index: u32) -> Address {
+        self.beneficiaries.get(index).expect("no such beneficiary")
+    }
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Scenario::new()
+    }
+}
+
+fn generate(env: &Env, parties: &mut Vec<Address>, count: u32) {
+    for _ in 0..count {
+        parties.push_back(Address::generate(env));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn test_issue_spend_and_expire() {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(2)
+            .with_beneficiaries(2)
+            .with_validity(30 * 24 * 60 * 60)
+            .issue(0, 0, 1000)
+            .issue(0, 1, 500);
+
+        let client = s.client();
+        client.transfer(&s.beneficiary(0), &s.school(0), &400);
+        client.transfer(&s.beneficiary(1), &s.school(1), &500);
+
+        let result = client.try_sweep_expired(&s.beneficiary(0));
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+
+        let s = s.advance_time(30 * 24 * 60 * 60);
+        let client = s.client();
+        assert_eq!(client.sweep_expired(&s.beneficiary(0)), 600);
+        assert_eq!(client.balance(&s.issuer(0)), 600);
+        assert_eq!(client.balance(&s.school(0)), 400);
+        assert_eq!(client.total_issued(), 1500);
+    }
+}
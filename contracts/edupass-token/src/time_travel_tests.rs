@@ -0,0 +1,224 @@
+// Deadline edges: each test checks behavior one second before and exactly
+// at a time boundary, moving the ledger clock with the testutils helpers.
+
+extern crate std;
+
+use soroban_sdk::testutils::Events;
+use soroban_sdk::{symbol_short, FromVal, String};
+
+use crate::testutils::{event_payload, Scenario, DAY_SECS, START_TIMESTAMP};
+use crate::{DemurrageConfig, Error};
+
+#[test]
+fn test_allocation_expiry_boundary() {
+    let s = Scenario::new()
+        .with_admin()
+        .with_issuers(1)
+        .with_beneficiaries(1)
+        .with_validity(DAY_SECS)
+        .issue(0, 0, 1000);
+    let expires_at = START_TIMESTAMP + DAY_SECS;
+
+    let s = s.set_time(expires_at - 1);
+    let result = s.client().try_sweep_expired(&s.beneficiary(0));
+    assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+
+    let s = s.set_time(expires_at);
+    let client = s.client();
+    assert_eq!(client.sweep_expired(&s.beneficiary(0)), 1000);
+    assert_eq!(client.balance(&s.issuer(0)), 1000);
+}
+
+#[test]
+fn test_closure_follows_expiry_boundary() {
+    let s = Scenario::new()
+        .with_admin()
+        .with_issuers(1)
+        .with_schools(1)
+        .with_beneficiaries(2)
+        .with_validity(DAY_SECS)
+        .issue(0, 0, 1000)
+        .issue(0, 1, 1000);
+    let expires_at = START_TIMESTAMP + DAY_SECS;
+
+    // Unexpired credits go where the account asks
+    let s = s.set_time(expires_at - 1);
+    let client = s.client();
+    client.close_account(&s.beneficiary(0), &Some(s.school(0)));
+    assert_eq!(client.balance(&s.school(0)), 1000);
+
+    // Expired credits follow the expiry policy instead
+    let s = s.set_time(expires_at);
+    let client = s.client();
+    client.close_account(&s.beneficiary(1), &Some(s.school(0)));
+    assert_eq!(client.balance(&s.school(0)), 1000);
+    assert_eq!(client.balance(&s.issuer(0)), 1000);
+}
+
+#[test]
+fn test_template_validity_starts_at_issuance() {
+    let s = Scenario::new()
+        .with_admin()
+        .with_issuers(1)
+        .with_beneficiaries(1);
+    let client = s.client();
+    client.save_template(
+        &s.issuer(0),
+        &symbol_short!("term"),
+        &String::from_str(&s.env, "Term Fees"),
+        &500,
+        &(30 * DAY_SECS),
+        &symbol_short!("uni"),
+    );
+
+    let s = s.advance_days(10);
+    let client = s.client();
+    let allocation =
+        client.issue_from_template(&s.issuer(0), &symbol_short!("term"), &s.beneficiary(0));
+    assert_eq!(allocation.expires_at, s.now() + 30 * DAY_SECS);
+}
+
+#[test]
+fn test_demurrage_epoch_boundary() {
+    let s = Scenario::new()
+        .with_admin()
+        .with_issuers(1)
+        .with_beneficiaries(1);
+    s.client().set_demurrage(&Some(DemurrageConfig {
+        rate_bps: 100,
+        epoch_secs: 30 * DAY_SECS,
+    }));
+    let s = s.issue(0, 0, 1000);
+
+    let s = s.set_time(START_TIMESTAMP + 30 * DAY_SECS - 1);
+    assert_eq!(s.client().balance(&s.beneficiary(0)), 1000);
+
+    let s = s.set_time(START_TIMESTAMP + 30 * DAY_SECS);
+    assert_eq!(s.client().balance(&s.beneficiary(0)), 990);
+}
+
+#[test]
+fn test_gc_dormancy_boundary() {
+    let s = Scenario::new()
+        .with_admin()
+        .with_issuers(1)
+        .with_schools(1)
+        .with_beneficiaries(1)
+        .issue(0, 0, 1000);
+    let client = s.client();
+    client.set_gc_dormancy(&DAY_SECS);
+
+    // The school empties its balance, then goes quiet
+    client.transfer(&s.beneficiary(0), &s.school(0), &1000);
+    client.burn(&s.school(0), &1000);
+
+    let gc_removed = |s: &Scenario| {
+        s.client().gc_accounts(&0, &10);
+        let (_, _, data) = s.env.events().all().last().unwrap();
+        let (_, removed): (u32, u32) = FromVal::from_val(&s.env, &event_payload(&s.env, &data));
+        removed
+    };
+
+    let s = s.set_time(START_TIMESTAMP + DAY_SECS - 1);
+    assert_eq!(gc_removed(&s), 0);
+
+    // The beneficiary is also empty but its allocation hasn't expired
+    let s = s.set_time(START_TIMESTAMP + DAY_SECS);
+    assert_eq!(gc_removed(&s), 1);
+}
+
+#[cfg(feature = "acceptance")]
+#[test]
+fn test_acceptance_window_boundary() {
+    use soroban_sdk::BytesN;
+
+    let s = Scenario::new()
+        .with_admin()
+        .with_issuers(1)
+        .with_beneficiaries(2);
+    let client = s.client();
+    client.set_acceptance_timeout(&DAY_SECS);
+    let purpose = String::from_str(&s.env, "Tuition");
+    let expires_at = s.expires_at();
+    let key = BytesN::from_array(&s.env, &[1; 32]);
+    let first = client.offer_credits(
+        &s.issuer(0),
+        &s.beneficiary(0),
+        &100,
+        &purpose,
+        &expires_at,
+        &key,
+    );
+    let second = client.offer_credits(
+        &s.issuer(0),
+        &s.beneficiary(1),
+        &100,
+        &purpose,
+        &expires_at,
+        &key,
+    );
+
+    let s = s.set_time(START_TIMESTAMP + DAY_SECS - 1);
+    s.client().accept_allocation(&first);
+    assert_eq!(s.client().balance(&s.beneficiary(0)), 100);
+
+    let s = s.set_time(START_TIMESTAMP + DAY_SECS);
+    let result = s.client().try_accept_allocation(&second);
+    assert_eq!(result, Err(Ok(Error::OfferExpired.into())));
+
+    // Unclaimed offers are kept until reclaimed for their issuer
+    let ids = soroban_sdk::vec![&s.env, second.clone()];
+    let s = s.set_time(START_TIMESTAMP + DAY_SECS - 1);
+    assert_eq!(s.client().reclaim_offers(&ids), 0);
+    let s = s.set_time(START_TIMESTAMP + DAY_SECS);
+    assert_eq!(s.client().reclaim_offers(&ids), 100);
+    assert!(s.client().pending_allocation(&second).is_none());
+}
+
+#[cfg(feature = "stipends")]
+#[test]
+fn test_attendance_month_boundaries() {
+    use crate::testutils::month_start;
+    use crate::MissedMonthPolicy;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::Address;
+
+    let s = Scenario::new()
+        .with_admin()
+        .with_issuers(1)
+        .with_beneficiaries(1)
+        .set_time(month_start(202601));
+    let attestor = Address::generate(&s.env);
+    let client = s.client();
+    client.set_attestor(&attestor, &true);
+    let schedule_id = client.create_schedule(
+        &s.issuer(0),
+        &s.beneficiary(0),
+        &100,
+        &202601,
+        &3,
+        &String::from_str(&s.env, "Stipend"),
+        &s.expires_at(),
+        &MissedMonthPolicy::Forfeit,
+    );
+
+    // The last second of January still counts for January, and February
+    // can't be attested before it starts
+    let s = s.set_time(month_start(202602) - 1);
+    let client = s.client();
+    client.attest_attendance(&attestor, &s.beneficiary(0), &202601);
+    let result = client.try_attest_attendance(&attestor, &s.beneficiary(0), &202602);
+    assert_eq!(result, Err(Ok(Error::InvalidMonth.into())));
+
+    let s = s.set_time(month_start(202602));
+    s.client()
+        .attest_attendance(&attestor, &s.beneficiary(0), &202602);
+
+    // March attested once April has begun is forfeited
+    let s = s.set_time(month_start(202604));
+    let client = s.client();
+    client.attest_attendance(&attestor, &s.beneficiary(0), &202603);
+    let summary = client.tranche_summary(&schedule_id);
+    assert_eq!((summary.claimable, summary.forfeited), (2, 1));
+    assert_eq!(client.claim_tranches(&s.beneficiary(0), &schedule_id), 200);
+}
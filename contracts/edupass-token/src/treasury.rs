@@ -0,0 +1,309 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, token, Address, Env,
+};
+
+use crate::{
+    checked_add, credit, dust, emergency, events, require_admin, telemetry, EduPassToken,
+    EduPassTokenClient, Error,
+};
+
+// Delay between scheduling and executing, long enough for the admin to
+// review and cancel
+const TREASURY_TIMELOCK_SECS: u64 = 2 * 24 * 60 * 60;
+// Length of one treasury statement period
+const STATEMENT_PERIOD_SECS: u64 = 30 * 24 * 60 * 60;
+
+#[contracttype]
+enum TreasuryKey {
+    Treasurer,               // Address allowed to move collected funds out
+    TreasuryWithdrawal(u64), // Scheduled treasury withdrawal
+    TreasuryWithdrawalCount, // Last assigned treasury withdrawal id
+    TreasuryStatement(u64),  // Collections and withdrawals of one period
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TreasuryWithdrawal {
+    pub asset: Option<Address>, // None for credits from the treasury
+    pub amount: i128,
+    pub destination: Address,
+    pub executable_at: u64,
+}
+
+// Treasury activity over one statement period
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TreasuryStatement {
+    pub collected: i128,        // Credits swept into the treasury
+    pub withdrawn: i128,        // Credits paid out of the treasury
+    pub asset_withdrawals: u32, // Withdrawals of reserve assets executed
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the treasurer (admin only)
+    pub fn set_treasurer(env: Env, treasurer: Option<Address>) {
+        telemetry::record(&env, "set_treasurer");
+        require_admin(&env);
+
+        match treasurer {
+            Some(treasurer) => env
+                .storage()
+                .instance()
+                .set(&TreasuryKey::Treasurer, &treasurer),
+            None => env.storage().instance().remove(&TreasuryKey::Treasurer),
+        }
+    }
+
+    /// Get the treasurer, if set
+    pub fn treasurer(env: Env) -> Option<Address> {
+        env.storage().instance().get(&TreasuryKey::Treasurer)
+    }
+
+    /// Credits collected by the contract and not yet withdrawn
+    pub fn treasury_balance(env: Env) -> i128 {
+        dust::dust_balance(&env)
+    }
+
+    /// Schedule a withdrawal of treasury credits (`asset` None) or of a
+    /// reserve asset held by the contract (treasurer only); executable
+    /// after the timelock unless the admin cancels it. Returns the
+    /// withdrawal id.
+    pub fn withdraw_treasury(
+        env: Env,
+        asset: Option<Address>,
+        amount: i128,
+        destination: Address,
+    ) -> u64 {
+        telemetry::record(&env, "withdraw_treasury");
+        require_treasurer(&env);
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        let id = env
+            .storage()
+            .instance()
+            .get(&TreasuryKey::TreasuryWithdrawalCount)
+            .unwrap_or(0u64)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&TreasuryKey::TreasuryWithdrawalCount, &id);
+
+        let withdrawal = TreasuryWithdrawal {
+            asset: asset.clone(),
+            amount,
+            destination: destination.clone(),
+            executable_at: env.ledger().timestamp() + TREASURY_TIMELOCK_SECS,
+        };
+        env.storage()
+            .persistent()
+            .set(&TreasuryKey::TreasuryWithdrawal(id), &withdrawal);
+        events::publish(
+            &env,
+            (symbol_short!("trsched"), id),
+            (asset, amount, destination, withdrawal.executable_at),
+        );
+
+        id
+    }
+
+    /// Cancel a scheduled treasury withdrawal (admin only)
+    pub fn cancel_treasury_withdrawal(env: Env, withdrawal_id: u64) {
+        telemetry::record(&env, "cancel_treasury_withdrawal");
+        require_admin(&env);
+
+        take_withdrawal(&env, withdrawal_id);
+        events::publish(&env, (symbol_short!("trcancel"), withdrawal_id), ());
+    }
+
+    /// Execute a scheduled treasury withdrawal once its timelock has passed
+    /// (treasurer only). Bonded funds and the wrap reserve are never in
+    /// scope.
+    pub fn execute_treasury_withdrawal(env: Env, withdrawal_id: u64) {
+        telemetry::record(&env, "execute_treasury_withdrawal");
+        require_treasurer(&env);
+
+        let withdrawal = take_withdrawal(&env, withdrawal_id);
+        if env.ledger().timestamp() < withdrawal.executable_at {
+            panic_with_error!(&env, Error::NotExpired);
+        }
+
+        match &withdrawal.asset {
+            None => pay_out(&env, &withdrawal.destination, withdrawal.amount),
+            Some(asset) => {
+                if withdrawal.amount > emergency::free_reserves(&env, asset) {
+                    panic_with_error!(&env, Error::InsufficientBalance);
+                }
+                token::Client::new(&env, asset).transfer(
+                    &env.current_contract_address(),
+                    &withdrawal.destination,
+                    &withdrawal.amount,
+                );
+                let mut statement = current_statement(&env);
+                statement.asset_withdrawals += 1;
+                write_statement(&env, &statement);
+            }
+        }
+        events::publish(
+            &env,
+            (symbol_short!("trexec"), withdrawal_id),
+            (withdrawal.asset, withdrawal.amount, withdrawal.destination),
+        );
+    }
+
+    /// Get a scheduled treasury withdrawal
+    pub fn treasury_withdrawal(env: Env, withdrawal_id: u64) -> Option<TreasuryWithdrawal> {
+        env.storage()
+            .persistent()
+            .get(&TreasuryKey::TreasuryWithdrawal(withdrawal_id))
+    }
+
+    /// Get the treasury statement of a 30-day period, numbered from the
+    /// Unix epoch (the current one is `now / 2_592_000`)
+    pub fn treasury_statement(env: Env, period: u64) -> TreasuryStatement {
+        env.storage()
+            .persistent()
+            .get(&TreasuryKey::TreasuryStatement(period))
+            .unwrap_or_default()
+    }
+}
+
+/// Record credits collected into the treasury on the current statement
+pub(crate) fn record_collected(env: &Env, amount: i128) {
+    let mut statement = current_statement(env);
+    statement.collected = checked_add(env, statement.collected, amount);
+    write_statement(env, &statement);
+}
+
+/// Pay treasury credits to an account, failing with InsufficientBalance if
+/// the treasury holds less
+pub(crate) fn pay_out(env: &Env, destination: &Address, amount: i128) {
+    dust::take_dust(env, amount);
+    credit(env, destination, amount);
+    let mut statement = current_statement(env);
+    statement.withdrawn = checked_add(env, statement.withdrawn, amount);
+    write_statement(env, &statement);
+}
+
+/// Load the treasurer and require its authorization
+pub(crate) fn require_treasurer(env: &Env) -> Address {
+    let treasurer = EduPassToken::treasurer(env.clone())
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotConfigured));
+    treasurer.require_auth();
+    treasurer
+}
+
+fn current_period(env: &Env) -> u64 {
+    env.ledger().timestamp() / STATEMENT_PERIOD_SECS
+}
+
+fn current_statement(env: &Env) -> TreasuryStatement {
+    EduPassToken::treasury_statement(env.clone(), current_period(env))
+}
+
+fn write_statement(env: &Env, statement: &TreasuryStatement) {
+    env.storage().persistent().set(
+        &TreasuryKey::TreasuryStatement(current_period(env)),
+        statement,
+    );
+}
+
+// Remove and return a scheduled withdrawal; each is executed or cancelled once
+fn take_withdrawal(env: &Env, withdrawal_id: u64) -> TreasuryWithdrawal {
+    let key = TreasuryKey::TreasuryWithdrawal(withdrawal_id);
+    let withdrawal = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+    env.storage().persistent().remove(&key);
+    withdrawal
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    fn setup() -> (Scenario, Address) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_schools(1)
+            .with_beneficiaries(1)
+            .issue(0, 0, 105);
+        let client = s.client();
+        let treasurer = Address::generate(&s.env);
+        client.set_treasurer(&Some(treasurer.clone()));
+        client.set_min_transfer(&Some(10));
+        client.transfer(&s.beneficiary(0), &s.school(0), &100);
+        client.sweep_dust(&s.beneficiary(0));
+        (s, treasurer)
+    }
+
+    #[test]
+    fn test_credit_withdrawal_after_timelock() {
+        let (s, _) = setup();
+        let client = s.client();
+        assert_eq!(client.treasury_balance(), 5);
+        let destination = Address::generate(&s.env);
+        let result = client.try_withdraw_treasury(&None, &0, &destination);
+        assert_eq!(result, Err(Ok(Error::InvalidAmount.into())));
+        let id = client.withdraw_treasury(&None, &5, &destination);
+        let too_much = client.withdraw_treasury(&None, &6, &destination);
+
+        let s = s.advance_time(TREASURY_TIMELOCK_SECS - 1);
+        let result = s.client().try_execute_treasury_withdrawal(&id);
+        assert_eq!(result, Err(Ok(Error::NotExpired.into())));
+
+        let s = s.advance_time(1);
+        let client = s.client();
+        let result = client.try_execute_treasury_withdrawal(&too_much);
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+        client.execute_treasury_withdrawal(&id);
+        assert_eq!(client.balance(&destination), 5);
+        assert_eq!(client.treasury_balance(), 0);
+        assert_eq!(client.treasury_withdrawal(&id), None);
+
+        let period = s.now() / STATEMENT_PERIOD_SECS;
+        let statement = client.treasury_statement(&period);
+        assert_eq!((statement.collected, statement.withdrawn), (5, 5));
+    }
+
+    #[test]
+    fn test_asset_withdrawal_spares_reserves_and_can_be_cancelled() {
+        let (s, _) = setup();
+        let client = s.client();
+        let asset = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        StellarAssetClient::new(&s.env, &asset).mint(&s.contract_id, &1000);
+        let destination = Address::generate(&s.env);
+        let id = client.withdraw_treasury(&Some(asset.clone()), &400, &destination);
+        let cancelled = client.withdraw_treasury(&Some(asset.clone()), &600, &destination);
+        let too_much = client.withdraw_treasury(&Some(asset.clone()), &601, &destination);
+        client.cancel_treasury_withdrawal(&cancelled);
+
+        let s = s.advance_time(TREASURY_TIMELOCK_SECS);
+        let client = s.client();
+        let result = client.try_execute_treasury_withdrawal(&cancelled);
+        assert_eq!(result, Err(Ok(Error::NotFound.into())));
+        client.execute_treasury_withdrawal(&id);
+        let result = client.try_execute_treasury_withdrawal(&too_much);
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+        assert_eq!(TokenClient::new(&s.env, &asset).balance(&destination), 400);
+
+        let period = s.now() / STATEMENT_PERIOD_SECS;
+        assert_eq!(client.treasury_statement(&period).asset_withdrawals, 1);
+
+        client.set_treasurer(&None);
+        let result = client.try_withdraw_treasury(&None, &1, &destination);
+        assert_eq!(result, Err(Ok(Error::NotConfigured.into())));
+    }
+}
@@ -0,0 +1,147 @@
+use soroban_sdk::{contractimpl, contracttype, panic_with_error, Env};
+
+use crate::{require_admin, telemetry, EduPassToken, EduPassTokenClient, Error};
+
+#[contracttype]
+enum ValidityKey {
+    ValidityBounds, // Allowed allocation lifetimes (absent when unchecked)
+}
+
+// How long issued allocations may stay valid, counted from issuance
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidityConfig {
+    pub min_validity_secs: u64,
+    pub max_validity_secs: u64,
+    pub default_validity_secs: u64, // Applied when an issuance passes expires_at 0
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the bounds on allocation expiries (admin only).
+    /// The default has to lie within the bounds.
+    pub fn set_validity_config(env: Env, config: Option<ValidityConfig>) {
+        telemetry::record(&env, "set_validity_config");
+        require_admin(&env);
+
+        match config {
+            Some(config) => {
+                if config.max_validity_secs == 0
+                    || config.min_validity_secs > config.default_validity_secs
+                    || config.default_validity_secs > config.max_validity_secs
+                {
+                    panic_with_error!(&env, Error::InvalidConfig);
+                }
+                env.storage()
+                    .instance()
+                    .set(&ValidityKey::ValidityBounds, &config);
+            }
+            None => env
+                .storage()
+                .instance()
+                .remove(&ValidityKey::ValidityBounds),
+        }
+    }
+
+    /// Get the bounds on allocation expiries, if set
+    pub fn validity_config(env: Env) -> Option<ValidityConfig> {
+        env.storage().instance().get(&ValidityKey::ValidityBounds)
+    }
+}
+
+/// Resolve the expiry an issuer asked for: 0 becomes the default lifetime,
+/// and anything sooner than the minimum or later than the maximum fails
+/// with ExpiryTooSoon or ExpiryTooLate. Expiries pass unchanged while no
+/// bounds are set.
+pub(crate) fn resolve_expiry(env: &Env, expires_at: u64) -> u64 {
+    let Some(config) = EduPassToken::validity_config(env.clone()) else {
+        return expires_at;
+    };
+
+    let now = env.ledger().timestamp();
+    if expires_at == 0 {
+        return now.saturating_add(config.default_validity_secs);
+    }
+    if expires_at < now.saturating_add(config.min_validity_secs) {
+        panic_with_error!(env, Error::ExpiryTooSoon);
+    }
+    if expires_at > now.saturating_add(config.max_validity_secs) {
+        panic_with_error!(env, Error::ExpiryTooLate);
+    }
+    expires_at
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::{Scenario, DAY_SECS};
+    use soroban_sdk::{symbol_short, String};
+
+    fn setup() -> Scenario {
+        let s = Scenario::new()
+            .with_admin()
+            .with_issuers(1)
+            .with_beneficiaries(1);
+        s.client().set_validity_config(&Some(ValidityConfig {
+            min_validity_secs: 30 * DAY_SECS,
+            max_validity_secs: 365 * DAY_SECS,
+            default_validity_secs: 180 * DAY_SECS,
+        }));
+        s
+    }
+
+    #[test]
+    fn test_expiries_bounded_and_defaulted() {
+        let s = setup();
+        let client = s.client();
+        let purpose = String::from_str(&s.env, "Tuition");
+        let issue = |expires_at: u64| {
+            client.try_issue_credits(&s.issuer(0), &s.beneficiary(0), &100, &purpose, &expires_at)
+        };
+
+        let result = issue(s.now() - 1);
+        assert_eq!(result.err(), Some(Ok(Error::ExpiryTooSoon.into())));
+        let result = issue(s.now() + 30 * DAY_SECS - 1);
+        assert_eq!(result.err(), Some(Ok(Error::ExpiryTooSoon.into())));
+        let result = issue(s.now() + 100 * 365 * DAY_SECS);
+        assert_eq!(result.err(), Some(Ok(Error::ExpiryTooLate.into())));
+
+        let allocation = issue(s.now() + 365 * DAY_SECS).unwrap().unwrap();
+        assert_eq!(allocation.expires_at, s.now() + 365 * DAY_SECS);
+        let allocation = issue(0).unwrap().unwrap();
+        assert_eq!(allocation.expires_at, s.now() + 180 * DAY_SECS);
+
+        // Templates are held to the same bounds
+        client.save_template(
+            &s.issuer(0),
+            &symbol_short!("decade"),
+            &purpose,
+            &100,
+            &(10 * 365 * DAY_SECS),
+            &symbol_short!("uni"),
+        );
+        let result = client.try_issue_from_template(
+            &s.issuer(0),
+            &symbol_short!("decade"),
+            &s.beneficiary(0),
+        );
+        assert_eq!(result.err(), Some(Ok(Error::ExpiryTooLate.into())));
+    }
+
+    #[test]
+    fn test_config_validated_and_cleared() {
+        let s = setup();
+        let client = s.client();
+        let result = client.try_set_validity_config(&Some(ValidityConfig {
+            min_validity_secs: 30 * DAY_SECS,
+            max_validity_secs: 60 * DAY_SECS,
+            default_validity_secs: 90 * DAY_SECS,
+        }));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+
+        client.set_validity_config(&None);
+        let purpose = String::from_str(&s.env, "Tuition");
+        let expires_at = s.now() + 100 * 365 * DAY_SECS;
+        client.issue_credits(&s.issuer(0), &s.beneficiary(0), &100, &purpose, &expires_at);
+    }
+}
@@ -0,0 +1,65 @@
+// Interface probes for configured contract addresses. Only the assets the
+// contract moves (wrapping, bonds and reserves) are probed; roles such as
+// governance or the compliance officer only authorize, so they can be any
+// account. The school registry is left unprobed so it can be configured
+// while down; its failures surface as RegistryUnavailable.
+
+use soroban_sdk::{panic_with_error, token, Address, Env};
+
+use crate::Error;
+
+/// Fail with InterfaceMismatch unless `asset` answers the token
+/// interface's `decimals` view, so an account or another kind of contract
+/// can't be configured as an asset
+pub(crate) fn require_asset(env: &Env, asset: &Address) {
+    if !matches!(token::Client::new(env, asset).try_decimals(), Ok(Ok(_))) {
+        panic_with_error!(env, Error::InterfaceMismatch);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testutils::Scenario;
+    use crate::{BondConfig, Error};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    #[contract]
+    struct Registry;
+
+    #[contractimpl]
+    impl Registry {
+        pub fn interface_version(_env: Env) -> u32 {
+            1
+        }
+    }
+
+    #[test]
+    fn test_assets_must_answer_the_token_interface() {
+        let s = Scenario::new().with_admin();
+        let client = s.client();
+        // Neither an account nor a contract without the token interface
+        let account = Address::generate(&s.env);
+        let other = s.env.register_contract(None, Registry);
+        for asset in [account, other] {
+            let result = client.try_set_wrap_asset(&Some(asset.clone()));
+            assert_eq!(result, Err(Ok(Error::InterfaceMismatch.into())));
+            let result = client.try_set_accepted_asset(&asset, &Some(100));
+            assert_eq!(result, Err(Ok(Error::InterfaceMismatch.into())));
+            let result = client.try_set_bond_config(&Some(BondConfig {
+                asset,
+                min_bond: 100,
+                cooldown_secs: 0,
+            }));
+            assert_eq!(result, Err(Ok(Error::InterfaceMismatch.into())));
+        }
+
+        let usdc = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        client.set_wrap_asset(&Some(usdc.clone()));
+        client.set_accepted_asset(&usdc, &Some(100));
+        assert_eq!(client.wrap_asset(), Some(usdc));
+    }
+}
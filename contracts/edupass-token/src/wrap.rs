@@ -0,0 +1,263 @@
+use soroban_sdk::{
+    contractimpl, contracttype, panic_with_error, symbol_short, token, Address, Env,
+};
+
+use crate::{
+    add_total_burned, add_total_issued, balance_cap, bonding, checked_add, demurrage, events,
+    is_beneficiary, redemptions, require_admin, reserves, rotation, screening, telemetry, wiring,
+    write_balance, EduPassToken, EduPassTokenClient, Error,
+};
+
+#[contracttype]
+enum WrapKey {
+    WrapAsset,             // Classic asset (via its SAC) that backs wrapped credits
+    WrapReserve,           // Asset held 1:1 against wrapped credits not yet unwrapped
+    UnwrapSchool(Address), // School approved to unwrap credits back into the asset
+}
+
+#[contractimpl]
+impl EduPassToken {
+    /// Set or clear (None) the classic asset credits can be wrapped from
+    /// (admin only). The asset can't change while a reserve is held.
+    pub fn set_wrap_asset(env: Env, asset: Option<Address>) {
+        telemetry::record(&env, "set_wrap_asset");
+        require_admin(&env);
+
+        if reserve(&env) > 0 && asset != wrap_asset(&env) {
+            panic_with_error!(&env, Error::InvalidConfig);
+        }
+        match asset {
+            Some(asset) => {
+                wiring::require_asset(&env, &asset);
+                env.storage().instance().set(&WrapKey::WrapAsset, &asset)
+            }
+            None => env.storage().instance().remove(&WrapKey::WrapAsset),
+        }
+    }
+
+    /// Get the classic asset credits are wrapped from, if set
+    pub fn wrap_asset(env: Env) -> Option<Address> {
+        wrap_asset(&env)
+    }
+
+    /// Asset held in reserve for wrapped credits
+    pub fn wrap_reserve(env: Env) -> i128 {
+        reserve(&env)
+    }
+
+    /// Approve or revoke a school's right to unwrap credits (admin only)
+    pub fn approve_unwrap(env: Env, school: Address, approved: bool) {
+        telemetry::record(&env, "approve_unwrap");
+        require_admin(&env);
+
+        let key = WrapKey::UnwrapSchool(school);
+        if approved {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    /// Check whether a school may unwrap credits
+    pub fn is_unwrap_approved(env: Env, school: Address) -> bool {
+        is_approved(&env, &school)
+    }
+
+    /// Deposit the classic asset and mint the same amount of credits to
+    /// the depositing account
+    pub fn wrap(env: Env, account: Address, amount: i128) {
+        telemetry::record(&env, "wrap");
+        account.require_auth();
+
+        let asset = require_asset(&env);
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        screening::require_clear(&env, &account);
+
+        let balance = demurrage::settle(&env, &account, is_beneficiary(&env, &account));
+        let balance = checked_add(&env, balance, amount);
+        if let Some(cap) = balance_cap(&env, &account) {
+            if balance > cap {
+                panic_with_error!(&env, Error::BalanceCapExceeded);
+            }
+        }
+        write_balance(&env, &account, balance);
+        add_total_issued(&env, &None, amount);
+        set_reserve(&env, checked_add(&env, reserve(&env), amount));
+        reserves::deposit(&env, &asset, amount);
+
+        token::Client::new(&env, &asset).transfer(
+            &account,
+            &env.current_contract_address(),
+            &amount,
+        );
+        events::publish(&env, (symbol_short!("wrapped"), account), amount);
+    }
+
+    /// Burn credits and pay out the same amount of the classic asset from
+    /// the reserve (approved schools only)
+    pub fn unwrap(env: Env, school: Address, amount: i128) {
+        telemetry::record(&env, "unwrap");
+        school.require_auth();
+
+        let asset = require_asset(&env);
+        if !is_approved(&env, &school) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+        screening::require_clear(&env, &school);
+
+        let balance = demurrage::settle(&env, &school, is_beneficiary(&env, &school));
+        let reserve = reserve(&env);
+        if balance < amount || reserve < amount {
+            panic_with_error!(&env, Error::InsufficientBalance);
+        }
+        redemptions::record_redemption(&env, &school, amount);
+        write_balance(&env, &school, balance - amount);
+        add_total_burned(&env, amount);
+        set_reserve(&env, reserve - amount);
+        reserves::withdraw(&env, &asset, amount);
+
+        token::Client::new(&env, &asset).transfer(
+            &env.current_contract_address(),
+            &school,
+            &amount,
+        );
+        events::publish(&env, (symbol_short!("unwrapped"), school), amount);
+    }
+}
+
+/// Asset held for wrapped credits, if `asset` is the wrap asset
+pub(crate) fn reserved_in(env: &Env, asset: &Address) -> i128 {
+    match wrap_asset(env) {
+        Some(wrapped) if wrapped == *asset => reserve(env),
+        _ => 0,
+    }
+}
+
+/// Whether the contract holds enough of the wrap asset to back the
+/// reserve (and any bonds posted in it)
+pub(crate) fn reserve_backed(env: &Env) -> bool {
+    let Some(asset) = wrap_asset(env) else {
+        return true;
+    };
+    let held = token::Client::new(env, &asset).balance(&env.current_contract_address());
+    held >= reserve(env).saturating_add(bonding::bonded_in(env, &asset))
+}
+
+/// Move a school's unwrap approval to its rotated key
+pub(crate) fn rotate(env: &Env, old: &Address, new: &Address) {
+    rotation::move_persistent(
+        env,
+        &WrapKey::UnwrapSchool(old.clone()),
+        &WrapKey::UnwrapSchool(new.clone()),
+    );
+}
+
+fn wrap_asset(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&WrapKey::WrapAsset)
+}
+
+fn require_asset(env: &Env) -> Address {
+    wrap_asset(env).unwrap_or_else(|| panic_with_error!(env, Error::NotConfigured))
+}
+
+fn reserve(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&WrapKey::WrapReserve)
+        .unwrap_or(0)
+}
+
+fn set_reserve(env: &Env, reserve: i128) {
+    env.storage()
+        .instance()
+        .set(&WrapKey::WrapReserve, &reserve);
+}
+
+fn is_approved(env: &Env, school: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&WrapKey::UnwrapSchool(school.clone()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::Scenario;
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+    use soroban_sdk::vec;
+
+    fn setup() -> (Scenario, Address) {
+        let s = Scenario::new()
+            .with_admin()
+            .with_schools(1)
+            .with_beneficiaries(1);
+        let asset = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        StellarAssetClient::new(&s.env, &asset).mint(&s.beneficiary(0), &1000);
+        s.client().set_wrap_asset(&Some(asset.clone()));
+        (s, asset)
+    }
+
+    #[test]
+    fn test_wrap_and_unwrap_one_to_one() {
+        let (s, asset) = setup();
+        let client = s.client();
+        let token = TokenClient::new(&s.env, &asset);
+        client.approve_unwrap(&s.school(0), &true);
+
+        client.wrap(&s.beneficiary(0), &600);
+        assert_eq!(client.balance(&s.beneficiary(0)), 600);
+        assert_eq!(token.balance(&s.contract_id), 600);
+        assert_eq!(client.wrap_reserve(), 600);
+
+        client.transfer(&s.beneficiary(0), &s.school(0), &400);
+        client.unwrap(&s.school(0), &400);
+        assert_eq!(token.balance(&s.school(0)), 400);
+        assert_eq!(client.balance(&s.school(0)), 0);
+        assert_eq!(client.wrap_reserve(), 200);
+        assert_eq!(client.total_issued() - client.total_burned(), 200);
+
+        let report = client.check_invariants(&vec![&s.env, s.beneficiary(0), s.school(0)]);
+        assert!(report.ok && report.reserve_backed);
+    }
+
+    #[test]
+    fn test_unwrap_requires_approval() {
+        let (s, _) = setup();
+        let client = s.client();
+        client.wrap(&s.beneficiary(0), &500);
+        client.transfer(&s.beneficiary(0), &s.school(0), &500);
+
+        let result = client.try_unwrap(&s.school(0), &500);
+        assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+        client.approve_unwrap(&s.school(0), &true);
+        let result = client.try_unwrap(&s.school(0), &501);
+        assert_eq!(result, Err(Ok(Error::InsufficientBalance.into())));
+        client.unwrap(&s.school(0), &500);
+        assert_eq!(client.wrap_reserve(), 0);
+    }
+
+    #[test]
+    fn test_asset_pinned_while_reserve_held() {
+        let (s, _) = setup();
+        let client = s.client();
+        client.wrap(&s.beneficiary(0), &100);
+
+        let other = s
+            .env
+            .register_stellar_asset_contract_v2(s.admin())
+            .address();
+        let result = client.try_set_wrap_asset(&Some(other));
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+        let result = client.try_set_wrap_asset(&None);
+        assert_eq!(result, Err(Ok(Error::InvalidConfig.into())));
+    }
+}